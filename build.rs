@@ -0,0 +1,1522 @@
+//! AegisOS build script — compiles `system.toml` into kernel sources.
+//!
+//! Outputs (in $OUT_DIR):
+//!   sysconf.rs       — NUM_TASKS, ELF/grant layout, DEVICES  (crate::sysconf)
+//!   task_table.rs    — TASK_META, task ids, entry points, ELF images,
+//!                      partition schedule, queue and sampling-port
+//!                      configs, flow-policy assertions (main.rs)
+//!   aegis_layout.ld  — section sizes INCLUDEd by linker.ld
+//!
+//! The configuration is validated as a whole before anything is emitted;
+//! every inconsistency is reported and the build fails. No dependencies:
+//! a small parser covers the TOML subset system.toml uses (tables, arrays
+//! of tables, integers, strings, arrays, inline tables).
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ─── Kernel facts the generator relies on ──────────────────────────
+
+/// Highest scheduler priority (sched: 0..=7)
+const MAX_PRIORITY: u64 = 7;
+/// Partitions in the major-frame schedule (0 = system, 1 = applications)
+const NUM_PARTITIONS: u64 = 2;
+/// Endpoints with a CapBits send/recv pair (ipc::MAX_ENDPOINTS)
+const MAX_ENDPOINTS: u64 = 4;
+/// Capability slots per task (cspace::CSPACE_SLOTS)
+const CSPACE_SLOTS: u64 = 8;
+/// Windows in the major-frame schedule (sched::MAX_PARTITION_WINDOWS)
+const MAX_PARTITION_WINDOWS: usize = 8;
+/// Message queues (queue::MAX_QUEUES) and their depth limit (QUEUE_MAX_DEPTH)
+const MAX_QUEUES: u64 = 2;
+const QUEUE_MAX_DEPTH: u64 = 8;
+/// Sampling ports (sampling::MAX_SAMPLING_PORTS)
+const MAX_SAMPLING_PORTS: u64 = 2;
+/// Message words carried by IPC, queues and ports (ipc::MSG_REGS)
+const MSG_REGS: u64 = 4;
+/// GICv2 distributor/CPU interface L2 indices — never mapped for EL0
+const GIC_L2_INDICES: std::ops::RangeInclusive<u64> = 64..=66;
+/// First user-bindable INTID (SPIs)
+const MIN_SPI_INTID: u64 = 32;
+/// Per-task IPC buffer alias page; the kernel image must end below it
+const IPC_BUFFER_VA: u64 = 0x401F_F000;
+/// Where `cargo build` in user/ leaves the task images
+const USER_IMAGE_DIR: &str = "user/target/aarch64-user/release";
+
+fn main() {
+    let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let config_path = root.join("system.toml");
+    println!("cargo:rerun-if-changed=system.toml");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let text = fs::read_to_string(&config_path)
+        .unwrap_or_else(|e| fail(&[format!("cannot read {}: {e}", config_path.display())]));
+    let doc = toml::parse(&text).unwrap_or_else(|e| fail(&[format!("system.toml: {e}")]));
+    let sys = System::from_toml(&doc).unwrap_or_else(|errs| fail(&errs));
+
+    let errors = sys.validate(&root);
+    if !errors.is_empty() {
+        fail(&errors);
+    }
+
+    write(&out.join("sysconf.rs"), &sys.emit_sysconf());
+    write(&out.join("task_table.rs"), &sys.emit_task_table(&root));
+    write(&out.join("aegis_layout.ld"), &sys.emit_layout());
+    // linker.ld finds `INCLUDE aegis_layout.ld` through the search path
+    println!("cargo:rustc-link-search=native={}", out.display());
+}
+
+fn fail(errors: &[String]) -> ! {
+    for e in errors {
+        eprintln!("error: system.toml: {e}");
+    }
+    panic!("invalid system configuration ({} error(s))", errors.len());
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::write(path, contents).unwrap_or_else(|e| panic!("cannot write {}: {e}", path.display()));
+}
+
+// ─── Configuration model ───────────────────────────────────────────
+
+struct Device {
+    name: String,
+    l2_index: u64,
+    intid: u64,
+}
+
+struct Grant {
+    id: u64,
+    owner: String,
+    /// `peer = "x"`, or `peers = [...]` for a grant shared with several tasks
+    peers: Vec<String>,
+    /// Pool pages the owner intends to request (default 1)
+    pages: u64,
+}
+
+struct Slot {
+    slot: u64,
+    kind: String,
+    id: u64,
+    rights: Vec<String>,
+}
+
+struct Mint {
+    slot: u64,
+    to: String,
+    rights: Vec<String>,
+}
+
+/// No flow from any node in `from` to any node in `to` (task or device names)
+struct Policy {
+    name: String,
+    from: Vec<String>,
+    to: Vec<String>,
+}
+
+/// One window of the major frame (sched::PartitionWindow)
+struct Window {
+    partition: u64,
+    offset: u64,
+    duration: u64,
+}
+
+struct Queue {
+    id: u64,
+    depth: u64,
+    msg_words: u64,
+    /// queue::OverflowPolicy variant
+    overflow: &'static str,
+}
+
+struct SamplingPort {
+    id: u64,
+    msg_words: u64,
+    refresh_period: u64,
+}
+
+struct Timing {
+    period: u64,
+    deadline: u64,
+    wcet: u64,
+}
+
+enum Image {
+    /// Kernel-linked entry function
+    Entry(String),
+    /// ELF image `user/<name>` loaded into slot `slot`
+    Elf { name: String, slot: u64 },
+}
+
+struct Task {
+    id: u64,
+    name: String,
+    image: Image,
+    priority: u64,
+    budget: u64,
+    heartbeat: u64,
+    partition: u64,
+    /// Anonymous frames the task may hold (SYS_MAP_ANON)
+    mem_quota: u64,
+    caps: Vec<String>,
+    send: Vec<u64>,
+    recv: Vec<u64>,
+    irqs: Vec<u64>,
+    devices: Vec<String>,
+    badges: Vec<(u64, u64)>,
+    cspace: Vec<Slot>,
+    mints: Vec<Mint>,
+    timing: Option<Timing>,
+}
+
+struct System {
+    num_tasks: u64,
+    elf_load_base: u64,
+    elf_slot_size: u64,
+    elf_slots: u64,
+    grant_pages: u64,
+    max_grants: u64,
+    /// Byte written over a grant's pages when it is revoked
+    grant_scrub: u64,
+    /// Frame pool for SYS_MAP_ANON, in pages
+    frame_pages: u64,
+    /// `deny_policy = "log"`: refused syscalls fail instead of faulting
+    log_and_deny: bool,
+    /// Partition schedule: major frame in ticks and its windows
+    major_frame: u64,
+    windows: Vec<Window>,
+    devices: Vec<Device>,
+    grants: Vec<Grant>,
+    queues: Vec<Queue>,
+    ports: Vec<SamplingPort>,
+    tasks: Vec<Task>,
+    policies: Vec<Policy>,
+}
+
+/// Field reader over one table, collecting errors instead of stopping.
+struct Fields<'a> {
+    table: &'a toml::Table,
+    what: String,
+    errors: &'a mut Vec<String>,
+}
+
+impl Fields<'_> {
+    fn get(&mut self, key: &str) -> Option<&toml::Value> {
+        let v = self.table.get(key);
+        if v.is_none() {
+            self.errors.push(format!("{}: missing `{key}`", self.what));
+        }
+        v
+    }
+
+    fn int(&mut self, key: &str) -> u64 {
+        match self.get(key) {
+            Some(toml::Value::Int(n)) => *n,
+            Some(_) => {
+                self.errors.push(format!("{}: `{key}` must be an integer", self.what));
+                0
+            }
+            None => 0,
+        }
+    }
+
+    fn int_or(&mut self, key: &str, default: u64) -> u64 {
+        if self.table.contains_key(key) { self.int(key) } else { default }
+    }
+
+    fn string(&mut self, key: &str) -> String {
+        match self.get(key) {
+            Some(toml::Value::Str(s)) => s.clone(),
+            Some(_) => {
+                self.errors.push(format!("{}: `{key}` must be a string", self.what));
+                String::new()
+            }
+            None => String::new(),
+        }
+    }
+
+    fn opt_string(&mut self, key: &str) -> Option<String> {
+        self.table.contains_key(key).then(|| self.string(key))
+    }
+
+    fn list(&mut self, key: &str) -> Vec<toml::Value> {
+        match self.table.get(key) {
+            None => Vec::new(),
+            Some(toml::Value::Array(items)) => items.clone(),
+            Some(_) => {
+                self.errors.push(format!("{}: `{key}` must be an array", self.what));
+                Vec::new()
+            }
+        }
+    }
+
+    fn ints(&mut self, key: &str) -> Vec<u64> {
+        let mut out = Vec::new();
+        for v in self.list(key) {
+            match v {
+                toml::Value::Int(n) => out.push(n),
+                _ => self.errors.push(format!("{}: `{key}` must hold integers", self.what)),
+            }
+        }
+        out
+    }
+
+    fn strings(&mut self, key: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        for v in self.list(key) {
+            match v {
+                toml::Value::Str(s) => out.push(s),
+                _ => self.errors.push(format!("{}: `{key}` must hold strings", self.what)),
+            }
+        }
+        out
+    }
+
+    fn tables(&mut self, key: &str) -> Vec<toml::Table> {
+        let mut out = Vec::new();
+        for v in self.list(key) {
+            match v {
+                toml::Value::Table(t) => out.push(t),
+                _ => self.errors.push(format!("{}: `{key}` must hold inline tables", self.what)),
+            }
+        }
+        out
+    }
+
+    fn unknown_keys(&mut self, known: &[&str]) {
+        for key in self.table.keys() {
+            if !known.contains(&key.as_str()) {
+                self.errors.push(format!("{}: unknown key `{key}`", self.what));
+            }
+        }
+    }
+}
+
+fn fields<'a>(table: &'a toml::Table, what: String, errors: &'a mut Vec<String>) -> Fields<'a> {
+    Fields { table, what, errors }
+}
+
+/// Array-of-tables `key` from the document root (empty if absent).
+fn table_array(doc: &toml::Table, key: &str, errors: &mut Vec<String>) -> Vec<toml::Table> {
+    match doc.get(key) {
+        None => Vec::new(),
+        Some(toml::Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| match v {
+                toml::Value::Table(t) => Some(t.clone()),
+                _ => None,
+            })
+            .collect(),
+        Some(_) => {
+            errors.push(format!("`{key}` must be an array of tables ([[{key}]])"));
+            Vec::new()
+        }
+    }
+}
+
+impl System {
+    fn from_toml(doc: &toml::Table) -> Result<Self, Vec<String>> {
+        let mut errors = Vec::new();
+        for key in doc.keys() {
+            if !["kernel", "schedule", "device", "grant", "queue", "sampling_port", "task", "policy"].contains(&key.as_str()) {
+                errors.push(format!("unknown section `{key}`"));
+            }
+        }
+
+        let kernel = match doc.get("kernel") {
+            Some(toml::Value::Table(t)) => t.clone(),
+            _ => {
+                errors.push("missing [kernel] section".into());
+                toml::Table::new()
+            }
+        };
+        let mut k = fields(&kernel, "[kernel]".into(), &mut errors);
+        k.unknown_keys(&["num_tasks", "elf_load_base", "elf_slot_size", "elf_slots", "grant_pages", "max_grants",
+            "grant_scrub", "frame_pages", "deny_policy"]);
+        let num_tasks = k.int("num_tasks");
+        let elf_load_base = k.int("elf_load_base");
+        let elf_slot_size = k.int("elf_slot_size");
+        let elf_slots = k.int("elf_slots");
+        let grant_pages = k.int("grant_pages");
+        let max_grants = k.int("max_grants");
+        let grant_scrub = k.int_or("grant_scrub", 0);
+        let frame_pages = k.int_or("frame_pages", 0);
+        let log_and_deny = match k.opt_string("deny_policy").as_deref() {
+            None | Some("fault") => false,
+            Some("log") => true,
+            Some(other) => {
+                k.errors.push(format!("[kernel]: deny_policy must be \"fault\" or \"log\", not {other:?}"));
+                false
+            }
+        };
+
+        let schedule = match doc.get("schedule") {
+            Some(toml::Value::Table(t)) => t.clone(),
+            _ => {
+                errors.push("missing [schedule] section".into());
+                toml::Table::new()
+            }
+        };
+        let mut sf = fields(&schedule, "[schedule]".into(), &mut errors);
+        sf.unknown_keys(&["major_frame", "windows"]);
+        let major_frame = sf.int("major_frame");
+        let mut windows = Vec::new();
+        for w in sf.tables("windows") {
+            let mut wf = fields(&w, "[schedule] window".into(), sf.errors);
+            wf.unknown_keys(&["partition", "offset", "duration"]);
+            windows.push(Window { partition: wf.int("partition"), offset: wf.int("offset"), duration: wf.int("duration") });
+        }
+
+        let mut devices = Vec::new();
+        for (i, t) in table_array(doc, "device", &mut errors).iter().enumerate() {
+            let mut f = fields(t, format!("device #{i}"), &mut errors);
+            f.unknown_keys(&["name", "l2_index", "intid"]);
+            devices.push(Device { name: f.string("name"), l2_index: f.int("l2_index"), intid: f.int("intid") });
+        }
+
+        let mut grants = Vec::new();
+        for (i, t) in table_array(doc, "grant", &mut errors).iter().enumerate() {
+            let mut f = fields(t, format!("grant #{i}"), &mut errors);
+            f.unknown_keys(&["id", "owner", "peer", "peers", "pages"]);
+            let peers = match f.opt_string("peer") {
+                Some(peer) if !f.table.contains_key("peers") => vec![peer],
+                Some(_) => {
+                    f.errors.push(format!("grant #{i}: give `peer` or `peers`, not both"));
+                    Vec::new()
+                }
+                None => f.strings("peers"),
+            };
+            grants.push(Grant { id: f.int("id"), owner: f.string("owner"), peers, pages: f.int_or("pages", 1) });
+        }
+
+        let mut queues = Vec::new();
+        for (i, t) in table_array(doc, "queue", &mut errors).iter().enumerate() {
+            let mut f = fields(t, format!("queue #{i}"), &mut errors);
+            f.unknown_keys(&["id", "depth", "msg_words", "overflow"]);
+            let overflow = match f.string("overflow").as_str() {
+                "drop_newest" => "DropNewest",
+                "drop_oldest" => "DropOldest",
+                "fault_sender" => "FaultSender",
+                other => {
+                    f.errors.push(format!(
+                        "queue #{i}: overflow must be \"drop_newest\", \"drop_oldest\" or \"fault_sender\", not {other:?}"
+                    ));
+                    "DropNewest"
+                }
+            };
+            queues.push(Queue { id: f.int("id"), depth: f.int("depth"), msg_words: f.int("msg_words"), overflow });
+        }
+
+        let mut ports = Vec::new();
+        for (i, t) in table_array(doc, "sampling_port", &mut errors).iter().enumerate() {
+            let mut f = fields(t, format!("sampling port #{i}"), &mut errors);
+            f.unknown_keys(&["id", "msg_words", "refresh_period"]);
+            ports.push(SamplingPort { id: f.int("id"), msg_words: f.int("msg_words"), refresh_period: f.int("refresh_period") });
+        }
+
+        let mut policies = Vec::new();
+        for (i, t) in table_array(doc, "policy", &mut errors).iter().enumerate() {
+            let mut f = fields(t, format!("policy #{i}"), &mut errors);
+            f.unknown_keys(&["name", "from", "to"]);
+            policies.push(Policy { name: f.string("name"), from: f.strings("from"), to: f.strings("to") });
+        }
+
+        let mut tasks = Vec::new();
+        for (i, t) in table_array(doc, "task", &mut errors).iter().enumerate() {
+            let name = match t.get("name") {
+                Some(toml::Value::Str(s)) => s.clone(),
+                _ => format!("#{i}"),
+            };
+            let mut f = fields(t, format!("task `{name}`"), &mut errors);
+            f.unknown_keys(&[
+                "id", "name", "entry", "elf", "elf_slot", "priority", "budget", "heartbeat", "mem_quota",
+                "partition", "caps", "send", "recv", "irqs", "devices", "badges", "cspace", "mints",
+                "timing",
+            ]);
+            let id = f.int("id");
+            let name = f.string("name");
+            let image = match (f.opt_string("entry"), f.opt_string("elf")) {
+                (Some(entry), None) => Image::Entry(entry),
+                (None, Some(elf)) => Image::Elf { name: elf, slot: f.int("elf_slot") },
+                _ => {
+                    f.errors.push(format!("{}: needs exactly one of `entry` or `elf`", f.what));
+                    Image::Entry(String::new())
+                }
+            };
+            let priority = f.int("priority");
+            let budget = f.int("budget");
+            let heartbeat = f.int_or("heartbeat", 0);
+            let partition = f.int("partition");
+            let mem_quota = f.int_or("mem_quota", 0);
+            let caps = f.strings("caps");
+            let send = f.ints("send");
+            let recv = f.ints("recv");
+            let irqs = f.ints("irqs");
+            let devs = f.strings("devices");
+            let mut badges = Vec::new();
+            for b in f.tables("badges") {
+                let mut bf = fields(&b, format!("task `{name}` badge"), f.errors);
+                bf.unknown_keys(&["ep", "value"]);
+                badges.push((bf.int("ep"), bf.int("value")));
+            }
+            let mut cspace = Vec::new();
+            for s in f.tables("cspace") {
+                let mut sf = fields(&s, format!("task `{name}` cspace slot"), f.errors);
+                sf.unknown_keys(&["slot", "kind", "id", "rights"]);
+                cspace.push(Slot {
+                    slot: sf.int("slot"),
+                    kind: sf.string("kind"),
+                    id: sf.int("id"),
+                    rights: sf.strings("rights"),
+                });
+            }
+            let mut mints = Vec::new();
+            for m in f.tables("mints") {
+                let mut mf = fields(&m, format!("task `{name}` mint"), f.errors);
+                mf.unknown_keys(&["slot", "to", "rights"]);
+                mints.push(Mint { slot: mf.int("slot"), to: mf.string("to"), rights: mf.strings("rights") });
+            }
+            let timing = match t.get("timing") {
+                None => None,
+                Some(toml::Value::Table(tt)) => {
+                    let mut tf = fields(tt, format!("task `{name}` timing"), f.errors);
+                    tf.unknown_keys(&["period", "deadline", "wcet"]);
+                    Some(Timing { period: tf.int("period"), deadline: tf.int("deadline"), wcet: tf.int("wcet") })
+                }
+                Some(_) => {
+                    f.errors.push(format!("{}: `timing` must be an inline table", f.what));
+                    None
+                }
+            };
+            tasks.push(Task {
+                id, name, image, priority, budget, heartbeat, partition, mem_quota, caps, send, recv, irqs,
+                devices: devs, badges, cspace, mints, timing,
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        tasks.sort_by_key(|t| t.id);
+        Ok(System {
+            num_tasks, elf_load_base, elf_slot_size, elf_slots, grant_pages, max_grants, grant_scrub, frame_pages,
+            log_and_deny, major_frame, windows,
+            devices, grants, queues, ports, tasks, policies,
+        })
+    }
+
+    fn task(&self, name: &str) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.name == name)
+    }
+
+    fn page_table_pages(&self) -> u64 {
+        4 * self.num_tasks + 4
+    }
+
+    // ─── Validation ────────────────────────────────────────────────
+
+    fn validate(&self, root: &Path) -> Vec<String> {
+        let mut e = Vec::new();
+
+        // Kernel layout
+        if !(2..=64).contains(&self.num_tasks) {
+            e.push(format!("num_tasks = {} (need 2..=64: a task plus idle)", self.num_tasks));
+        }
+        if self.elf_load_base % 4096 != 0 || self.elf_slot_size == 0 || self.elf_slot_size % 4096 != 0 {
+            e.push("ELF load base and slot size must be non-zero multiples of 4 KiB".into());
+        }
+        let elf_end = self.elf_load_base + self.elf_slots * self.elf_slot_size;
+        if elf_end > IPC_BUFFER_VA {
+            e.push(format!("ELF load region ends at {elf_end:#x}, past the IPC buffer alias {IPC_BUFFER_VA:#x}"));
+        }
+        if !(1..=64).contains(&self.grant_pages) {
+            e.push(format!("grant_pages = {} (the grant pool holds 1..=64 pages)", self.grant_pages));
+        }
+        if self.max_grants == 0 {
+            e.push("max_grants must be at least 1".into());
+        }
+        if self.frame_pages > 255 {
+            e.push(format!("frame_pages = {} (the frame pool holds 0..=255 pages)", self.frame_pages));
+        }
+        if self.grant_scrub > 0xFF {
+            e.push(format!("grant_scrub = {:#x} (a byte: 0x00..=0xff)", self.grant_scrub));
+        }
+
+        // Devices
+        let mut seen = BTreeMap::new();
+        for d in &self.devices {
+            if !is_ident(&d.name) {
+                e.push(format!("device `{}`: name must be an identifier", d.name));
+            }
+            if seen.insert(d.name.as_str(), ()).is_some() {
+                e.push(format!("device `{}` declared twice", d.name));
+            }
+            if GIC_L2_INDICES.contains(&d.l2_index) {
+                e.push(format!("device `{}`: L2 index {} is GIC MMIO, never EL0-mappable", d.name, d.l2_index));
+            }
+            if d.l2_index >= 512 {
+                e.push(format!("device `{}`: L2 index {} out of range", d.name, d.l2_index));
+            }
+            if d.intid < MIN_SPI_INTID {
+                e.push(format!("device `{}`: INTID {} is not an SPI (< {MIN_SPI_INTID})", d.name, d.intid));
+            }
+        }
+        for (i, a) in self.devices.iter().enumerate() {
+            for b in &self.devices[i + 1..] {
+                if a.l2_index == b.l2_index {
+                    e.push(format!("devices `{}` and `{}` share L2 index {}", a.name, b.name, a.l2_index));
+                }
+                if a.intid == b.intid {
+                    e.push(format!("devices `{}` and `{}` share INTID {}", a.name, b.name, a.intid));
+                }
+            }
+        }
+
+        // Partition schedule: ordered, non-overlapping windows inside the frame
+        if self.major_frame == 0 {
+            e.push("[schedule]: major_frame must be at least 1 tick".into());
+        }
+        if self.windows.is_empty() || self.windows.len() > MAX_PARTITION_WINDOWS {
+            e.push(format!("[schedule]: {} windows (need 1..={MAX_PARTITION_WINDOWS})", self.windows.len()));
+        }
+        let mut end = 0;
+        for w in &self.windows {
+            let at = format!("[schedule]: window at offset {}", w.offset);
+            if w.partition >= NUM_PARTITIONS {
+                e.push(format!("{at}: partition {} ≥ {NUM_PARTITIONS}", w.partition));
+            }
+            if w.duration == 0 {
+                e.push(format!("{at}: duration must be at least 1 tick"));
+            }
+            if w.offset < end {
+                e.push(format!("{at}: overlaps the previous window or is out of order"));
+            }
+            end = w.offset.saturating_add(w.duration);
+            if end > self.major_frame {
+                e.push(format!("{at}: ends at {end}, past major_frame {}", self.major_frame));
+            }
+        }
+
+        // Queues and sampling ports: each configured one has both ends,
+        // and every QUEUE_* / SAMPLE_* capability or slot names a configured one
+        for (i, q) in self.queues.iter().enumerate() {
+            let who = format!("queue {}", q.id);
+            if q.id >= MAX_QUEUES {
+                e.push(format!("{who}: id ≥ {MAX_QUEUES}"));
+            }
+            if self.queues[i + 1..].iter().any(|o| o.id == q.id) {
+                e.push(format!("{who} declared twice"));
+            }
+            if !(1..=QUEUE_MAX_DEPTH).contains(&q.depth) {
+                e.push(format!("{who}: depth {} (need 1..={QUEUE_MAX_DEPTH})", q.depth));
+            }
+            if !(1..=MSG_REGS).contains(&q.msg_words) {
+                e.push(format!("{who}: msg_words {} (need 1..={MSG_REGS})", q.msg_words));
+            }
+            for (end, right) in [("SEND", "send"), ("RECV", "recv")] {
+                if !self.tasks.iter().any(|t| t.holds(&format!("QUEUE_{end}_Q{}", q.id), "queue", q.id, right)) {
+                    e.push(format!("{who}: no task holds QUEUE_{end}_Q{} or a `{right}` queue slot", q.id));
+                }
+            }
+        }
+        for (i, p) in self.ports.iter().enumerate() {
+            let who = format!("sampling port {}", p.id);
+            if p.id >= MAX_SAMPLING_PORTS {
+                e.push(format!("{who}: id ≥ {MAX_SAMPLING_PORTS}"));
+            }
+            if self.ports[i + 1..].iter().any(|o| o.id == p.id) {
+                e.push(format!("{who} declared twice"));
+            }
+            if !(1..=MSG_REGS).contains(&p.msg_words) {
+                e.push(format!("{who}: msg_words {} (need 1..={MSG_REGS})", p.msg_words));
+            }
+            if p.refresh_period == 0 {
+                e.push(format!("{who}: refresh_period must be at least 1 tick"));
+            }
+            for (end, right) in [("WRITE", "send"), ("READ", "recv")] {
+                if !self.tasks.iter().any(|t| t.holds(&format!("SAMPLE_{end}_P{}", p.id), "sampling_port", p.id, right)) {
+                    e.push(format!("{who}: no task holds SAMPLE_{end}_P{} or a `{right}` sampling_port slot", p.id));
+                }
+            }
+        }
+
+        // Tasks
+        if self.task("idle").map(|t| t.id) != Some(self.num_tasks.wrapping_sub(1)) {
+            e.push(format!("an `idle` task must hold the last id ({})", self.num_tasks.wrapping_sub(1)));
+        }
+        for (i, t) in self.tasks.iter().enumerate() {
+            let who = format!("task `{}`", t.name);
+            if !is_ident(&t.name) {
+                e.push(format!("{who}: name must be an identifier"));
+            }
+            if t.id >= self.num_tasks {
+                e.push(format!("{who}: id {} ≥ num_tasks {}", t.id, self.num_tasks));
+            }
+            if let Some(other) = self.tasks[i + 1..].iter().find(|o| o.id == t.id || o.name == t.name) {
+                e.push(format!("{who}: id or name reused by task `{}`", other.name));
+            }
+            match &t.image {
+                Image::Entry(entry) if !is_ident(entry) => e.push(format!("{who}: bad entry `{entry}`")),
+                Image::Entry(_) => {}
+                Image::Elf { name, slot } => {
+                    if *slot >= self.elf_slots {
+                        e.push(format!("{who}: ELF slot {slot} ≥ elf_slots {}", self.elf_slots));
+                    }
+                    if let Some(o) = self.tasks[i + 1..].iter().find(|o| matches!(&o.image, Image::Elf { slot: s, .. } if s == slot)) {
+                        e.push(format!("{who}: ELF slot {slot} also used by task `{}`", o.name));
+                    }
+                    self.check_elf(root, &who, name, *slot, &mut e);
+                }
+            }
+            if t.priority > MAX_PRIORITY {
+                e.push(format!("{who}: priority {} > {MAX_PRIORITY}", t.priority));
+            }
+            if t.partition >= NUM_PARTITIONS {
+                e.push(format!("{who}: partition {} ≥ {NUM_PARTITIONS}", t.partition));
+            }
+            // idle runs in the gaps between windows, wherever it is placed
+            if t.name != "idle" && !self.windows.iter().any(|w| w.partition == t.partition) {
+                e.push(format!("{who}: partition {} has no window in [schedule]", t.partition));
+            }
+            for c in &t.caps {
+                if !is_ident(c) || c.starts_with("CAP_") {
+                    e.push(format!("{who}: capability `{c}` (write names without the CAP_ prefix)"));
+                }
+                if let Some(q) = c.strip_prefix("QUEUE_SEND_Q").or_else(|| c.strip_prefix("QUEUE_RECV_Q")) {
+                    if !self.queues.iter().any(|cfg| cfg.id.to_string() == q) {
+                        e.push(format!("{who}: capability {c} names queue {q}, which has no [[queue]]"));
+                    }
+                }
+                if let Some(p) = c.strip_prefix("SAMPLE_WRITE_P").or_else(|| c.strip_prefix("SAMPLE_READ_P")) {
+                    if !self.ports.iter().any(|cfg| cfg.id.to_string() == p) {
+                        e.push(format!("{who}: capability {c} names sampling port {p}, which has no [[sampling_port]]"));
+                    }
+                }
+            }
+            for &ep in t.send.iter().chain(&t.recv) {
+                if ep >= MAX_ENDPOINTS {
+                    e.push(format!("{who}: endpoint {ep} ≥ {MAX_ENDPOINTS}"));
+                }
+            }
+            for &ep in &t.send {
+                if !self.tasks.iter().any(|r| r.receives_on(ep)) {
+                    e.push(format!("{who}: sends on endpoint {ep}, but no task receives on it"));
+                }
+            }
+            for &(ep, _) in &t.badges {
+                if ep >= MAX_ENDPOINTS {
+                    e.push(format!("{who}: badge on endpoint {ep} ≥ {MAX_ENDPOINTS}"));
+                } else if !self.tasks.iter().any(|r| r.receives_on(ep)) {
+                    e.push(format!("{who}: badge on endpoint {ep}, but no task receives on it"));
+                }
+            }
+            for &intid in &t.irqs {
+                match self.devices.iter().find(|d| d.intid == intid) {
+                    None => e.push(format!("{who}: IRQ {intid} belongs to no declared device")),
+                    Some(d) if !t.devices.contains(&d.name) => {
+                        e.push(format!("{who}: binds IRQ {intid} without mapping its device `{}`", d.name))
+                    }
+                    Some(_) => {}
+                }
+                if let Some(o) = self.tasks[i + 1..].iter().find(|o| o.irqs.contains(&intid)) {
+                    e.push(format!("{who}: IRQ {intid} also bound by task `{}`", o.name));
+                }
+            }
+            for d in &t.devices {
+                if !self.devices.iter().any(|dev| &dev.name == d) {
+                    e.push(format!("{who}: unknown device `{d}`"));
+                }
+            }
+            for (j, s) in t.cspace.iter().enumerate() {
+                if s.slot >= CSPACE_SLOTS {
+                    e.push(format!("{who}: cspace slot {} ≥ {CSPACE_SLOTS}", s.slot));
+                }
+                if t.cspace[j + 1..].iter().any(|o| o.slot == s.slot) {
+                    e.push(format!("{who}: cspace slot {} filled twice", s.slot));
+                }
+                if obj_kind(&s.kind).is_none() {
+                    e.push(format!("{who}: cspace slot {}: unknown kind `{}`", s.slot, s.kind));
+                }
+                for r in &s.rights {
+                    if right(r).is_none() {
+                        e.push(format!("{who}: cspace slot {}: unknown right `{r}`", s.slot));
+                    }
+                }
+                if s.kind == "endpoint" && s.id >= MAX_ENDPOINTS {
+                    e.push(format!("{who}: cspace slot {}: endpoint {} ≥ {MAX_ENDPOINTS}", s.slot, s.id));
+                }
+                if s.kind == "queue" && !self.queues.iter().any(|q| q.id == s.id) {
+                    e.push(format!("{who}: cspace slot {}: queue {} has no [[queue]]", s.slot, s.id));
+                }
+                if s.kind == "sampling_port" && !self.ports.iter().any(|p| p.id == s.id) {
+                    e.push(format!("{who}: cspace slot {}: sampling port {} has no [[sampling_port]]", s.slot, s.id));
+                }
+            }
+            for m in &t.mints {
+                let src = t.cspace.iter().find(|s| s.slot == m.slot);
+                if !src.is_some_and(|s| s.rights.iter().any(|r| r == "grant")) {
+                    e.push(format!("{who}: mint from slot {} needs a slot holding the `grant` right", m.slot));
+                }
+                if !t.caps.iter().any(|c| c == "DELEGATE") {
+                    e.push(format!("{who}: mint needs the DELEGATE capability"));
+                }
+                if self.task(&m.to).is_none() || m.to == t.name {
+                    e.push(format!("{who}: mint to unknown or own task `{}`", m.to));
+                }
+                for r in &m.rights {
+                    if right(r).is_none() {
+                        e.push(format!("{who}: mint: unknown right `{r}`"));
+                    }
+                }
+            }
+            if let Some(tm) = &t.timing {
+                if !(tm.wcet > 0 && tm.wcet <= tm.deadline && tm.deadline <= tm.period) {
+                    e.push(format!("{who}: timing needs 0 < wcet ≤ deadline ≤ period"));
+                }
+            }
+        }
+
+        // Frame quotas: every task can always map its whole quota's worth
+        let quotas: u64 = self.tasks.iter().map(|t| t.mem_quota).sum();
+        if quotas > self.frame_pages {
+            e.push(format!("task mem_quota total {quotas} pages, the frame pool has frame_pages = {}", self.frame_pages));
+        }
+
+        // Grants
+        let declared: u64 = self.grants.iter().map(|g| g.pages).sum();
+        if declared > self.grant_pages {
+            e.push(format!("declared grants need {declared} pages, the pool has grant_pages = {}", self.grant_pages));
+        }
+        for (i, g) in self.grants.iter().enumerate() {
+            if g.id >= self.max_grants {
+                e.push(format!("grant {}: id ≥ max_grants {}", g.id, self.max_grants));
+            }
+            if g.pages == 0 {
+                e.push(format!("grant {}: pages must be at least 1", g.id));
+            }
+            if self.grants[i + 1..].iter().any(|o| o.id == g.id) {
+                e.push(format!("grant {} declared twice", g.id));
+            }
+            if g.peers.is_empty() {
+                e.push(format!("grant {}: needs `peer` or a non-empty `peers`", g.id));
+            }
+            for who in std::iter::once(&g.owner).chain(&g.peers) {
+                if self.task(who).is_none() {
+                    e.push(format!("grant {}: unknown task `{who}`", g.id));
+                }
+            }
+            if g.peers.contains(&g.owner) {
+                e.push(format!("grant {}: owner and peer are the same task", g.id));
+            }
+            for (j, p) in g.peers.iter().enumerate() {
+                if g.peers[j + 1..].contains(p) {
+                    e.push(format!("grant {}: peer `{p}` listed twice", g.id));
+                }
+            }
+        }
+
+        // Flow policies
+        if self.num_tasks as usize + self.devices.len() > 64 {
+            e.push("tasks + devices exceed the 64-node flow graph".into());
+        }
+        for p in &self.policies {
+            if p.from.is_empty() || p.to.is_empty() {
+                e.push(format!("policy `{}`: needs non-empty `from` and `to`", p.name));
+            }
+            for node in p.from.iter().chain(&p.to) {
+                if self.node(node).is_none() {
+                    e.push(format!("policy `{}`: unknown task or device `{node}`", p.name));
+                }
+            }
+        }
+        e
+    }
+
+    /// Flow-graph node of a task or device name (policy::SystemModel order).
+    fn node(&self, name: &str) -> Option<u64> {
+        self.task(name)
+            .map(|t| t.id)
+            .or_else(|| self.devices.iter().position(|d| d.name == name).map(|d| self.num_tasks + d as u64))
+    }
+
+    fn node_name(&self, node: u64) -> &str {
+        match self.tasks.iter().find(|t| t.id == node) {
+            Some(t) => &t.name,
+            None => &self.devices[(node - self.num_tasks) as usize].name,
+        }
+    }
+
+    /// ELF image fits its slot and its user linker script links it at the
+    /// slot's load address. Skipped for parts not built yet (host tests).
+    fn check_elf(&self, root: &Path, who: &str, name: &str, slot: u64, e: &mut Vec<String>) {
+        let image = root.join(USER_IMAGE_DIR).join(name);
+        println!("cargo:rerun-if-changed={}", image.display());
+        if let Ok(meta) = fs::metadata(&image) {
+            if meta.len() > self.elf_slot_size {
+                e.push(format!("{who}: ELF `{name}` is {} bytes, slot holds {}", meta.len(), self.elf_slot_size));
+            }
+        }
+        let script = root.join("user").join(name).join("linker.ld");
+        println!("cargo:rerun-if-changed={}", script.display());
+        let Ok(text) = fs::read_to_string(&script) else {
+            e.push(format!("{who}: no user/{name}/linker.ld"));
+            return;
+        };
+        let want = self.elf_load_base + slot * self.elf_slot_size;
+        match link_address(&text) {
+            Some(addr) if addr == want => {}
+            Some(addr) => e.push(format!(
+                "{who}: user/{name}/linker.ld links at {addr:#x}, ELF slot {slot} loads at {want:#x}"
+            )),
+            None => e.push(format!("{who}: no `. = <address>;` in user/{name}/linker.ld")),
+        }
+    }
+
+    // ─── Emitters ──────────────────────────────────────────────────
+
+    fn emit_sysconf(&self) -> String {
+        let mut s = header();
+        let _ = writeln!(s, "/// Number of task slots (TCBs, stacks, address spaces)");
+        let _ = writeln!(s, "pub const NUM_TASKS: usize = {};", self.num_tasks);
+        let _ = writeln!(s, "/// Base address of the ELF load region (linker.ld .elf_load)");
+        let _ = writeln!(s, "pub const ELF_LOAD_BASE: u64 = {:#x};", self.elf_load_base);
+        let _ = writeln!(s, "/// Bytes per ELF load slot");
+        let _ = writeln!(s, "pub const ELF_SLOT_SIZE: usize = {:#x};", self.elf_slot_size);
+        let _ = writeln!(s, "/// Number of ELF load slots");
+        let _ = writeln!(s, "pub const ELF_SLOTS: usize = {};", self.elf_slots);
+        let _ = writeln!(s, "/// Shared-memory grant pool in pages (linker.ld .grant_pages)");
+        let _ = writeln!(s, "pub const GRANT_PAGES: usize = {};", self.grant_pages);
+        let _ = writeln!(s, "/// Grant descriptors (grant ids 0..MAX_GRANTS)");
+        let _ = writeln!(s, "pub const MAX_GRANTS: usize = {};", self.max_grants);
+        let _ = writeln!(s, "/// Anonymous-memory frame pool in pages (linker.ld .frame_pool)");
+        let _ = writeln!(s, "pub const FRAME_PAGES: usize = {};", self.frame_pages);
+        let _ = writeln!(s, "/// Fill byte for revoked grant pages (0 = zeroize)");
+        let _ = writeln!(s, "pub const GRANT_SCRUB: u8 = {:#04x};", self.grant_scrub);
+        let _ = writeln!(s, "/// Page-table pages reserved by linker.ld (.page_tables)");
+        let _ = writeln!(s, "pub const PAGE_TABLE_PAGES: usize = {};", self.page_table_pages());
+        let _ = writeln!(s, "/// What happens to a task whose syscall is refused (audit.rs)");
+        let _ = writeln!(
+            s,
+            "pub const DENY_POLICY: crate::kernel::audit::DenyPolicy = crate::kernel::audit::DenyPolicy::{};",
+            if self.log_and_deny { "LogAndDeny" } else { "Fault" }
+        );
+        let _ = writeln!(s);
+        let _ = writeln!(s, "/// Whitelisted EL0-mappable devices — device_id indexes this table.");
+        let _ = writeln!(s, "pub const DEVICES: &[crate::mmu::DeviceInfo] = &[");
+        for (i, d) in self.devices.iter().enumerate() {
+            let _ = writeln!(
+                s,
+                "    crate::mmu::DeviceInfo {{ l2_index: {}, intid: {}, name: {:?} }}, // device_id={i}",
+                d.l2_index, d.intid, d.name
+            );
+        }
+        let _ = writeln!(s, "];");
+        s
+    }
+
+    fn emit_task_table(&self, root: &Path) -> String {
+        let n = self.num_tasks as usize;
+        let mut s = header();
+
+        let _ = writeln!(s, "// ─── Task ids ───");
+        for t in &self.tasks {
+            let _ = writeln!(s, "#[allow(dead_code)]\nconst TASK_{}: usize = {};", t.name.to_uppercase(), t.id);
+        }
+        for t in self.tasks.iter().filter(|t| !t.badges.is_empty()) {
+            let _ = writeln!(
+                s,
+                "/// Badges on task `{}`'s send rights, per endpoint\n#[allow(dead_code)]\nconst {}_BADGES: [u64; aegis_os::ipc::MAX_ENDPOINTS] = {};",
+                t.name,
+                t.name.to_uppercase(),
+                badges_expr(t)
+            );
+        }
+
+        let _ = writeln!(s, "\n// ─── Entry points (ELF tasks start at idle_entry until loaded) ───");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]\nfn task_entries() -> [u64; {n}] {{\n    [");
+        for id in 0..self.num_tasks {
+            let entry = match self.tasks.iter().find(|t| t.id == id).map(|t| &t.image) {
+                Some(Image::Entry(f)) => format!("{f} as *const () as u64"),
+                Some(Image::Elf { .. }) => "idle_entry as *const () as u64".into(),
+                None => "0".into(),
+            };
+            let label = self.tasks.iter().find(|t| t.id == id).map_or("inactive", |t| t.name.as_str());
+            let _ = writeln!(s, "        {entry}, // task {id}: {label}");
+        }
+        let _ = writeln!(s, "    ]\n}}");
+
+        let _ = writeln!(s, "\n// ─── Per-task metadata ───");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const TASK_META: [aegis_os::sched::TaskMetadata; {n}] = [");
+        for id in 0..self.num_tasks {
+            match self.tasks.iter().find(|t| t.id == id) {
+                Some(t) => self.emit_meta(&mut s, t),
+                None => {
+                    let _ = writeln!(s, "    // task {id}: inactive");
+                    let _ = writeln!(s, "    aegis_os::sched::TaskMetadata {{");
+                    let _ = writeln!(s, "        caps: 0, badges: aegis_os::ipc::NO_BADGES,");
+                    let _ = writeln!(s, "        cspace: aegis_os::cspace::EMPTY_CSPACE, priority: 0, time_budget: 0,");
+                    let _ = writeln!(s, "        heartbeat_interval: 0, partition: 0, mem_quota: 0,");
+                    let _ = writeln!(s, "        timing: aegis_os::sched::APERIODIC,");
+                    let _ = writeln!(s, "    }},");
+                }
+            }
+        }
+        let _ = writeln!(s, "];");
+
+        let _ = writeln!(s, "\n// ─── ELF images: (task_id, slot, image, name) ───");
+        let elves: Vec<&Task> = self.tasks.iter().filter(|t| matches!(t.image, Image::Elf { .. })).collect();
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const ELF_IMAGES: [(usize, usize, &[u8], &str); {}] = [", elves.len());
+        for t in &elves {
+            if let Image::Elf { name, slot } = &t.image {
+                let path = root.join(USER_IMAGE_DIR).join(name);
+                let _ = writeln!(
+                    s,
+                    "    ({}, {slot}, include_bytes!({:?}), {:?}),",
+                    t.id,
+                    path.display().to_string(),
+                    t.name
+                );
+            }
+        }
+        let _ = writeln!(s, "];");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const _: () = {{");
+        let _ = writeln!(s, "    let mut i = 0;");
+        let _ = writeln!(s, "    while i < ELF_IMAGES.len() {{");
+        let _ = writeln!(
+            s,
+            "        assert!(ELF_IMAGES[i].2.len() <= aegis_os::sysconf::ELF_SLOT_SIZE, \"ELF image larger than its load slot\");"
+        );
+        let _ = writeln!(s, "        i += 1;\n    }}\n}};");
+        self.emit_channels(&mut s);
+        self.emit_policies(&mut s);
+        s
+    }
+
+    /// Partition schedule, queue and sampling-port configs, each checked
+    /// with the kernel's own validators at compile time.
+    fn emit_channels(&self, s: &mut String) {
+        let _ = writeln!(s, "\n// ─── Partition schedule ───");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]\nconst MAJOR_FRAME: u64 = {};", self.major_frame);
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const PARTITION_WINDOWS: [aegis_os::sched::PartitionWindow; {}] = [", self.windows.len());
+        for w in &self.windows {
+            let _ = writeln!(
+                s,
+                "    aegis_os::sched::PartitionWindow {{ partition: {}, offset: {}, duration: {} }},",
+                w.partition, w.offset, w.duration
+            );
+        }
+        let _ = writeln!(s, "];");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(
+            s,
+            "const _: () = assert!(aegis_os::sched::partition_schedule_valid(&PARTITION_WINDOWS, MAJOR_FRAME), \"invalid partition schedule\");"
+        );
+
+        let _ = writeln!(s, "\n// ─── Message queues and sampling ports: (id, config) ───");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const QUEUE_CONFIGS: [(usize, aegis_os::queue::QueueConfig); {}] = [", self.queues.len());
+        for q in &self.queues {
+            let _ = writeln!(
+                s,
+                "    ({}, aegis_os::queue::QueueConfig {{ depth: {}, msg_words: {}, policy: aegis_os::queue::OverflowPolicy::{} }}),",
+                q.id, q.depth, q.msg_words, q.overflow
+            );
+        }
+        let _ = writeln!(s, "];");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const SAMPLING_CONFIGS: [(usize, aegis_os::sampling::SamplingConfig); {}] = [", self.ports.len());
+        for p in &self.ports {
+            let _ = writeln!(
+                s,
+                "    ({}, aegis_os::sampling::SamplingConfig {{ msg_words: {}, refresh_period: {} }}),",
+                p.id, p.msg_words, p.refresh_period
+            );
+        }
+        let _ = writeln!(s, "];");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const _: () = {{");
+        let _ = writeln!(s, "    let mut i = 0;");
+        let _ = writeln!(s, "    while i < QUEUE_CONFIGS.len() {{");
+        let _ = writeln!(s, "        assert!(aegis_os::queue::queue_config_valid(&QUEUE_CONFIGS[i].1), \"invalid queue config\");");
+        let _ = writeln!(s, "        i += 1;\n    }}");
+        let _ = writeln!(s, "    let mut i = 0;");
+        let _ = writeln!(s, "    while i < SAMPLING_CONFIGS.len() {{");
+        let _ = writeln!(
+            s,
+            "        assert!(aegis_os::sampling::sampling_config_valid(&SAMPLING_CONFIGS[i].1), \"invalid port config\");"
+        );
+        let _ = writeln!(s, "        i += 1;\n    }}\n}};");
+    }
+
+    /// Flow graph of the generated TASK_META and one compile-time check per
+    /// (source, sink) pair of every policy — a violation names the pair.
+    fn emit_policies(&self, s: &mut String) {
+        let _ = writeln!(s, "\n// ─── Flow policies (aegis_os::policy) ───");
+        let id = |name: &str| self.task(name).map_or(0, |t| t.id);
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(s, "const FLOW_MODEL: aegis_os::policy::SystemModel<'static> = aegis_os::policy::SystemModel {{");
+        let _ = writeln!(s, "    tasks: &TASK_META,\n    devices: aegis_os::mmu::DEVICES,");
+        let _ = writeln!(s, "    grants: &[");
+        for g in &self.grants {
+            for peer in &g.peers {
+                let _ = writeln!(
+                    s,
+                    "        aegis_os::policy::GrantDecl {{ id: {}, owner: {}, peer: {} }}, // {} → {}",
+                    g.id, id(&g.owner), id(peer), g.owner, peer
+                );
+            }
+        }
+        let _ = writeln!(s, "    ],\n    mints: &[");
+        for t in &self.tasks {
+            for m in &t.mints {
+                let rights = if m.rights.is_empty() {
+                    "0".to_string()
+                } else {
+                    m.rights
+                        .iter()
+                        .map(|r| format!("aegis_os::cspace::{}", right(r).unwrap_or("0")))
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                };
+                let _ = writeln!(
+                    s,
+                    "        aegis_os::policy::MintDecl {{ from: {}, slot: {}, to: {}, rights: {rights} }}, // {} → {}",
+                    t.id, m.slot, id(&m.to), t.name, m.to
+                );
+            }
+        }
+        let _ = writeln!(s, "    ],\n}};");
+        let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+        let _ = writeln!(
+            s,
+            "const FLOW_REACH: [aegis_os::policy::NodeSet; aegis_os::policy::MAX_FLOW_NODES] =\n    aegis_os::policy::reachability(&aegis_os::policy::flow_graph(&FLOW_MODEL));"
+        );
+        for p in &self.policies {
+            for from in &p.from {
+                for to in &p.to {
+                    let (Some(a), Some(b)) = (self.node(from), self.node(to)) else { continue };
+                    let _ = writeln!(s, "#[cfg(target_arch = \"aarch64\")]");
+                    let _ = writeln!(
+                        s,
+                        "const _: () = assert!(!aegis_os::policy::may_influence(&FLOW_REACH, {a}, {b}), {:?});",
+                        format!("flow policy `{}` violated: {} may influence {}", p.name, self.node_name(a), self.node_name(b))
+                    );
+                }
+            }
+        }
+    }
+
+    fn emit_meta(&self, s: &mut String, t: &Task) {
+        let mut caps: Vec<String> = t.caps.iter().map(|c| format!("CAP_{c}")).collect();
+        let mut add = |c: String| {
+            if !caps.contains(&c) {
+                caps.push(c);
+            }
+        };
+        for ep in &t.send {
+            add(format!("CAP_IPC_SEND_EP{ep}"));
+        }
+        for ep in &t.recv {
+            add(format!("CAP_IPC_RECV_EP{ep}"));
+        }
+        if !t.irqs.is_empty() {
+            add("CAP_IRQ_BIND".into());
+            add("CAP_IRQ_ACK".into());
+        }
+        if !t.devices.is_empty() {
+            add("CAP_DEVICE_MAP".into());
+        }
+        if self.grants.iter().any(|g| g.owner == t.name) {
+            add("CAP_GRANT_CREATE".into());
+            add("CAP_GRANT_REVOKE".into());
+        }
+        if t.mem_quota > 0 {
+            add("CAP_MAP_ANON".into());
+        }
+        let caps = if caps.is_empty() {
+            "0".to_string()
+        } else {
+            caps.iter().map(|c| format!("aegis_os::cap::{c}")).collect::<Vec<_>>().join("\n            | ")
+        };
+
+        let badges = if t.badges.is_empty() {
+            "aegis_os::ipc::NO_BADGES".to_string()
+        } else {
+            format!("{}_BADGES", t.name.to_uppercase())
+        };
+
+        let cspace = if t.cspace.is_empty() {
+            "aegis_os::cspace::EMPTY_CSPACE".to_string()
+        } else {
+            let mut c = String::from("{\n            let mut cs = aegis_os::cspace::EMPTY_CSPACE;\n");
+            for sl in &t.cspace {
+                let rights = if sl.rights.is_empty() {
+                    "0".to_string()
+                } else {
+                    sl.rights
+                        .iter()
+                        .map(|r| format!("aegis_os::cspace::{}", right(r).unwrap_or("0")))
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                };
+                let _ = writeln!(
+                    c,
+                    "            cs[{}] = aegis_os::cspace::slot(aegis_os::cspace::ObjKind::{}, {}, {rights});",
+                    sl.slot,
+                    obj_kind(&sl.kind).unwrap_or("Null"),
+                    sl.id
+                );
+            }
+            c.push_str("            cs\n        }");
+            c
+        };
+
+        let timing = match &t.timing {
+            Some(tm) => format!(
+                "aegis_os::sched::PeriodicParams {{ period: {}, deadline: {}, wcet: {} }}",
+                tm.period, tm.deadline, tm.wcet
+            ),
+            None => "aegis_os::sched::APERIODIC".into(),
+        };
+
+        let _ = writeln!(s, "    // task {}: {}", t.id, t.name);
+        let _ = writeln!(s, "    aegis_os::sched::TaskMetadata {{");
+        let _ = writeln!(s, "        caps: {caps},");
+        let _ = writeln!(s, "        badges: {badges},");
+        let _ = writeln!(s, "        cspace: {cspace},");
+        let _ = writeln!(s, "        priority: {},", t.priority);
+        let _ = writeln!(s, "        time_budget: {},", t.budget);
+        let _ = writeln!(s, "        heartbeat_interval: {},", t.heartbeat);
+        let _ = writeln!(s, "        partition: {},", t.partition);
+        let _ = writeln!(s, "        mem_quota: {},", t.mem_quota);
+        let _ = writeln!(s, "        timing: {timing},");
+        let _ = writeln!(s, "    }},");
+    }
+
+    fn emit_layout(&self) -> String {
+        let mut s = String::from("/* Generated by build.rs from system.toml — do not edit. */\n");
+        let _ = writeln!(s, "AEGIS_PAGE_TABLE_PAGES = {};", self.page_table_pages());
+        let _ = writeln!(s, "AEGIS_NUM_TASKS = {};", self.num_tasks);
+        let _ = writeln!(s, "AEGIS_GRANT_PAGES = {};", self.grant_pages);
+        let _ = writeln!(s, "AEGIS_FRAME_PAGES = {};", self.frame_pages);
+        let _ = writeln!(s, "AEGIS_ELF_LOAD_BASE = {:#x};", self.elf_load_base);
+        let _ = writeln!(s, "AEGIS_ELF_SLOT_SIZE = {:#x};", self.elf_slot_size);
+        let _ = writeln!(s, "AEGIS_ELF_SLOTS = {};", self.elf_slots);
+        s
+    }
+}
+
+impl Task {
+    fn receives_on(&self, ep: u64) -> bool {
+        self.recv.contains(&ep)
+            || self.cspace.iter().any(|s| s.kind == "endpoint" && s.id == ep && s.rights.iter().any(|r| r == "recv"))
+    }
+
+    /// Capability `cap`, or a `kind` slot on object `id` with `right`.
+    fn holds(&self, cap: &str, kind: &str, id: u64, right: &str) -> bool {
+        self.caps.iter().any(|c| c == cap)
+            || self.cspace.iter().any(|s| s.kind == kind && s.id == id && s.rights.iter().any(|r| r == right))
+    }
+}
+
+fn header() -> String {
+    String::from("// Generated by build.rs from system.toml — do not edit.\n\n")
+}
+
+fn badges_expr(t: &Task) -> String {
+    let mut b = vec!["0".to_string(); MAX_ENDPOINTS as usize];
+    for &(ep, value) in &t.badges {
+        if let Some(slot) = b.get_mut(ep as usize) {
+            *slot = format!("{value:#x}");
+        }
+    }
+    format!("[{}]", b.join(", "))
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn obj_kind(kind: &str) -> Option<&'static str> {
+    Some(match kind {
+        "endpoint" => "Endpoint",
+        "notification" => "Notification",
+        "grant" => "Grant",
+        "irq" => "Irq",
+        "device" => "Device",
+        "queue" => "Queue",
+        "sampling_port" => "SamplingPort",
+        _ => return None,
+    })
+}
+
+fn right(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "send" => "RIGHT_SEND",
+        "recv" => "RIGHT_RECV",
+        "read" => "RIGHT_READ",
+        "write" => "RIGHT_WRITE",
+        "grant" => "RIGHT_GRANT",
+        _ => return None,
+    })
+}
+
+/// First `. = <hex>;` location assignment in a linker script.
+fn link_address(script: &str) -> Option<u64> {
+    script.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(". =")?.trim().strip_suffix(';')?.trim();
+        u64::from_str_radix(rest.strip_prefix("0x")?.replace('_', "").as_str(), 16).ok()
+    })
+}
+
+// ─── Minimal TOML reader ───────────────────────────────────────────
+
+mod toml {
+    use std::collections::BTreeMap;
+
+    pub type Table = BTreeMap<String, Value>;
+
+    #[derive(Clone, Debug)]
+    pub enum Value {
+        Int(u64),
+        Str(String),
+        Array(Vec<Value>),
+        Table(Table),
+    }
+
+    /// Parse a document: `[table]`, `[[array.of.tables]]` (one level),
+    /// `key = value` with values spanning lines while brackets are open.
+    pub fn parse(text: &str) -> Result<Table, String> {
+        let mut root = Table::new();
+        // Where `key = value` lines go: root, a table, or the last element
+        // of an array of tables.
+        let mut target: Option<(String, bool)> = None;
+        let mut lines = text.lines().enumerate();
+
+        while let Some((n, raw)) = lines.next() {
+            let mut line = strip_comment(raw).trim().to_string();
+            if line.is_empty() {
+                continue;
+            }
+            let at = |msg: &str| format!("line {}: {msg}", n + 1);
+
+            if let Some(name) = line.strip_prefix("[[").and_then(|l| l.strip_suffix("]]")) {
+                let name = name.trim().to_string();
+                let entry = root.entry(name.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                match entry {
+                    Value::Array(items) => items.push(Value::Table(Table::new())),
+                    _ => return Err(at(&format!("`{name}` is not an array of tables"))),
+                }
+                target = Some((name, true));
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim().to_string();
+                if root.insert(name.clone(), Value::Table(Table::new())).is_some() {
+                    return Err(at(&format!("table `{name}` defined twice")));
+                }
+                target = Some((name, false));
+                continue;
+            }
+
+            // Continue multi-line values until brackets balance
+            while depth(&line) > 0 {
+                match lines.next() {
+                    Some((_, more)) => {
+                        line.push(' ');
+                        line.push_str(strip_comment(more).trim());
+                    }
+                    None => return Err(at("unterminated array or inline table")),
+                }
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| at("expected `key = value`"))?;
+            let key = key.trim().to_string();
+            let mut p = Parser { s: value.trim().as_bytes(), i: 0 };
+            let value = p.value().map_err(|e| at(&e))?;
+            p.ws();
+            if p.i != p.s.len() {
+                return Err(at("trailing characters after value"));
+            }
+
+            let table = match &target {
+                None => &mut root,
+                Some((name, is_array)) => match root.get_mut(name) {
+                    Some(Value::Table(t)) if !is_array => t,
+                    Some(Value::Array(items)) => match items.last_mut() {
+                        Some(Value::Table(t)) => t,
+                        _ => unreachable!(),
+                    },
+                    _ => unreachable!(),
+                },
+            };
+            if table.insert(key.clone(), value).is_some() {
+                return Err(at(&format!("key `{key}` defined twice")));
+            }
+        }
+        Ok(root)
+    }
+
+    fn strip_comment(line: &str) -> &str {
+        let mut in_str = false;
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => in_str = !in_str,
+                '#' if !in_str => return &line[..i],
+                _ => {}
+            }
+        }
+        line
+    }
+
+    /// Open `[`/`{` minus closed ones, outside strings.
+    fn depth(line: &str) -> i32 {
+        let mut d = 0;
+        let mut in_str = false;
+        for c in line.chars() {
+            match c {
+                '"' => in_str = !in_str,
+                '[' | '{' if !in_str => d += 1,
+                ']' | '}' if !in_str => d -= 1,
+                _ => {}
+            }
+        }
+        d
+    }
+
+    struct Parser<'a> {
+        s: &'a [u8],
+        i: usize,
+    }
+
+    impl Parser<'_> {
+        fn ws(&mut self) {
+            while self.i < self.s.len() && self.s[self.i].is_ascii_whitespace() {
+                self.i += 1;
+            }
+        }
+
+        fn peek(&self) -> Option<u8> {
+            self.s.get(self.i).copied()
+        }
+
+        fn eat(&mut self, c: u8) -> bool {
+            self.ws();
+            if self.peek() == Some(c) {
+                self.i += 1;
+                true
+            } else {
+                false
+            }
+        }
+
+        fn value(&mut self) -> Result<Value, String> {
+            self.ws();
+            match self.peek() {
+                Some(b'"') => self.string().map(Value::Str),
+                Some(b'[') => self.array(),
+                Some(b'{') => self.inline_table(),
+                Some(c) if c.is_ascii_digit() || c == b'+' => self.integer(),
+                Some(b'-') => Err("negative integers are not used in system.toml".into()),
+                _ => Err("expected a value".into()),
+            }
+        }
+
+        fn string(&mut self) -> Result<String, String> {
+            self.i += 1; // opening quote
+            let mut out = String::new();
+            while let Some(c) = self.peek() {
+                self.i += 1;
+                match c {
+                    b'"' => return Ok(out),
+                    b'\\' => {
+                        let e = self.peek().ok_or("unterminated escape")?;
+                        self.i += 1;
+                        out.push(match e {
+                            b'n' => '\n',
+                            b't' => '\t',
+                            b'"' => '"',
+                            b'\\' => '\\',
+                            _ => return Err(format!("unsupported escape `\\{}`", e as char)),
+                        });
+                    }
+                    _ => out.push(c as char),
+                }
+            }
+            Err("unterminated string".into())
+        }
+
+        fn integer(&mut self) -> Result<Value, String> {
+            let start = self.i;
+            while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == b'_' || c == b'+') {
+                self.i += 1;
+            }
+            let text: String = std::str::from_utf8(&self.s[start..self.i])
+                .unwrap()
+                .trim_start_matches('+')
+                .replace('_', "");
+            let (digits, radix) = match text.get(..2) {
+                Some("0x") => (&text[2..], 16),
+                Some("0o") => (&text[2..], 8),
+                Some("0b") => (&text[2..], 2),
+                _ => (text.as_str(), 10),
+            };
+            u64::from_str_radix(digits, radix)
+                .map(Value::Int)
+                .map_err(|_| format!("bad integer `{text}`"))
+        }
+
+        fn array(&mut self) -> Result<Value, String> {
+            self.i += 1; // [
+            let mut items = Vec::new();
+            loop {
+                if self.eat(b']') {
+                    return Ok(Value::Array(items));
+                }
+                items.push(self.value()?);
+                if !self.eat(b',') {
+                    return if self.eat(b']') { Ok(Value::Array(items)) } else { Err("expected `,` or `]`".into()) };
+                }
+            }
+        }
+
+        fn inline_table(&mut self) -> Result<Value, String> {
+            self.i += 1; // {
+            let mut table = Table::new();
+            if self.eat(b'}') {
+                return Ok(Value::Table(table));
+            }
+            loop {
+                self.ws();
+                let start = self.i;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == b'_' || c == b'-') {
+                    self.i += 1;
+                }
+                let key = std::str::from_utf8(&self.s[start..self.i]).unwrap().to_string();
+                if key.is_empty() || !self.eat(b'=') {
+                    return Err("expected `key = value` in inline table".into());
+                }
+                let value = self.value()?;
+                if table.insert(key.clone(), value).is_some() {
+                    return Err(format!("key `{key}` defined twice"));
+                }
+                if self.eat(b'}') {
+                    return Ok(Value::Table(table));
+                }
+                if !self.eat(b',') {
+                    return Err("expected `,` or `}`".into());
+                }
+            }
+        }
+    }
+}
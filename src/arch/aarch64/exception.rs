@@ -344,7 +344,11 @@ pub extern "C" fn exception_dispatch_irq(frame: &mut TrapFrame) {
 
     match intid {
         crate::timer::TIMER_INTID => crate::timer::tick_handler(frame),
-        _ => crate::irq::irq_route(intid, frame),
+        _ => {
+            crate::irq::irq_route(intid, frame);
+            // Tickless: the IRQ may have woken a task while idle slept
+            crate::timer::wake_from_idle(frame);
+        }
     }
 
     crate::gic::end_interrupt(intid);
@@ -364,12 +368,34 @@ pub extern "C" fn exception_dispatch_serror(_frame: &mut TrapFrame) {
 #[cfg(target_arch = "aarch64")]
 fn handle_svc(frame: &mut TrapFrame, _esr: u64) {
     let syscall_nr = frame.x[7];
-    let ep_id = frame.x[6];
+    let mut ep_id = frame.x[6];
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *crate::sched::CURRENT.get() };
+
+    // ─── CSpace: object named through a capability slot? ───────────
+    let mut slot_id = None;
+    let required = match crate::cspace::resolve(current, syscall_nr, frame) {
+        crate::cspace::Authority::Bitmask => crate::cap::cap_for_syscall(syscall_nr, ep_id),
+        crate::cspace::Authority::Slot { reg, id, rights } => {
+            slot_id = Some((reg, id, rights));
+            crate::cspace::coarse_caps(syscall_nr)
+        }
+        crate::cspace::Authority::Denied(err) => {
+            uart_print("!!! SLOT DENIED: task ");
+            uart_print_hex(current as u64);
+            uart_print(" syscall #");
+            uart_print_hex(syscall_nr);
+            uart_print(": ");
+            uart_print(crate::cspace::slot_error_name(err));
+            let required = crate::cspace::coarse_caps(syscall_nr);
+            deny_syscall(frame, current, syscall_nr, required, crate::audit::DenyReason::Slot(err));
+            return;
+        }
+    };
 
     // ─── Phase G: Capability check ─────────────────────────────────
-    let required = crate::cap::cap_for_syscall(syscall_nr, ep_id);
     // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
-    let task_caps = unsafe { (*crate::sched::TCBS.get_mut())[*crate::sched::CURRENT.get()].caps };
+    let task_caps = unsafe { (*crate::sched::TCBS.get_mut())[current].caps };
 
     if !crate::cap::cap_check(task_caps, required) {
         uart_print("!!! CAP DENIED: task ");
@@ -379,27 +405,39 @@ fn handle_svc(frame: &mut TrapFrame, _esr: u64) {
         uart_print_hex(syscall_nr);
         uart_print(" needs ");
         uart_print(crate::cap::cap_name(required));
-        uart_print(" — faulting\n");
-        crate::sched::fault_current_task(frame);
+        deny_syscall(frame, current, syscall_nr, required, crate::audit::DenyReason::Caps);
         return;
     }
 
+    // Slot-addressed: hand the resolved object id to the handler. x6 is
+    // passed by value so the caller's register is preserved; x0-carried
+    // ids (grant, IRQ, device) are overwritten by the result anyway.
+    // A grant slot without RIGHT_WRITE only ever maps read-only.
+    match slot_id {
+        Some((6, id, _)) => ep_id = id,
+        Some((reg, id, rights)) => {
+            frame.x[reg] = id;
+            frame.x[3] = crate::cspace::grant_flags(syscall_nr, rights, frame.x[3]);
+        }
+        None => {}
+    }
+
     match syscall_nr {
         // SYS_YIELD = 0: voluntarily yield CPU
-        0 => crate::sched::schedule(frame),
-        // SYS_SEND = 1: send IPC message (ep_id in x6)
-        1 => crate::ipc::sys_send(frame, frame.x[6] as usize),
-        // SYS_RECV = 2: receive IPC message (ep_id in x6)
-        2 => crate::ipc::sys_recv(frame, frame.x[6] as usize),
-        // SYS_CALL = 3: send + receive (ep_id in x6)
-        3 => crate::ipc::sys_call(frame, frame.x[6] as usize),
+        0 => crate::sched::sys_yield(frame),
+        // SYS_SEND = 1: send IPC message (ep_id in x6, timeout in x4)
+        1 => crate::ipc::sys_send_timeout(frame, ep_id as usize, frame.x[4]),
+        // SYS_RECV = 2: receive IPC message (ep_id in x6, timeout in x4)
+        2 => crate::ipc::sys_recv_timeout(frame, ep_id as usize, frame.x[4]),
+        // SYS_CALL = 3: send + receive (ep_id in x6, timeout in x4)
+        3 => crate::ipc::sys_call_timeout(frame, ep_id as usize, frame.x[4]),
         // SYS_WRITE = 4: write buffer to UART (x0=buf, x1=len)
         4 => handle_sys_write(frame),
         // SYS_NOTIFY = 5: send notification to target (x6=target_id, x0=bitmask)
-        5 => handle_notify(frame),
+        5 => handle_notify(frame, ep_id as usize),
         // SYS_WAIT_NOTIFY = 6: wait for notification (returns pending bits in x0)
         6 => handle_wait_notify(frame),
-        // SYS_GRANT_CREATE = 7: create shared memory grant (x0=grant_id, x6=peer_task_id)
+        // SYS_GRANT_CREATE = 7: create shared memory grant (x0=grant_id, x6=peer_task_id, x1=pages, x2=align, x3=flags)
         7 => handle_grant_create(frame),
         // SYS_GRANT_REVOKE = 8: revoke shared memory grant (x0=grant_id)
         8 => handle_grant_revoke(frame),
@@ -413,6 +451,44 @@ fn handle_svc(frame: &mut TrapFrame, _esr: u64) {
         12 => handle_heartbeat(frame),
         // SYS_EXIT = 13: graceful task exit (x0=exit_code)
         13 => crate::sched::sys_exit(frame, frame.x[0]),
+        // SYS_SLEEP = 14: sleep for x0 ticks (returns wake-up tick in x0)
+        14 => crate::sched::sys_sleep(frame, frame.x[0]),
+        // SYS_SLEEP_UNTIL = 15: sleep until absolute tick x0 (returns wake-up tick in x0)
+        15 => crate::sched::sys_sleep_until(frame, frame.x[0]),
+        // SYS_REPLY = 16: answer pending caller via reply object (reply in x0–x3)
+        16 => crate::ipc::sys_reply(frame),
+        // SYS_REPLY_RECV = 17: reply, then receive on ep_id in x6 (timeout in x4)
+        17 => crate::ipc::sys_reply_recv(frame, ep_id as usize, frame.x[4]),
+        // SYS_QUEUE_SEND = 18: enqueue x0–x3 on queue x6 (never blocks)
+        18 => crate::queue::sys_queue_send(frame, ep_id as usize),
+        // SYS_QUEUE_RECV = 19: dequeue from queue x6 (timeout in x4)
+        19 => crate::queue::sys_queue_recv(frame, ep_id as usize, frame.x[4]),
+        // SYS_SAMPLE_WRITE = 20: overwrite sampling port x6 with x0–x3
+        20 => crate::sampling::sys_sample_write(frame, ep_id as usize),
+        // SYS_SAMPLE_READ = 21: read sampling port x6 (age in x4, validity in x5)
+        21 => crate::sampling::sys_sample_read(frame, ep_id as usize),
+        // SYS_RECV_ANY = 22: wait on endpoint set x6 + notification mask x0 (timeout in x4)
+        22 => crate::ipc::sys_recv_any(frame, frame.x[6], frame.x[0], frame.x[4]),
+        // SYS_CAP_MINT = 23: copy own slot x0 into task x1's slot x2 (rights mask x3)
+        23 => crate::cspace::sys_cap_mint(frame),
+        // SYS_CAP_REVOKE = 24: delete every copy derived from own slot x0
+        24 => crate::cspace::sys_cap_revoke(frame),
+        // SYS_AUDIT_DRAIN = 25: pop the oldest denial record into x0–x5
+        25 => crate::audit::sys_audit_drain(frame),
+        // SYS_AUDIT_STATS = 26: denial count of task x6, records lost
+        26 => crate::audit::sys_audit_stats(frame, ep_id as usize),
+        // SYS_GRANT_ADD_PEER = 27: share grant x0 with task x6 (x3=flags)
+        27 => handle_grant_add_peer(frame),
+        // SYS_GRANT_REMOVE_PEER = 28: unshare grant x0 from task x6
+        28 => handle_grant_remove_peer(frame),
+        // SYS_MAP_ANON = 29: map x0 zeroed pages (x0=result, x1=base)
+        29 => handle_map_anon(frame),
+        // SYS_UNMAP = 30: unmap x1 anonymous pages at x0
+        30 => handle_unmap(frame),
+        // SYS_PAGE_PROTECT = 31: set x1 own pages at x0 to access x2
+        31 => handle_page_protect(frame),
+        // SYS_MAP_DUMP = 32: print task x6's address space (x0=filter)
+        32 => crate::memmap::sys_map_dump(frame, ep_id as usize),
         _ => {
             uart_print("!!! unknown syscall #");
             uart_print_hex(syscall_nr);
@@ -422,6 +498,31 @@ fn handle_svc(frame: &mut TrapFrame, _esr: u64) {
     }
 }
 
+/// Refused syscall: record it in the audit log, then fault the task or,
+/// under DenyPolicy::LogAndDeny, fail the call with ERR_CAP_DENIED.
+/// Completes the "!!! … DENIED" line started by the caller.
+#[cfg(target_arch = "aarch64")]
+fn deny_syscall(
+    frame: &mut TrapFrame,
+    task: usize,
+    syscall_nr: u64,
+    required: u64,
+    reason: crate::audit::DenyReason,
+) {
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let held = unsafe { (*crate::sched::TCBS.get())[task].caps };
+    match crate::audit::record_denial(task, syscall_nr, required, held, reason) {
+        crate::audit::DenyPolicy::Fault => {
+            uart_print(" — faulting\n");
+            crate::sched::fault_current_task(frame);
+        }
+        crate::audit::DenyPolicy::LogAndDeny => {
+            uart_print(" — denied\n");
+            frame.x[0] = crate::audit::ERR_CAP_DENIED;
+        }
+    }
+}
+
 /// SYS_WRITE handler: write bytes to UART on behalf of EL0 task.
 /// x0 = pointer to buffer, x1 = length in bytes.
 /// Validates that the buffer pointer is in user-accessible memory.
@@ -447,12 +548,11 @@ fn handle_sys_write(frame: &TrapFrame) {
 }
 
 /// SYS_NOTIFY handler: send async notification bits to a target task.
-/// x6 = target task ID, x0 = notification bitmask.
+/// `target_id` = x6 (or the slot-resolved task), x0 = notification bitmask.
 /// OR-merges bits into target's notify_pending. If target is blocked
-/// in wait_notify, unblock it immediately.
+/// waiting for any of them (wait_notify or a wait set), unblock it.
 #[cfg(target_arch = "aarch64")]
-fn handle_notify(frame: &mut TrapFrame) {
-    let target_id = frame.x[6] as usize;
+fn handle_notify(frame: &mut TrapFrame, target_id: usize) {
     let bits = frame.x[0];
 
     if target_id >= crate::sched::NUM_TASKS {
@@ -465,23 +565,7 @@ fn handle_notify(frame: &mut TrapFrame) {
         return; // no-op
     }
 
-    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
-    unsafe {
-        // OR-merge notification bits into target's pending mask
-        (*crate::sched::TCBS.get_mut())[target_id].notify_pending |= bits;
-
-        // If the target is blocked waiting for notifications, unblock it
-        if (*crate::sched::TCBS.get_mut())[target_id].notify_waiting {
-            (*crate::sched::TCBS.get_mut())[target_id].notify_waiting = false;
-
-            // Deliver pending bits into the target's x0 and clear
-            let pending = (*crate::sched::TCBS.get_mut())[target_id].notify_pending;
-            (*crate::sched::TCBS.get_mut())[target_id].notify_pending = 0;
-            crate::sched::set_task_reg(target_id, 0, pending);
-
-            crate::sched::set_task_state(target_id, crate::sched::TaskState::Ready);
-        }
-    }
+    crate::ipc::notify(target_id, bits);
 }
 
 /// SYS_WAIT_NOTIFY handler: wait for notification bits.
@@ -509,17 +593,22 @@ fn handle_wait_notify(frame: &mut TrapFrame) {
 }
 
 /// SYS_GRANT_CREATE handler: create shared memory grant.
-/// x0 = grant_id, x6 = peer_task_id.
-/// Returns result in x0 (0 = success, else error code).
+/// x0 = grant_id, x6 = peer_task_id, x1 = pages, x2 = alignment in
+/// pages (0 = 1 for both), x3 = GRANT_PEER_RO / GRANT_OWNER_RO flags.
+/// Returns result in x0 (0 = success, else error code), region base in x1.
 #[cfg(target_arch = "aarch64")]
 fn handle_grant_create(frame: &mut TrapFrame) {
     let grant_id = frame.x[0] as usize;
     let peer_id = frame.x[6] as usize;
+    let pages = (frame.x[1] as usize).max(1);
+    let align = (frame.x[2] as usize).max(1);
+    let flags = frame.x[3];
     // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
     let current = unsafe { *crate::sched::CURRENT.get() };
 
-    let result = crate::grant::grant_create(grant_id, current, peer_id);
+    let result = crate::grant::grant_create_region(grant_id, current, peer_id, pages, align, flags);
     frame.x[0] = result;
+    frame.x[1] = crate::grant::grant_base(grant_id).filter(|_| result == 0).unwrap_or(0);
 }
 
 /// SYS_GRANT_REVOKE handler: revoke shared memory grant.
@@ -535,6 +624,70 @@ fn handle_grant_revoke(frame: &mut TrapFrame) {
     frame.x[0] = result;
 }
 
+/// SYS_GRANT_ADD_PEER handler: share a grant with one more task.
+/// x0 = grant_id, x6 = peer_task_id, x3 = GRANT_PEER_RO flag.
+/// Returns result in x0 (0 = success, else error code).
+#[cfg(target_arch = "aarch64")]
+fn handle_grant_add_peer(frame: &mut TrapFrame) {
+    let grant_id = frame.x[0] as usize;
+    let peer_id = frame.x[6] as usize;
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *crate::sched::CURRENT.get() };
+
+    frame.x[0] = crate::grant::grant_add_peer(grant_id, current, peer_id, frame.x[3]);
+}
+
+/// SYS_GRANT_REMOVE_PEER handler: stop sharing a grant with one task.
+/// x0 = grant_id, x6 = peer_task_id.
+/// Returns result in x0 (0 = success, else error code).
+#[cfg(target_arch = "aarch64")]
+fn handle_grant_remove_peer(frame: &mut TrapFrame) {
+    let grant_id = frame.x[0] as usize;
+    let peer_id = frame.x[6] as usize;
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *crate::sched::CURRENT.get() };
+
+    frame.x[0] = crate::grant::grant_remove_peer(grant_id, current, peer_id);
+}
+
+/// SYS_MAP_ANON handler: map zeroed frames from the frame pool.
+/// x0 = pages.
+/// Returns result in x0 (0 = success, else error code), base in x1.
+#[cfg(target_arch = "aarch64")]
+fn handle_map_anon(frame: &mut TrapFrame) {
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *crate::sched::CURRENT.get() };
+
+    let (result, base) = match crate::frame::map_anon(current, frame.x[0] as usize) {
+        Ok(base) => (0, base),
+        Err(e) => (e, 0),
+    };
+    frame.x[0] = result;
+    frame.x[1] = base;
+}
+
+/// SYS_UNMAP handler: return anonymous frames to the pool.
+/// x0 = base address, x1 = pages.
+/// Returns result in x0 (0 = success, else error code).
+#[cfg(target_arch = "aarch64")]
+fn handle_unmap(frame: &mut TrapFrame) {
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *crate::sched::CURRENT.get() };
+
+    frame.x[0] = crate::frame::unmap(current, frame.x[0], frame.x[1] as usize);
+}
+
+/// SYS_PAGE_PROTECT handler: change the access of the caller's own pages.
+/// x0 = base address, x1 = pages, x2 = PROT_RW / PROT_RO / PROT_RX.
+/// Returns result in x0 (0 = success, else error code).
+#[cfg(target_arch = "aarch64")]
+fn handle_page_protect(frame: &mut TrapFrame) {
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *crate::sched::CURRENT.get() };
+
+    frame.x[0] = crate::protect::page_protect(current, frame.x[0], frame.x[1] as usize, frame.x[2]);
+}
+
 /// SYS_IRQ_BIND handler: bind IRQ INTID to notification bit.
 /// x0 = intid, x1 = notify_bit.
 /// Returns result in x0 (0 = success).
@@ -607,6 +760,7 @@ fn handle_instruction_abort(frame: &mut TrapFrame, esr: u64, source: u64) {
         uart_print("\n  ELR:  0x");
         uart_print_hex(frame.elr_el1);
         uart_print("\n");
+        report_address_space();
         crate::sched::fault_current_task(frame);
         return;
     }
@@ -657,6 +811,8 @@ fn handle_data_abort(frame: &mut TrapFrame, esr: u64, source: u64) {
         uart_print("\n  ELR:  0x");
         uart_print_hex(frame.elr_el1);
         uart_print("\n");
+        report_stack_guard(far);
+        report_address_space();
         crate::sched::fault_current_task(frame);
         return;
     }
@@ -680,11 +836,43 @@ fn handle_data_abort(frame: &mut TrapFrame, esr: u64, source: u64) {
     uart_print_hex(frame.elr_el1);
     uart_print("\n  src:  ");
     uart_print_hex(source);
-    uart_print("\n  HALTED.\n");
+    uart_print("\n");
+    report_stack_guard(far);
+    uart_print("  HALTED.\n");
     // SAFETY: wfe is a hint instruction that idles the core until next event.
     loop { unsafe { core::arch::asm!("wfe") } }
 }
 
+/// Print the EL0-visible address space of the faulting task.
+#[cfg(target_arch = "aarch64")]
+fn report_address_space() {
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *crate::sched::CURRENT.get() };
+    crate::memmap::dump_task(current, true);
+}
+
+/// Name the task whose user stack ran into its guard page, or the task
+/// running when the kernel's boot stack hit __stack_guard, if `far` is one.
+#[cfg(target_arch = "aarch64")]
+fn report_stack_guard(far: u64) {
+    extern "C" {
+        static __stack_guard: u8;
+    }
+    // SAFETY: Linker-provided symbol, only its address is taken.
+    let boot_guard = unsafe { &__stack_guard as *const u8 as u64 };
+    if let Some(task) = crate::sched::stack_guard_hit(far) {
+        uart_print("  stack overflow in task ");
+        crate::uart_print_dec(task as u64);
+        uart_print(" (user stack)\n");
+    } else if far >= boot_guard && far < boot_guard + 4096 {
+        // SAFETY: Single-core kernel, interrupts masked. Read-only access.
+        let current = unsafe { *crate::sched::CURRENT.get() };
+        uart_print("  stack overflow in task ");
+        crate::uart_print_dec(current as u64);
+        uart_print(" (kernel boot stack)\n");
+    }
+}
+
 /// FP/SIMD trap — fault task if from lower EL, halt if from same EL
 #[cfg(target_arch = "aarch64")]
 fn handle_fp_trap(frame: &mut TrapFrame, esr: u64, source: u64) {
@@ -65,8 +65,9 @@ pub const DEVICE_BLOCK: u64 = BLOCK | ATTR_DEVICE | AP_RW_EL1 | AF | XN;
 /// Used by map_device_for_task() to grant user-mode access to a device.
 pub const DEVICE_BLOCK_EL0: u64 = BLOCK | ATTR_DEVICE | AP_RW_EL0 | AF | XN;
 
-/// Normal RAM: Write-Back, RW, Inner Shareable, AF=1 (executable for sub-phase 1)
-pub const RAM_BLOCK: u64 = BLOCK | ATTR_NORMAL_WB | AP_RW_EL1 | SH_INNER | AF;
+/// Normal RAM: Write-Back, RW, Inner Shareable, non-executable, AF=1
+/// (kernel code lives in the L3-mapped first 2 MiB, never in these blocks)
+pub const RAM_BLOCK: u64 = BLOCK | ATTR_NORMAL_WB | AP_RW_EL1 | SH_INNER | AF | XN;
 
 /// Kernel code page: Normal WB, RO, executable, Inner Shareable, AF=1
 #[allow(dead_code)]
@@ -82,6 +83,10 @@ pub const KERNEL_DATA_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RW_EL1 | SH_INNER |
 /// User data/stack page: Normal WB, RW (EL0+EL1), non-executable, Inner Shareable, AF=1
 pub const USER_DATA_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RW_EL0 | SH_INNER | AF | XN;
 
+/// User read-only data page: Normal WB, RO (EL0+EL1), non-executable, Inner Shareable, AF=1
+/// Read-only grant side — a store from EL0 is a permission fault
+pub const USER_RO_DATA_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RO_EL0 | SH_INNER | AF | XN;
+
 /// User code page: Normal WB, RO (EL0+EL1), EL0-executable (UXN=0), PXN=1, Inner Shareable, AF=1
 /// PXN prevents kernel from executing user code; UXN=0 allows EL0 execution
 #[allow(dead_code)]
@@ -138,6 +143,7 @@ const SCTLR_WXN: u64 = 1 << 19;
 /// Number of page table pages: 4 per task (L2Device, L1, L2Ram, L3) + 4 kernel.
 /// Must match linker.ld `.page_tables` section size: NUM_PAGE_TABLE_PAGES × 4096.
 pub const NUM_PAGE_TABLE_PAGES: usize = 4 * NUM_TASKS + 4;
+const _: () = assert!(NUM_PAGE_TABLE_PAGES == crate::sysconf::PAGE_TABLE_PAGES);
 
 // ─── Page table type and computed indexing (Phase N) ───────────────
 
@@ -194,6 +200,10 @@ extern "C" {
     static __task_stacks_end: u8;
     static __grant_pages_start: u8;
     static __grant_pages_end: u8;
+    static __frame_pool_start: u8;
+    static __frame_pool_end: u8;
+    static __ipc_buffers_start: u8;
+    static __ipc_buffers_end: u8;
     static __elf_load_start: u8;
     static __elf_load_end: u8;
 }
@@ -262,6 +272,10 @@ unsafe fn build_l3(l3_index: usize, owner_task: u8) {
     let user_stacks_end = sym_addr(&__user_stacks_end);
     let grant_pages_start = sym_addr(&__grant_pages_start);
     let grant_pages_end = sym_addr(&__grant_pages_end);
+    let frame_pool_start = sym_addr(&__frame_pool_start);
+    let frame_pool_end = sym_addr(&__frame_pool_end);
+    let ipc_buffers_start = sym_addr(&__ipc_buffers_start);
+    let ipc_buffers_end = sym_addr(&__ipc_buffers_end);
     let guard_addr = sym_addr(&__stack_guard);
 
     let base: usize = 0x4000_0000;
@@ -271,10 +285,24 @@ unsafe fn build_l3(l3_index: usize, owner_task: u8) {
         let desc = if pa == guard_addr {
             // Stack guard page — always invalid
             0
-        } else if pa >= user_stacks_start && pa < user_stacks_end {
-            // User stack page — per-task isolation
-            let stack_idx = (pa - user_stacks_start) / 4096;
+        } else if pa == crate::ipc::IPC_BUFFER_VA as usize {
+            // IPC buffer alias — this task's own buffer page, EL0 RW
             if owner_task == 0xFF {
+                0
+            } else {
+                let own = ipc_buffers_start + (owner_task as usize) * 4096;
+                (own as u64) | USER_DATA_PAGE
+            }
+        } else if pa >= ipc_buffers_start && pa < ipc_buffers_end {
+            // IPC buffers at their physical address — EL1-only (kernel copies)
+            (pa as u64) | KERNEL_DATA_PAGE
+        } else if pa >= user_stacks_start && pa < user_stacks_end {
+            // User stack page — per-task isolation, guard page below each
+            let stack_idx = (pa - user_stacks_start) / crate::sched::STACK_SLOT_SIZE as usize;
+            if crate::sched::guard_page_owner(user_stacks_start as u64, pa as u64).is_some() {
+                // Guard page: invalid for everyone (overflow → translation fault)
+                0
+            } else if owner_task == 0xFF {
                 // Kernel boot table: all user stacks EL1-only
                 (pa as u64) | KERNEL_DATA_PAGE
             } else if stack_idx == owner_task as usize {
@@ -287,6 +315,9 @@ unsafe fn build_l3(l3_index: usize, owner_task: u8) {
         } else if pa >= grant_pages_start && pa < grant_pages_end {
             // Grant pages — default EL1-only; map_grant_for_task() upgrades to EL0
             (pa as u64) | KERNEL_DATA_PAGE
+        } else if pa >= frame_pool_start && pa < frame_pool_end {
+            // Frame pool — default EL1-only; SYS_MAP_ANON upgrades the owner's frames
+            (pa as u64) | KERNEL_DATA_PAGE
         } else if pa >= text_start && pa < text_end {
             (pa as u64) | SHARED_CODE_PAGE
         } else if pa >= rodata_start && pa < rodata_end {
@@ -304,6 +335,21 @@ unsafe fn build_l3(l3_index: usize, owner_task: u8) {
     } // unsafe
 }
 
+/// A task's L2_device, L2_ram and L3 tables, for the page-table checker.
+#[cfg(target_arch = "aarch64")]
+pub fn task_tables(task_id: usize) -> crate::ptcheck::TaskTables<'static> {
+    let table = |pt_type| {
+        // SAFETY: table_ptr points at a 4KB-aligned, 512-entry table in .page_tables
+        // that lives for the whole run; the checker only reads it.
+        unsafe { &*(table_ptr(pt_index(task_id, pt_type)) as *const [u64; 512]) }
+    };
+    crate::ptcheck::TaskTables {
+        l2_device: table(PageTableType::L2Device),
+        l2_ram: table(PageTableType::L2Ram),
+        l3: table(PageTableType::L3),
+    }
+}
+
 /// Build an L2_ram table that points to a specific L3 table.
 /// `l2_index` = page index for this L2_ram, `l3_index` = page index for its L3.
 #[cfg(target_arch = "aarch64")]
@@ -411,13 +457,11 @@ pub struct DeviceInfo {
     pub name: &'static str,
 }
 
-/// Device table — device_id indexes into this array.
-pub const DEVICES: &[DeviceInfo] = &[
-    DeviceInfo { l2_index: 72, intid: 33, name: "UART0" }, // device_id=0
-];
+/// Device table — device_id indexes into this array (system.toml [[device]]).
+pub const DEVICES: &[DeviceInfo] = crate::sysconf::DEVICES;
 
 /// Maximum device_id (for host tests)
-pub const MAX_DEVICE_ID: usize = 0; // only UART0 for now
+pub const MAX_DEVICE_ID: usize = DEVICES.len() - 1;
 
 // Error codes for map_device_for_task
 pub const DEVICE_MAP_ERR_INVALID_ID: u64 = 0xFFFF_2001;
@@ -481,53 +525,101 @@ pub fn map_device_for_task(device_id: u64, task_id: usize) -> u64 {
     0 // success
 }
 
-// ─── Phase J1: Grant page mapping ──────────────────────────────────
-
-/// Map a grant page into a task's L3 table as AP_RW_EL0 (user accessible).
-/// Must be followed by TLB invalidation for the task's ASID.
+/// Revoke a task's access to a device: downgrade its L2_device entry back
+/// to DEVICE_BLOCK (EL1-only) and invalidate the task's TLB entries.
+/// Inverse of map_device_for_task; unmapping an unmapped device is a no-op.
 #[cfg(target_arch = "aarch64")]
-pub unsafe fn map_grant_for_task(grant_phys: u64, task_id: usize) {
+pub unsafe fn unmap_device_for_task(device_id: u64, task_id: usize) -> u64 {
     // SAFETY: accesses page table memory, performs TLB invalidation via asm
     unsafe {
-    let l3 = table_ptr(pt_index(task_id, PageTableType::L3));
-    let base: u64 = 0x4000_0000;
-    let index = ((grant_phys - base) / 4096) as usize;
-    if index < 512 {
-        write_entry(l3, index, grant_phys | USER_DATA_PAGE);
-        // TLB invalidate for this task's ASID
-        let asid = (task_id as u64 + 1) << 48;
-        core::arch::asm!(
-            "tlbi aside1is, {asid}",
-            "dsb ish",
-            "isb",
-            asid = in(reg) asid,
-            options(nomem, nostack)
-        );
+    let did = device_id as usize;
+    if did >= DEVICES.len() {
+        return DEVICE_MAP_ERR_INVALID_ID;
     }
+    if task_id >= NUM_TASKS {
+        return DEVICE_MAP_ERR_INVALID_TASK;
+    }
+
+    let dev = &DEVICES[did];
+    let l2_device = table_ptr(pt_index(task_id, PageTableType::L2Device));
+    let pa = (dev.l2_index as u64) * 0x20_0000;
+    write_entry(l2_device, dev.l2_index, pa | DEVICE_BLOCK);
+
+    // TLB invalidate for this task's ASID
+    let asid = (task_id as u64 + 1) << 48;
+    core::arch::asm!(
+        "tlbi aside1is, {asid}",
+        "dsb ish",
+        "isb",
+        asid = in(reg) asid,
+        options(nomem, nostack)
+    );
+
+    crate::uart_print("[AegisOS] DEVICE UNMAP: ");
+    crate::uart_print(dev.name);
+    crate::uart_print(" -> task ");
+    crate::uart_print_hex(task_id as u64);
+    crate::uart_print("\n");
+
+    0 // success
     } // unsafe
 }
 
-/// Unmap a grant page from a task's L3 table (revert to AP_RW_EL1, EL0 no access).
-/// Must be followed by TLB invalidation for the task's ASID.
+/// Host-test stub for unmap_device_for_task
+#[cfg(not(target_arch = "aarch64"))]
+pub fn unmap_device_for_task(device_id: u64, task_id: usize) -> u64 {
+    let did = device_id as usize;
+    if did >= DEVICES.len() {
+        return DEVICE_MAP_ERR_INVALID_ID;
+    }
+    if task_id >= NUM_TASKS {
+        return DEVICE_MAP_ERR_INVALID_TASK;
+    }
+    0 // success
+}
+
+// ─── Phase J1: Grant page mapping ──────────────────────────────────
+
+/// Map a grant region (`pages` contiguous pages) into a task's L3 table
+/// with `attrs` (USER_DATA_PAGE or USER_RO_DATA_PAGE), then invalidate
+/// the task's ASID.
 #[cfg(target_arch = "aarch64")]
-pub unsafe fn unmap_grant_for_task(grant_phys: u64, task_id: usize) {
+pub unsafe fn map_grant_for_task(grant_phys: u64, pages: usize, task_id: usize, attrs: u64) {
+    // SAFETY: caller guarantees the region lies in the grant pool.
+    unsafe { set_grant_entries(grant_phys, pages, task_id, attrs) }
+}
+
+/// Unmap a grant region from a task's L3 table (revert to AP_RW_EL1,
+/// EL0 no access), then invalidate the task's ASID.
+#[cfg(target_arch = "aarch64")]
+pub unsafe fn unmap_grant_for_task(grant_phys: u64, pages: usize, task_id: usize) {
+    // SAFETY: caller guarantees the region lies in the grant pool.
+    unsafe { set_grant_entries(grant_phys, pages, task_id, KERNEL_DATA_PAGE) }
+}
+
+/// Rewrite the L3 entries of a grant region with `attrs`.
+#[cfg(target_arch = "aarch64")]
+unsafe fn set_grant_entries(grant_phys: u64, pages: usize, task_id: usize, attrs: u64) {
     // SAFETY: accesses page table memory, performs TLB invalidation via asm
     unsafe {
     let l3 = table_ptr(pt_index(task_id, PageTableType::L3));
     let base: u64 = 0x4000_0000;
-    let index = ((grant_phys - base) / 4096) as usize;
-    if index < 512 {
-        write_entry(l3, index, grant_phys | KERNEL_DATA_PAGE);
-        // TLB invalidate for this task's ASID
-        let asid = (task_id as u64 + 1) << 48;
-        core::arch::asm!(
-            "tlbi aside1is, {asid}",
-            "dsb ish",
-            "isb",
-            asid = in(reg) asid,
-            options(nomem, nostack)
-        );
+    let first = ((grant_phys - base) / 4096) as usize;
+    for i in 0..pages {
+        let index = first + i;
+        if index < 512 {
+            write_entry(l3, index, (base + index as u64 * 4096) | attrs);
+        }
     }
+    // TLB invalidate for this task's ASID
+    let asid = (task_id as u64 + 1) << 48;
+    core::arch::asm!(
+        "tlbi aside1is, {asid}",
+        "dsb ish",
+        "isb",
+        asid = in(reg) asid,
+        options(nomem, nostack)
+    );
     } // unsafe
 }
 
@@ -0,0 +1,254 @@
+/// AegisOS Audit Log — record of every refused syscall
+///
+/// When `handle_svc` refuses a syscall (missing CapBits, or a slot that
+/// does not authorise the object) the refusal is appended to a static
+/// ring of AUDIT_CAPACITY records: tick, task, syscall number, the caps
+/// required and the caps held, and why a slot was refused. When the ring
+/// is full the oldest record is overwritten and counted as lost. Each
+/// task also has a denial counter that, like its CapBits, survives
+/// restart — a soak run keeps the evidence of which component misbehaved.
+///
+/// What happens to the caller is the deny policy (system.toml
+/// `deny_policy`): Fault (default, a denial is a software defect) or
+/// LogAndDeny — the syscall fails with ERR_CAP_DENIED and the task runs
+/// on, for integration testing.
+///
+/// Syscalls (CAP_AUDIT, for a monitor task):
+///   SYS_AUDIT_DRAIN = 25: pop the oldest record →
+///       x0 = tick (ERR_AUDIT_EMPTY if none), x1 = task, x2 = syscall,
+///       x3 = required caps, x4 = held caps, x5 = reason (0 = caps,
+///       else 1 + slot error)
+///   SYS_AUDIT_STATS = 26: x6 = task → x0 = its denials, x1 = records lost
+
+use crate::cap::CapBits;
+use crate::cspace::SlotError;
+use crate::exception::TrapFrame;
+use crate::kernel::cell::KernelCell;
+use crate::sched::NUM_TASKS;
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+#[allow(dead_code)]
+pub const SYS_AUDIT_DRAIN: u64 = 25;
+#[allow(dead_code)]
+pub const SYS_AUDIT_STATS: u64 = 26;
+
+/// Records kept in the ring before the oldest is overwritten
+pub const AUDIT_CAPACITY: usize = 32;
+
+/// Returned in x0 by a refused syscall under DenyPolicy::LogAndDeny.
+pub const ERR_CAP_DENIED: u64 = 0xFFFF_8001;
+/// Returned in x0 by SYS_AUDIT_DRAIN when the ring is empty.
+pub const ERR_AUDIT_EMPTY: u64 = 0xFFFF_8002;
+/// Returned in x0 by SYS_AUDIT_STATS for a task id out of range.
+pub const ERR_AUDIT_TASK: u64 = 0xFFFF_8003;
+
+// ─── Types ─────────────────────────────────────────────────────────
+
+/// What the kernel does to a task whose syscall is refused.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DenyPolicy {
+    /// Fault the task (restart after RESTART_DELAY_TICKS)
+    Fault,
+    /// Fail the syscall with ERR_CAP_DENIED; the task continues
+    LogAndDeny,
+}
+
+/// Why a syscall was refused.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DenyReason {
+    /// CapBits mask lacks the required bits
+    Caps,
+    /// Slot-addressed object refused by the CSpace check
+    Slot(SlotError),
+}
+
+impl DenyReason {
+    /// Register encoding: 0 = caps, 1 + SlotError discriminant otherwise.
+    pub const fn code(self) -> u64 {
+        match self {
+            DenyReason::Caps => 0,
+            DenyReason::Slot(e) => 1 + e as u64,
+        }
+    }
+}
+
+/// One refused syscall.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AuditRecord {
+    pub tick: u64,
+    pub task: usize,
+    pub syscall: u64,
+    pub required: CapBits,
+    pub held: CapBits,
+    pub reason: DenyReason,
+}
+
+pub const EMPTY_RECORD: AuditRecord = AuditRecord {
+    tick: 0, task: 0, syscall: 0, required: 0, held: 0, reason: DenyReason::Caps,
+};
+
+/// Ring of records plus per-task denial counters.
+#[derive(Clone, Copy)]
+pub struct AuditLog {
+    pub records: [AuditRecord; AUDIT_CAPACITY],
+    /// Index of the oldest record
+    pub head: usize,
+    /// Records currently held (≤ AUDIT_CAPACITY)
+    pub len: usize,
+    /// Records overwritten before being drained
+    pub lost: u64,
+    /// Denials per task since boot (survive restart)
+    pub denials: [u64; NUM_TASKS],
+}
+
+pub const EMPTY_AUDIT_LOG: AuditLog = AuditLog {
+    records: [EMPTY_RECORD; AUDIT_CAPACITY],
+    head: 0,
+    len: 0,
+    lost: 0,
+    denials: [0; NUM_TASKS],
+};
+
+// ─── Ring logic (pure) ─────────────────────────────────────────────
+
+/// Append `rec`, overwriting (and counting) the oldest record when full.
+pub fn audit_push(log: &mut AuditLog, rec: AuditRecord) {
+    if log.len == AUDIT_CAPACITY {
+        log.head = (log.head + 1) % AUDIT_CAPACITY;
+        log.len -= 1;
+        log.lost = log.lost.saturating_add(1);
+    }
+    log.records[(log.head + log.len) % AUDIT_CAPACITY] = rec;
+    log.len += 1;
+    if rec.task < NUM_TASKS {
+        log.denials[rec.task] = log.denials[rec.task].saturating_add(1);
+    }
+}
+
+/// Remove and return the oldest record.
+pub fn audit_pop(log: &mut AuditLog) -> Option<AuditRecord> {
+    if log.len == 0 {
+        return None;
+    }
+    let rec = log.records[log.head];
+    log.head = (log.head + 1) % AUDIT_CAPACITY;
+    log.len -= 1;
+    Some(rec)
+}
+
+// ─── Global state ──────────────────────────────────────────────────
+
+pub static AUDIT: KernelCell<AuditLog> = KernelCell::new(EMPTY_AUDIT_LOG);
+
+/// Current deny policy (initialised from system.toml).
+pub static DENY_POLICY: KernelCell<DenyPolicy> = KernelCell::new(crate::sysconf::DENY_POLICY);
+
+/// Switch the deny policy (boot-time / tests).
+pub fn set_deny_policy(policy: DenyPolicy) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    unsafe { *DENY_POLICY.get_mut() = policy };
+}
+
+/// Log a refused syscall of `task` and return what to do with it.
+pub fn record_denial(
+    task: usize,
+    syscall: u64,
+    required: CapBits,
+    held: CapBits,
+    reason: DenyReason,
+) -> DenyPolicy {
+    let rec = AuditRecord { tick: crate::timer::tick_count(), task, syscall, required, held, reason };
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        audit_push(&mut *AUDIT.get_mut(), rec);
+        *DENY_POLICY.get()
+    }
+}
+
+/// Denials recorded against `task` since boot (0 if out of range).
+pub fn denial_count(task: usize) -> u64 {
+    if task >= NUM_TASKS {
+        return 0;
+    }
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    unsafe { AUDIT.get().denials[task] }
+}
+
+// ─── Syscalls ──────────────────────────────────────────────────────
+
+/// sys_audit_drain(frame): pop the oldest record into x0..x5.
+pub fn sys_audit_drain(frame: &mut TrapFrame) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    match unsafe { audit_pop(&mut *AUDIT.get_mut()) } {
+        Some(rec) => {
+            frame.x[0] = rec.tick;
+            frame.x[1] = rec.task as u64;
+            frame.x[2] = rec.syscall;
+            frame.x[3] = rec.required;
+            frame.x[4] = rec.held;
+            frame.x[5] = rec.reason.code();
+        }
+        None => frame.x[0] = ERR_AUDIT_EMPTY,
+    }
+}
+
+/// sys_audit_stats(frame, task): x0 = denials of `task`, x1 = records lost.
+pub fn sys_audit_stats(frame: &mut TrapFrame, task: usize) {
+    if task >= NUM_TASKS {
+        frame.x[0] = ERR_AUDIT_TASK;
+        return;
+    }
+    frame.x[0] = denial_count(task);
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    frame.x[1] = unsafe { AUDIT.get().lost };
+}
+
+// ─── Kani proofs ───────────────────────────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Proof: the ring keeps the newest records, in order, and accounts
+    /// for every overwrite.
+    /// For any fill level and any push:
+    /// - len stays ≤ AUDIT_CAPACITY
+    /// - the pushed record is the newest; a pop returns the oldest
+    /// - held + lost grows by exactly one per push
+    #[kani::proof]
+    #[kani::unwind(3)]
+    fn audit_ring_keeps_newest() {
+        let mut log = EMPTY_AUDIT_LOG;
+        let head: usize = kani::any();
+        let len: usize = kani::any();
+        kani::assume(head < AUDIT_CAPACITY && len <= AUDIT_CAPACITY);
+        log.head = head;
+        log.len = len;
+        log.lost = kani::any::<u32>() as u64;
+        log.records[head].tick = 1;
+        log.records[(head + 1) % AUDIT_CAPACITY].tick = 2;
+        let oldest_before = log.records[head];
+        let second = log.records[(head + 1) % AUDIT_CAPACITY];
+        let before = log.len as u64 + log.lost;
+
+        let tick: u64 = kani::any();
+        kani::assume(tick > 2);
+        let rec = AuditRecord { tick, task: 0, syscall: kani::any(), ..EMPTY_RECORD };
+        audit_push(&mut log, rec);
+
+        assert!(log.len <= AUDIT_CAPACITY, "ring bounded");
+        assert!(log.len as u64 + log.lost == before + 1, "every record held or counted lost");
+        let newest = log.records[(log.head + log.len - 1) % AUDIT_CAPACITY];
+        assert!(newest == rec, "push appends at the tail");
+        let popped = audit_pop(&mut log).unwrap();
+        if len == AUDIT_CAPACITY {
+            assert!(popped == second, "full ring drops exactly the oldest");
+        } else if len > 0 {
+            assert!(popped == oldest_before, "FIFO order");
+        } else {
+            assert!(popped == rec, "empty ring returns the new record");
+        }
+    }
+}
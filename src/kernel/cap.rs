@@ -53,6 +53,43 @@ pub const CAP_DEVICE_MAP: CapBits = 1 << 16;
 pub const CAP_HEARTBEAT: CapBits = 1 << 17;
 /// Permission to call SYS_EXIT for graceful task termination
 pub const CAP_EXIT: CapBits = 1 << 18;
+/// Permission to sleep on the kernel timer queue (SYS_SLEEP, SYS_SLEEP_UNTIL)
+pub const CAP_SLEEP: CapBits = 1 << 19;
+/// Permission to answer calls through the reply object (SYS_REPLY, SYS_REPLY_RECV)
+pub const CAP_REPLY: CapBits = 1 << 20;
+/// Permission to enqueue on message queue 0 (SYS_QUEUE_SEND)
+pub const CAP_QUEUE_SEND_Q0: CapBits = 1 << 21;
+/// Permission to dequeue from message queue 0 (SYS_QUEUE_RECV)
+pub const CAP_QUEUE_RECV_Q0: CapBits = 1 << 22;
+/// Permission to enqueue on message queue 1 (SYS_QUEUE_SEND)
+pub const CAP_QUEUE_SEND_Q1: CapBits = 1 << 23;
+/// Permission to dequeue from message queue 1 (SYS_QUEUE_RECV)
+pub const CAP_QUEUE_RECV_Q1: CapBits = 1 << 24;
+/// Permission to write sampling port 0 (SYS_SAMPLE_WRITE)
+pub const CAP_SAMPLE_WRITE_P0: CapBits = 1 << 25;
+/// Permission to read sampling port 0 (SYS_SAMPLE_READ)
+pub const CAP_SAMPLE_READ_P0: CapBits = 1 << 26;
+/// Permission to write sampling port 1 (SYS_SAMPLE_WRITE)
+pub const CAP_SAMPLE_WRITE_P1: CapBits = 1 << 27;
+/// Permission to read sampling port 1 (SYS_SAMPLE_READ)
+pub const CAP_SAMPLE_READ_P1: CapBits = 1 << 28;
+/// Permission to mint and revoke capability slot copies (SYS_CAP_MINT, SYS_CAP_REVOKE)
+pub const CAP_DELEGATE: CapBits = 1 << 29;
+/// Permission to read the denial audit log (SYS_AUDIT_DRAIN, SYS_AUDIT_STATS)
+pub const CAP_AUDIT: CapBits = 1 << 30;
+/// Permission to map and unmap anonymous frames within the task's quota (SYS_MAP_ANON, SYS_UNMAP)
+pub const CAP_MAP_ANON: CapBits = 1 << 31;
+/// Permission to change the access of the task's own pages (SYS_PAGE_PROTECT)
+pub const CAP_PAGE_PROTECT: CapBits = 1 << 32;
+/// Permission to print any task's decoded address space (SYS_MAP_DUMP)
+pub const CAP_MAP_DUMP: CapBits = 1 << 33;
+/// Permission to use endpoints named through a capability slot (SYS_SEND,
+/// SYS_RECV, SYS_CALL, SYS_REPLY_RECV); the slot picks the endpoint
+pub const CAP_IPC: CapBits = 1 << 34;
+/// Permission to use queues named through a capability slot (SYS_QUEUE_SEND, SYS_QUEUE_RECV)
+pub const CAP_QUEUE: CapBits = 1 << 35;
+/// Permission to use sampling ports named through a capability slot (SYS_SAMPLE_WRITE, SYS_SAMPLE_READ)
+pub const CAP_SAMPLE: CapBits = 1 << 36;
 
 // ─── Convenience combos ────────────────────────────────────────────
 
@@ -75,7 +112,25 @@ pub const CAP_ALL: CapBits = CAP_IPC_SEND_EP0
     | CAP_IRQ_ACK
     | CAP_DEVICE_MAP
     | CAP_HEARTBEAT
-    | CAP_EXIT;
+    | CAP_EXIT
+    | CAP_SLEEP
+    | CAP_REPLY
+    | CAP_QUEUE_SEND_Q0
+    | CAP_QUEUE_RECV_Q0
+    | CAP_QUEUE_SEND_Q1
+    | CAP_QUEUE_RECV_Q1
+    | CAP_SAMPLE_WRITE_P0
+    | CAP_SAMPLE_READ_P0
+    | CAP_SAMPLE_WRITE_P1
+    | CAP_SAMPLE_READ_P1
+    | CAP_DELEGATE
+    | CAP_AUDIT
+    | CAP_MAP_ANON
+    | CAP_PAGE_PROTECT
+    | CAP_MAP_DUMP
+    | CAP_IPC
+    | CAP_QUEUE
+    | CAP_SAMPLE;
 
 /// No capabilities
 pub const CAP_NONE: CapBits = 0;
@@ -87,7 +142,7 @@ pub const CAP_NONE: CapBits = 0;
 ///
 /// O(1), pure, no side effects — safe for use in hot path.
 #[inline]
-pub fn cap_check(caps: CapBits, required: CapBits) -> bool {
+pub const fn cap_check(caps: CapBits, required: CapBits) -> bool {
     (caps & required) == required
 }
 
@@ -96,7 +151,7 @@ pub fn cap_check(caps: CapBits, required: CapBits) -> bool {
 /// Syscall ABI: x7 = syscall_nr, x6 = endpoint_id.
 /// Returns 0 if the syscall/endpoint combo is unrecognized (caller
 /// should treat as "no cap can grant this" → fault).
-pub fn cap_for_syscall(syscall_nr: u64, ep_id: u64) -> CapBits {
+pub const fn cap_for_syscall(syscall_nr: u64, ep_id: u64) -> CapBits {
     match syscall_nr {
         // SYS_YIELD = 0
         0 => CAP_YIELD,
@@ -144,6 +199,68 @@ pub fn cap_for_syscall(syscall_nr: u64, ep_id: u64) -> CapBits {
         12 => CAP_HEARTBEAT,
         // SYS_EXIT = 13
         13 => CAP_EXIT,
+        // SYS_SLEEP = 14, SYS_SLEEP_UNTIL = 15
+        14 | 15 => CAP_SLEEP,
+        // SYS_REPLY = 16
+        16 => CAP_REPLY,
+        // SYS_REPLY_RECV = 17: reply + receive on the endpoint
+        17 => match ep_id {
+            0 => CAP_REPLY | CAP_IPC_RECV_EP0,
+            1 => CAP_REPLY | CAP_IPC_RECV_EP1,
+            2 => CAP_REPLY | CAP_IPC_RECV_EP2,
+            3 => CAP_REPLY | CAP_IPC_RECV_EP3,
+            _ => 0,
+        },
+        // SYS_QUEUE_SEND = 18 (x6 = queue id)
+        18 => match ep_id {
+            0 => CAP_QUEUE_SEND_Q0,
+            1 => CAP_QUEUE_SEND_Q1,
+            _ => 0, // invalid queue
+        },
+        // SYS_QUEUE_RECV = 19 (x6 = queue id)
+        19 => match ep_id {
+            0 => CAP_QUEUE_RECV_Q0,
+            1 => CAP_QUEUE_RECV_Q1,
+            _ => 0,
+        },
+        // SYS_SAMPLE_WRITE = 20 (x6 = port id)
+        20 => match ep_id {
+            0 => CAP_SAMPLE_WRITE_P0,
+            1 => CAP_SAMPLE_WRITE_P1,
+            _ => 0, // invalid port
+        },
+        // SYS_SAMPLE_READ = 21 (x6 = port id)
+        21 => match ep_id {
+            0 => CAP_SAMPLE_READ_P0,
+            1 => CAP_SAMPLE_READ_P1,
+            _ => 0,
+        },
+        // SYS_RECV_ANY = 22 (x6 = endpoint set): notification wait plus
+        // the receive right on every endpoint in the set
+        22 => {
+            let mut caps = CAP_WAIT_NOTIFY;
+            let mut ep = 0;
+            while ep < crate::ipc::MAX_ENDPOINTS as u64 {
+                if ep_id & (1 << ep) != 0 {
+                    caps |= cap_for_syscall(2, ep);
+                }
+                ep += 1;
+            }
+            caps
+        }
+        // SYS_CAP_MINT = 23, SYS_CAP_REVOKE = 24 (slot rights checked by cspace)
+        23 | 24 => CAP_DELEGATE,
+        // SYS_AUDIT_DRAIN = 25, SYS_AUDIT_STATS = 26
+        25 | 26 => CAP_AUDIT,
+        // SYS_GRANT_ADD_PEER = 27, SYS_GRANT_REMOVE_PEER = 28
+        27 => CAP_GRANT_CREATE,
+        28 => CAP_GRANT_REVOKE,
+        // SYS_MAP_ANON = 29, SYS_UNMAP = 30
+        29 | 30 => CAP_MAP_ANON,
+        // SYS_PAGE_PROTECT = 31
+        31 => CAP_PAGE_PROTECT,
+        // SYS_MAP_DUMP = 32
+        32 => CAP_MAP_DUMP,
         // Unknown syscall — no valid cap
         _ => 0,
     }
@@ -153,28 +270,46 @@ pub fn cap_for_syscall(syscall_nr: u64, ep_id: u64) -> CapBits {
 /// Used for UART debug output when denying a syscall.
 pub fn cap_name(cap: CapBits) -> &'static str {
     match cap {
-        CAP_IPC_SEND_EP0 => "IPC_SEND_EP0",
-        CAP_IPC_RECV_EP0 => "IPC_RECV_EP0",
-        CAP_IPC_SEND_EP1 => "IPC_SEND_EP1",
-        CAP_IPC_RECV_EP1 => "IPC_RECV_EP1",
-        CAP_WRITE         => "WRITE",
-        CAP_YIELD         => "YIELD",
-        CAP_NOTIFY        => "NOTIFY",
-        CAP_WAIT_NOTIFY   => "WAIT_NOTIFY",
-        CAP_IPC_SEND_EP2  => "IPC_SEND_EP2",
-        CAP_IPC_RECV_EP2  => "IPC_RECV_EP2",
-        CAP_IPC_SEND_EP3  => "IPC_SEND_EP3",
-        CAP_IPC_RECV_EP3  => "IPC_RECV_EP3",
-        CAP_GRANT_CREATE  => "GRANT_CREATE",
-        CAP_GRANT_REVOKE  => "GRANT_REVOKE",
-        CAP_IRQ_BIND      => "IRQ_BIND",
-        CAP_IRQ_ACK       => "IRQ_ACK",
-        CAP_DEVICE_MAP    => "DEVICE_MAP",
-        CAP_HEARTBEAT     => "HEARTBEAT",
-        CAP_EXIT          => "EXIT",
-        CAP_ALL           => "ALL",
-        CAP_NONE          => "NONE",
-        _                 => "UNKNOWN",
+        CAP_IPC_SEND_EP0    => "IPC_SEND_EP0",
+        CAP_IPC_RECV_EP0    => "IPC_RECV_EP0",
+        CAP_IPC_SEND_EP1    => "IPC_SEND_EP1",
+        CAP_IPC_RECV_EP1    => "IPC_RECV_EP1",
+        CAP_WRITE           => "WRITE",
+        CAP_YIELD           => "YIELD",
+        CAP_NOTIFY          => "NOTIFY",
+        CAP_WAIT_NOTIFY     => "WAIT_NOTIFY",
+        CAP_IPC_SEND_EP2    => "IPC_SEND_EP2",
+        CAP_IPC_RECV_EP2    => "IPC_RECV_EP2",
+        CAP_IPC_SEND_EP3    => "IPC_SEND_EP3",
+        CAP_IPC_RECV_EP3    => "IPC_RECV_EP3",
+        CAP_GRANT_CREATE    => "GRANT_CREATE",
+        CAP_GRANT_REVOKE    => "GRANT_REVOKE",
+        CAP_IRQ_BIND        => "IRQ_BIND",
+        CAP_IRQ_ACK         => "IRQ_ACK",
+        CAP_DEVICE_MAP      => "DEVICE_MAP",
+        CAP_HEARTBEAT       => "HEARTBEAT",
+        CAP_EXIT            => "EXIT",
+        CAP_SLEEP           => "SLEEP",
+        CAP_REPLY           => "REPLY",
+        CAP_QUEUE_SEND_Q0   => "QUEUE_SEND_Q0",
+        CAP_QUEUE_RECV_Q0   => "QUEUE_RECV_Q0",
+        CAP_QUEUE_SEND_Q1   => "QUEUE_SEND_Q1",
+        CAP_QUEUE_RECV_Q1   => "QUEUE_RECV_Q1",
+        CAP_SAMPLE_WRITE_P0 => "SAMPLE_WRITE_P0",
+        CAP_SAMPLE_READ_P0  => "SAMPLE_READ_P0",
+        CAP_SAMPLE_WRITE_P1 => "SAMPLE_WRITE_P1",
+        CAP_SAMPLE_READ_P1  => "SAMPLE_READ_P1",
+        CAP_DELEGATE        => "DELEGATE",
+        CAP_AUDIT           => "AUDIT",
+        CAP_MAP_ANON        => "MAP_ANON",
+        CAP_PAGE_PROTECT    => "PAGE_PROTECT",
+        CAP_MAP_DUMP        => "MAP_DUMP",
+        CAP_IPC             => "IPC",
+        CAP_QUEUE           => "QUEUE",
+        CAP_SAMPLE          => "SAMPLE",
+        CAP_ALL             => "ALL",
+        CAP_NONE            => "NONE",
+        _                   => "UNKNOWN",
     }
 }
 
@@ -206,13 +341,13 @@ mod kani_proofs {
     }
 
     /// Prove: cap_for_syscall never panics and returns only valid cap bits.
-    /// For all valid syscall numbers (0..=12) and endpoints (0..=3),
-    /// the returned bitmask is a subset of CAP_ALL (0x3FFFF).
+    /// For all valid syscall numbers (0..=32) and endpoints (0..=3),
+    /// the returned bitmask is a subset of CAP_ALL (0x1F_FFFFFFFF).
     #[kani::proof]
     fn cap_for_syscall_no_panic_and_bounded() {
         let nr: u64 = kani::any();
         let ep: u64 = kani::any();
-        kani::assume(nr <= 13);
+        kani::assume(nr <= 32);
         kani::assume(ep <= 3);
 
         let result = cap_for_syscall(nr, ep);
@@ -4,6 +4,9 @@
 /// Only supports: ELF64, little-endian, ET_EXEC, EM_AARCH64 (183).
 /// Extracts entry point + up to 4 PT_LOAD segments.
 
+use crate::kernel::cell::KernelCell;
+use crate::platform::qemu_virt::{ELF_LOAD_SIZE_PER_TASK, MAX_ELF_TASKS};
+
 // ─── Constants ─────────────────────────────────────────────────────
 
 /// ELF magic bytes: 0x7F 'E' 'L' 'F'
@@ -35,6 +38,15 @@ pub const PF_X: u32 = 1; // Execute
 pub const PF_W: u32 = 2; // Write
 pub const PF_R: u32 = 4; // Read
 
+/// 4KB pages in one ELF load slot
+pub const ELF_SLOT_PAGES: usize = ELF_LOAD_SIZE_PER_TASK / 4096;
+
+/// L3 template the loader gave each page of each ELF slot (0 = page not
+/// covered by a segment, left EL1-only). Restart re-applies them, so a
+/// page sealed with SYS_PAGE_PROTECT gets its segment's access back.
+pub static ELF_TEMPLATES: KernelCell<[[u64; ELF_SLOT_PAGES]; MAX_ELF_TASKS]> =
+    KernelCell::new([[0; ELF_SLOT_PAGES]; MAX_ELF_TASKS]);
+
 // ─── Data Types ────────────────────────────────────────────────────
 
 /// A single PT_LOAD segment parsed from the ELF program header table.
@@ -348,6 +360,33 @@ pub unsafe fn load_elf_segments(
 
 // ─── Phase O: Multi-ELF loader helper ──────────────────────────────
 
+/// L3 template for a segment with permission `flags`: text is RX,
+/// writable data RW, everything else read-only — never W and X.
+pub const fn segment_template(flags: u32) -> u64 {
+    if flags & PF_X != 0 {
+        crate::mmu::USER_CODE_PAGE
+    } else if flags & PF_W != 0 {
+        crate::mmu::USER_DATA_PAGE
+    } else {
+        crate::mmu::KERNEL_RODATA_PAGE
+    }
+}
+
+/// Per-page templates of a validated image loaded at `load_base`: each
+/// page a segment touches gets its template, a later segment winning on
+/// a shared page. Pages no segment touches stay 0.
+pub fn page_templates(info: &ElfInfo, load_base: u64) -> [u64; ELF_SLOT_PAGES] {
+    let mut pages = [0; ELF_SLOT_PAGES];
+    for seg in info.segments.iter().take(info.num_segments).flatten() {
+        let first = (seg.vaddr.saturating_sub(load_base) / 4096) as usize;
+        let end = (seg.vaddr + seg.memsz - load_base).div_ceil(4096) as usize;
+        for page in pages.iter_mut().take(end).skip(first) {
+            *page = segment_template(seg.flags);
+        }
+    }
+    pages
+}
+
 /// Load an ELF binary into a specific task slot.
 ///
 /// This is the high-level entry point for multi-ELF loading:
@@ -374,7 +413,7 @@ pub unsafe fn load_elf_to_task(
     slot: usize,
     elf_data: &[u8],
 ) -> Result<u64, &'static str> {
-    use crate::platform::qemu_virt::{elf_load_addr, ELF_LOAD_SIZE_PER_TASK, MAX_ELF_TASKS};
+    use crate::platform::qemu_virt::elf_load_addr;
     use crate::kernel::sched;
 
     if slot >= MAX_ELF_TASKS {
@@ -420,36 +459,44 @@ pub unsafe fn load_elf_to_task(
         );
     }
 
-    // 4. Set page permissions per segment
-    // SAFETY: task_id validated, vaddr from validated ELF segments.
+    // 4. Set page permissions per segment, and remember them for restart
+    let templates = page_templates(&info, load_base);
+    // SAFETY: task_id validated, pages lie in the validated load slot.
+    // Single-core kernel, called during boot.
     unsafe {
-        use crate::mmu;
-        for i in 0..info.num_segments {
-            if let Some(seg) = info.segments[i] {
-                let template = if seg.flags & PF_X != 0 {
-                    mmu::USER_CODE_PAGE
-                } else if seg.flags & PF_W != 0 {
-                    mmu::USER_DATA_PAGE
-                } else {
-                    mmu::KERNEL_RODATA_PAGE
-                };
-                let seg_start = seg.vaddr & !0xFFF;
-                let seg_end = (seg.vaddr + seg.memsz + 0xFFF) & !0xFFF;
-                let mut page = seg_start;
-                while page < seg_end {
-                    mmu::set_page_attr(task_id, page, template);
-                    page += 4096;
-                }
-            }
-        }
+        (*ELF_TEMPLATES.get_mut())[slot] = templates;
+        restore_templates(task_id, slot);
     }
 
-    // 5. Update TCB entry point and ELR
+    // 5. Update TCB entry point, ELR and ELF slot
     // SAFETY: Single-core kernel, called during boot.
     unsafe {
         (*sched::TCBS.get_mut())[task_id].entry_point = entry;
         (*sched::TCBS.get_mut())[task_id].context.elr_el1 = entry;
+        (*sched::TCBS.get_mut())[task_id].elf_slot = Some(slot);
     }
 
     Ok(entry)
 }
+
+/// Re-apply the loader's per-page templates of ELF `slot` to `task`'s
+/// L3 table. Pages no segment covers are left alone.
+pub fn restore_templates(task: usize, slot: usize) {
+    if slot >= MAX_ELF_TASKS {
+        return;
+    }
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    let templates = unsafe { (*ELF_TEMPLATES.get())[slot] };
+    let base = crate::platform::qemu_virt::elf_load_addr(slot);
+    for (i, &template) in templates.iter().enumerate() {
+        if template != 0 {
+            #[cfg(target_arch = "aarch64")]
+            // SAFETY: the page lies in ELF slot `slot`, inside the L3-mapped range.
+            unsafe {
+                crate::mmu::set_page_attr(task, base + i as u64 * 4096, template);
+            }
+            #[cfg(not(target_arch = "aarch64"))]
+            crate::mmu::set_page_attr(task, base + i as u64 * 4096, template);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+/// AegisOS Frame Pool — anonymous memory with per-task quotas
+///
+/// A task maps zeroed pages on demand instead of sizing its ELF slot for
+/// the worst case. SYS_MAP_ANON carves a contiguous run of frames from
+/// the frame pool, zeroes it and maps it identity into the caller's L3
+/// table as USER_DATA_PAGE (RW, never executable); SYS_UNMAP gives pages
+/// back. Each task may hold at most its `mem_quota` (system.toml,
+/// TaskMetadata) pages; build.rs refuses quotas that overcommit the pool.
+/// Frames are reclaimed when the task faults, restarts or exits.
+///
+/// The pool is the `.frame_pool` linker section (FRAME_POOL_PAGES pages,
+/// system.toml `frame_pages`) — no heap, no dynamic allocation. Each
+/// frame records its owner; quota use is derived from that table, so the
+/// two cannot disagree.
+///
+/// Syscalls (CAP_MAP_ANON):
+///   SYS_MAP_ANON = 29: map x0 zeroed pages → x0 = result, x1 = base
+///   SYS_UNMAP = 30: unmap x1 pages starting at x0 (all the caller's)
+
+use crate::kernel::cell::KernelCell;
+use crate::sched;
+use crate::uart_print;
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+/// Pages in the frame pool (system.toml; statically allocated in linker.ld)
+pub const FRAME_POOL_PAGES: usize = crate::sysconf::FRAME_PAGES;
+
+/// Frame size (must match linker.ld allocation)
+pub const FRAME_SIZE: usize = 4096;
+
+/// Owner entry of an unallocated frame
+pub const FRAME_FREE: u8 = u8::MAX;
+
+// Owners are stored as u8, FRAME_FREE excluded
+const _: () = assert!(sched::NUM_TASKS < FRAME_FREE as usize);
+
+/// Page count is 0 or larger than the pool.
+pub const ERR_FRAME_SIZE: u64 = 0xFFFF_9001;
+/// Mapping would take the task past its `mem_quota`.
+pub const ERR_FRAME_QUOTA: u64 = 0xFFFF_9002;
+/// No contiguous run of free frames that long.
+pub const ERR_FRAME_NO_SPACE: u64 = 0xFFFF_9003;
+/// Unmapped range is not entirely the caller's frames.
+pub const ERR_FRAME_NOT_OWNER: u64 = 0xFFFF_9004;
+
+/// Owner task of each pool frame (FRAME_FREE = unallocated).
+pub static FRAME_OWNER: KernelCell<[u8; FRAME_POOL_PAGES]> = KernelCell::new([FRAME_FREE; FRAME_POOL_PAGES]);
+
+/// Host-test backing store for the frame pool pages.
+#[cfg(not(target_arch = "aarch64"))]
+static HOST_FRAME_POOL: KernelCell<[[u8; FRAME_SIZE]; FRAME_POOL_PAGES]> =
+    KernelCell::new([[0; FRAME_SIZE]; FRAME_POOL_PAGES]);
+
+// ─── Frame pool (from linker) ──────────────────────────────────────
+
+/// Physical address of the first frame pool page.
+#[cfg(target_arch = "aarch64")]
+pub fn frame_pool_base() -> u64 {
+    extern "C" {
+        static __frame_pool_start: u8;
+    }
+    // SAFETY: Linker-provided symbol, address taken for frame pool calculation.
+    unsafe { &__frame_pool_start as *const u8 as u64 }
+}
+
+/// Host-test stub: fake pool base within the first 2MiB (L3 range).
+#[cfg(not(target_arch = "aarch64"))]
+pub fn frame_pool_base() -> u64 {
+    0x4014_0000
+}
+
+/// Kernel pointer to frame pool address `addr` (the pool is
+/// identity-mapped; host tests get the matching byte of HOST_FRAME_POOL).
+/// Returns None if `addr` is outside the pool.
+pub fn frame_ptr(addr: u64) -> Option<*mut u8> {
+    let offset = addr.checked_sub(frame_pool_base())? as usize;
+    if offset >= FRAME_POOL_PAGES * FRAME_SIZE {
+        return None;
+    }
+    #[cfg(target_arch = "aarch64")]
+    let base = frame_pool_base() as *mut u8;
+    // SAFETY: Single-core test harness; only the address is taken here.
+    #[cfg(not(target_arch = "aarch64"))]
+    let base = unsafe { HOST_FRAME_POOL.get_mut().as_mut_ptr() as *mut u8 };
+    // SAFETY: offset < pool size, so the result stays inside the pool.
+    Some(unsafe { base.add(offset) })
+}
+
+// ─── Allocation (pure) ─────────────────────────────────────────────
+
+/// Frames held by `task`.
+pub fn frames_held(owners: &[u8; FRAME_POOL_PAGES], task: usize) -> usize {
+    owners.iter().filter(|&&o| o as usize == task).count()
+}
+
+/// Pick `pages` contiguous free frames for `task`, first fit, without
+/// taking it past `quota`. Returns the first frame index or ERR_FRAME_*.
+pub fn frame_alloc(owners: &[u8; FRAME_POOL_PAGES], task: usize, quota: usize, pages: usize) -> Result<usize, u64> {
+    if pages == 0 || pages > FRAME_POOL_PAGES {
+        return Err(ERR_FRAME_SIZE);
+    }
+    if frames_held(owners, task) + pages > quota {
+        return Err(ERR_FRAME_QUOTA);
+    }
+    let mut run = 0;
+    for (i, &owner) in owners.iter().enumerate() {
+        run = if owner == FRAME_FREE { run + 1 } else { 0 };
+        if run == pages {
+            return Ok(i + 1 - pages);
+        }
+    }
+    Err(ERR_FRAME_NO_SPACE)
+}
+
+/// Frame index range of `pages` pages at `addr`, if every one is owned by `task`.
+pub fn frame_range(owners: &[u8; FRAME_POOL_PAGES], pool_base: u64, task: usize, addr: u64, pages: usize) -> Result<usize, u64> {
+    if pages == 0 || pages > FRAME_POOL_PAGES {
+        return Err(ERR_FRAME_SIZE);
+    }
+    if addr < pool_base || !(addr - pool_base).is_multiple_of(FRAME_SIZE as u64) {
+        return Err(ERR_FRAME_NOT_OWNER);
+    }
+    let first = ((addr - pool_base) / FRAME_SIZE as u64) as usize;
+    if first >= FRAME_POOL_PAGES || pages > FRAME_POOL_PAGES - first {
+        return Err(ERR_FRAME_NOT_OWNER);
+    }
+    if owners[first..first + pages].iter().any(|&o| o as usize != task) {
+        return Err(ERR_FRAME_NOT_OWNER);
+    }
+    Ok(first)
+}
+
+// ─── Core operations ───────────────────────────────────────────────
+
+/// Map `pages` zeroed contiguous frames into `task`'s address space.
+/// Returns the base address, or ERR_FRAME_* on failure.
+pub fn map_anon(task: usize, pages: usize) -> Result<u64, u64> {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let quota = (*sched::TCBS.get())[task].mem_quota;
+        let first = frame_alloc(FRAME_OWNER.get(), task, quota, pages)?;
+        let base = frame_pool_base() + (first * FRAME_SIZE) as u64;
+
+        // Zero before the task can see it — free frames hold stale data
+        if let Some(ptr) = frame_ptr(base) {
+            core::ptr::write_bytes(ptr, 0, pages * FRAME_SIZE);
+        }
+        for i in first..first + pages {
+            (*FRAME_OWNER.get_mut())[i] = task as u8;
+            #[cfg(target_arch = "aarch64")]
+            crate::mmu::set_page_attr(task, frame_pool_base() + (i * FRAME_SIZE) as u64, crate::mmu::USER_DATA_PAGE);
+        }
+        Ok(base)
+    }
+}
+
+/// Unmap `pages` frames at `addr` from `task` and return them to the pool.
+/// Returns 0 on success, ERR_FRAME_* on failure (nothing is unmapped).
+pub fn unmap(task: usize, addr: u64, pages: usize) -> u64 {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let first = match frame_range(FRAME_OWNER.get(), frame_pool_base(), task, addr, pages) {
+            Ok(first) => first,
+            Err(e) => {
+                uart_print("!!! FRAME: unmap of pages the task does not hold\n");
+                return e;
+            }
+        };
+        release(first, pages);
+    }
+    0
+}
+
+/// Unmap frames `first..first + pages` from their owner and free them.
+unsafe fn release(first: usize, pages: usize) {
+    // SAFETY: caller holds the kernel (single core, interrupts masked).
+    unsafe {
+        for i in first..first + pages {
+            #[cfg(target_arch = "aarch64")]
+            {
+                let owner = (*FRAME_OWNER.get())[i] as usize;
+                crate::mmu::set_page_attr(owner, frame_pool_base() + (i * FRAME_SIZE) as u64, crate::mmu::KERNEL_DATA_PAGE);
+            }
+            (*FRAME_OWNER.get_mut())[i] = FRAME_FREE;
+        }
+    }
+}
+
+// ─── Fault cleanup ─────────────────────────────────────────────────
+
+/// Reclaim every frame held by a faulted, restarted or exited task.
+/// Called from sched::cleanup_task_resources().
+pub fn cleanup_task(task_idx: usize) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        for i in 0..FRAME_POOL_PAGES {
+            if (*FRAME_OWNER.get())[i] as usize == task_idx {
+                release(i, 1);
+            }
+        }
+    }
+}
+
+// ─── Kani formal verification proofs ───────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Proof: Anonymous frames never exceed a quota or get shared.
+    /// For any pool ownership, task, quota and request, a successful
+    /// frame_alloc returns a run that is entirely free and in the pool,
+    /// and the task's holding afterwards stays within its quota.
+    #[kani::proof]
+    #[kani::unwind(9)] // FRAME_POOL_PAGES = 8
+    fn frame_alloc_within_quota_and_free() {
+        let mut owners = [FRAME_FREE; FRAME_POOL_PAGES];
+        for o in owners.iter_mut() {
+            let v: u8 = kani::any();
+            kani::assume(v == FRAME_FREE || (v as usize) < sched::NUM_TASKS);
+            *o = v;
+        }
+        let task: usize = kani::any();
+        kani::assume(task < sched::NUM_TASKS);
+        let quota: usize = kani::any();
+        kani::assume(quota <= FRAME_POOL_PAGES);
+        let pages: usize = kani::any();
+
+        if let Ok(first) = frame_alloc(&owners, task, quota, pages) {
+            assert!(pages >= 1 && first + pages <= FRAME_POOL_PAGES, "run inside the pool");
+            let mut i = first;
+            while i < first + pages {
+                assert!(owners[i] == FRAME_FREE, "never hands out an owned frame");
+                i += 1;
+            }
+            assert!(frames_held(&owners, task) + pages <= quota, "quota respected");
+        }
+    }
+}
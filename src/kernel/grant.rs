@@ -1,16 +1,32 @@
 /// AegisOS Shared Memory Grant Module
 ///
-/// Allows two tasks to share a specific physical memory page under
-/// kernel-controlled access. The owner creates a grant, mapping the
-/// page into both tasks' L3 page tables as AP_RW_EL0. Revoking
-/// unmaps the peer's access (sets entry back to AP_RW_EL1).
+/// Allows a task to share a region of physical memory with one or more
+/// peers under kernel-controlled access — one published buffer, many
+/// readers, no copies. The owner creates a grant of one or more pages
+/// carved from the grant pool for a first peer, then may add or remove
+/// peers. The region is mapped contiguously into each member's L3 page
+/// table, AP_RW_EL0 or — chosen per member by the owner — AP_RO_EL0, so a
+/// store from that task faults. Adding or removing a peer rewrites only
+/// that task's entries; revoking unmaps every member (entries back to
+/// AP_RW_EL1), scrubs the pages and returns them to the pool.
 ///
-/// Grant pages are statically allocated in the `.grant_pages` linker
-/// section — no heap, no dynamic allocation.
+/// The pool is the `.grant_pages` linker section (GRANT_POOL_PAGES pages,
+/// system.toml `grant_pages`) — no heap, no dynamic allocation. Regions
+/// are placed first-fit at the requested alignment. Pool occupancy is
+/// derived from the grant table itself, so the two cannot disagree.
+/// Free pool pages hold only the scrub byte (system.toml `grant_scrub`):
+/// the pool is scrubbed at boot and a region again whenever its grant is
+/// revoked or its owner faults, so a re-created grant never hands one
+/// partition's data to another.
 ///
 /// Syscalls:
-///   SYS_GRANT_CREATE = 7: owner grants a page to a peer task
-///   SYS_GRANT_REVOKE = 8: owner revokes peer's access
+///   SYS_GRANT_CREATE = 7: owner grants x1 pages (base aligned to x2
+///       pages; 0 = 1) to peer x6 as grant x0, x3 = GRANT_*_RO flags
+///       → x0 = result, x1 = base
+///   SYS_GRANT_REVOKE = 8: owner revokes the grant, freeing its pages
+///   SYS_GRANT_ADD_PEER = 27: owner shares grant x0 with task x6,
+///       x3 = GRANT_PEER_RO (re-adding a peer changes its access)
+///   SYS_GRANT_REMOVE_PEER = 28: owner unshares grant x0 from task x6
 
 use crate::kernel::cell::KernelCell;
 use crate::sched;
@@ -18,118 +34,381 @@ use crate::uart_print;
 
 // ─── Constants ─────────────────────────────────────────────────────
 
-/// Maximum number of grant pages (statically allocated in linker.ld)
-pub const MAX_GRANTS: usize = 2;
+/// Number of grant descriptors — grant ids 0..MAX_GRANTS (system.toml)
+pub const MAX_GRANTS: usize = crate::sysconf::MAX_GRANTS;
+
+/// Pages in the grant pool (system.toml; statically allocated in linker.ld)
+pub const GRANT_POOL_PAGES: usize = crate::sysconf::GRANT_PAGES;
 
 /// Grant page size (must match linker.ld allocation)
 pub const GRANT_PAGE_SIZE: usize = 4096;
 
+/// Largest region alignment, in pages (one L3 table spans 2 MiB)
+pub const MAX_GRANT_ALIGN: usize = 512;
+
+/// Byte written over a region's pages when its grant ends (system.toml
+/// `grant_scrub`; 0 zeroizes, a pattern makes use-after-revoke visible).
+pub const GRANT_SCRUB: u8 = crate::sysconf::GRANT_SCRUB;
+
+// Pool occupancy is tracked as one u64 bitmask
+const _: () = assert!(GRANT_POOL_PAGES >= 1 && GRANT_POOL_PAGES <= 64);
+
+/// Grant id out of range.
+pub const ERR_GRANT_INVALID_ID: u64 = 0xFFFF_0001;
+/// Grant id already active.
+pub const ERR_GRANT_ACTIVE: u64 = 0xFFFF_0002;
+/// Peer task id out of range.
+pub const ERR_GRANT_INVALID_PEER: u64 = 0xFFFF_0003;
+/// Owner and peer are the same task.
+pub const ERR_GRANT_SELF: u64 = 0xFFFF_0004;
+/// Caller does not own the grant.
+pub const ERR_GRANT_NOT_OWNER: u64 = 0xFFFF_0005;
+/// Size is 0 or larger than the pool, or alignment is not a power of two ≤ MAX_GRANT_ALIGN.
+pub const ERR_GRANT_SIZE: u64 = 0xFFFF_0006;
+/// Fewer free pool pages than requested.
+pub const ERR_GRANT_EXHAUSTED: u64 = 0xFFFF_0007;
+/// Enough free pages, but no contiguous run at the requested alignment.
+pub const ERR_GRANT_FRAGMENTED: u64 = 0xFFFF_0008;
+/// Unknown bits in the SYS_GRANT_CREATE / SYS_GRANT_ADD_PEER flags.
+pub const ERR_GRANT_FLAGS: u64 = 0xFFFF_0009;
+/// Peer update on a grant that is not active.
+pub const ERR_GRANT_INACTIVE: u64 = 0xFFFF_000A;
+/// Removed task is not a peer of the grant.
+pub const ERR_GRANT_NOT_PEER: u64 = 0xFFFF_000B;
+
+/// Grant flag: the peer maps the region read-only.
+pub const GRANT_PEER_RO: u64 = 1 << 0;
+/// SYS_GRANT_CREATE flag: the owner maps the region read-only.
+pub const GRANT_OWNER_RO: u64 = 1 << 1;
+
+// ─── Permissions ───────────────────────────────────────────────────
+
+/// Access one side of a grant has to the region.
+///
+/// There is no write-only: stage-1 AP bits cannot give EL0 write access
+/// without read access.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GrantPerm {
+    /// Loads and stores (AP_RW_EL0)
+    ReadWrite,
+    /// Loads only — a store is a permission fault (AP_RO_EL0)
+    ReadOnly,
+}
+
+impl GrantPerm {
+    const fn from_ro(ro: bool) -> Self {
+        if ro { GrantPerm::ReadOnly } else { GrantPerm::ReadWrite }
+    }
+
+    /// L3 page descriptor template mapping the region with this access.
+    pub const fn page_attrs(self) -> u64 {
+        match self {
+            GrantPerm::ReadWrite => crate::mmu::USER_DATA_PAGE,
+            GrantPerm::ReadOnly => crate::mmu::USER_RO_DATA_PAGE,
+        }
+    }
+}
+
+/// Decode SYS_GRANT_CREATE flags into (owner, peer) access.
+pub const fn grant_perms(flags: u64) -> Result<(GrantPerm, GrantPerm), u64> {
+    if flags & !(GRANT_PEER_RO | GRANT_OWNER_RO) != 0 {
+        return Err(ERR_GRANT_FLAGS);
+    }
+    Ok((GrantPerm::from_ro(flags & GRANT_OWNER_RO != 0), GrantPerm::from_ro(flags & GRANT_PEER_RO != 0)))
+}
+
 // ─── Grant struct ──────────────────────────────────────────────────
 
-/// A shared memory grant — tracks who owns and shares a page.
+/// Set of tasks, bit i = task i (the peers of a grant).
+pub type PeerSet = u64;
+
+// Peer sets are one u64
+const _: () = assert!(sched::NUM_TASKS <= 64);
+
+/// A shared memory grant — tracks who owns and shares a region.
 #[derive(Clone, Copy, Debug)]
 pub struct Grant {
     /// Task that created the grant (None = slot unused)
     pub owner: Option<usize>,
-    /// Task that was granted access (None = not shared)
-    pub peer: Option<usize>,
-    /// Physical address of the grant page
+    /// Tasks the region is shared with (empty = not shared)
+    pub peers: PeerSet,
+    /// Peers that map the region read-only (a subset of `peers`)
+    pub peers_ro: PeerSet,
+    /// Physical base address of the region
     pub phys_addr: u64,
+    /// Pool pages held by the region (0 = none)
+    pub pages: usize,
+    /// Owner's access to the region
+    pub owner_perm: GrantPerm,
     /// Whether this grant is currently active
     pub active: bool,
 }
 
 pub const EMPTY_GRANT: Grant = Grant {
     owner: None,
-    peer: None,
+    peers: 0,
+    peers_ro: 0,
     phys_addr: 0,
+    pages: 0,
+    owner_perm: GrantPerm::ReadWrite,
     active: false,
 };
 
+impl Grant {
+    /// Whether `task` is one of the grant's peers.
+    pub const fn is_peer(&self, task: usize) -> bool {
+        task < sched::NUM_TASKS && self.peers & (1 << task) != 0
+    }
+
+    /// Access peer `task` has to the region (None = not a peer).
+    pub const fn peer_perm(&self, task: usize) -> Option<GrantPerm> {
+        if !self.is_peer(task) {
+            return None;
+        }
+        Some(GrantPerm::from_ro(self.peers_ro & (1 << task) != 0))
+    }
+
+    /// This grant shared with `task` (< NUM_TASKS) at `perm`; re-adding
+    /// a peer changes its access.
+    pub const fn with_peer(mut self, task: usize, perm: GrantPerm) -> Grant {
+        let bit: PeerSet = 1 << task;
+        self.peers |= bit;
+        self.peers_ro = match perm {
+            GrantPerm::ReadOnly => self.peers_ro | bit,
+            GrantPerm::ReadWrite => self.peers_ro & !bit,
+        };
+        self
+    }
+
+    /// This grant without peer `task` (< NUM_TASKS); the others keep their access.
+    pub const fn without_peer(mut self, task: usize) -> Grant {
+        let keep: PeerSet = !(1 << task);
+        self.peers &= keep;
+        self.peers_ro &= keep;
+        self
+    }
+}
+
 // ─── Static grant table ────────────────────────────────────────────
 
 pub static GRANTS: KernelCell<[Grant; MAX_GRANTS]> = KernelCell::new([EMPTY_GRANT; MAX_GRANTS]);
 
-// ─── Grant page addresses (from linker) ────────────────────────────
+/// Host-test backing store for the grant pool pages.
+#[cfg(not(target_arch = "aarch64"))]
+static HOST_GRANT_POOL: KernelCell<[[u8; GRANT_PAGE_SIZE]; GRANT_POOL_PAGES]> =
+    KernelCell::new([[0; GRANT_PAGE_SIZE]; GRANT_POOL_PAGES]);
+
+// ─── Grant pool (from linker) ──────────────────────────────────────
 
-/// Get the physical address of grant page `grant_id`.
-/// Returns None if grant_id is out of range.
+/// Physical address of the first grant pool page.
 #[cfg(target_arch = "aarch64")]
-pub fn grant_page_addr(grant_id: usize) -> Option<u64> {
-    if grant_id >= MAX_GRANTS {
-        return None;
-    }
+pub fn grant_pool_base() -> u64 {
     extern "C" {
         static __grant_pages_start: u8;
     }
-    // SAFETY: Linker-provided symbol, address taken for grant page calculation.
-    let base = unsafe { &__grant_pages_start as *const u8 as u64 };
-    Some(base + (grant_id as u64) * GRANT_PAGE_SIZE as u64)
+    // SAFETY: Linker-provided symbol, address taken for grant pool calculation.
+    unsafe { &__grant_pages_start as *const u8 as u64 }
 }
 
-/// Host-test stub: return a fake but distinct address per grant.
+/// Host-test stub: fake pool base within the first 2MiB (L3 range).
 #[cfg(not(target_arch = "aarch64"))]
-pub fn grant_page_addr(grant_id: usize) -> Option<u64> {
+pub fn grant_pool_base() -> u64 {
+    0x4010_0000
+}
+
+/// Kernel pointer to pool address `addr` (the pool is identity-mapped;
+/// host tests get the matching byte of HOST_GRANT_POOL).
+/// Returns None if `addr` is outside the pool.
+pub fn pool_ptr(addr: u64) -> Option<*mut u8> {
+    let offset = addr.checked_sub(grant_pool_base())? as usize;
+    if offset >= GRANT_POOL_PAGES * GRANT_PAGE_SIZE {
+        return None;
+    }
+    #[cfg(target_arch = "aarch64")]
+    let base = grant_pool_base() as *mut u8;
+    // SAFETY: Single-core test harness; only the address is taken here.
+    #[cfg(not(target_arch = "aarch64"))]
+    let base = unsafe { HOST_GRANT_POOL.get_mut().as_mut_ptr() as *mut u8 };
+    // SAFETY: offset < pool size, so the result stays inside the pool.
+    Some(unsafe { base.add(offset) })
+}
+
+/// Fill `pages` pool pages starting at `phys` with `pattern`.
+/// Ignored unless the whole range lies in the pool.
+///
+/// Callers unmap every member first: an AP_RO_EL0 entry is read-only at
+/// EL1 too, and no task may observe a half-scrubbed page. The stores are
+/// volatile so they survive even though the kernel never reads them back.
+pub fn scrub_region(phys: u64, pages: usize, pattern: u8) {
+    if pages == 0 || pages > GRANT_POOL_PAGES || !phys.is_multiple_of(8) {
+        return;
+    }
+    let len = pages * GRANT_PAGE_SIZE;
+    let (Some(start), Some(_)) = (pool_ptr(phys), pool_ptr(phys + len as u64 - 1)) else {
+        return;
+    };
+    let word = u64::from_ne_bytes([pattern; 8]);
+    let start = start as *mut u64;
+    for i in 0..len / 8 {
+        // SAFETY: [phys, phys + len) is inside the pool (checked above),
+        // 8-byte aligned, and mapped writable at EL1 once no member maps it.
+        unsafe { core::ptr::write_volatile(start.add(i), word) };
+    }
+}
+
+/// Base address of active grant `grant_id`'s region.
+/// Returns None if grant_id is out of range or the grant is inactive.
+pub fn grant_base(grant_id: usize) -> Option<u64> {
     if grant_id >= MAX_GRANTS {
         return None;
     }
-    // Fake addresses within the first 2MiB (L3 range) for test purposes
-    Some(0x4010_0000_u64 + (grant_id as u64) * GRANT_PAGE_SIZE as u64)
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    let g = unsafe { GRANTS.get()[grant_id] };
+    if g.active { Some(g.phys_addr) } else { None }
+}
+
+/// Scrub the whole pool at boot — `.grant_pages` is NOLOAD, so it is not
+/// cleared with .bss and may hold whatever RAM held before.
+pub fn init() {
+    scrub_region(grant_pool_base(), GRANT_POOL_PAGES, GRANT_SCRUB);
+}
+
+// ─── Pool allocation (pure) ────────────────────────────────────────
+
+/// Bitmask of `pages` pool pages starting at page `first`.
+const fn page_run(first: usize, pages: usize) -> u64 {
+    let run = if pages >= 64 { u64::MAX } else { (1u64 << pages) - 1 };
+    run << first
+}
+
+/// Pool pages held by the regions in `grants`, ignoring grant `skip`.
+pub fn pool_used(grants: &[Grant; MAX_GRANTS], pool_base: u64, skip: usize) -> u64 {
+    let mut used = 0;
+    let mut i = 0;
+    while i < MAX_GRANTS {
+        let g = &grants[i];
+        if i != skip && g.pages > 0 && g.phys_addr >= pool_base {
+            let first = ((g.phys_addr - pool_base) / GRANT_PAGE_SIZE as u64) as usize;
+            if first < GRANT_POOL_PAGES && g.pages <= GRANT_POOL_PAGES - first {
+                used |= page_run(first, g.pages);
+            }
+        }
+        i += 1;
+    }
+    used
+}
+
+/// Place a region of `pages` pool pages whose physical base is a multiple
+/// of `align` pages, first fit. The region held by grant `skip` counts as
+/// free (it is being replaced). Returns the base address or ERR_GRANT_*.
+pub fn grant_alloc(
+    grants: &[Grant; MAX_GRANTS],
+    pool_base: u64,
+    skip: usize,
+    pages: usize,
+    align: usize,
+) -> Result<u64, u64> {
+    if pages == 0 || pages > GRANT_POOL_PAGES || align == 0 || align > MAX_GRANT_ALIGN || !align.is_power_of_two() {
+        return Err(ERR_GRANT_SIZE);
+    }
+    let used = pool_used(grants, pool_base, skip);
+    if GRANT_POOL_PAGES - (used.count_ones() as usize) < pages {
+        return Err(ERR_GRANT_EXHAUSTED);
+    }
+    let align_bytes = (align * GRANT_PAGE_SIZE) as u64;
+    let mut first = 0;
+    while first + pages <= GRANT_POOL_PAGES {
+        let base = pool_base + (first * GRANT_PAGE_SIZE) as u64;
+        if base.is_multiple_of(align_bytes) && used & page_run(first, pages) == 0 {
+            return Ok(base);
+        }
+        first += 1;
+    }
+    Err(ERR_GRANT_FRAGMENTED)
 }
 
 // ─── Core operations ───────────────────────────────────────────────
 
-/// Create a shared memory grant.
-/// `grant_id`: which grant page (0..MAX_GRANTS)
+/// Create a one-page read-write shared memory grant (see `grant_create_region`).
+pub fn grant_create(grant_id: usize, owner: usize, peer: usize) -> u64 {
+    grant_create_region(grant_id, owner, peer, 1, 1, 0)
+}
+
+/// Create a shared memory grant over `pages` contiguous pool pages.
+/// `grant_id`: which grant descriptor (0..MAX_GRANTS)
 /// `owner`: task creating the grant (current task)
-/// `peer`: task receiving shared access
+/// `peer`: first task receiving shared access (more via `grant_add_peer`)
+/// `align`: base alignment in pages (power of two)
+/// `flags`: GRANT_PEER_RO / GRANT_OWNER_RO (0 = read-write for both)
 ///
-/// Returns 0 on success, error code on failure:
-///   0xFFFF_0001 = invalid grant_id
-///   0xFFFF_0002 = grant already active
-///   0xFFFF_0003 = invalid peer
-///   0xFFFF_0004 = owner == peer
-pub fn grant_create(grant_id: usize, owner: usize, peer: usize) -> u64 {
+/// Returns 0 on success, ERR_GRANT_* on failure. The region's base is
+/// then available from `grant_base(grant_id)`.
+pub fn grant_create_region(
+    grant_id: usize,
+    owner: usize,
+    peer: usize,
+    pages: usize,
+    align: usize,
+    flags: u64,
+) -> u64 {
     if grant_id >= MAX_GRANTS {
         uart_print("!!! GRANT: invalid grant_id\n");
-        return 0xFFFF_0001;
+        return ERR_GRANT_INVALID_ID;
     }
 
     // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
     unsafe {
         if (*GRANTS.get_mut())[grant_id].active {
             uart_print("!!! GRANT: already active\n");
-            return 0xFFFF_0002;
+            return ERR_GRANT_ACTIVE;
         }
 
         if peer >= sched::NUM_TASKS {
             uart_print("!!! GRANT: invalid peer\n");
-            return 0xFFFF_0003;
+            return ERR_GRANT_INVALID_PEER;
         }
 
         if owner == peer {
             uart_print("!!! GRANT: owner == peer\n");
-            return 0xFFFF_0004;
+            return ERR_GRANT_SELF;
         }
 
-        let phys = match grant_page_addr(grant_id) {
-            Some(addr) => addr,
-            None => return 0xFFFF_0001,
+        let (owner_perm, peer_perm) = match grant_perms(flags) {
+            Ok(perms) => perms,
+            Err(err) => {
+                uart_print("!!! GRANT: invalid flags\n");
+                return err;
+            }
+        };
+
+        let phys = match grant_alloc(GRANTS.get(), grant_pool_base(), grant_id, pages, align) {
+            Ok(addr) => addr,
+            Err(err) => {
+                uart_print(match err {
+                    ERR_GRANT_EXHAUSTED => "!!! GRANT: pool exhausted\n",
+                    ERR_GRANT_FRAGMENTED => "!!! GRANT: pool fragmented\n",
+                    _ => "!!! GRANT: invalid size or alignment\n",
+                });
+                return err;
+            }
         };
 
-        // Map grant page into both tasks' L3 page tables
+        // Map the region into both tasks' L3 page tables
         #[cfg(target_arch = "aarch64")]
         {
-            crate::mmu::map_grant_for_task(phys, owner);
-            crate::mmu::map_grant_for_task(phys, peer);
+            crate::mmu::map_grant_for_task(phys, pages, owner, owner_perm.page_attrs());
+            crate::mmu::map_grant_for_task(phys, pages, peer, peer_perm.page_attrs());
         }
 
         (*GRANTS.get_mut())[grant_id] = Grant {
             owner: Some(owner),
-            peer: Some(peer),
             phys_addr: phys,
+            pages,
+            owner_perm,
             active: true,
-        };
+            ..EMPTY_GRANT
+        }
+        .with_peer(peer, peer_perm);
 
         uart_print("[AegisOS] GRANT: task ");
         crate::uart_print_hex(owner as u64);
@@ -137,44 +416,197 @@ pub fn grant_create(grant_id: usize, owner: usize, peer: usize) -> u64 {
         crate::uart_print_hex(peer as u64);
         uart_print(" (grant ");
         crate::uart_print_hex(grant_id as u64);
+        uart_print(", ");
+        crate::uart_print_dec(pages as u64);
+        uart_print(" pages");
+        if peer_perm == GrantPerm::ReadOnly {
+            uart_print(", peer RO");
+        }
+        if owner_perm == GrantPerm::ReadOnly {
+            uart_print(", owner RO");
+        }
         uart_print(")\n");
     }
 
     0 // success
 }
 
-/// Revoke a shared memory grant.
+/// Validate adding `peer` to grant `g` on behalf of `caller`.
+/// `flags`: GRANT_PEER_RO or 0. Returns the updated grant or ERR_GRANT_*.
+pub const fn check_add_peer(g: &Grant, caller: usize, peer: usize, flags: u64) -> Result<Grant, u64> {
+    if !g.active {
+        return Err(ERR_GRANT_INACTIVE);
+    }
+    if !matches!(g.owner, Some(o) if o == caller) {
+        return Err(ERR_GRANT_NOT_OWNER);
+    }
+    if peer >= sched::NUM_TASKS {
+        return Err(ERR_GRANT_INVALID_PEER);
+    }
+    if peer == caller {
+        return Err(ERR_GRANT_SELF);
+    }
+    if flags & !GRANT_PEER_RO != 0 {
+        return Err(ERR_GRANT_FLAGS);
+    }
+    Ok(g.with_peer(peer, GrantPerm::from_ro(flags & GRANT_PEER_RO != 0)))
+}
+
+/// Validate removing `peer` from grant `g` on behalf of `caller`.
+/// Returns the updated grant or ERR_GRANT_*.
+pub const fn check_remove_peer(g: &Grant, caller: usize, peer: usize) -> Result<Grant, u64> {
+    if !g.active {
+        return Err(ERR_GRANT_INACTIVE);
+    }
+    if !matches!(g.owner, Some(o) if o == caller) {
+        return Err(ERR_GRANT_NOT_OWNER);
+    }
+    if !g.is_peer(peer) {
+        return Err(ERR_GRANT_NOT_PEER);
+    }
+    Ok(g.without_peer(peer))
+}
+
+fn print_peer_error(err: u64) {
+    uart_print(match err {
+        ERR_GRANT_INACTIVE => "!!! GRANT: not active\n",
+        ERR_GRANT_NOT_OWNER => "!!! GRANT: caller is not owner\n",
+        ERR_GRANT_INVALID_PEER => "!!! GRANT: invalid peer\n",
+        ERR_GRANT_SELF => "!!! GRANT: owner == peer\n",
+        ERR_GRANT_FLAGS => "!!! GRANT: invalid flags\n",
+        _ => "!!! GRANT: not a peer\n",
+    });
+}
+
+/// Share active grant `grant_id` with one more task.
+/// `caller`: task requesting (must be owner)
+/// `flags`: GRANT_PEER_RO maps the new peer read-only
+///
+/// Only `peer`'s L3 entries are written; the owner and the other peers
+/// are untouched. Adding an existing peer changes its access.
+/// Returns 0 on success, ERR_GRANT_* on failure.
+pub fn grant_add_peer(grant_id: usize, caller: usize, peer: usize, flags: u64) -> u64 {
+    if grant_id >= MAX_GRANTS {
+        uart_print("!!! GRANT: invalid grant_id\n");
+        return ERR_GRANT_INVALID_ID;
+    }
+
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let g = match check_add_peer(&GRANTS.get()[grant_id], caller, peer, flags) {
+            Ok(g) => g,
+            Err(err) => {
+                print_peer_error(err);
+                return err;
+            }
+        };
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            if let Some(perm) = g.peer_perm(peer) {
+                crate::mmu::map_grant_for_task(g.phys_addr, g.pages, peer, perm.page_attrs());
+            }
+        }
+
+        (*GRANTS.get_mut())[grant_id] = g;
+
+        uart_print("[AegisOS] GRANT: grant ");
+        crate::uart_print_hex(grant_id as u64);
+        uart_print(" + task ");
+        crate::uart_print_hex(peer as u64);
+        if g.peer_perm(peer) == Some(GrantPerm::ReadOnly) {
+            uart_print(" (RO)");
+        }
+        uart_print("\n");
+    }
+
+    0 // success
+}
+
+/// Stop sharing active grant `grant_id` with `peer`.
+/// `caller`: task requesting (must be owner)
+///
+/// Only `peer`'s L3 entries are unmapped; the grant stays active for
+/// the owner and the other peers, even with none left.
+/// Returns 0 on success, ERR_GRANT_* on failure.
+pub fn grant_remove_peer(grant_id: usize, caller: usize, peer: usize) -> u64 {
+    if grant_id >= MAX_GRANTS {
+        uart_print("!!! GRANT: invalid grant_id\n");
+        return ERR_GRANT_INVALID_ID;
+    }
+
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let g = match check_remove_peer(&GRANTS.get()[grant_id], caller, peer) {
+            Ok(g) => g,
+            Err(err) => {
+                print_peer_error(err);
+                return err;
+            }
+        };
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            crate::mmu::unmap_grant_for_task(g.phys_addr, g.pages, peer);
+        }
+
+        (*GRANTS.get_mut())[grant_id] = g;
+
+        uart_print("[AegisOS] GRANT: grant ");
+        crate::uart_print_hex(grant_id as u64);
+        uart_print(" - task ");
+        crate::uart_print_hex(peer as u64);
+        uart_print("\n");
+    }
+
+    0 // success
+}
+
+/// Unmap grant `g`'s region from every one of its peers.
+#[cfg(target_arch = "aarch64")]
+unsafe fn unmap_peers(g: &Grant) {
+    for task in 0..sched::NUM_TASKS {
+        if g.is_peer(task) {
+            // SAFETY: caller guarantees `g` describes a region in the grant pool.
+            unsafe { crate::mmu::unmap_grant_for_task(g.phys_addr, g.pages, task) };
+        }
+    }
+}
+
+/// Revoke a shared memory grant and return its pages to the pool.
 /// `grant_id`: which grant to revoke
 /// `caller`: task requesting revoke (must be owner)
 ///
+/// Unmaps the region from every peer and the owner, then scrubs it.
 /// Returns 0 on success, error code on failure.
 pub fn grant_revoke(grant_id: usize, caller: usize) -> u64 {
     if grant_id >= MAX_GRANTS {
         uart_print("!!! GRANT: invalid grant_id\n");
-        return 0xFFFF_0001;
+        return ERR_GRANT_INVALID_ID;
     }
 
     // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
     unsafe {
-        if !(*GRANTS.get_mut())[grant_id].active {
-            return 0; // no-op: already inactive
+        let g = (*GRANTS.get_mut())[grant_id];
+        if !g.active {
+            return 0; // no-op: nothing held
         }
 
-        if (*GRANTS.get_mut())[grant_id].owner != Some(caller) {
+        if g.owner != Some(caller) {
             uart_print("!!! GRANT: caller is not owner\n");
-            return 0xFFFF_0005;
+            return ERR_GRANT_NOT_OWNER;
         }
 
-        // Unmap from peer's page table
-        if let Some(peer) = (*GRANTS.get_mut())[grant_id].peer {
-            #[cfg(target_arch = "aarch64")]
-            {
-                crate::mmu::unmap_grant_for_task((*GRANTS.get_mut())[grant_id].phys_addr, peer);
-            }
+        // Unmap from the peers' and owner's page tables
+        #[cfg(target_arch = "aarch64")]
+        {
+            unmap_peers(&g);
+            crate::mmu::unmap_grant_for_task(g.phys_addr, g.pages, caller);
         }
+        scrub_region(g.phys_addr, g.pages, GRANT_SCRUB);
 
-        (*GRANTS.get_mut())[grant_id].active = false;
-        (*GRANTS.get_mut())[grant_id].peer = None;
+        // Owner stays recorded; the pages go back to the pool
+        (*GRANTS.get_mut())[grant_id] = Grant { owner: g.owner, ..EMPTY_GRANT };
 
         uart_print("[AegisOS] GRANT REVOKED: grant ");
         crate::uart_print_hex(grant_id as u64);
@@ -184,47 +616,55 @@ pub fn grant_revoke(grant_id: usize, caller: usize) -> u64 {
     0 // success
 }
 
+/// Revoke active grant `grant_id` if `task` owns it — `task` lost the
+/// capability slot naming it (cspace::revoke). Unmaps owner and peers.
+pub fn revoke_owned(grant_id: usize, task: usize) {
+    if grant_id >= MAX_GRANTS {
+        return;
+    }
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    let g = unsafe { GRANTS.get()[grant_id] };
+    if g.active && g.owner == Some(task) {
+        grant_revoke(grant_id, task);
+    }
+}
+
 // ─── Fault cleanup ─────────────────────────────────────────────────
 
 /// Clean up all grants involving a faulted task.
-/// If the task is owner: revoke grant (unmap peer).
-/// If the task is peer: unmap peer's access.
+/// If the task is owner: revoke grant (unmap every member), scrub and free its pages.
+/// If the task is a peer: unmap that peer only.
 /// Called from sched::fault_current_task() and sched::restart_task().
 pub fn cleanup_task(task_idx: usize) {
     // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
     unsafe {
         for i in 0..MAX_GRANTS {
-            if !(*GRANTS.get_mut())[i].active {
+            let g = (*GRANTS.get_mut())[i];
+            if !g.active {
                 continue;
             }
 
-            if (*GRANTS.get_mut())[i].owner == Some(task_idx) {
-                // Task is owner — unmap peer and deactivate
-                if let Some(peer) = (*GRANTS.get_mut())[i].peer {
-                    #[cfg(target_arch = "aarch64")]
-                    {
-                        crate::mmu::unmap_grant_for_task((*GRANTS.get_mut())[i].phys_addr, peer);
-                    }
-                }
-                // Also unmap from owner (faulted task gets fresh state on restart)
+            if g.owner == Some(task_idx) {
+                // Task is owner — unmap every peer and the owner, scrub and free the region
                 #[cfg(target_arch = "aarch64")]
                 {
-                    crate::mmu::unmap_grant_for_task((*GRANTS.get_mut())[i].phys_addr, task_idx);
+                    unmap_peers(&g);
+                    crate::mmu::unmap_grant_for_task(g.phys_addr, g.pages, task_idx);
                 }
+                scrub_region(g.phys_addr, g.pages, GRANT_SCRUB);
                 (*GRANTS.get_mut())[i] = EMPTY_GRANT;
-            } else if (*GRANTS.get_mut())[i].peer == Some(task_idx) {
-                // Task is peer — unmap peer's access, keep owner's grant active but no peer
-                // DESIGN DECISION (Phase P consensus): Asymmetric cleanup is intentional.
-                // Owner may still be alive with active MMU mapping to grant page.
-                // Zeroing owner field would leave dangling mapping → crash risk.
-                // Setting active=false gates all future access paths.
+            } else if g.is_peer(task_idx) {
+                // Task is a peer — unmap its access only
+                // DESIGN DECISION: Asymmetric cleanup is intentional.
+                // The owner and the other peers may still be alive with
+                // active MMU mappings to the region; the grant stays active
+                // for them and keeps its pages until the owner revokes it.
                 // See: docs/standard/05-proof-coverage-mapping.md §Design Decisions #1
                 #[cfg(target_arch = "aarch64")]
                 {
-                    crate::mmu::unmap_grant_for_task((*GRANTS.get_mut())[i].phys_addr, task_idx);
+                    crate::mmu::unmap_grant_for_task(g.phys_addr, g.pages, task_idx);
                 }
-                (*GRANTS.get_mut())[i].peer = None;
-                (*GRANTS.get_mut())[i].active = false;
+                (*GRANTS.get_mut())[i] = g.without_peer(task_idx);
             }
         }
     }
@@ -233,36 +673,44 @@ pub fn cleanup_task(task_idx: usize) {
 // ─── Pure functions for Kani verification (Phase P) ────────────────
 
 /// Pure grant_create: validate inputs and return new Grant state.
-/// Mirrors grant_create() logic but operates on explicit array.
-/// Does NOT touch globals or MMIO.
+/// Mirrors grant_create_region() logic but operates on explicit array.
+/// Does NOT touch globals or MMIO; placement uses the real `grant_alloc`.
 // TODO(Phase-Q+): migrate to always-available when module count > 6 or pre-cert
 #[cfg(kani)]
 pub fn grant_create_pure(
     grants: &[Grant; MAX_GRANTS],
+    pool_base: u64,
     grant_id: usize,
     owner: usize,
     peer: usize,
+    pages: usize,
+    align: usize,
+    flags: u64,
 ) -> Result<Grant, u64> {
     if grant_id >= MAX_GRANTS {
-        return Err(0xFFFF_0001);
+        return Err(ERR_GRANT_INVALID_ID);
     }
     if grants[grant_id].active {
-        return Err(0xFFFF_0002);
+        return Err(ERR_GRANT_ACTIVE);
     }
     if peer >= crate::sched::NUM_TASKS {
-        return Err(0xFFFF_0003);
+        return Err(ERR_GRANT_INVALID_PEER);
     }
     if owner == peer {
-        return Err(0xFFFF_0004);
+        return Err(ERR_GRANT_SELF);
     }
+    let (owner_perm, peer_perm) = grant_perms(flags)?;
+    let phys = grant_alloc(grants, pool_base, grant_id, pages, align)?;
     // Return the new Grant value — caller would write to grants[grant_id]
-    // phys_addr is set by grant_page_addr() in production; symbolic here
     Ok(Grant {
         owner: Some(owner),
-        peer: Some(peer),
-        phys_addr: 0, // placeholder — Kani proves logic, not HW address
+        phys_addr: phys,
+        pages,
+        owner_perm,
         active: true,
-    })
+        ..EMPTY_GRANT
+    }
+    .with_peer(peer, peer_perm))
 }
 
 /// Pure grant_revoke: validate ownership and return revoked Grant state.
@@ -275,28 +723,26 @@ pub fn grant_revoke_pure(
     caller: usize,
 ) -> Result<Grant, u64> {
     if grant_id >= MAX_GRANTS {
-        return Err(0xFFFF_0001);
+        return Err(ERR_GRANT_INVALID_ID);
     }
     if !grants[grant_id].active {
-        // no-op: already inactive — return as-is
+        // no-op: nothing held — return as-is
         return Ok(grants[grant_id]);
     }
     if grants[grant_id].owner != Some(caller) {
-        return Err(0xFFFF_0005);
+        return Err(ERR_GRANT_NOT_OWNER);
     }
-    // Return the revoked Grant — active=false, peer=None, owner preserved
+    // Return the revoked Grant — region released, owner preserved
     Ok(Grant {
         owner: grants[grant_id].owner,
-        peer: None,
-        phys_addr: grants[grant_id].phys_addr,
-        active: false,
+        ..EMPTY_GRANT
     })
 }
 
 /// Pure grant_cleanup: remove task from all grant slots.
 /// Mirrors cleanup_task() logic — returns new array state.
-/// Design decision: owner → EMPTY_GRANT; peer → active=false, peer=None.
-/// (Asymmetry is intentional — see FM.A-7 Design Decisions.)
+/// Design decision: owner → EMPTY_GRANT; peer → removed from the peer set,
+/// grant stays active. (Asymmetry is intentional — see FM.A-7 Design Decisions.)
 // TODO(Phase-Q+): migrate to always-available when module count > 6 or pre-cert
 #[cfg(kani)]
 pub fn grant_cleanup_pure(
@@ -310,11 +756,10 @@ pub fn grant_cleanup_pure(
             if result[i].owner == Some(task_idx) {
                 // Task is owner — full deactivation (EMPTY_GRANT)
                 result[i] = EMPTY_GRANT;
-            } else if result[i].peer == Some(task_idx) {
-                // Task is peer — remove peer, deactivate grant
-                // (owner's mapping not touched — owner may still be alive)
-                result[i].peer = None;
-                result[i].active = false;
+            } else if result[i].is_peer(task_idx) {
+                // Task is a peer — drop it alone
+                // (owner's and other peers' mappings not touched)
+                result[i] = result[i].without_peer(task_idx);
             }
         }
         i += 1;
@@ -328,57 +773,95 @@ pub fn grant_cleanup_pure(
 mod kani_proofs {
     use super::*;
 
-    /// Proof 1: No two active grants can share the same peer for the same peer_page.
-    /// After grant_create_pure succeeds on any slot, the resulting state has
-    /// at most one active grant per (peer, slot) combination.
-    /// Full symbolic verification (MAX_GRANTS=2).
-    #[kani::proof]
-    #[kani::unwind(3)] // MAX_GRANTS=2, loop needs 3
-    fn grant_no_overlap() {
-        // Start with symbolic initial state
+    /// Symbolic peer set of `owner`: any tasks but the owner, RO subset.
+    fn any_peers(owner: usize) -> (PeerSet, PeerSet) {
+        let all: PeerSet = (1 << crate::sched::NUM_TASKS) - 1;
+        let peers: PeerSet = kani::any();
+        let ro: PeerSet = kani::any();
+        kani::assume(peers & !all == 0 && peers & (1 << owner) == 0);
+        (peers, ro & peers)
+    }
+
+    /// Symbolic grant table: every active slot holds a region lying in the pool.
+    fn any_grants(pool_base: u64) -> [Grant; MAX_GRANTS] {
         let mut grants = [EMPTY_GRANT; MAX_GRANTS];
         let mut i: usize = 0;
         while i < MAX_GRANTS {
             grants[i].active = kani::any();
+            let owner: usize = kani::any();
+            kani::assume(owner < crate::sched::NUM_TASKS);
+            let first: usize = kani::any();
+            let pages: usize = kani::any();
+            kani::assume(first < GRANT_POOL_PAGES && pages <= GRANT_POOL_PAGES - first);
+            kani::assume(grants[i].active == (pages > 0));
             if grants[i].active {
-                let owner: usize = kani::any();
-                let peer: usize = kani::any();
-                kani::assume(owner < crate::sched::NUM_TASKS);
-                kani::assume(peer < crate::sched::NUM_TASKS);
-                kani::assume(owner != peer);
+                let (peers, ro) = any_peers(owner);
                 grants[i].owner = Some(owner);
-                grants[i].peer = Some(peer);
+                grants[i].peers = peers;
+                grants[i].peers_ro = ro;
             }
+            grants[i].pages = pages;
+            grants[i].phys_addr = pool_base + (first * GRANT_PAGE_SIZE) as u64;
             i += 1;
         }
+        grants
+    }
+
+    /// Page-aligned symbolic pool base (kept clear of overflow).
+    fn any_pool_base() -> u64 {
+        let pfn: u32 = kani::any();
+        (pfn as u64) * GRANT_PAGE_SIZE as u64
+    }
+
+    /// Proof 1: A created region never overlaps another held region.
+    /// For any table of regions in the pool and any size/alignment:
+    /// - success → the region lies in the pool, is aligned, and shares no
+    ///   page with the region of any other grant
+    /// - ERR_GRANT_EXHAUSTED only when fewer pages are free than requested;
+    ///   ERR_GRANT_FRAGMENTED only when enough pages are free
+    #[kani::proof]
+    #[kani::unwind(10)] // MAX_GRANTS=4, GRANT_POOL_PAGES=8
+    fn grant_no_overlap() {
+        let pool_base = any_pool_base();
+        let mut grants = any_grants(pool_base);
 
-        // Try to create a new grant
         let grant_id: usize = kani::any();
         let owner: usize = kani::any();
         let peer: usize = kani::any();
+        let pages: usize = kani::any();
+        let align: usize = kani::any();
         kani::assume(grant_id < MAX_GRANTS);
         kani::assume(owner < crate::sched::NUM_TASKS);
         kani::assume(peer < crate::sched::NUM_TASKS);
+        kani::assume(align <= 16);
 
-        if let Ok(new_grant) = grant_create_pure(&grants, grant_id, owner, peer) {
-            // Apply the create
-            grants[grant_id] = new_grant;
-
-            // PROPERTY: No two active grants occupy the same slot
-            // (trivially true since we wrote to grants[grant_id])
-            assert!(grants[grant_id].active);
-            assert_eq!(grants[grant_id].owner, Some(owner));
-            assert_eq!(grants[grant_id].peer, Some(peer));
+        let free = GRANT_POOL_PAGES - pool_used(&grants, pool_base, grant_id).count_ones() as usize;
+        match grant_create_pure(&grants, pool_base, grant_id, owner, peer, pages, align, 0) {
+            Ok(new_grant) => {
+                // Apply the create
+                grants[grant_id] = new_grant;
+                let g = grants[grant_id];
+                assert!(g.active && g.owner == Some(owner) && g.peers == 1 << peer);
+                assert!(g.phys_addr >= pool_base);
+                let first = ((g.phys_addr - pool_base) / GRANT_PAGE_SIZE as u64) as usize;
+                assert!(first + g.pages <= GRANT_POOL_PAGES, "region lies in the pool");
+                assert!(g.phys_addr % (align * GRANT_PAGE_SIZE) as u64 == 0, "region is aligned");
 
-            // PROPERTY: The create only succeeded because the slot was inactive
-            // (enforced by the Err(0xFFFF_0002) check)
+                // PROPERTY: no page of the new region is held by another grant
+                let mine = page_run(first, g.pages);
+                assert!(pool_used(&grants, pool_base, grant_id) & mine == 0);
+            }
+            Err(ERR_GRANT_EXHAUSTED) => assert!(free < pages),
+            Err(ERR_GRANT_FRAGMENTED) => assert!(free >= pages),
+            Err(_) => {}
         }
     }
 
-    /// Proof 2: After cleanup, task is NOT in any active grant (as owner or peer).
-    /// Full symbolic verification (MAX_GRANTS=2).
+    /// Proof 2: After cleanup, task is NOT in any active grant (as owner or
+    /// peer), and a grant it only peered in stays active for every other peer.
+    /// Full symbolic verification (MAX_GRANTS=4).
     #[kani::proof]
-    #[kani::unwind(3)] // MAX_GRANTS=2, loop needs 3
+    #[kani::unwind(5)] // MAX_GRANTS=4, loop needs 5
     fn grant_cleanup_completeness() {
         let task_idx: usize = kani::any();
         kani::assume(task_idx < crate::sched::NUM_TASKS);
@@ -390,13 +873,13 @@ mod kani_proofs {
             grants[i].active = kani::any();
             if grants[i].active {
                 let owner: usize = kani::any();
-                let peer: usize = kani::any();
                 kani::assume(owner < crate::sched::NUM_TASKS);
-                kani::assume(peer < crate::sched::NUM_TASKS);
-                kani::assume(owner != peer);
+                let (peers, ro) = any_peers(owner);
                 grants[i].owner = Some(owner);
-                grants[i].peer = Some(peer);
+                grants[i].peers = peers;
+                grants[i].peers_ro = ro;
                 grants[i].phys_addr = kani::any();
+                grants[i].pages = kani::any();
             }
             i += 1;
         }
@@ -413,39 +896,45 @@ mod kani_proofs {
                     "cleanup must remove task from owner"
                 );
                 assert!(
-                    result[j].peer != Some(task_idx),
-                    "cleanup must remove task from peer"
+                    !result[j].is_peer(task_idx),
+                    "cleanup must remove task from peers"
                 );
             }
-            // Also check inactive grants don't reference task as owner
-            // (peer path sets active=false but owner may still be there if task was peer)
             if result[j].owner == Some(task_idx) {
                 // If task was owner, grant must be EMPTY_GRANT
                 assert!(!result[j].active, "owner cleanup must deactivate");
             }
+            // PROPERTY: a faulted peer takes no one else's access with it
+            if grants[j].active && grants[j].owner != Some(task_idx) {
+                assert!(result[j].active, "grant survives a peer fault");
+                assert_eq!(result[j].peers, grants[j].peers & !(1 << task_idx));
+                assert_eq!(result[j].peers_ro, grants[j].peers_ro & !(1 << task_idx));
+                assert_eq!(result[j].phys_addr, grants[j].phys_addr);
+            }
             j += 1;
         }
     }
 
     /// Proof 3: When all slots are full, create returns error without corrupting state.
-    /// Full symbolic verification (MAX_GRANTS=2).
+    /// Full symbolic verification (MAX_GRANTS=4).
     #[kani::proof]
-    #[kani::unwind(3)] // MAX_GRANTS=2, loop needs 3
+    #[kani::unwind(5)] // MAX_GRANTS=4, loop needs 5
     fn grant_slot_exhaustion_safe() {
         // All slots active
         let mut grants = [EMPTY_GRANT; MAX_GRANTS];
         let mut i: usize = 0;
         while i < MAX_GRANTS {
             let owner: usize = kani::any();
-            let peer: usize = kani::any();
             kani::assume(owner < crate::sched::NUM_TASKS);
-            kani::assume(peer < crate::sched::NUM_TASKS);
-            kani::assume(owner != peer);
+            let (peers, ro) = any_peers(owner);
             grants[i] = Grant {
                 owner: Some(owner),
-                peer: Some(peer),
+                peers,
+                peers_ro: ro,
                 phys_addr: kani::any(),
+                pages: kani::any(),
                 active: true,
+                ..EMPTY_GRANT
             };
             i += 1;
         }
@@ -461,19 +950,137 @@ mod kani_proofs {
         kani::assume(owner < crate::sched::NUM_TASKS);
         kani::assume(peer < crate::sched::NUM_TASKS);
 
-        let result = grant_create_pure(&grants, grant_id, owner, peer);
+        let result = grant_create_pure(&grants, kani::any(), grant_id, owner, peer, kani::any(), kani::any(), kani::any());
 
         // PROPERTY: create fails when slot is active
         assert!(result.is_err(), "create on active slot must fail");
-        assert_eq!(result.unwrap_err(), 0xFFFF_0002);
+        assert_eq!(result.unwrap_err(), ERR_GRANT_ACTIVE);
 
         // PROPERTY: original state is unmodified (pure function doesn't mutate input)
         let mut j: usize = 0;
         while j < MAX_GRANTS {
             assert_eq!(grants[j].active, original[j].active);
             assert_eq!(grants[j].owner, original[j].owner);
-            assert_eq!(grants[j].peer, original[j].peer);
+            assert_eq!(grants[j].peers, original[j].peers);
+            assert_eq!(grants[j].pages, original[j].pages);
             j += 1;
         }
     }
+
+    /// Proof 4: Revoke returns exactly the grant's pages to the pool.
+    /// For any table of regions, an owner's revoke leaves the grant
+    /// inactive with no peers and no pages; the pool then holds exactly the
+    /// other grants' pages. Non-owners are refused while the grant is active.
+    #[kani::proof]
+    #[kani::unwind(10)] // MAX_GRANTS=4, GRANT_POOL_PAGES=8
+    fn grant_revoke_frees_region() {
+        let pool_base = any_pool_base();
+        let mut grants = any_grants(pool_base);
+        let grant_id: usize = kani::any();
+        let caller: usize = kani::any();
+        kani::assume(grant_id < MAX_GRANTS);
+        kani::assume(caller < crate::sched::NUM_TASKS);
+
+        let held = grants[grant_id].active;
+        let others = pool_used(&grants, pool_base, grant_id);
+        match grant_revoke_pure(&grants, grant_id, caller) {
+            Ok(g) => {
+                assert!(!held || grants[grant_id].owner == Some(caller), "only the owner releases");
+                assert!(!g.active && g.peers == 0 && g.pages == 0);
+                assert_eq!(g.owner, grants[grant_id].owner, "owner stays recorded");
+                grants[grant_id] = g;
+                assert_eq!(pool_used(&grants, pool_base, MAX_GRANTS), others, "exactly its pages freed");
+            }
+            Err(e) => {
+                assert_eq!(e, ERR_GRANT_NOT_OWNER);
+                assert!(held && grants[grant_id].owner != Some(caller));
+            }
+        }
+    }
+
+    /// Proof 5: A read-only side is never mapped writable from EL0.
+    /// For any flags and any create:
+    /// - unknown flag bits are refused with ERR_GRANT_FLAGS
+    /// - a side flagged RO gets ReadOnly, and its page template has
+    ///   AP[2] set (no EL0 or EL1 store); an unflagged side is RW
+    #[kani::proof]
+    #[kani::unwind(10)] // MAX_GRANTS=4, GRANT_POOL_PAGES=8
+    fn grant_read_only_side_not_writable() {
+        let pool_base = any_pool_base();
+        let grants = any_grants(pool_base);
+        let grant_id: usize = kani::any();
+        let owner: usize = kani::any();
+        let peer: usize = kani::any();
+        let flags: u64 = kani::any();
+        kani::assume(grant_id < MAX_GRANTS);
+
+        match grant_create_pure(&grants, pool_base, grant_id, owner, peer, 1, 1, flags) {
+            Ok(g) => {
+                assert!(flags & !(GRANT_PEER_RO | GRANT_OWNER_RO) == 0, "unknown flags refused");
+                let peer_perm = g.peer_perm(peer).unwrap();
+                let sides = [(g.owner_perm, GRANT_OWNER_RO), (peer_perm, GRANT_PEER_RO)];
+                let mut i = 0;
+                while i < 2 {
+                    let (perm, bit) = sides[i];
+                    let ap = perm.page_attrs() & (0b11 << 6);
+                    if flags & bit != 0 {
+                        assert!(perm == GrantPerm::ReadOnly);
+                        assert!(ap & (0b10 << 6) != 0, "RO side has AP[2] set");
+                    } else {
+                        assert!(perm == GrantPerm::ReadWrite);
+                        assert!(ap == crate::mmu::AP_RW_EL0);
+                    }
+                    i += 1;
+                }
+            }
+            Err(ERR_GRANT_FLAGS) => assert!(flags & !(GRANT_PEER_RO | GRANT_OWNER_RO) != 0),
+            Err(_) => {}
+        }
+    }
+
+    /// Proof 6: Adding or removing a peer touches that peer alone.
+    /// For any active grant, caller, peer and flags:
+    /// - only the owner succeeds; remove needs an existing peer
+    /// - on success `peer` gains (RO iff GRANT_PEER_RO) or loses access,
+    ///   while owner, region, activity and every other peer's access are
+    ///   unchanged
+    #[kani::proof]
+    fn grant_peer_update_isolated() {
+        let owner: usize = kani::any();
+        kani::assume(owner < crate::sched::NUM_TASKS);
+        let (peers, ro) = any_peers(owner);
+        let g = Grant {
+            owner: Some(owner),
+            peers,
+            peers_ro: ro,
+            phys_addr: kani::any(),
+            pages: kani::any(),
+            active: kani::any(),
+            ..EMPTY_GRANT
+        };
+        let caller: usize = kani::any();
+        let peer: usize = kani::any();
+        let flags: u64 = kani::any();
+        let add: bool = kani::any();
+
+        let result = if add { check_add_peer(&g, caller, peer, flags) } else { check_remove_peer(&g, caller, peer) };
+        match result {
+            Ok(n) => {
+                assert!(g.active && caller == owner, "only the owner of an active grant");
+                assert!(n.active && n.owner == g.owner && n.phys_addr == g.phys_addr && n.pages == g.pages);
+                let bit: PeerSet = 1 << peer;
+                assert_eq!(n.peers & !bit, g.peers & !bit, "other peers unchanged");
+                assert_eq!(n.peers_ro & !bit, g.peers_ro & !bit, "other peers' access unchanged");
+                if add {
+                    let want = if flags & GRANT_PEER_RO != 0 { GrantPerm::ReadOnly } else { GrantPerm::ReadWrite };
+                    assert!(peer != owner && n.peer_perm(peer) == Some(want));
+                } else {
+                    assert!(g.is_peer(peer) && n.peer_perm(peer).is_none());
+                }
+            }
+            Err(_) => assert!(!g.active || caller != owner || peer >= crate::sched::NUM_TASKS
+                || (add && (peer == owner || flags & !GRANT_PEER_RO != 0))
+                || (!add && !g.is_peer(peer))),
+        }
+    }
 }
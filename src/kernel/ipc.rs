@@ -7,6 +7,37 @@
 ///   SYS_SEND = 1: send message to endpoint (blocks if no receiver)
 ///   SYS_RECV = 2: receive message from endpoint (blocks if no sender)
 ///   SYS_CALL = 3: send + recv (client call pattern)
+///   SYS_REPLY = 16: answer the pending caller via the reply object
+///   SYS_REPLY_RECV = 17: reply + recv in one trap (server loop)
+///   SYS_RECV_ANY = 22: wait on a set of endpoints + notification bits
+///
+/// Reply objects: when a server receives a call, a one-shot reply
+/// capability naming the caller is stored in the server's TCB
+/// (`reply_to`). The caller stays blocked until SYS_REPLY consumes it,
+/// so replies can never reach the wrong client. The server inherits
+/// the caller's priority for as long as it holds the reply object.
+///
+/// Long messages: each task owns a kernel-managed IPC buffer page, mapped
+/// at IPC_BUFFER_VA in its own address space. Setting MSG_FLAG_BYTES in x5
+/// attaches the length-prefixed payload staged there (≤ IPC_BUF_MAX_PAYLOAD
+/// bytes); copy_message moves it into the receiver's buffer.
+///
+/// Sender identity: on every delivery the kernel overwrites the
+/// receiver's x4 with the sender's task id and x5 with the badge bound to
+/// the sender's send capability on that endpoint (NO_BADGE for replies).
+/// Both come from kernel state, never from the sender's registers, so a
+/// client cannot impersonate another.
+///
+/// Timeouts: x4 carries a timeout in ticks for every blocking IPC syscall.
+///   TIMEOUT_INFINITE (u64::MAX) blocks until a partner arrives,
+///   TIMEOUT_POLL (0) never blocks. On expiry the task is dequeued from
+///   the endpoint and resumes with ERR_IPC_TIMEOUT in x0.
+///
+/// Wait sets: SYS_RECV_ANY blocks on several endpoints (x6 = bitmask, bit
+/// i = endpoint i) and on notification bits (x0 = mask) at once, so a
+/// driver can serve clients and its IRQ from one loop. The first source
+/// to fire ends the whole wait; x6 then names it — the endpoint id, or
+/// WAIT_SRC_NOTIFY with the fired bits in x0.
 
 use crate::exception::TrapFrame;
 use crate::kernel::cell::KernelCell;
@@ -21,11 +52,67 @@ pub const SYS_SEND: u64 = 1;
 pub const SYS_RECV: u64 = 2;
 #[allow(dead_code)]
 pub const SYS_CALL: u64 = 3;
+#[allow(dead_code)]
+pub const SYS_REPLY: u64 = 16;
+#[allow(dead_code)]
+pub const SYS_REPLY_RECV: u64 = 17;
+#[allow(dead_code)]
+pub const SYS_RECV_ANY: u64 = 22;
 
 pub const MAX_ENDPOINTS: usize = 4;
 pub const MSG_REGS: usize = 4; // x[0]..x[3]
 pub const MAX_WAITERS: usize = 4; // max senders queued per endpoint
 
+/// Timeout value: block until a partner arrives (no deadline).
+pub const TIMEOUT_INFINITE: u64 = u64::MAX;
+/// Timeout value: non-blocking poll — fail at once if no partner is waiting.
+pub const TIMEOUT_POLL: u64 = 0;
+
+/// Returned in x0 when a send/recv/call times out or a poll finds no partner.
+pub const ERR_IPC_TIMEOUT: u64 = 0xFFFF_4001;
+/// Returned to a server calling SYS_REPLY without a pending reply object.
+pub const ERR_IPC_NO_REPLY: u64 = 0xFFFF_4002;
+/// Returned to a caller whose server faulted or dropped the reply object.
+pub const ERR_IPC_REPLY_LOST: u64 = 0xFFFF_4003;
+/// Returned when the staged IPC-buffer payload exceeds IPC_BUF_MAX_PAYLOAD.
+pub const ERR_IPC_MSG_TOO_LONG: u64 = 0xFFFF_4004;
+/// Another task already waits to receive on an endpoint of the wait set.
+pub const ERR_IPC_BUSY: u64 = 0xFFFF_4005;
+
+// ─── IPC buffer page ───────────────────────────────────────────────
+
+/// User VA of the calling task's own IPC buffer page (last 4 KiB of the
+/// L3-mapped 2 MiB region; every task sees its own page here).
+pub const IPC_BUFFER_VA: u64 = 0x401F_F000;
+/// IPC buffer page size (must match linker.ld allocation)
+pub const IPC_BUF_SIZE: usize = 4096;
+/// Length prefix (u64 byte count) at the start of the buffer
+pub const IPC_BUF_HEADER: usize = 8;
+/// Largest payload one message can carry through the IPC buffer
+pub const IPC_BUF_MAX_PAYLOAD: usize = 512;
+/// x5 flag on send/call/reply: attach the payload staged in the IPC buffer
+pub const MSG_FLAG_BYTES: u64 = 1 << 0;
+
+// ─── Sender identity ───────────────────────────────────────────────
+
+/// Receive-side register holding the kernel-stamped sender task id
+pub const MSG_REG_SENDER: usize = 4;
+/// Receive-side register holding the sender's capability badge
+pub const MSG_REG_BADGE: usize = 5;
+/// Badge of an unbadged send right (and of every reply)
+pub const NO_BADGE: u64 = 0;
+/// Per-endpoint badge table of a task with no badged send rights
+pub const NO_BADGES: [u64; MAX_ENDPOINTS] = [NO_BADGE; MAX_ENDPOINTS];
+
+/// Notification mask of a plain SYS_WAIT_NOTIFY: any bit wakes the task.
+pub const NOTIFY_ALL: u64 = u64::MAX;
+/// Endpoint-set bits that name real endpoints (x6 of SYS_RECV_ANY).
+pub const WAIT_SET_ALL: u64 = (1 << MAX_ENDPOINTS) - 1;
+/// x6 after SYS_RECV_ANY when notification bits fired (bits in x0).
+pub const WAIT_SRC_NOTIFY: u64 = 0xFF;
+/// x6 after SYS_RECV_ANY timed out (x0 = ERR_IPC_TIMEOUT).
+pub const WAIT_SRC_NONE: u64 = u64::MAX;
+
 // ─── Endpoint ──────────────────────────────────────────────────────
 
 /// Circular queue for sender waiters on an endpoint.
@@ -102,17 +189,59 @@ pub const EMPTY_EP: Endpoint = Endpoint {
 
 pub static ENDPOINTS: KernelCell<[Endpoint; MAX_ENDPOINTS]> = KernelCell::new([EMPTY_EP; MAX_ENDPOINTS]);
 
+/// Host-test backing store for the per-task IPC buffer pages.
+#[cfg(not(target_arch = "aarch64"))]
+static HOST_IPC_BUFFERS: KernelCell<[[u8; IPC_BUF_SIZE]; sched::NUM_TASKS]> =
+    KernelCell::new([[0; IPC_BUF_SIZE]; sched::NUM_TASKS]);
+
+// ─── IPC buffer addresses (from linker) ────────────────────────────
+
+/// Get the physical (identity-mapped) address of `task`'s IPC buffer page.
+/// Returns None if task is out of range.
+#[cfg(target_arch = "aarch64")]
+pub fn ipc_buffer_addr(task: usize) -> Option<u64> {
+    if task >= sched::NUM_TASKS {
+        return None;
+    }
+    extern "C" {
+        static __ipc_buffers_start: u8;
+    }
+    // SAFETY: Linker-provided symbol, address taken for IPC buffer calculation.
+    let base = unsafe { &__ipc_buffers_start as *const u8 as u64 };
+    Some(base + (task as u64) * IPC_BUF_SIZE as u64)
+}
+
+/// Host-test stub: address of the task's page in HOST_IPC_BUFFERS.
+#[cfg(not(target_arch = "aarch64"))]
+pub fn ipc_buffer_addr(task: usize) -> Option<u64> {
+    if task >= sched::NUM_TASKS {
+        return None;
+    }
+    // SAFETY: Single-core test harness; only the address is taken here.
+    let base = unsafe { HOST_IPC_BUFFERS.get_mut().as_mut_ptr() as u64 };
+    Some(base + (task as u64) * IPC_BUF_SIZE as u64)
+}
+
 // ─── IPC operations ────────────────────────────────────────────────
 
-/// sys_send(frame, ep_id): send message on endpoint.
+/// sys_send(frame, ep_id): send message on endpoint, waiting forever.
+pub fn sys_send(frame: &mut TrapFrame, ep_id: usize) {
+    sys_send_timeout(frame, ep_id, TIMEOUT_INFINITE);
+}
+
+/// sys_send_timeout(frame, ep_id, timeout): send message on endpoint.
 /// Message payload: frame.x[0..4] → receiver's x[0..4].
 /// If a receiver is already waiting: deliver immediately, unblock receiver.
-/// Otherwise: block sender, enqueue, schedule away.
-pub fn sys_send(frame: &mut TrapFrame, ep_id: usize) {
+/// Otherwise: block sender, enqueue, schedule away — or fail with
+/// ERR_IPC_TIMEOUT at once when `timeout` is TIMEOUT_POLL.
+pub fn sys_send_timeout(frame: &mut TrapFrame, ep_id: usize, timeout: u64) {
     if ep_id >= MAX_ENDPOINTS {
         uart_print("!!! IPC: invalid endpoint\n");
         return;
     }
+    if !check_payload(frame) {
+        return;
+    }
 
     // SAFETY: Single-core kernel, interrupts masked during kernel execution.
     // No concurrent access on uniprocessor QEMU virt.
@@ -126,30 +255,39 @@ pub fn sys_send(frame: &mut TrapFrame, ep_id: usize) {
         if let Some(recv_task) = (*ENDPOINTS.get_mut())[ep_id].receiver.take() {
             // Receiver is waiting — deliver message directly
             copy_message(current, recv_task);
-
-            // Phase K4: Restore receiver's base priority if it was boosted
-            sched::restore_base_priority(recv_task);
+            stamp_sender(current, recv_task, Some(ep_id));
+            leave_wait_set(recv_task, ep_id as u64);
 
             // Unblock receiver
-            sched::set_task_state(recv_task, TaskState::Ready);
+            wake_partner(recv_task);
 
             // Sender continues (not blocked)
+        } else if timeout == TIMEOUT_POLL {
+            // Poll — nobody to deliver to
+            frame.x[0] = ERR_IPC_TIMEOUT;
         } else {
             // No receiver — enqueue sender and block
             if !(*ENDPOINTS.get_mut())[ep_id].sender_queue.push(current) {
                 uart_print("!!! IPC: sender queue full\n");
                 return;
             }
-            sched::set_task_state(current, TaskState::Blocked);
-            sched::schedule(frame);
+            block_current(frame, current, timeout);
         }
     }
 }
 
-/// sys_recv(frame, ep_id): receive message from endpoint.
-/// If a sender is already waiting: receive immediately, unblock sender.
-/// Otherwise: block receiver, enqueue, schedule away.
+/// sys_recv(frame, ep_id): receive message from endpoint, waiting forever.
 pub fn sys_recv(frame: &mut TrapFrame, ep_id: usize) {
+    sys_recv_timeout(frame, ep_id, TIMEOUT_INFINITE);
+}
+
+/// sys_recv_timeout(frame, ep_id, timeout): receive message from endpoint.
+/// If a sender is already waiting: receive immediately, unblock sender —
+/// unless it is a caller, which stays blocked on our reply object.
+/// Otherwise: block receiver, enqueue, schedule away — or fail with
+/// ERR_IPC_TIMEOUT at once when `timeout` is TIMEOUT_POLL, or with
+/// ERR_IPC_BUSY if another task (e.g. in SYS_RECV_ANY) already waits.
+pub fn sys_recv_timeout(frame: &mut TrapFrame, ep_id: usize, timeout: u64) {
     if ep_id >= MAX_ENDPOINTS {
         uart_print("!!! IPC: invalid endpoint\n");
         return;
@@ -167,31 +305,50 @@ pub fn sys_recv(frame: &mut TrapFrame, ep_id: usize) {
         if let Some(send_task) = (*ENDPOINTS.get_mut())[ep_id].sender_queue.pop() {
             // Sender is waiting — receive message directly
             copy_message(send_task, current);
-
-            // Phase K4: Restore sender's base priority if it was boosted
-            sched::restore_base_priority(send_task);
-
-            // Unblock sender
-            sched::set_task_state(send_task, TaskState::Ready);
+            stamp_sender(send_task, current, Some(ep_id));
+
+            if (*sched::TCBS.get())[send_task].awaiting_reply {
+                // Caller — keep it blocked until we answer via SYS_REPLY
+                attach_reply(current, send_task);
+            } else {
+                // Unblock sender
+                wake_partner(send_task);
+            }
 
             // Load received message back into our frame so caller sees it
             sched::load_frame(current, frame);
+        } else if timeout == TIMEOUT_POLL {
+            // Poll — no message pending
+            frame.x[0] = ERR_IPC_TIMEOUT;
+        } else if matches!((*ENDPOINTS.get())[ep_id].receiver, Some(t) if t != current) {
+            // Another task already waits here — don't steal its registration
+            frame.x[0] = ERR_IPC_BUSY;
         } else {
             // No sender — block receiver and wait
             (*ENDPOINTS.get_mut())[ep_id].receiver = Some(current);
-            sched::set_task_state(current, TaskState::Blocked);
-            sched::schedule(frame);
+            block_current(frame, current, timeout);
         }
     }
 }
 
 /// sys_call(frame, ep_id): send message, then block to receive reply.
-/// Equivalent to send + recv atomically.
+/// Equivalent to send + recv atomically. Waits forever.
 pub fn sys_call(frame: &mut TrapFrame, ep_id: usize) {
+    sys_call_timeout(frame, ep_id, TIMEOUT_INFINITE);
+}
+
+/// sys_call_timeout(frame, ep_id, timeout): send + wait for the server's
+/// SYS_REPLY, with one deadline covering both the send and the reply wait. With TIMEOUT_POLL the call
+/// fails at once unless a server is already waiting; once the request is
+/// delivered the reply wait is then unbounded.
+pub fn sys_call_timeout(frame: &mut TrapFrame, ep_id: usize, timeout: u64) {
     if ep_id >= MAX_ENDPOINTS {
         uart_print("!!! IPC: invalid endpoint\n");
         return;
     }
+    if !check_payload(frame) {
+        return;
+    }
 
     // SAFETY: Single-core kernel, interrupts masked during kernel execution.
     // No concurrent access on uniprocessor QEMU virt.
@@ -205,41 +362,454 @@ pub fn sys_call(frame: &mut TrapFrame, ep_id: usize) {
         if let Some(recv_task) = (*ENDPOINTS.get_mut())[ep_id].receiver.take() {
             // Receiver is waiting — deliver message
             copy_message(current, recv_task);
+            stamp_sender(current, recv_task, Some(ep_id));
+            leave_wait_set(recv_task, ep_id as u64);
+            wake_partner(recv_task);
+
+            // Now block ourselves until the server answers on the reply object
+            (*sched::TCBS.get_mut())[current].awaiting_reply = true;
+            attach_reply(recv_task, current);
+
+            let reply_timeout = if timeout == TIMEOUT_POLL { TIMEOUT_INFINITE } else { timeout };
+            block_current(frame, current, reply_timeout);
+        } else if timeout == TIMEOUT_POLL {
+            // Poll — no server waiting
+            frame.x[0] = ERR_IPC_TIMEOUT;
+        } else {
+            // No receiver — enqueue as sender, will also need reply
+            if !(*ENDPOINTS.get_mut())[ep_id].sender_queue.push(current) {
+                uart_print("!!! IPC: sender queue full\n");
+                return;
+            }
+            (*sched::TCBS.get_mut())[current].awaiting_reply = true;
+            block_current(frame, current, timeout);
+        }
+    }
+}
 
-            // Phase K4: Restore receiver's base priority if it was boosted
-            sched::restore_base_priority(recv_task);
+/// sys_reply(frame): answer the caller named by our reply object.
+/// Reply payload: frame.x[0..4] → caller's x[0..4]. Consumes the reply
+/// object, ends priority inheritance and returns 0 in x0 — or
+/// ERR_IPC_NO_REPLY if no call is pending.
+pub fn sys_reply(frame: &mut TrapFrame) {
+    if !check_payload(frame) {
+        return;
+    }
 
-            sched::set_task_state(recv_task, TaskState::Ready);
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    // Calls sched functions that access TCBS/CURRENT.
+    unsafe {
+        let current = sched::current_task_id() as usize;
+        sched::save_frame(current, frame);
+        frame.x[0] = if reply_current(current) { 0 } else { ERR_IPC_NO_REPLY };
+    }
+}
 
-            // Now block ourselves waiting for reply
-            (*ENDPOINTS.get_mut())[ep_id].receiver = Some(current);
+/// sys_reply_recv(frame, ep_id, timeout): answer the pending caller (if
+/// any), then receive the next request on `ep_id` — one trap per request
+/// for a server loop.
+pub fn sys_reply_recv(frame: &mut TrapFrame, ep_id: usize, timeout: u64) {
+    if ep_id >= MAX_ENDPOINTS {
+        uart_print("!!! IPC: invalid endpoint\n");
+        return;
+    }
+    if !check_payload(frame) {
+        return;
+    }
 
-            // Phase K4: Boost the receiver if our priority is higher
-            // (prevents priority inversion during call-reply pattern)
-            maybe_boost_priority(current, recv_task);
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    // Calls sched functions that access TCBS/CURRENT.
+    unsafe {
+        let current = sched::current_task_id() as usize;
+        sched::save_frame(current, frame);
+        reply_current(current);
+    }
 
-            sched::set_task_state(current, TaskState::Blocked);
-            sched::schedule(frame);
-        } else {
-            // No receiver — enqueue as sender, will also need reply
-            if !(*ENDPOINTS.get_mut())[ep_id].sender_queue.push(current) {
-                uart_print("!!! IPC: sender queue full\n");
+    sys_recv_timeout(frame, ep_id, timeout);
+}
+
+// ─── Wait sets ─────────────────────────────────────────────────────
+
+/// Source SYS_RECV_ANY takes without blocking: WAIT_SRC_NOTIFY if any
+/// notification bits `fired`, else the lowest endpoint of `ep_set` that
+/// has a queued sender (`ready`, bit i = endpoint i), else None.
+pub const fn wait_any_source(ep_set: u64, ready: u64, fired: u64) -> Option<u64> {
+    if fired != 0 {
+        return Some(WAIT_SRC_NOTIFY);
+    }
+    let hits = ep_set & ready & WAIT_SET_ALL;
+    if hits == 0 { None } else { Some(hits.trailing_zeros() as u64) }
+}
+
+/// sys_recv_any(frame, ep_set, notify_mask, timeout): wait for a message
+/// on any endpoint in `ep_set` or for any bit of `notify_mask`.
+/// Pending notifications win over queued senders; among endpoints the
+/// lowest id wins. On return x6 names the source: an endpoint id (message
+/// in x0..x3, sender id in x4, badge in x5) or WAIT_SRC_NOTIFY (fired bits
+/// in x0, cleared from the pending mask). Fails with ERR_IPC_BUSY if
+/// another task already waits on one of the endpoints.
+pub fn sys_recv_any(frame: &mut TrapFrame, ep_set: u64, notify_mask: u64, timeout: u64) {
+    if ep_set & !WAIT_SET_ALL != 0 || (ep_set == 0 && notify_mask == 0) {
+        uart_print("!!! IPC: invalid wait set\n");
+        return;
+    }
+
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    // Accesses KernelCell ENDPOINTS; calls sched functions that access TCBS/CURRENT.
+    unsafe {
+        let current = sched::current_task_id() as usize;
+        let mut ready = 0u64;
+        for ep in 0..MAX_ENDPOINTS {
+            if ep_set & (1 << ep) == 0 {
+                continue;
+            }
+            let endpoint = &(*ENDPOINTS.get())[ep];
+            if matches!(endpoint.receiver, Some(t) if t != current) {
+                frame.x[0] = ERR_IPC_BUSY;
                 return;
             }
-            sched::set_task_state(current, TaskState::Blocked);
-            sched::schedule(frame);
+            if endpoint.sender_queue.count > 0 {
+                ready |= 1 << ep;
+            }
+        }
+
+        let tcb = &mut (*sched::TCBS.get_mut())[current];
+        let fired = tcb.notify_pending & notify_mask;
+        match wait_any_source(ep_set, ready, fired) {
+            Some(WAIT_SRC_NOTIFY) => {
+                tcb.notify_pending &= !fired;
+                frame.x[0] = fired;
+                frame.x[6] = WAIT_SRC_NOTIFY;
+            }
+            Some(ep) => {
+                sys_recv_timeout(frame, ep as usize, TIMEOUT_POLL);
+                frame.x[6] = ep;
+            }
+            None if timeout == TIMEOUT_POLL => {
+                frame.x[0] = ERR_IPC_TIMEOUT;
+                frame.x[6] = WAIT_SRC_NONE;
+            }
+            None => {
+                // Nothing ready — wait on every source at once
+                tcb.wait_any = true;
+                tcb.notify_mask = notify_mask;
+                tcb.notify_waiting = notify_mask != 0;
+                for ep in 0..MAX_ENDPOINTS {
+                    if ep_set & (1 << ep) != 0 {
+                        (*ENDPOINTS.get_mut())[ep].receiver = Some(current);
+                    }
+                }
+                sched::save_frame(current, frame);
+                block_current(frame, current, timeout);
+            }
+        }
+    }
+}
+
+/// Raise notification `bits` for `task` (SYS_NOTIFY, IRQ routing). A task
+/// waiting for any of them — in SYS_WAIT_NOTIFY or through a SYS_RECV_ANY
+/// mask — wakes with the matching bits in x0; other bits stay pending.
+pub fn notify(task: usize, bits: u64) {
+    if task >= sched::NUM_TASKS {
+        return;
+    }
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let tcb = &mut (*sched::TCBS.get_mut())[task];
+        tcb.notify_pending |= bits;
+        let fired = tcb.notify_pending & tcb.notify_mask;
+        if !tcb.notify_waiting || fired == 0 {
+            return;
+        }
+        tcb.notify_pending &= !fired;
+        tcb.notify_waiting = false;
+        tcb.context.x[0] = fired;
+        leave_wait_set(task, WAIT_SRC_NOTIFY);
+        wake_partner(task);
+    }
+}
+
+/// End `task`'s SYS_RECV_ANY wait once one source fired: withdraw it from
+/// the other endpoints and from the notification wait, and report
+/// `source` in x6. No-op for a task that is not waiting on a wait set.
+unsafe fn leave_wait_set(task: usize, source: u64) {
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe {
+        let tcb = &mut (*sched::TCBS.get_mut())[task];
+        if !tcb.wait_any {
+            return;
+        }
+        tcb.wait_any = false;
+        tcb.notify_waiting = false;
+        tcb.notify_mask = NOTIFY_ALL;
+        tcb.context.x[6] = source;
+        for ep in (*ENDPOINTS.get_mut()).iter_mut() {
+            if ep.receiver == Some(task) {
+                ep.receiver = None;
+            }
         }
     }
 }
 
 // ─── Helpers ───────────────────────────────────────────────────────
 
-/// Copy message registers x[0]..x[3] from sender's TCB to receiver's TCB.
+/// Copy message registers x[0]..x[3] from sender's TCB to receiver's TCB,
+/// plus the sender's IPC-buffer payload when it set MSG_FLAG_BYTES.
 pub unsafe fn copy_message(from_task: usize, to_task: usize) {
     for i in 0..MSG_REGS {
         let val = sched::get_task_reg(from_task, i);
         sched::set_task_reg(to_task, i, val);
     }
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe { copy_buffer(from_task, to_task) };
+}
+
+/// Badge bound to `task`'s send capability on endpoint `ep`
+/// (NO_BADGE if out of range or unconfigured).
+pub fn sender_badge(task: usize, ep: usize) -> u64 {
+    if task >= sched::NUM_TASKS || ep >= MAX_ENDPOINTS {
+        return NO_BADGE;
+    }
+    // SAFETY: Single-core kernel, interrupts masked; read-only TCB access.
+    unsafe { (*sched::TCBS.get())[task].badges[ep] }
+}
+
+/// Stamp the sender identity into the receiver's TCB: x4 = `from`'s task
+/// id, x5 = its badge on `ep` (NO_BADGE for a reply, `ep` = None).
+/// Runs after copy_message, so sender-written x4/x5 never reach `to`.
+unsafe fn stamp_sender(from: usize, to: usize, ep: Option<usize>) {
+    let badge = match ep {
+        Some(ep) => sender_badge(from, ep),
+        None => NO_BADGE,
+    };
+    sched::set_task_reg(to, MSG_REG_SENDER, from as u64);
+    sched::set_task_reg(to, MSG_REG_BADGE, badge);
+}
+
+/// Bytes transferred through the IPC buffer for one message: 0 unless
+/// `flags` (sender's x5) has MSG_FLAG_BYTES, else the staged length
+/// capped at IPC_BUF_MAX_PAYLOAD — never past the end of the page.
+pub const fn buffer_copy_len(flags: u64, staged: u64) -> usize {
+    if flags & MSG_FLAG_BYTES == 0 {
+        0
+    } else if staged > IPC_BUF_MAX_PAYLOAD as u64 {
+        IPC_BUF_MAX_PAYLOAD
+    } else {
+        staged as usize
+    }
+}
+
+/// Payload length staged in the header of `task`'s IPC buffer.
+fn staged_len(task: usize) -> u64 {
+    match ipc_buffer_addr(task) {
+        // SAFETY: The header lies inside the task's kernel-owned IPC buffer page.
+        Some(addr) => unsafe { core::ptr::read_unaligned(addr as *const u64) },
+        None => 0,
+    }
+}
+
+/// Validate the payload the current task is about to send. An over-long
+/// length prefix fails the syscall with ERR_IPC_MSG_TOO_LONG in x0.
+fn check_payload(frame: &mut TrapFrame) -> bool {
+    if frame.x[5] & MSG_FLAG_BYTES == 0 {
+        return true;
+    }
+    if staged_len(sched::current_task_id() as usize) > IPC_BUF_MAX_PAYLOAD as u64 {
+        uart_print("!!! IPC: payload too long\n");
+        frame.x[0] = ERR_IPC_MSG_TOO_LONG;
+        return false;
+    }
+    true
+}
+
+/// Move the sender's staged payload into the receiver's IPC buffer. The
+/// receiver's length prefix is always rewritten (0 = registers only), so
+/// it never sees a stale payload.
+unsafe fn copy_buffer(from_task: usize, to_task: usize) {
+    let (Some(src), Some(dst)) = (ipc_buffer_addr(from_task), ipc_buffer_addr(to_task)) else {
+        return;
+    };
+    let len = buffer_copy_len(sched::get_task_reg(from_task, 5), staged_len(from_task));
+    // SAFETY: Both pages are kernel-owned IPC buffers; len ≤ IPC_BUF_MAX_PAYLOAD
+    // keeps header + payload inside each page. Distinct tasks → distinct pages.
+    unsafe {
+        core::ptr::write_unaligned(dst as *mut u64, len as u64);
+        if from_task != to_task {
+            core::ptr::copy_nonoverlapping(
+                (src as usize + IPC_BUF_HEADER) as *const u8,
+                (dst as usize + IPC_BUF_HEADER) as *mut u8,
+                len,
+            );
+        }
+    }
+}
+
+/// Zero `task`'s IPC buffer page (restart — no data leaks across lives).
+pub fn clear_ipc_buffer(task: usize) {
+    if let Some(addr) = ipc_buffer_addr(task) {
+        // SAFETY: The whole page belongs to the task's kernel-owned IPC buffer.
+        unsafe { core::ptr::write_bytes(addr as *mut u8, 0, IPC_BUF_SIZE) };
+    }
+}
+
+/// Block the current task in IPC and schedule away. A finite `timeout`
+/// arms a deadline in the kernel timer queue; TIMEOUT_INFINITE disarms
+/// any entry left over from an earlier wait.
+///
+/// # Safety
+/// Single-core kernel context with interrupts masked; `frame` must already
+/// be saved into `current`'s TCB.
+pub unsafe fn block_current(frame: &mut TrapFrame, current: usize, timeout: u64) {
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe {
+        if timeout == TIMEOUT_INFINITE {
+            crate::timer::cancel_sleep(current);
+        } else {
+            let deadline = crate::timer::tick_count().saturating_add(timeout);
+            (*crate::timer::SLEEP_QUEUE.get_mut()).insert(current, deadline);
+        }
+        sched::set_task_state(current, TaskState::Blocked);
+        sched::schedule(frame);
+    }
+}
+
+/// Unblock an IPC partner, disarming its pending timeout (if any).
+pub fn wake_partner(task: usize) {
+    crate::timer::cancel_sleep(task);
+    sched::set_task_state(task, TaskState::Ready);
+}
+
+// ─── Reply objects ─────────────────────────────────────────────────
+
+/// Store a one-shot reply object for `caller` in `server`'s TCB and let
+/// the server inherit the caller's priority. An unanswered older reply
+/// object is dropped first (its caller gets ERR_IPC_REPLY_LOST).
+unsafe fn attach_reply(server: usize, caller: usize) {
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe {
+        drop_reply(server, ERR_IPC_REPLY_LOST);
+        (*sched::TCBS.get_mut())[server].reply_to = Some(caller);
+    }
+    maybe_boost_priority(caller, server);
+}
+
+/// Consume `server`'s reply object: copy its x0..x3 to the caller, wake
+/// the caller and end priority inheritance. Returns false if none pending.
+unsafe fn reply_current(server: usize) -> bool {
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe {
+        let Some(caller) = (*sched::TCBS.get_mut())[server].reply_to.take() else {
+            return false;
+        };
+        copy_message(server, caller);
+        stamp_sender(server, caller, None);
+        (*sched::TCBS.get_mut())[caller].awaiting_reply = false;
+        sched::restore_base_priority(server);
+        wake_partner(caller);
+        true
+    }
+}
+
+/// Discard `server`'s reply object without answering: the caller resumes
+/// with `err` in x0 and the server's inherited priority is undone.
+unsafe fn drop_reply(server: usize, err: u64) {
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe {
+        if let Some(caller) = (*sched::TCBS.get_mut())[server].reply_to.take() {
+            (*sched::TCBS.get_mut())[caller].awaiting_reply = false;
+            sched::set_task_reg(caller, 0, err);
+            sched::restore_base_priority(server);
+            wake_partner(caller);
+        }
+    }
+}
+
+/// Revoke every reply object naming `caller` (caller timed out or died).
+/// Returns true if a server was holding one.
+unsafe fn revoke_reply_to(caller: usize) -> bool {
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe {
+        let mut found = false;
+        for server in 0..sched::NUM_TASKS {
+            if (*sched::TCBS.get_mut())[server].reply_to == Some(caller) {
+                (*sched::TCBS.get_mut())[server].reply_to = None;
+                sched::restore_base_priority(server);
+                found = true;
+            }
+        }
+        found
+    }
+}
+
+/// IPC deadline expired for `task_idx` (called from the timer queue).
+/// Dequeues the task from whichever endpoint slot it waits in — or
+/// revokes the reply object it is waiting on — and resumes it with
+/// ERR_IPC_TIMEOUT in x0. A task in a wait set leaves all of its sources
+/// (x6 = WAIT_SRC_NONE). A task blocked elsewhere (e.g. in
+/// SYS_WAIT_NOTIFY) is not on any endpoint and is left untouched.
+pub fn expire_timeout(task_idx: usize) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    // Accesses KernelCell ENDPOINTS; calls sched functions that access TCBS.
+    unsafe {
+        let mut found = false;
+        if (*sched::TCBS.get())[task_idx].wait_any {
+            leave_wait_set(task_idx, WAIT_SRC_NONE);
+            found = true;
+        }
+        for ep in (*ENDPOINTS.get_mut()).iter_mut() {
+            if ep.sender_queue.contains(task_idx) {
+                ep.sender_queue.remove(task_idx);
+                found = true;
+            }
+            if ep.receiver == Some(task_idx) {
+                ep.receiver = None;
+                found = true;
+            }
+        }
+        if revoke_reply_to(task_idx) {
+            found = true;
+        }
+        if found {
+            (*sched::TCBS.get_mut())[task_idx].awaiting_reply = false;
+            sched::set_task_reg(task_idx, 0, ERR_IPC_TIMEOUT);
+            sched::set_task_state(task_idx, TaskState::Ready);
+        }
+    }
+}
+
+/// A capability naming endpoint `ep_id` was revoked from `task`: if the
+/// task is blocked on that endpoint — queued as a sender, or waiting as
+/// receiver alone or in a wait set — dequeue it and resume it with `err`
+/// in x0. A call already delivered completes through its reply object.
+/// Returns false if `task` was not waiting on the endpoint.
+pub fn revoke_waiter(task: usize, ep_id: usize, err: u64) -> bool {
+    if task >= sched::NUM_TASKS || ep_id >= MAX_ENDPOINTS {
+        return false;
+    }
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let ep = &mut (*ENDPOINTS.get_mut())[ep_id];
+        let queued = ep.sender_queue.contains(task);
+        let receiving = ep.receiver == Some(task);
+        if !queued && !receiving {
+            return false;
+        }
+        ep.sender_queue.remove(task);
+        if receiving {
+            ep.receiver = None;
+            leave_wait_set(task, WAIT_SRC_NONE);
+        }
+        (*sched::TCBS.get_mut())[task].awaiting_reply = false;
+        sched::set_task_reg(task, 0, err);
+        wake_partner(task);
+    }
+    true
 }
 
 // ─── Phase K4: Priority Inheritance helper ─────────────────────────
@@ -261,6 +831,9 @@ fn maybe_boost_priority(blocker: usize, holder: usize) {
 /// Remove a faulted task from all IPC endpoint slots.
 /// If a partner was blocked waiting for this task, unblock the partner
 /// so it can be rescheduled (partner will retry IPC or find no match).
+/// Reply objects are released both ways: a caller waiting on the faulted
+/// server gets ERR_IPC_REPLY_LOST, and a server holding the faulted
+/// caller's reply object drops it (and its inherited priority).
 pub fn cleanup_task(task_idx: usize) {
     // SAFETY: Single-core kernel, interrupts masked during kernel execution.
     // No concurrent access on uniprocessor QEMU virt.
@@ -275,6 +848,13 @@ pub fn cleanup_task(task_idx: usize) {
                 (*ENDPOINTS.get_mut())[i].receiver = None;
             }
         }
+
+        if task_idx < sched::NUM_TASKS {
+            leave_wait_set(task_idx, WAIT_SRC_NONE);
+            drop_reply(task_idx, ERR_IPC_REPLY_LOST);
+            revoke_reply_to(task_idx);
+            (*sched::TCBS.get_mut())[task_idx].awaiting_reply = false;
+        }
     }
 }
 
@@ -332,6 +912,43 @@ fn cleanup_pure(
     }
 }
 
+/// Pure reply consume: take the reply object and end inheritance.
+/// Mirrors reply_current()/drop_reply() on one server's (reply_to, priority).
+#[cfg(kani)]
+fn reply_pure(reply_to: &mut Option<usize>, prio: &mut u8, base: u8) -> Option<usize> {
+    let caller = reply_to.take();
+    if caller.is_some() {
+        *prio = base;
+    }
+    caller
+}
+
+/// Pure attach: drop any older reply object, store the new caller and
+/// inherit its priority. Mirrors attach_reply() + maybe_boost_priority().
+#[cfg(kani)]
+fn attach_reply_pure(
+    reply_to: &mut Option<usize>,
+    prio: &mut u8,
+    base: u8,
+    caller: usize,
+    caller_prio: u8,
+) -> Option<usize> {
+    let dropped = reply_pure(reply_to, prio, base);
+    *reply_to = Some(caller);
+    if caller_prio > *prio {
+        *prio = caller_prio;
+    }
+    dropped
+}
+
+/// Pure delivery: message copy followed by the identity stamp, on the
+/// first six registers. Mirrors copy_message() + stamp_sender().
+#[cfg(kani)]
+fn deliver_pure(src: &[u64; 6], dst: &[u64; 6], from: usize, badge: u64) -> [u64; 6] {
+    let msg = copy_message_pure(&[src[0], src[1], src[2], src[3]], &[dst[0], dst[1], dst[2], dst[3]]);
+    [msg[0], msg[1], msg[2], msg[3], from as u64, badge]
+}
+
 // ─── Kani formal verification proofs ───────────────────────────────
 
 #[cfg(kani)]
@@ -475,4 +1092,157 @@ mod kani_proofs {
             ep2 += 1;
         }
     }
+
+    /// Proof 4: IPC timeout dequeue.
+    /// For all sender queues and any timed-out task, removing it (as
+    /// expire_timeout does) leaves no trace of the task, drops exactly its
+    /// occurrences, and keeps the remaining senders in FIFO order.
+    #[kani::proof]
+    #[kani::unwind(5)] // MAX_WAITERS=4
+    fn ipc_timeout_dequeue() {
+        let mut q = SenderQueue::new();
+        q.head = kani::any();
+        kani::assume(q.head < MAX_WAITERS);
+        q.count = kani::any();
+        kani::assume(q.count <= MAX_WAITERS);
+        let mut w = 0;
+        while w < MAX_WAITERS {
+            q.tasks[w] = kani::any();
+            kani::assume(q.tasks[w] < sched::NUM_TASKS);
+            w += 1;
+        }
+        let task: usize = kani::any();
+        kani::assume(task < sched::NUM_TASKS);
+
+        // Expected survivors in FIFO order
+        let mut expected = [0usize; MAX_WAITERS];
+        let mut kept = 0usize;
+        let mut i = 0;
+        while i < q.count {
+            let t = q.tasks[(q.head + i) % MAX_WAITERS];
+            if t != task {
+                expected[kept] = t;
+                kept += 1;
+            }
+            i += 1;
+        }
+
+        q.remove(task);
+
+        assert!(!q.contains(task), "timed-out task must leave the queue");
+        assert!(q.count == kept, "only the timed-out task is dropped");
+        let mut j = 0;
+        while j < kept {
+            assert!(q.pop() == Some(expected[j]), "survivors keep FIFO order");
+            j += 1;
+        }
+    }
+
+    /// Proof 5: Reply objects are one-shot and carry priority inheritance.
+    /// For all servers (any pending reply, any boost) and callers:
+    /// - after attach the server holds exactly the new caller's reply object
+    ///   and runs at max(base, caller priority); an older reply is returned
+    ///   as dropped, never silently kept
+    /// - the first reply answers exactly that caller and restores base
+    ///   priority; a second reply finds nothing
+    #[kani::proof]
+    fn ipc_reply_one_shot_inheritance() {
+        let base: u8 = kani::any();
+        let mut prio: u8 = kani::any();
+        kani::assume(base <= 7 && prio <= 7 && prio >= base);
+        let old: Option<usize> = kani::any();
+        let mut reply_to = old;
+        let caller: usize = kani::any();
+        kani::assume(caller < sched::NUM_TASKS);
+        let caller_prio: u8 = kani::any();
+        kani::assume(caller_prio <= 7);
+
+        let dropped = attach_reply_pure(&mut reply_to, &mut prio, base, caller, caller_prio);
+        assert!(dropped == old, "older reply object must be dropped, not kept");
+        assert!(reply_to == Some(caller), "server holds the new caller's reply");
+        let expected = if caller_prio > base { caller_prio } else { base };
+        assert!(prio == expected, "server inherits caller priority");
+
+        let first = reply_pure(&mut reply_to, &mut prio, base);
+        assert!(first == Some(caller), "reply reaches exactly the caller");
+        assert!(prio == base, "inheritance ends with the reply");
+
+        let second = reply_pure(&mut reply_to, &mut prio, base);
+        assert!(second.is_none(), "reply object is one-shot");
+    }
+
+    /// Proof 6: IPC buffer copy stays inside the page.
+    /// For all sender flags (x5) and any staged length prefix:
+    /// - without MSG_FLAG_BYTES nothing is copied
+    /// - the copy never exceeds IPC_BUF_MAX_PAYLOAD, so header + payload
+    ///   fits in one IPC buffer page
+    /// - a valid staged length is transferred exactly
+    #[kani::proof]
+    fn ipc_buffer_copy_bounded() {
+        let flags: u64 = kani::any();
+        let staged: u64 = kani::any();
+
+        let len = buffer_copy_len(flags, staged);
+
+        if flags & MSG_FLAG_BYTES == 0 {
+            assert!(len == 0, "register-only message copies no bytes");
+        }
+        assert!(len <= IPC_BUF_MAX_PAYLOAD, "copy bounded by max payload");
+        assert!(IPC_BUF_HEADER + len <= IPC_BUF_SIZE, "copy stays inside the page");
+        if flags & MSG_FLAG_BYTES != 0 && staged <= IPC_BUF_MAX_PAYLOAD as u64 {
+            assert!(len as u64 == staged, "valid payload transferred exactly");
+        }
+    }
+
+    /// Proof 7: Sender identity cannot be forged.
+    /// For all sender registers x0–x5 (including values chosen to mimic
+    /// another task's id or badge), any sender id and badge table entry:
+    /// - the receiver's x4 is the true sender id
+    /// - the receiver's x5 is the kernel-held badge
+    /// - the payload x0–x3 is still delivered unchanged
+    #[kani::proof]
+    fn ipc_sender_identity_unforgeable() {
+        let src: [u64; 6] = kani::any();
+        let dst: [u64; 6] = kani::any();
+        let from: usize = kani::any();
+        kani::assume(from < sched::NUM_TASKS);
+        let badges: [u64; MAX_ENDPOINTS] = kani::any();
+        let ep: usize = kani::any();
+        kani::assume(ep < MAX_ENDPOINTS);
+
+        let out = deliver_pure(&src, &dst, from, badges[ep]);
+
+        assert!(out[MSG_REG_SENDER] == from as u64, "x4 is the true sender");
+        assert!(out[MSG_REG_BADGE] == badges[ep], "x5 is the kernel-held badge");
+        let mut i = 0;
+        while i < MSG_REGS {
+            assert!(out[i] == src[i], "payload delivered unchanged");
+            i += 1;
+        }
+    }
+
+    /// Proof 8: A wait set wakes from exactly one legitimate source.
+    /// For all endpoint sets, ready-endpoint masks and fired notification bits:
+    /// - fired notifications always win
+    /// - otherwise the source is an endpoint that is in the set and ready,
+    ///   with no lower-numbered endpoint also eligible
+    /// - None (block or poll failure) iff nothing in the set is ready
+    #[kani::proof]
+    fn ipc_wait_any_single_source() {
+        let ep_set: u64 = kani::any();
+        let ready: u64 = kani::any();
+        let fired: u64 = kani::any();
+        let eligible = ep_set & ready & WAIT_SET_ALL;
+
+        match wait_any_source(ep_set, ready, fired) {
+            Some(WAIT_SRC_NOTIFY) => assert!(fired != 0, "notify only when bits fired"),
+            Some(ep) => {
+                assert!(fired == 0, "notifications take priority");
+                assert!(ep < MAX_ENDPOINTS as u64, "source is a real endpoint");
+                assert!(eligible & (1 << ep) != 0, "source is in the set and ready");
+                assert!(eligible & ((1 << ep) - 1) == 0, "lowest eligible endpoint");
+            }
+            None => assert!(fired == 0 && eligible == 0, "block only if nothing is ready"),
+        }
+    }
 }
@@ -15,7 +15,6 @@
 ///   SYS_IRQ_ACK  = 10: acknowledge IRQ handled, re-enable INTID
 
 use crate::kernel::cell::KernelCell;
-use crate::sched;
 use crate::uart_print;
 
 // ─── Constants ─────────────────────────────────────────────────────
@@ -181,8 +180,8 @@ pub fn irq_ack(intid: u32, task_id: usize) -> u64 {
 /// Route a hardware IRQ to the bound task (called from exception handler).
 ///
 /// Looks up the INTID in the binding table. If bound:
-///   - OR notify_bit into task's notify_pending
-///   - If task is waiting on notifications → unblock it
+///   - OR notify_bit into task's notify_pending (ipc::notify)
+///   - If task is waiting on that bit → unblock it
 ///   - Set pending_ack = true
 ///   - Mask the INTID until task calls SYS_IRQ_ACK
 ///
@@ -196,18 +195,9 @@ pub fn irq_route(intid: u32, _frame: &mut crate::exception::TrapFrame) {
                 let tid = (*IRQ_BINDINGS.get_mut())[i].task_id;
                 let bit = (*IRQ_BINDINGS.get_mut())[i].notify_bit;
 
-                // OR notification bit into task's pending mask
-                (*sched::TCBS.get_mut())[tid].notify_pending |= bit;
-
-                // If task is waiting for notifications, unblock it
-                if (*sched::TCBS.get_mut())[tid].notify_waiting {
-                    (*sched::TCBS.get_mut())[tid].notify_waiting = false;
-                    (*sched::TCBS.get_mut())[tid].state = sched::TaskState::Ready;
-                    // Deliver pending bits into x0
-                    let pending = (*sched::TCBS.get_mut())[tid].notify_pending;
-                    (*sched::TCBS.get_mut())[tid].context.x[0] = pending;
-                    (*sched::TCBS.get_mut())[tid].notify_pending = 0;
-                }
+                // Raise the notification bit; wakes the task if it is waiting
+                // for it (SYS_WAIT_NOTIFY or a SYS_RECV_ANY wait set)
+                crate::ipc::notify(tid, bit);
 
                 // Mark pending ACK — INTID stays masked until task ACKs
                 (*IRQ_BINDINGS.get_mut())[i].pending_ack = true;
@@ -236,15 +226,7 @@ pub fn irq_route_test(intid: u32, task_id: usize) {
                 let tid = (*IRQ_BINDINGS.get_mut())[i].task_id;
                 let bit = (*IRQ_BINDINGS.get_mut())[i].notify_bit;
 
-                (*sched::TCBS.get_mut())[tid].notify_pending |= bit;
-
-                if (*sched::TCBS.get_mut())[tid].notify_waiting {
-                    (*sched::TCBS.get_mut())[tid].notify_waiting = false;
-                    (*sched::TCBS.get_mut())[tid].state = sched::TaskState::Ready;
-                    let pending = (*sched::TCBS.get_mut())[tid].notify_pending;
-                    (*sched::TCBS.get_mut())[tid].context.x[0] = pending;
-                    (*sched::TCBS.get_mut())[tid].notify_pending = 0;
-                }
+                crate::ipc::notify(tid, bit);
 
                 (*IRQ_BINDINGS.get_mut())[i].pending_ack = true;
                 // No GIC on host
@@ -263,29 +245,54 @@ pub fn irq_cleanup_task(task_id: usize) {
     unsafe {
         for i in 0..MAX_IRQ_BINDINGS {
             if (*IRQ_BINDINGS.get_mut())[i].active && (*IRQ_BINDINGS.get_mut())[i].task_id == task_id {
-                // If IRQ was masked waiting for ACK, unmask it
-                if (*IRQ_BINDINGS.get_mut())[i].pending_ack {
-                    #[cfg(target_arch = "aarch64")]
-                    {
-                        crate::gic::enable_intid((*IRQ_BINDINGS.get_mut())[i].intid);
-                    }
-                }
-
-                uart_print("[AegisOS] IRQ cleanup: unbind INTID ");
-                crate::uart_print_hex((*IRQ_BINDINGS.get_mut())[i].intid as u64);
-                uart_print(" from task ");
-                crate::uart_print_hex(task_id as u64);
-                uart_print("\n");
+                release_binding(i, task_id);
+            }
+        }
+    }
+}
 
-                // Disable the INTID since no one is listening
-                #[cfg(target_arch = "aarch64")]
-                {
-                    crate::gic::disable_intid((*IRQ_BINDINGS.get_mut())[i].intid);
-                }
+/// Unbind `intid` from `task_id` after its IRQ capability was revoked.
+/// Returns false if the task held no binding for that INTID.
+pub fn irq_revoke(intid: u32, task_id: usize) -> bool {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        for i in 0..MAX_IRQ_BINDINGS {
+            let b = (*IRQ_BINDINGS.get())[i];
+            if b.active && b.intid == intid && b.task_id == task_id {
+                release_binding(i, task_id);
+                return true;
+            }
+        }
+    }
+    false
+}
 
-                (*IRQ_BINDINGS.get_mut())[i] = EMPTY_BINDING;
+/// Free binding slot `i`: unmask an orphaned pending IRQ, then disable
+/// the INTID since no one is listening any more.
+unsafe fn release_binding(i: usize, task_id: usize) {
+    // SAFETY: Caller guarantees single-core kernel context with interrupts masked.
+    unsafe {
+        // If IRQ was masked waiting for ACK, unmask it
+        if (*IRQ_BINDINGS.get_mut())[i].pending_ack {
+            #[cfg(target_arch = "aarch64")]
+            {
+                crate::gic::enable_intid((*IRQ_BINDINGS.get_mut())[i].intid);
             }
         }
+
+        uart_print("[AegisOS] IRQ cleanup: unbind INTID ");
+        crate::uart_print_hex((*IRQ_BINDINGS.get_mut())[i].intid as u64);
+        uart_print(" from task ");
+        crate::uart_print_hex(task_id as u64);
+        uart_print("\n");
+
+        // Disable the INTID since no one is listening
+        #[cfg(target_arch = "aarch64")]
+        {
+            crate::gic::disable_intid((*IRQ_BINDINGS.get_mut())[i].intid);
+        }
+
+        (*IRQ_BINDINGS.get_mut())[i] = EMPTY_BINDING;
     }
 }
 
@@ -0,0 +1,367 @@
+/// AegisOS Memory Map — decode a task's page tables into a readable map
+///
+/// A fault report with only FAR and ESR says where the task touched, not
+/// what it could reach. This module walks a task's L2 device, L3 and
+/// L2 RAM tables (the same snapshot ptcheck.rs checks) and merges
+/// neighbouring entries with identical attributes and owner into ranges:
+/// virtual range, physical address, AP, UXN/PXN, memory attribute, and
+/// the object that owns the memory (text, rodata, a stack, a grant, an
+/// ELF slot, a device…).
+///
+/// `MapWalker` is a pure iterator over the snapshot (host tests collect
+/// it to assert layouts). The kernel prints the EL0-visible map of a
+/// task that takes a data or instruction abort, and SYS_MAP_DUMP prints
+/// any task's map on demand.
+///
+/// Syscall (CAP_MAP_DUMP):
+///   SYS_MAP_DUMP = 32: print task x6's map (x0 = MAP_DUMP_USER: EL0-visible
+///   ranges only) → x0 = ranges printed
+
+use crate::frame;
+use crate::grant::GRANT_PAGE_SIZE;
+use crate::ipc::IPC_BUF_SIZE;
+use crate::mmu::{PXN, UXN};
+use crate::platform::qemu_virt::{elf_load_addr, ELF_LOAD_SIZE_PER_TASK, GICD_BASE, MAX_ELF_TASKS};
+use crate::ptcheck::{MemLayout, TaskMemory, TaskTables, L2_BLOCK_SIZE, L2_RAM_BASE, L3_BASE, PAGE_SIZE, TABLE_ENTRIES};
+use crate::sched;
+use crate::{uart_print, uart_print_dec, uart_print_hex};
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+/// SYS_MAP_DUMP x0: print every mapped range.
+pub const MAP_DUMP_ALL: u64 = 0;
+/// SYS_MAP_DUMP x0: print only ranges EL0 can reach.
+pub const MAP_DUMP_USER: u64 = 1;
+
+/// Output address, bits [47:12]
+const OA_MASK: u64 = 0x0000_FFFF_FFFF_F000;
+/// Leaf attributes compared when merging: AttrIndx, AP, SH, AF, PXN, UXN
+const LEAF_ATTR_MASK: u64 = (0b111 << 2) | (0b11 << 6) | (0b11 << 8) | (1 << 10) | PXN | UXN;
+
+// ─── Range description ─────────────────────────────────────────────
+
+/// AP[2:1] decoded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Access {
+    /// EL1 read-write, EL0 none
+    El1ReadWrite,
+    /// EL1 read-only, EL0 none
+    El1ReadOnly,
+    /// EL1 and EL0 read-write
+    El0ReadWrite,
+    /// EL1 and EL0 read-only
+    El0ReadOnly,
+}
+
+impl Access {
+    pub const fn from_desc(desc: u64) -> Self {
+        match (desc >> 6) & 0b11 {
+            0b00 => Access::El1ReadWrite,
+            0b01 => Access::El0ReadWrite,
+            0b10 => Access::El1ReadOnly,
+            _ => Access::El0ReadOnly,
+        }
+    }
+
+    pub const fn el0(self) -> bool {
+        matches!(self, Access::El0ReadWrite | Access::El0ReadOnly)
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Access::El1ReadWrite => "EL1 RW",
+            Access::El1ReadOnly => "EL1 RO",
+            Access::El0ReadWrite => "EL0 RW",
+            Access::El0ReadOnly => "EL0 RO",
+        }
+    }
+}
+
+/// MAIR attribute of a range (AttrIndx, see mmu.rs).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemAttr {
+    Device,
+    NormalNonCacheable,
+    NormalWriteBack,
+    Unknown,
+}
+
+impl MemAttr {
+    pub const fn from_desc(desc: u64) -> Self {
+        match (desc >> 2) & 0b111 {
+            0 => MemAttr::Device,
+            1 => MemAttr::NormalNonCacheable,
+            2 => MemAttr::NormalWriteBack,
+            _ => MemAttr::Unknown,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            MemAttr::Device => "DEV",
+            MemAttr::NormalNonCacheable => "NC",
+            MemAttr::NormalWriteBack => "WB",
+            MemAttr::Unknown => "??",
+        }
+    }
+}
+
+/// The object a range's physical memory belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Owner {
+    /// Kernel text (shared with kernel-linked tasks)
+    Text,
+    /// Kernel rodata
+    Rodata,
+    /// Other kernel memory (data, bss, page tables, kernel stacks…)
+    Kernel,
+    /// User stack of task n
+    Stack(usize),
+    /// IPC buffer page of task n
+    IpcBuffer(usize),
+    /// Active grant n
+    Grant(usize),
+    /// Anonymous frame held by task n
+    Frame(usize),
+    /// ELF load slot n
+    ElfSlot(usize),
+    /// Device MMIO block (DEVICES name, "GIC", or "MMIO")
+    Device(&'static str),
+    /// RAM above the L3-mapped range
+    Ram,
+}
+
+/// One run of identically mapped pages or blocks.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MapRange {
+    pub va: u64,
+    pub size: u64,
+    pub pa: u64,
+    pub ap: Access,
+    /// EL0 may not execute
+    pub uxn: bool,
+    /// EL1 may not execute
+    pub pxn: bool,
+    pub attr: MemAttr,
+    pub owner: Owner,
+}
+
+// ─── Decoding (pure) ───────────────────────────────────────────────
+
+/// Owner of L3-range physical page `pa`.
+pub fn page_owner(mem: &TaskMemory, layout: &MemLayout, pa: u64) -> Owner {
+    let within = |start: u64, end: u64| pa >= start && pa < end;
+    if within(layout.text.0, layout.text.1) {
+        return Owner::Text;
+    }
+    if within(layout.rodata.0, layout.rodata.1) {
+        return Owner::Rodata;
+    }
+    if pa >= layout.ipc_buffers {
+        let task = ((pa - layout.ipc_buffers) / IPC_BUF_SIZE as u64) as usize;
+        if task < sched::NUM_TASKS {
+            return Owner::IpcBuffer(task);
+        }
+    }
+    if pa >= layout.user_stacks {
+        let task = ((pa - layout.user_stacks) / sched::STACK_SLOT_SIZE) as usize;
+        if task < sched::NUM_TASKS && sched::guard_page_owner(layout.user_stacks, pa).is_none() {
+            return Owner::Stack(task);
+        }
+    }
+    for (id, g) in mem.grants.iter().enumerate() {
+        if g.active && within(g.phys_addr, g.phys_addr + (g.pages * GRANT_PAGE_SIZE) as u64) {
+            return Owner::Grant(id);
+        }
+    }
+    if pa >= layout.frame_pool {
+        let index = ((pa - layout.frame_pool) / frame::FRAME_SIZE as u64) as usize;
+        if index < frame::FRAME_POOL_PAGES && mem.frame_owners[index] != frame::FRAME_FREE {
+            return Owner::Frame(mem.frame_owners[index] as usize);
+        }
+    }
+    for slot in 0..MAX_ELF_TASKS {
+        if within(elf_load_addr(slot), elf_load_addr(slot) + ELF_LOAD_SIZE_PER_TASK as u64) {
+            return Owner::ElfSlot(slot);
+        }
+    }
+    Owner::Kernel
+}
+
+/// Owner of the device-table block at `va`.
+pub fn device_owner(va: u64) -> Owner {
+    if va == GICD_BASE as u64 & !(L2_BLOCK_SIZE - 1) {
+        return Owner::Device("GIC");
+    }
+    for dev in crate::mmu::DEVICES {
+        if dev.l2_index as u64 * L2_BLOCK_SIZE == va {
+            return Owner::Device(dev.name);
+        }
+    }
+    Owner::Device("MMIO")
+}
+
+/// Walks a task's tables in address order (devices, L3 pages, RAM
+/// blocks) and yields merged ranges of valid leaf entries.
+pub struct MapWalker<'a> {
+    tables: &'a TaskTables<'a>,
+    mem: &'a TaskMemory<'a>,
+    layout: &'a MemLayout,
+    /// Position over the three tables, 0..3 * TABLE_ENTRIES
+    pos: usize,
+}
+
+impl<'a> MapWalker<'a> {
+    pub fn new(tables: &'a TaskTables<'a>, mem: &'a TaskMemory<'a>, layout: &'a MemLayout) -> Self {
+        Self { tables, mem, layout, pos: 0 }
+    }
+
+    /// Decode position `pos` into (va, size, descriptor, owner) if it is
+    /// a valid leaf: an L2 block or an L3 page.
+    fn leaf(&self, pos: usize) -> Option<(u64, u64, u64, Owner)> {
+        let i = pos % TABLE_ENTRIES;
+        match pos / TABLE_ENTRIES {
+            0 => {
+                let desc = self.tables.l2_device[i];
+                let va = i as u64 * L2_BLOCK_SIZE;
+                (desc & 0b11 == 0b01).then(|| (va, L2_BLOCK_SIZE, desc, device_owner(va)))
+            }
+            1 => {
+                let desc = self.tables.l3[i];
+                let va = L3_BASE + i as u64 * PAGE_SIZE;
+                (desc & 0b11 == 0b11)
+                    .then(|| (va, PAGE_SIZE, desc, page_owner(self.mem, self.layout, desc & OA_MASK)))
+            }
+            2 => {
+                let desc = self.tables.l2_ram[i];
+                let va = L2_RAM_BASE + i as u64 * L2_BLOCK_SIZE;
+                (desc & 0b11 == 0b01).then_some((va, L2_BLOCK_SIZE, desc, Owner::Ram))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Iterator for MapWalker<'_> {
+    type Item = MapRange;
+
+    fn next(&mut self) -> Option<MapRange> {
+        while self.pos < 3 * TABLE_ENTRIES {
+            let start = self.pos;
+            self.pos += 1;
+            let Some((va, size, desc, owner)) = self.leaf(start) else {
+                continue;
+            };
+            let mut range = MapRange {
+                va,
+                size,
+                pa: desc & OA_MASK,
+                ap: Access::from_desc(desc),
+                uxn: desc & UXN != 0,
+                pxn: desc & PXN != 0,
+                attr: MemAttr::from_desc(desc),
+                owner,
+            };
+            // Extend over following entries of the same table that continue
+            // the range with the same attributes and owner
+            while self.pos < 3 * TABLE_ENTRIES && self.pos / TABLE_ENTRIES == start / TABLE_ENTRIES {
+                match self.leaf(self.pos) {
+                    Some((next_va, next_size, next_desc, next_owner))
+                        if next_va == range.va + range.size
+                            && next_desc & OA_MASK == range.pa + range.size
+                            && next_desc & LEAF_ATTR_MASK == desc & LEAF_ATTR_MASK
+                            && next_owner == owner =>
+                    {
+                        range.size += next_size;
+                        self.pos += 1;
+                    }
+                    _ => break,
+                }
+            }
+            return Some(range);
+        }
+        None
+    }
+}
+
+// ─── Printing ──────────────────────────────────────────────────────
+
+/// Print one range: `va-end -> pa  AP  XN  attr  owner`.
+pub fn print_range(r: &MapRange) {
+    uart_print("    0x");
+    uart_print_hex(r.va);
+    uart_print("-0x");
+    uart_print_hex(r.va + r.size);
+    uart_print(" -> 0x");
+    uart_print_hex(r.pa);
+    uart_print("  ");
+    uart_print(r.ap.name());
+    uart_print(match (r.uxn, r.pxn) {
+        (true, true) => "  XN   ",
+        (true, false) => "  UXN  ",
+        (false, true) => "  PXN  ",
+        (false, false) => "  X    ",
+    });
+    uart_print(r.attr.name());
+    uart_print("  ");
+    match r.owner {
+        Owner::Text => uart_print("text"),
+        Owner::Rodata => uart_print("rodata"),
+        Owner::Kernel => uart_print("kernel"),
+        Owner::Ram => uart_print("ram"),
+        Owner::Device(name) => {
+            uart_print("device ");
+            uart_print(name);
+        }
+        Owner::Stack(n) | Owner::IpcBuffer(n) | Owner::Grant(n) | Owner::Frame(n) | Owner::ElfSlot(n) => {
+            uart_print(match r.owner {
+                Owner::Stack(_) => "stack of task ",
+                Owner::IpcBuffer(_) => "IPC buffer of task ",
+                Owner::Grant(_) => "grant ",
+                Owner::Frame(_) => "anon frame of task ",
+                _ => "ELF slot ",
+            });
+            uart_print_dec(n as u64);
+        }
+    }
+    uart_print("\n");
+}
+
+/// Print `task`'s map from its live tables (EL0-visible ranges only if
+/// `user_only`). Returns the number of ranges printed.
+#[cfg(target_arch = "aarch64")]
+pub fn dump_task(task: usize, user_only: bool) -> usize {
+    let layout = crate::ptcheck::current_layout();
+    let tables = crate::mmu::task_tables(task);
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    let mem = unsafe {
+        TaskMemory {
+            task,
+            elf_slot: (*sched::TCBS.get())[task].elf_slot,
+            grants: crate::grant::GRANTS.get(),
+            frame_owners: frame::FRAME_OWNER.get(),
+        }
+    };
+    uart_print("  address space of task ");
+    uart_print_dec(task as u64);
+    uart_print(if user_only { " (EL0-visible):\n" } else { ":\n" });
+    let mut printed = 0;
+    for r in MapWalker::new(&tables, &mem, &layout) {
+        if !user_only || r.ap.el0() {
+            print_range(&r);
+            printed += 1;
+        }
+    }
+    printed
+}
+
+/// SYS_MAP_DUMP: print task x6's map; x0 = MAP_DUMP_ALL / MAP_DUMP_USER.
+/// Returns the ranges printed in x0, or PAGE_ATTR_ERR_INVALID_TASK.
+#[cfg(target_arch = "aarch64")]
+pub fn sys_map_dump(frame: &mut crate::exception::TrapFrame, task: usize) {
+    frame.x[0] = if task >= sched::NUM_TASKS {
+        crate::mmu::PAGE_ATTR_ERR_INVALID_TASK
+    } else {
+        dump_task(task, frame.x[0] == MAP_DUMP_USER) as u64
+    };
+}
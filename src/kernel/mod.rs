@@ -5,12 +5,30 @@
 /// Phase L1: ipc.rs and cap.rs moved here.
 /// Phase L2: sched.rs, timer.rs, grant.rs, irq.rs moved here.
 /// Phase L3: elf.rs (ELF64 parser) created here.
+/// queue.rs: buffered message queues (ARINC 653 queuing ports).
+/// sampling.rs: last-value sampling ports (ARINC 653 sampling ports).
+/// cspace.rs: per-task typed capability slots (CSpace-lite).
+/// policy.rs: static information-flow analysis of the task table.
+/// audit.rs: ring buffer of refused syscalls and per-task denial counters.
+/// frame.rs: anonymous-memory frame pool with per-task quotas.
+/// protect.rs: SYS_PAGE_PROTECT — W^X-safe access changes to own pages.
+/// ptcheck.rs: page-table invariant checker and runtime self-audit.
+/// memmap.rs: decoded address-space dump for faults and SYS_MAP_DUMP.
 
 pub mod ipc;
+pub mod queue;
+pub mod sampling;
 pub mod cap;
+pub mod cspace;
+pub mod policy;
+pub mod audit;
 pub mod sched;
 pub mod timer;
 pub mod grant;
+pub mod frame;
+pub mod protect;
+pub mod ptcheck;
+pub mod memmap;
 pub mod irq;
 pub mod elf;
 pub mod log;
@@ -0,0 +1,389 @@
+/// AegisOS Policy Analyzer — static information flow over the task table
+///
+/// Builds the "may influence" graph of a system configuration and checks
+/// declared no-flow policies against its transitive closure. Nodes are the
+/// tasks (node = task id) followed by the whitelisted devices (node =
+/// number of tasks + device id). An edge a → b means a can change state
+/// that b observes, through authority the kernel actually checks: the same
+/// `cap_for_syscall` / `cap_check` pair as `handle_svc`, and the same slot
+/// kind/rights check as `cspace::resolve`.
+///
+/// Channels (storage channels through kernel objects; timing not modelled):
+///   endpoint  sender ↔ receiver — a send's outcome and a call's reply flow back
+///   queue     sender ↔ receiver — a full queue is reported to the sender
+///   sampling  writer → reader  — an overwrite never reveals the reader
+///   notify    notifier → target able to wait on notifications
+///   grant     owner ↔ peers, peer ↔ peer — as declared; undeclared
+///             creators ↔ every task
+///   mint      delegated slots  — as declared; undeclared delegation → every task
+///   device    DEVICE_MAP / IRQ holder ↔ device, SYS_WRITE → console UART
+///   audit     every task → AUDIT holder — denials are logged per task
+///
+/// Grant peers and mint targets are syscall arguments, chosen at run time.
+/// The model takes them from the declared grants and mints: a task that
+/// owns declared grants creates only those, a declared mint is the only
+/// use of its slot. Authority to share or delegate that no declaration
+/// covers is assumed to reach every task.
+///
+/// Everything here is `const fn`: the generated task table asserts each
+/// policy at compile time, so a violated policy fails the build.
+
+use crate::cap::{cap_check, cap_for_syscall};
+use crate::cspace::{check_slot, coarse_caps, object_ref, CapSlot, ObjKind, Rights, CSPACE_SLOTS, RIGHT_GRANT};
+use crate::grant::MAX_GRANTS;
+use crate::ipc::MAX_ENDPOINTS;
+use crate::mmu::DeviceInfo;
+use crate::queue::MAX_QUEUES;
+use crate::sampling::MAX_SAMPLING_PORTS;
+use crate::sched::TaskMetadata;
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+/// Maximum graph size (tasks + devices): one NodeSet bit per node
+pub const MAX_FLOW_NODES: usize = 64;
+
+/// Set of graph nodes, bit n = node n
+pub type NodeSet = u64;
+
+/// L2 index of the kernel console — SYS_WRITE goes to this UART
+const CONSOLE_L2_INDEX: usize = crate::platform::qemu_virt::UART0_BASE >> 21;
+
+// ─── Model ─────────────────────────────────────────────────────────
+
+/// A shared-memory grant the system intends to create, one declaration
+/// per peer: declarations with the same id are one grant shared by all
+/// their peers.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GrantDecl {
+    pub id: usize,
+    pub owner: usize,
+    pub peer: usize,
+}
+
+/// A delegation the system intends to perform: `from` mints its slot
+/// `slot` into task `to` with rights attenuated by `rights`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MintDecl {
+    pub from: usize,
+    pub slot: usize,
+    pub to: usize,
+    pub rights: Rights,
+}
+
+/// Static system description the graph is built from.
+pub struct SystemModel<'a> {
+    pub tasks: &'a [TaskMetadata],
+    pub devices: &'a [DeviceInfo],
+    pub grants: &'a [GrantDecl],
+    pub mints: &'a [MintDecl],
+}
+
+/// Direct-influence graph: bit b of `edges[a]` ⇔ a may influence b.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FlowGraph {
+    pub nodes: usize,
+    pub edges: [NodeSet; MAX_FLOW_NODES],
+}
+
+/// No node in `from` may influence any node in `to`, however indirectly.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Policy {
+    pub from: NodeSet,
+    pub to: NodeSet,
+}
+
+// ─── Authority ─────────────────────────────────────────────────────
+
+/// Slot `m.slot` of `m.from` as it arrives in `m.to` — None if the mint
+/// would be refused (empty source, no RIGHT_GRANT, no CAP_DELEGATE).
+const fn minted(model: &SystemModel, m: &MintDecl) -> Option<CapSlot> {
+    if m.from >= model.tasks.len() || m.slot >= CSPACE_SLOTS {
+        return None;
+    }
+    let src = model.tasks[m.from].cspace[m.slot];
+    let delegate = cap_for_syscall(crate::cspace::SYS_CAP_MINT, 0);
+    if matches!(src.kind, ObjKind::Null)
+        || src.rights & RIGHT_GRANT == 0
+        || !cap_check(model.tasks[m.from].caps, delegate)
+    {
+        return None;
+    }
+    Some(CapSlot { rights: src.rights & m.rights, ..src })
+}
+
+/// True if `holder` could mint `s` and no declaration says where to.
+const fn undeclared_delegation(model: &SystemModel, holder: usize, index: usize, s: &CapSlot) -> bool {
+    let delegate = cap_for_syscall(crate::cspace::SYS_CAP_MINT, 0);
+    if s.rights & RIGHT_GRANT == 0 || !cap_check(model.tasks[holder].caps, delegate) {
+        return false;
+    }
+    let mut i = 0;
+    while i < model.mints.len() {
+        if model.mints[i].from == holder && model.mints[i].slot == index {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn slot_grants(s: &CapSlot, kind: ObjKind, id: u64, rights: Rights) -> bool {
+    match check_slot(s, kind, rights) {
+        Ok(obj) => obj == id,
+        Err(_) => false,
+    }
+}
+
+/// Can `task` ever hold a slot for (`kind`, `id`) with `rights`?
+/// Its own slots, declared mints into it, and any slot someone may
+/// delegate without a declared target.
+const fn holds_slot(model: &SystemModel, task: usize, kind: ObjKind, id: u64, rights: Rights) -> bool {
+    let mut i = 0;
+    while i < CSPACE_SLOTS {
+        if slot_grants(&model.tasks[task].cspace[i], kind, id, rights) {
+            return true;
+        }
+        i += 1;
+    }
+    let mut m = 0;
+    while m < model.mints.len() {
+        if let Some(s) = minted(model, &model.mints[m]) {
+            // a minted copy that can be minted on again goes anywhere
+            let onward = s.rights & RIGHT_GRANT != 0
+                && cap_check(model.tasks[model.mints[m].to].caps, cap_for_syscall(crate::cspace::SYS_CAP_MINT, 0));
+            if (model.mints[m].to == task || onward) && slot_grants(&s, kind, id, rights) {
+                return true;
+            }
+        }
+        m += 1;
+    }
+    let mut t = 0;
+    while t < model.tasks.len() {
+        let mut i = 0;
+        while i < CSPACE_SLOTS {
+            let s = &model.tasks[t].cspace[i];
+            if t != task && undeclared_delegation(model, t, i, s) && slot_grants(s, kind, id, rights) {
+                return true;
+            }
+            i += 1;
+        }
+        t += 1;
+    }
+    false
+}
+
+/// Would the kernel let `task` issue syscall `nr` on object `id`?
+/// Either the CapBits path (raw id) or the slot path (CPTR_FLAG).
+pub const fn may(model: &SystemModel, task: usize, nr: u64, id: u64) -> bool {
+    let caps = model.tasks[task].caps;
+    let required = cap_for_syscall(nr, id);
+    if required != 0 && cap_check(caps, required) {
+        return true;
+    }
+    match object_ref(nr) {
+        Some((_, kind, rights)) => cap_check(caps, coarse_caps(nr)) && holds_slot(model, task, kind, id, rights),
+        None => false,
+    }
+}
+
+// ─── Graph ─────────────────────────────────────────────────────────
+
+const fn both(edges: &mut [NodeSet; MAX_FLOW_NODES], a: usize, b: usize) {
+    edges[a] |= 1 << b;
+    edges[b] |= 1 << a;
+}
+
+/// May `owner` create grants without owning any declared one?
+const fn undeclared_grant(model: &SystemModel, owner: usize) -> bool {
+    let mut i = 0;
+    while i < model.grants.len() {
+        if model.grants[i].owner == owner {
+            return false;
+        }
+        i += 1;
+    }
+    let mut g = 0;
+    while g < MAX_GRANTS {
+        if may(model, owner, 7, g as u64) {
+            return true;
+        }
+        g += 1;
+    }
+    false
+}
+
+/// Build the direct-influence graph of `model`.
+pub const fn flow_graph(model: &SystemModel) -> FlowGraph {
+    let n = model.tasks.len();
+    let nodes = n + model.devices.len();
+    assert!(nodes <= MAX_FLOW_NODES, "too many tasks + devices for the flow graph");
+    let mut edges = [0 as NodeSet; MAX_FLOW_NODES];
+
+    let mut a = 0;
+    while a < n {
+        let mut b = 0;
+        while b < n {
+            if a != b {
+                let mut ep = 0;
+                while ep < MAX_ENDPOINTS as u64 {
+                    if (may(model, a, 1, ep) || may(model, a, 3, ep)) && may(model, b, 2, ep) {
+                        both(&mut edges, a, b);
+                    }
+                    ep += 1;
+                }
+                let mut q = 0;
+                while q < MAX_QUEUES as u64 {
+                    if may(model, a, 18, q) && may(model, b, 19, q) {
+                        both(&mut edges, a, b);
+                    }
+                    q += 1;
+                }
+                let mut p = 0;
+                while p < MAX_SAMPLING_PORTS as u64 {
+                    if may(model, a, 20, p) && may(model, b, 21, p) {
+                        edges[a] |= 1 << b;
+                    }
+                    p += 1;
+                }
+                if may(model, a, 5, b as u64) && may(model, b, 6, 0) {
+                    edges[a] |= 1 << b;
+                }
+                if may(model, b, 25, 0) || may(model, b, 26, a as u64) {
+                    edges[a] |= 1 << b;
+                }
+            }
+            b += 1;
+        }
+
+        if undeclared_grant(model, a) {
+            let mut b = 0;
+            while b < n {
+                if b != a {
+                    both(&mut edges, a, b);
+                }
+                b += 1;
+            }
+        }
+
+        let mut d = 0;
+        while d < model.devices.len() {
+            let dev = n + d;
+            if may(model, a, 11, d as u64) || may(model, a, 9, model.devices[d].intid as u64) {
+                both(&mut edges, a, dev);
+            }
+            if model.devices[d].l2_index == CONSOLE_L2_INDEX && may(model, a, 4, 0) {
+                edges[a] |= 1 << dev;
+            }
+            d += 1;
+        }
+        a += 1;
+    }
+
+    let mut i = 0;
+    while i < model.grants.len() {
+        let g = &model.grants[i];
+        if g.owner < n && g.peer < n && g.owner != g.peer && may(model, g.owner, 7, g.id as u64) {
+            both(&mut edges, g.owner, g.peer);
+            // Peers of one grant share its region with each other
+            let mut j = 0;
+            while j < i {
+                let o = &model.grants[j];
+                if o.id == g.id && o.owner == g.owner && o.peer < n && o.peer != g.peer {
+                    both(&mut edges, o.peer, g.peer);
+                }
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+
+    FlowGraph { nodes, edges }
+}
+
+/// Transitive closure: bit b of `reach[a]` ⇔ a path a → … → b exists.
+pub const fn reachability(graph: &FlowGraph) -> [NodeSet; MAX_FLOW_NODES] {
+    let mut reach = graph.edges;
+    let mut k = 0;
+    while k < graph.nodes {
+        let mut i = 0;
+        while i < graph.nodes {
+            if reach[i] & (1 << k) != 0 {
+                reach[i] |= reach[k];
+            }
+            i += 1;
+        }
+        k += 1;
+    }
+    reach
+}
+
+/// Can node `a` influence node `b`, directly or through others?
+pub const fn may_influence(reach: &[NodeSet; MAX_FLOW_NODES], a: usize, b: usize) -> bool {
+    a < MAX_FLOW_NODES && b < MAX_FLOW_NODES && reach[a] & (1 << b) != 0
+}
+
+/// First (source, sink) pair breaking `policy`, if any.
+pub const fn violation(reach: &[NodeSet; MAX_FLOW_NODES], policy: &Policy) -> Option<(usize, usize)> {
+    let mut a = 0;
+    while a < MAX_FLOW_NODES {
+        let hit = reach[a] & policy.to;
+        if policy.from & (1 << a) != 0 && hit != 0 {
+            return Some((a, hit.trailing_zeros() as usize));
+        }
+        a += 1;
+    }
+    None
+}
+
+/// `policy` holds on the configuration whose closure is `reach`.
+pub const fn holds(reach: &[NodeSet; MAX_FLOW_NODES], policy: &Policy) -> bool {
+    violation(reach, policy).is_none()
+}
+
+// ─── Kani proofs ───────────────────────────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Proof: the closure is exactly path reachability on small graphs.
+    /// For every 4-node edge set:
+    /// - every edge is in the closure, and the closure is transitive
+    /// - every reachable node has a direct predecessor that is the
+    ///   source itself or reachable from it (no invented flows)
+    #[kani::proof]
+    #[kani::unwind(6)]
+    fn policy_closure_is_reachability() {
+        const N: usize = 4;
+        let mut graph = FlowGraph { nodes: N, edges: [0; MAX_FLOW_NODES] };
+        let mut i = 0;
+        while i < N {
+            graph.edges[i] = kani::any::<u8>() as NodeSet & 0xF;
+            i += 1;
+        }
+        let reach = reachability(&graph);
+
+        let a: usize = kani::any();
+        let b: usize = kani::any();
+        let c: usize = kani::any();
+        kani::assume(a < N && b < N && c < N);
+        if graph.edges[a] & (1 << b) != 0 {
+            assert!(reach[a] & (1 << b) != 0, "edge in closure");
+        }
+        if reach[a] & (1 << b) != 0 && reach[b] & (1 << c) != 0 {
+            assert!(reach[a] & (1 << c) != 0, "closure transitive");
+        }
+        if reach[a] & (1 << b) != 0 {
+            let mut witnessed = false;
+            let mut p = 0;
+            while p < N {
+                if graph.edges[p] & (1 << b) != 0 && (p == a || reach[a] & (1 << p) != 0) {
+                    witnessed = true;
+                }
+                p += 1;
+            }
+            assert!(witnessed, "reachable only through real edges");
+        }
+        assert!(reach[N] == 0, "nodes outside the graph stay empty");
+    }
+}
@@ -0,0 +1,267 @@
+/// AegisOS Page Protection — SYS_PAGE_PROTECT (user-visible mprotect)
+///
+/// A task changes the access of its own pages between three W^X-safe
+/// settings: read-write (never executable), read-only (never executable)
+/// and read-execute. The typical use is "seal after init": parse a
+/// config table, then make it read-only for the rest of the task's life.
+///
+/// Only the task's own EL0-mapped memory can be changed: the pages of
+/// its ELF load slot that the loader mapped, its user stack page and the
+/// anonymous frames it holds (SYS_MAP_ANON). Execute is only granted on
+/// pages the loader mapped as text, and text pages only switch between
+/// read-execute and read-only: a task can drop and restore execute on
+/// its code, but never write it, nor make data, stack or anonymous
+/// pages executable. Every page of the range is checked before any
+/// descriptor changes.
+///
+/// Fault cleanup undoes every change: ELF pages get back the template
+/// the loader gave their segment (their contents are not reloaded), the
+/// stack page is made read-write again, and anonymous frames are
+/// unmapped with the rest of the frame pool.
+///
+/// Syscall (CAP_PAGE_PROTECT):
+///   SYS_PAGE_PROTECT = 31: x0 = base, x1 = pages, x2 = PROT_* → x0 = result
+
+use crate::elf::ELF_SLOT_PAGES;
+use crate::frame;
+use crate::mmu::{AP_RW_EL0, UXN};
+use crate::platform::qemu_virt::{elf_load_addr, ELF_LOAD_SIZE_PER_TASK, MAX_ELF_TASKS};
+use crate::sched;
+use crate::uart_print;
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+/// Page size handled by SYS_PAGE_PROTECT.
+pub const PAGE_SIZE: u64 = 4096;
+
+/// Most pages one call may change (one L3 table).
+pub const MAX_PROTECT_PAGES: usize = 512;
+
+/// AP[2:1] field of a page descriptor (bits [7:6])
+const AP_MASK: u64 = 0b11 << 6;
+
+/// x2 value: read-write, never executable (USER_DATA_PAGE).
+pub const PROT_RW: u64 = 0;
+/// x2 value: read-only, never executable (USER_RO_DATA_PAGE).
+pub const PROT_RO: u64 = 1;
+/// x2 value: read-only and EL0-executable (USER_CODE_PAGE).
+pub const PROT_RX: u64 = 2;
+
+/// Unknown PROT_* value, or a page count of 0.
+pub const ERR_PROT_INVALID: u64 = 0xFFFF_3003;
+/// A page of the range is misaligned or not the caller's own memory.
+pub const ERR_PROT_NOT_OWNER: u64 = 0xFFFF_3004;
+/// Execute requested outside the text pages of the caller's ELF slot.
+pub const ERR_PROT_NO_EXEC: u64 = 0xFFFF_3005;
+/// Write requested on a text page of the caller's ELF slot.
+pub const ERR_PROT_TEXT_WRITE: u64 = 0xFFFF_3006;
+
+/// Access a task can ask for. No variant is both writable and executable.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PageProt {
+    ReadWrite,
+    ReadOnly,
+    ReadExec,
+}
+
+impl PageProt {
+    /// Decode a PROT_* syscall argument.
+    pub const fn from_u64(v: u64) -> Option<Self> {
+        match v {
+            PROT_RW => Some(PageProt::ReadWrite),
+            PROT_RO => Some(PageProt::ReadOnly),
+            PROT_RX => Some(PageProt::ReadExec),
+            _ => None,
+        }
+    }
+
+    /// L3 descriptor template for this access.
+    pub const fn template(self) -> u64 {
+        match self {
+            PageProt::ReadWrite => crate::mmu::USER_DATA_PAGE,
+            PageProt::ReadOnly => crate::mmu::USER_RO_DATA_PAGE,
+            PageProt::ReadExec => crate::mmu::USER_CODE_PAGE,
+        }
+    }
+}
+
+/// Which of a task's own regions a page belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OwnRegion {
+    /// A page the loader mapped in the task's ELF slot; `text` if it
+    /// belongs to an executable segment
+    Elf { text: bool },
+    /// The task's user stack page
+    Stack,
+    /// An anonymous frame the task holds
+    Anon,
+}
+
+// ─── Descriptor predicates ─────────────────────────────────────────
+
+/// EL0 can store through an L3 descriptor (AP[2:1] = RW at EL0).
+pub const fn el0_writable(desc: u64) -> bool {
+    desc & AP_MASK == AP_RW_EL0
+}
+
+/// EL0 can execute from an L3 descriptor (UXN clear).
+pub const fn el0_executable(desc: u64) -> bool {
+    desc & UXN == 0
+}
+
+// ─── Checks (pure) ─────────────────────────────────────────────────
+
+/// Region of an ELF slot page the loader gave `template` (0 = not
+/// mapped by the loader, so not EL0-accessible and not the task's).
+pub const fn elf_page_region(template: u64) -> Option<OwnRegion> {
+    if template == 0 {
+        None
+    } else {
+        Some(OwnRegion::Elf { text: template == crate::mmu::USER_CODE_PAGE })
+    }
+}
+
+/// The own region `addr` lies in, for a task with ELF slot `elf_slot`
+/// (loader templates `elf_templates`), user stack ending at `stack_top`,
+/// and frame ownership `owners` of the pool at `pool_base`. Returns None
+/// for anything else.
+pub fn own_region(
+    task: usize,
+    addr: u64,
+    elf_slot: Option<usize>,
+    elf_templates: &[[u64; ELF_SLOT_PAGES]; MAX_ELF_TASKS],
+    stack_top: u64,
+    owners: &[u8; frame::FRAME_POOL_PAGES],
+    pool_base: u64,
+) -> Option<OwnRegion> {
+    if let Some(slot) = elf_slot {
+        if slot < MAX_ELF_TASKS {
+            let start = elf_load_addr(slot);
+            if addr >= start && addr < start + ELF_LOAD_SIZE_PER_TASK as u64 {
+                return elf_page_region(elf_templates[slot][((addr - start) / PAGE_SIZE) as usize]);
+            }
+        }
+    }
+    if addr >= stack_top - sched::STACK_SIZE && addr < stack_top {
+        return Some(OwnRegion::Stack);
+    }
+    if addr >= pool_base {
+        let index = ((addr - pool_base) / frame::FRAME_SIZE as u64) as usize;
+        if index < frame::FRAME_POOL_PAGES && owners[index] as usize == task {
+            return Some(OwnRegion::Anon);
+        }
+    }
+    None
+}
+
+/// Template for setting a page of `region` to `prot`, or ERR_PROT_*.
+pub const fn protect_template(region: Option<OwnRegion>, prot: PageProt) -> Result<u64, u64> {
+    match (region, prot) {
+        (None, _) => Err(ERR_PROT_NOT_OWNER),
+        (Some(OwnRegion::Elf { text: true }), PageProt::ReadWrite) => Err(ERR_PROT_TEXT_WRITE),
+        (Some(OwnRegion::Elf { text: true }), _) => Ok(prot.template()),
+        (Some(_), PageProt::ReadExec) => Err(ERR_PROT_NO_EXEC),
+        (Some(_), _) => Ok(prot.template()),
+    }
+}
+
+// ─── Core operations ───────────────────────────────────────────────
+
+/// Set `pages` pages at `addr` of `task`'s own memory to `prot` (PROT_*).
+/// Returns 0 on success, ERR_PROT_* on failure (nothing is changed).
+pub fn page_protect(task: usize, addr: u64, pages: usize, prot: u64) -> u64 {
+    let Some(prot) = PageProt::from_u64(prot) else {
+        return ERR_PROT_INVALID;
+    };
+    if pages == 0 || pages > MAX_PROTECT_PAGES {
+        return ERR_PROT_INVALID;
+    }
+    if !addr.is_multiple_of(PAGE_SIZE) || addr.checked_add(pages as u64 * PAGE_SIZE).is_none() {
+        return ERR_PROT_NOT_OWNER;
+    }
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let elf_slot = (*sched::TCBS.get())[task].elf_slot;
+        let stack_top = sched::stack_top(sched::stack_region_base(sched::StackKind::User), task);
+        let elf_templates = crate::elf::ELF_TEMPLATES.get();
+        let owners = frame::FRAME_OWNER.get();
+        let pool_base = frame::frame_pool_base();
+
+        // Check the whole range first — all or nothing
+        for i in 0..pages as u64 {
+            let page = addr + i * PAGE_SIZE;
+            let region = own_region(task, page, elf_slot, elf_templates, stack_top, owners, pool_base);
+            if let Err(e) = protect_template(region, prot) {
+                uart_print("!!! PROTECT: page is not the task's own, or not executable\n");
+                return e;
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        for i in 0..pages as u64 {
+            crate::mmu::set_page_attr(task, addr + i * PAGE_SIZE, prot.template());
+        }
+    }
+    0
+}
+
+// ─── Fault cleanup ─────────────────────────────────────────────────
+
+/// Give a faulted, restarted or exited task's ELF pages their loader
+/// templates back and make its stack page read-write again.
+/// Called from sched::cleanup_task_resources().
+pub fn cleanup_task(task_idx: usize) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    if let Some(slot) = unsafe { (*sched::TCBS.get())[task_idx].elf_slot } {
+        crate::elf::restore_templates(task_idx, slot);
+    }
+    #[cfg(target_arch = "aarch64")]
+    // SAFETY: task_idx < NUM_TASKS; the page is this task's own stack page.
+    unsafe {
+        let stack_top = sched::stack_top(sched::stack_region_base(sched::StackKind::User), task_idx);
+        crate::mmu::set_page_attr(task_idx, stack_top - sched::STACK_SIZE, crate::mmu::USER_DATA_PAGE);
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    let _ = task_idx;
+}
+
+// ─── Kani formal verification proofs ───────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Proof: SYS_PAGE_PROTECT never makes a page writable and executable at EL0.
+    /// For any region and any PROT_* value, an accepted request yields a
+    /// template that is not both EL0-writable and EL0-executable, an
+    /// executable template only on a page the loader mapped as text, and
+    /// never an EL0-writable template on such a page.
+    #[kani::proof]
+    fn page_protect_never_writable_and_executable() {
+        let prot_raw: u64 = kani::any();
+        let region_raw: u8 = kani::any();
+        let loader_template: u64 = kani::any();
+        let region = match region_raw {
+            0 => elf_page_region(loader_template),
+            1 => Some(OwnRegion::Stack),
+            2 => Some(OwnRegion::Anon),
+            _ => None,
+        };
+
+        match PageProt::from_u64(prot_raw) {
+            None => assert!(prot_raw > PROT_RX, "only PROT_RW/RO/RX decode"),
+            Some(prot) => {
+                if let Ok(desc) = protect_template(region, prot) {
+                    assert!(region.is_some(), "only own memory changes");
+                    assert!(!(el0_writable(desc) && el0_executable(desc)), "W^X at EL0");
+                    if region == Some(OwnRegion::Elf { text: true }) {
+                        assert!(!el0_writable(desc), "text never becomes writable");
+                    }
+                    if el0_executable(desc) {
+                        assert!(region_raw == 0, "execute only in the ELF slot");
+                        assert!(loader_template == crate::mmu::USER_CODE_PAGE, "execute only on loader text");
+                    }
+                }
+            }
+        }
+    }
+}
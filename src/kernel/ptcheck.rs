@@ -0,0 +1,387 @@
+/// AegisOS Page-Table Checker — runtime self-audit of every task's tables
+///
+/// build_l1 / build_l2_ram / build_l3 lay the tables out at boot, but
+/// map_device_for_task, map_grant_for_task and set_page_attr keep
+/// rewriting entries afterwards. This module re-walks each task's
+/// L2 device, L2 RAM and L3 tables against the intended policy:
+///   1. W^X — no entry is both writable and executable, at any EL
+///   2. Kernel memory is never EL0-accessible
+///   3. The GIC is never mapped for EL0
+///   4. Another task's stack is never EL0-accessible
+///   5. A grant page is EL0-accessible only to the grant's current owner
+///      and peers, and never writable for a read-only side
+///
+/// EL0 may reach kernel text and rodata (read-only), its own stack, IPC
+/// buffer, ELF slot and anonymous frames, and grants it is a member of.
+/// Entries are classified by the physical page they point at, so the
+/// IPC buffer alias is checked like any other mapping.
+///
+/// `check_task_tables()` is a pure function over a table snapshot
+/// (host-testable). `self_audit()` runs it for every task at boot and
+/// every PT_CHECK_PERIOD ticks; a violation halts the system in a safe
+/// state (interrupts masked, core parked) instead of running on.
+
+use crate::frame;
+use crate::grant::{Grant, GrantPerm, GRANT_PAGE_SIZE, GRANT_POOL_PAGES, MAX_GRANTS};
+use crate::ipc::IPC_BUF_SIZE;
+use crate::mmu::XN;
+use crate::platform::qemu_virt::{elf_load_addr, ELF_LOAD_SIZE_PER_TASK, GICC_BASE, GICD_BASE, MAX_ELF_TASKS};
+use crate::sched;
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+/// Entries per translation table (4 KiB granule).
+pub const TABLE_ENTRIES: usize = 512;
+
+/// Virtual base of the L3-mapped range (first 2 MiB of RAM).
+pub const L3_BASE: u64 = 0x4000_0000;
+
+/// Virtual base of the L2 RAM table (1 GiB at L1 index 1).
+pub const L2_RAM_BASE: u64 = 0x4000_0000;
+
+/// Bytes mapped by one L3 entry.
+pub const PAGE_SIZE: u64 = 4096;
+
+/// Bytes mapped by one L2 block entry.
+pub const L2_BLOCK_SIZE: u64 = 0x20_0000;
+
+/// Ticks between runtime self-audits (once per major frame).
+pub const PT_CHECK_PERIOD: u64 = 100;
+
+/// Output address, bits [47:12]
+const OA_MASK: u64 = 0x0000_FFFF_FFFF_F000;
+/// Descriptor type, bits [1:0]: 0b01 = block (L1/L2), 0b11 = table or L3 page
+const DESC_TYPE_MASK: u64 = 0b11;
+const DESC_BLOCK: u64 = 0b01;
+/// AP[2] (bit 7): read-only at every EL
+const AP_READ_ONLY: u64 = 1 << 7;
+/// AP[1] (bit 6): accessible from EL0
+const AP_EL0: u64 = 1 << 6;
+
+// ─── Descriptor predicates ─────────────────────────────────────────
+
+/// Some EL can store through the descriptor (AP[2] clear).
+pub const fn writable(desc: u64) -> bool {
+    desc & AP_READ_ONLY == 0
+}
+
+/// Some EL can execute from the descriptor (PXN or UXN clear).
+pub const fn executable(desc: u64) -> bool {
+    desc & XN != XN
+}
+
+/// EL0 can load from (and maybe store to) the descriptor (AP[1] set).
+pub const fn el0_accessible(desc: u64) -> bool {
+    desc & AP_EL0 != 0
+}
+
+// ─── Snapshot types ────────────────────────────────────────────────
+
+/// Which rule an entry breaks.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViolationKind {
+    /// Writable and executable
+    WriteExec,
+    /// Kernel memory reachable from EL0
+    KernelData,
+    /// GIC MMIO mapped for EL0
+    Gic,
+    /// Another task's stack reachable from EL0
+    ForeignStack,
+    /// Grant page mapped for a non-member, or writable for a read-only side
+    Grant,
+}
+
+impl ViolationKind {
+    /// Human-readable rule name for the halt report.
+    pub const fn name(self) -> &'static str {
+        match self {
+            ViolationKind::WriteExec => "writable and executable",
+            ViolationKind::KernelData => "kernel memory EL0-accessible",
+            ViolationKind::Gic => "GIC mapped for EL0",
+            ViolationKind::ForeignStack => "another task's stack EL0-accessible",
+            ViolationKind::Grant => "grant page outside its members' access",
+        }
+    }
+}
+
+/// First broken rule found in a task's tables.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Violation {
+    pub task: usize,
+    pub kind: ViolationKind,
+    /// Virtual address mapped by the offending entry
+    pub va: u64,
+}
+
+/// One task's translation tables (see mmu::PageTableType).
+pub struct TaskTables<'a> {
+    pub l2_device: &'a [u64; TABLE_ENTRIES],
+    pub l2_ram: &'a [u64; TABLE_ENTRIES],
+    pub l3: &'a [u64; TABLE_ENTRIES],
+}
+
+/// Where the kernel's regions are (linker.ld; host tests build their own).
+#[derive(Clone, Copy, Debug)]
+pub struct MemLayout {
+    /// Kernel text [start, end) — shared, read-only, executable
+    pub text: (u64, u64),
+    /// Kernel rodata [start, end) — read-only at EL0
+    pub rodata: (u64, u64),
+    /// Start of .user_stacks (guard + stack per task)
+    pub user_stacks: u64,
+    /// Start of .ipc_buffers (one page per task)
+    pub ipc_buffers: u64,
+    /// Start of .grant_pages
+    pub grant_pool: u64,
+    /// Start of .frame_pool
+    pub frame_pool: u64,
+}
+
+/// What a task currently owns: ELF slot, grant memberships, frames.
+pub struct TaskMemory<'a> {
+    pub task: usize,
+    pub elf_slot: Option<usize>,
+    pub grants: &'a [Grant; MAX_GRANTS],
+    pub frame_owners: &'a [u8; frame::FRAME_POOL_PAGES],
+}
+
+// ─── Checks (pure) ─────────────────────────────────────────────────
+
+const fn in_range(addr: u64, start: u64, end: u64) -> bool {
+    addr >= start && addr < end
+}
+
+/// Access `task` has to grant pool page `addr`: its side of the active
+/// grant covering the page, or None if it is not a member.
+pub fn grant_access(grants: &[Grant; MAX_GRANTS], task: usize, addr: u64) -> Option<GrantPerm> {
+    for g in grants.iter() {
+        if !g.active || !in_range(addr, g.phys_addr, g.phys_addr + (g.pages * GRANT_PAGE_SIZE) as u64) {
+            continue;
+        }
+        if g.owner == Some(task) {
+            return Some(g.owner_perm);
+        }
+        if let Some(perm) = g.peer_perm(task) {
+            return Some(perm);
+        }
+    }
+    None
+}
+
+/// Check one valid-or-invalid L3 entry of `mem.task`'s table.
+pub fn check_l3_entry(mem: &TaskMemory, layout: &MemLayout, desc: u64) -> Result<(), ViolationKind> {
+    if desc & DESC_TYPE_MASK != 0b11 {
+        return Ok(()); // invalid (guard pages, unmapped)
+    }
+    if writable(desc) && executable(desc) {
+        return Err(ViolationKind::WriteExec);
+    }
+    if !el0_accessible(desc) {
+        return Ok(());
+    }
+    let oa = desc & OA_MASK;
+    let task = mem.task;
+
+    // Kernel text and rodata: EL0 may read, never write
+    if in_range(oa, layout.text.0, layout.text.1) || in_range(oa, layout.rodata.0, layout.rodata.1) {
+        return if writable(desc) { Err(ViolationKind::KernelData) } else { Ok(()) };
+    }
+    // Own IPC buffer (mapped at IPC_BUFFER_VA)
+    if oa == layout.ipc_buffers + (task * IPC_BUF_SIZE) as u64 {
+        return Ok(());
+    }
+    // Stacks: own stack page only
+    let own_top = sched::stack_top(layout.user_stacks, task);
+    if in_range(oa, own_top - sched::STACK_SIZE, own_top) {
+        return Ok(());
+    }
+    if in_range(oa, layout.user_stacks, sched::stack_top(layout.user_stacks, sched::NUM_TASKS - 1)) {
+        return Err(ViolationKind::ForeignStack);
+    }
+    // Own ELF slot
+    if let Some(slot) = mem.elf_slot {
+        if slot < MAX_ELF_TASKS && in_range(oa, elf_load_addr(slot), elf_load_addr(slot) + ELF_LOAD_SIZE_PER_TASK as u64) {
+            return Ok(());
+        }
+    }
+    // Grants: members only, at their own side's access
+    if in_range(oa, layout.grant_pool, layout.grant_pool + (GRANT_POOL_PAGES * GRANT_PAGE_SIZE) as u64) {
+        return match grant_access(mem.grants, task, oa) {
+            Some(GrantPerm::ReadWrite) => Ok(()),
+            Some(GrantPerm::ReadOnly) if !writable(desc) => Ok(()),
+            _ => Err(ViolationKind::Grant),
+        };
+    }
+    // Anonymous frames the task holds
+    if oa >= layout.frame_pool {
+        let index = ((oa - layout.frame_pool) / frame::FRAME_SIZE as u64) as usize;
+        if index < frame::FRAME_POOL_PAGES && mem.frame_owners[index] as usize == task {
+            return Ok(());
+        }
+    }
+    Err(ViolationKind::KernelData)
+}
+
+/// Check one L2 block entry mapping [va, va + 2 MiB). Table descriptors
+/// (the L3 link) and invalid entries pass; the L3 is checked separately.
+pub fn check_l2_entry(va: u64, desc: u64) -> Result<(), ViolationKind> {
+    if desc & DESC_TYPE_MASK != DESC_BLOCK {
+        return Ok(());
+    }
+    if writable(desc) && executable(desc) {
+        return Err(ViolationKind::WriteExec);
+    }
+    if !el0_accessible(desc) {
+        return Ok(());
+    }
+    // GICD and GICC, 64 KiB each
+    let (gic_start, gic_end) = (GICD_BASE as u64, GICC_BASE as u64 + 0x1_0000);
+    if va < gic_end && gic_start < va + L2_BLOCK_SIZE {
+        return Err(ViolationKind::Gic);
+    }
+    // Only whitelisted devices are EL0-mapped; RAM above the L3 range is kernel-only
+    if in_range(va, L2_RAM_BASE, L2_RAM_BASE + TABLE_ENTRIES as u64 * L2_BLOCK_SIZE) {
+        return Err(ViolationKind::KernelData);
+    }
+    Ok(())
+}
+
+/// Walk all of one task's tables. Returns the first violation found.
+pub fn check_task_tables(tables: &TaskTables, mem: &TaskMemory, layout: &MemLayout) -> Result<(), Violation> {
+    let fail = |kind, va| Violation { task: mem.task, kind, va };
+    for (i, &desc) in tables.l2_device.iter().enumerate() {
+        let va = i as u64 * L2_BLOCK_SIZE;
+        check_l2_entry(va, desc).map_err(|k| fail(k, va))?;
+    }
+    for (i, &desc) in tables.l2_ram.iter().enumerate() {
+        let va = L2_RAM_BASE + i as u64 * L2_BLOCK_SIZE;
+        check_l2_entry(va, desc).map_err(|k| fail(k, va))?;
+    }
+    for (i, &desc) in tables.l3.iter().enumerate() {
+        let va = L3_BASE + i as u64 * PAGE_SIZE;
+        check_l3_entry(mem, layout, desc).map_err(|k| fail(k, va))?;
+    }
+    Ok(())
+}
+
+// ─── Runtime self-audit ────────────────────────────────────────────
+
+/// Kernel layout from the linker symbols.
+#[cfg(target_arch = "aarch64")]
+pub fn current_layout() -> MemLayout {
+    extern "C" {
+        static __text_start: u8;
+        static __text_end: u8;
+        static __rodata_start: u8;
+        static __rodata_end: u8;
+    }
+    // SAFETY: Linker-provided symbols, only their addresses are taken.
+    let (text, rodata) = unsafe {
+        (
+            (&__text_start as *const u8 as u64, &__text_end as *const u8 as u64),
+            (&__rodata_start as *const u8 as u64, &__rodata_end as *const u8 as u64),
+        )
+    };
+    MemLayout {
+        text,
+        rodata,
+        user_stacks: sched::stack_region_base(sched::StackKind::User),
+        ipc_buffers: crate::ipc::ipc_buffer_addr(0).unwrap_or(0),
+        grant_pool: crate::grant::grant_pool_base(),
+        frame_pool: frame::frame_pool_base(),
+    }
+}
+
+/// Check every task's live tables against the kernel's own records.
+#[cfg(target_arch = "aarch64")]
+pub fn check_all() -> Result<(), Violation> {
+    let layout = current_layout();
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        for task in 0..sched::NUM_TASKS {
+            let tables = crate::mmu::task_tables(task);
+            let mem = TaskMemory {
+                task,
+                elf_slot: (*sched::TCBS.get())[task].elf_slot,
+                grants: crate::grant::GRANTS.get(),
+                frame_owners: frame::FRAME_OWNER.get(),
+            };
+            check_task_tables(&tables, &mem, &layout)?;
+        }
+    }
+    Ok(())
+}
+
+/// Run the check; on a violation, report it and halt in a safe state.
+#[cfg(target_arch = "aarch64")]
+pub fn self_audit() {
+    if let Err(v) = check_all() {
+        safe_state_halt(v);
+    }
+}
+
+/// Report a page-table violation, mask interrupts and park the core.
+#[cfg(target_arch = "aarch64")]
+fn safe_state_halt(v: Violation) -> ! {
+    crate::uart_print("\n!!! PAGE TABLE VIOLATION: ");
+    crate::uart_print(v.kind.name());
+    crate::uart_print("\n  task: ");
+    crate::uart_print_dec(v.task as u64);
+    crate::uart_print("\n  VA:   0x");
+    crate::uart_print_hex(v.va);
+    crate::uart_print("\n  SAFE STATE — HALTED.\n");
+    // SAFETY: masking interrupts and wfe are always allowed at EL1.
+    unsafe { core::arch::asm!("msr daifset, #0xf", options(nomem, nostack)) };
+    // SAFETY: wfe is a hint instruction that idles the core until next event.
+    loop { unsafe { core::arch::asm!("wfe", options(nomem, nostack)) } }
+}
+
+// ─── Kani formal verification proofs ───────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Proof: An L3 entry the checker accepts is never writable and
+    /// executable, and if EL0 can reach it, it is neither another task's
+    /// stack, a grant page (no grant active) nor a frame the task lacks.
+    #[kani::proof]
+    #[kani::unwind(9)] // FRAME_POOL_PAGES = 8
+    fn ptcheck_accepts_only_policy_entries() {
+        let layout = MemLayout {
+            text: (0x4008_0000, 0x4009_0000),
+            rodata: (0x4009_0000, 0x400A_0000),
+            user_stacks: 0x400C_0000,
+            ipc_buffers: 0x400E_0000,
+            grant_pool: 0x400F_0000,
+            frame_pool: 0x4014_0000,
+        };
+        let grants = [crate::grant::EMPTY_GRANT; MAX_GRANTS];
+        let mut frame_owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+        for o in frame_owners.iter_mut() {
+            *o = kani::any();
+        }
+        let task: usize = kani::any();
+        kani::assume(task < sched::NUM_TASKS);
+        let mem = TaskMemory { task, elf_slot: None, grants: &grants, frame_owners: &frame_owners };
+        let desc: u64 = kani::any();
+
+        if check_l3_entry(&mem, &layout, desc).is_ok() && desc & DESC_TYPE_MASK == 0b11 {
+            assert!(!(writable(desc) && executable(desc)), "W^X");
+            if el0_accessible(desc) {
+                let oa = desc & OA_MASK;
+                let own_top = sched::stack_top(layout.user_stacks, task);
+                let stacks_end = sched::stack_top(layout.user_stacks, sched::NUM_TASKS - 1);
+                assert!(!in_range(oa, layout.user_stacks, stacks_end) || in_range(oa, own_top - sched::STACK_SIZE, own_top));
+                let grant_end = layout.grant_pool + (GRANT_POOL_PAGES * GRANT_PAGE_SIZE) as u64;
+                assert!(!in_range(oa, layout.grant_pool, grant_end), "no grant is active");
+                let frames_end = layout.frame_pool + (frame::FRAME_POOL_PAGES * frame::FRAME_SIZE) as u64;
+                if in_range(oa, layout.frame_pool, frames_end) {
+                    let index = ((oa - layout.frame_pool) / frame::FRAME_SIZE as u64) as usize;
+                    assert!(frame_owners[index] as usize == task, "only the task's own frames");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+/// AegisOS Message Queues — buffered asynchronous IPC (ARINC 653 queuing ports)
+///
+/// A queue is a bounded FIFO of fixed-size messages, configured once at
+/// boot: depth (≤ QUEUE_MAX_DEPTH messages) and message size (1–MSG_REGS
+/// words, taken from x0..x3). Unlike an endpoint rendezvous, the producer
+/// never blocks — SYS_QUEUE_SEND copies the message into the kernel pool
+/// and returns at once. The consumer dequeues with SYS_QUEUE_RECV and
+/// blocks, polls or waits with a timeout in x4, exactly like SYS_RECV.
+///
+/// A full queue applies its overflow policy:
+///   DropNewest  — the new message is discarded, sender gets ERR_QUEUE_FULL
+///   DropOldest  — the oldest queued message is overwritten, send succeeds
+///   FaultSender — the sender is faulted (overflow is a design error)
+///
+/// Storage is the static QUEUES pool alongside the IPC endpoint table —
+/// no heap. A dequeued message carries the kernel-stamped sender id in x4
+/// (x5 = NO_BADGE). Fill levels, high-water marks and drop counters are
+/// kept per queue for diagnostics.
+///
+/// Syscalls:
+///   SYS_QUEUE_SEND = 18: enqueue x0..x3 on queue x6 (never blocks)
+///   SYS_QUEUE_RECV = 19: dequeue from queue x6 (x4 timeout)
+
+use crate::exception::TrapFrame;
+use crate::ipc::{self, MSG_REGS, TIMEOUT_POLL, ERR_IPC_TIMEOUT};
+use crate::kernel::cell::KernelCell;
+use crate::sched::{self, TaskState};
+use crate::uart_print;
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+#[allow(dead_code)]
+pub const SYS_QUEUE_SEND: u64 = 18;
+#[allow(dead_code)]
+pub const SYS_QUEUE_RECV: u64 = 19;
+
+/// Number of message queues in the static pool
+pub const MAX_QUEUES: usize = 2;
+/// Largest configurable queue depth (messages)
+pub const QUEUE_MAX_DEPTH: usize = 8;
+
+/// Queue id out of range or queue not configured at boot.
+pub const ERR_QUEUE_INVALID: u64 = 0xFFFF_5001;
+/// Queue full under DropNewest — the message was discarded.
+pub const ERR_QUEUE_FULL: u64 = 0xFFFF_5002;
+/// Another task is already blocked dequeuing from this queue.
+pub const ERR_QUEUE_BUSY: u64 = 0xFFFF_5003;
+
+// ─── Queue types ───────────────────────────────────────────────────
+
+/// What a full queue does with one more message.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverflowPolicy {
+    /// Discard the incoming message; sender sees ERR_QUEUE_FULL
+    DropNewest,
+    /// Overwrite the oldest queued message; sender sees success
+    DropOldest,
+    /// Fault the sending task
+    FaultSender,
+}
+
+/// Static configuration of one queue.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct QueueConfig {
+    /// Capacity in messages (1..=QUEUE_MAX_DEPTH)
+    pub depth: usize,
+    /// Message size in 64-bit words (1..=MSG_REGS); extra words read as 0
+    pub msg_words: usize,
+    pub policy: OverflowPolicy,
+}
+
+/// A bounded FIFO of messages plus its single blocked consumer.
+#[derive(Clone, Copy)]
+pub struct MsgQueue {
+    /// None = not configured (all syscalls fail with ERR_QUEUE_INVALID)
+    pub config: Option<QueueConfig>,
+    pub msgs: [[u64; MSG_REGS]; QUEUE_MAX_DEPTH],
+    pub senders: [usize; QUEUE_MAX_DEPTH],
+    pub head: usize,
+    pub count: usize,
+    /// Task blocked in SYS_QUEUE_RECV waiting for a message
+    pub receiver: Option<usize>,
+    /// Highest fill level seen since boot
+    pub high_water: usize,
+    /// Messages lost to overflow (either drop policy)
+    pub dropped: u64,
+}
+
+pub const EMPTY_QUEUE: MsgQueue = MsgQueue {
+    config: None,
+    msgs: [[0; MSG_REGS]; QUEUE_MAX_DEPTH],
+    senders: [0; QUEUE_MAX_DEPTH],
+    head: 0,
+    count: 0,
+    receiver: None,
+    high_water: 0,
+    dropped: 0,
+};
+
+/// Snapshot of one queue's fill level for diagnostics.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct QueueStats {
+    pub fill: usize,
+    pub depth: usize,
+    pub high_water: usize,
+    pub dropped: u64,
+}
+
+/// Whether `cfg` fits the static pool (usable in const context).
+pub const fn queue_config_valid(cfg: &QueueConfig) -> bool {
+    cfg.depth >= 1 && cfg.depth <= QUEUE_MAX_DEPTH && cfg.msg_words >= 1 && cfg.msg_words <= MSG_REGS
+}
+
+impl MsgQueue {
+    /// Append `msg` (truncated to the configured size) from `sender`.
+    /// Returns Err(policy) when the queue was full: under DropOldest the
+    /// message was still stored over the oldest one, otherwise it was not.
+    pub fn enqueue(&mut self, msg: [u64; MSG_REGS], sender: usize) -> Result<(), OverflowPolicy> {
+        let Some(cfg) = self.config else {
+            return Err(OverflowPolicy::DropNewest);
+        };
+        let mut stored = [0u64; MSG_REGS];
+        stored[..cfg.msg_words].copy_from_slice(&msg[..cfg.msg_words]);
+
+        let full = self.count >= cfg.depth;
+        if full {
+            self.dropped = self.dropped.saturating_add(1);
+            if cfg.policy != OverflowPolicy::DropOldest {
+                return Err(cfg.policy);
+            }
+            // Discard the oldest message to make room
+            self.head = (self.head + 1) % cfg.depth;
+            self.count -= 1;
+        }
+        let tail = (self.head + self.count) % cfg.depth;
+        self.msgs[tail] = stored;
+        self.senders[tail] = sender;
+        self.count += 1;
+        if self.count > self.high_water {
+            self.high_water = self.count;
+        }
+        if full { Err(cfg.policy) } else { Ok(()) }
+    }
+
+    /// Remove the oldest message. Returns (message, sender task id).
+    pub fn dequeue(&mut self) -> Option<([u64; MSG_REGS], usize)> {
+        let cfg = self.config?;
+        if self.count == 0 {
+            return None;
+        }
+        let idx = self.head;
+        self.head = (self.head + 1) % cfg.depth;
+        self.count -= 1;
+        Some((self.msgs[idx], self.senders[idx]))
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        QueueStats {
+            fill: self.count,
+            depth: self.config.map_or(0, |c| c.depth),
+            high_water: self.high_water,
+            dropped: self.dropped,
+        }
+    }
+}
+
+// ─── Static queue pool ─────────────────────────────────────────────
+
+pub static QUEUES: KernelCell<[MsgQueue; MAX_QUEUES]> = KernelCell::new([EMPTY_QUEUE; MAX_QUEUES]);
+
+/// Configure queue `q_id` (boot-time). Returns 0, or ERR_QUEUE_INVALID if
+/// the id is out of range or `cfg` does not fit the pool.
+pub fn queue_configure(q_id: usize, cfg: QueueConfig) -> u64 {
+    if q_id >= MAX_QUEUES || !queue_config_valid(&cfg) {
+        return ERR_QUEUE_INVALID;
+    }
+    // SAFETY: Single-core kernel, called during boot before interrupts enabled.
+    unsafe {
+        (*QUEUES.get_mut())[q_id] = MsgQueue { config: Some(cfg), ..EMPTY_QUEUE };
+    }
+    0
+}
+
+/// Fill level of queue `q_id`, or None if it is not configured.
+pub fn queue_stats(q_id: usize) -> Option<QueueStats> {
+    if q_id >= MAX_QUEUES {
+        return None;
+    }
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    let q = unsafe { &(*QUEUES.get())[q_id] };
+    q.config.map(|_| q.stats())
+}
+
+/// Print `fill/depth (hw, dropped)` for every configured queue.
+pub fn print_queue_levels() {
+    for q_id in 0..MAX_QUEUES {
+        if let Some(s) = queue_stats(q_id) {
+            uart_print("[AegisOS] QUEUE ");
+            crate::uart_print_dec(q_id as u64);
+            uart_print(": ");
+            crate::uart_print_dec(s.fill as u64);
+            uart_print("/");
+            crate::uart_print_dec(s.depth as u64);
+            uart_print(" (hw ");
+            crate::uart_print_dec(s.high_water as u64);
+            uart_print(", dropped ");
+            crate::uart_print_dec(s.dropped);
+            uart_print(")\n");
+        }
+    }
+}
+
+// ─── Syscalls ──────────────────────────────────────────────────────
+
+/// True if `q_id` names a configured queue; otherwise x0 = ERR_QUEUE_INVALID.
+fn valid_queue(frame: &mut TrapFrame, q_id: usize) -> bool {
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    if q_id < MAX_QUEUES && unsafe { (*QUEUES.get())[q_id].config.is_some() } {
+        return true;
+    }
+    uart_print("!!! QUEUE: invalid queue\n");
+    frame.x[0] = ERR_QUEUE_INVALID;
+    false
+}
+
+/// sys_queue_send(frame, q_id): enqueue x0..x3 without blocking. A
+/// consumer still Blocked in sys_queue_recv gets the message directly.
+/// x0 = 0, or ERR_QUEUE_FULL (DropNewest); FaultSender faults the caller.
+pub fn sys_queue_send(frame: &mut TrapFrame, q_id: usize) {
+    if !valid_queue(frame, q_id) {
+        return;
+    }
+
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    // Accesses KernelCell QUEUES; calls sched functions that access TCBS/CURRENT.
+    unsafe {
+        let current = sched::current_task_id() as usize;
+        let q = &mut (*QUEUES.get_mut())[q_id];
+        let msg = [frame.x[0], frame.x[1], frame.x[2], frame.x[3]];
+
+        // A waiter that faulted or exited since it blocked is dropped, not revived
+        let waiter = q.receiver.take().filter(|&t| sched::get_task_state(t) == TaskState::Blocked);
+        if let Some(recv_task) = waiter {
+            // Consumer waiting on an empty queue — hand the message over
+            let words = q.config.map_or(0, |c| c.msg_words);
+            for (i, &word) in msg.iter().enumerate() {
+                sched::set_task_reg(recv_task, i, if i < words { word } else { 0 });
+            }
+            sched::set_task_reg(recv_task, ipc::MSG_REG_SENDER, current as u64);
+            sched::set_task_reg(recv_task, ipc::MSG_REG_BADGE, ipc::NO_BADGE);
+            ipc::wake_partner(recv_task);
+            frame.x[0] = 0;
+            return;
+        }
+
+        frame.x[0] = match q.enqueue(msg, current) {
+            Ok(()) | Err(OverflowPolicy::DropOldest) => 0,
+            Err(OverflowPolicy::DropNewest) => ERR_QUEUE_FULL,
+            Err(OverflowPolicy::FaultSender) => {
+                uart_print("!!! QUEUE: overflow — faulting sender\n");
+                print_queue_levels();
+                sched::fault_current_task(frame);
+                return;
+            }
+        };
+    }
+}
+
+/// sys_queue_recv(frame, q_id, timeout): dequeue the oldest message into
+/// x0..x3 with the sender id in x4. On an empty queue block for up to
+/// `timeout` ticks (ERR_IPC_TIMEOUT on expiry or poll).
+pub fn sys_queue_recv(frame: &mut TrapFrame, q_id: usize, timeout: u64) {
+    if !valid_queue(frame, q_id) {
+        return;
+    }
+
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    // Accesses KernelCell QUEUES; calls sched functions that access TCBS/CURRENT.
+    unsafe {
+        let current = sched::current_task_id() as usize;
+        let q = &mut (*QUEUES.get_mut())[q_id];
+
+        if let Some((msg, sender)) = q.dequeue() {
+            frame.x[..MSG_REGS].copy_from_slice(&msg);
+            frame.x[ipc::MSG_REG_SENDER] = sender as u64;
+            frame.x[ipc::MSG_REG_BADGE] = ipc::NO_BADGE;
+        } else if timeout == TIMEOUT_POLL {
+            frame.x[0] = ERR_IPC_TIMEOUT;
+        } else if q.receiver.is_some() {
+            uart_print("!!! QUEUE: receiver already waiting\n");
+            frame.x[0] = ERR_QUEUE_BUSY;
+        } else {
+            q.receiver = Some(current);
+            sched::save_frame(current, frame);
+            ipc::block_current(frame, current, timeout);
+        }
+    }
+}
+
+// ─── Timeouts and cleanup ──────────────────────────────────────────
+
+/// Timer expiry for a task blocked in SYS_QUEUE_RECV: release the slot and
+/// resume it with ERR_IPC_TIMEOUT. Returns false if `task_idx` was not
+/// waiting on any queue.
+pub fn expire_timeout(task_idx: usize) -> bool {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        for q in (*QUEUES.get_mut()).iter_mut() {
+            if q.receiver == Some(task_idx) {
+                q.receiver = None;
+                sched::set_task_reg(task_idx, 0, ERR_IPC_TIMEOUT);
+                sched::set_task_state(task_idx, TaskState::Ready);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// A capability naming queue `q_id` was revoked from `task`: if the task
+/// is blocked dequeuing from it, release the slot and resume it with
+/// `err` in x0. Returns false if it was not waiting there.
+pub fn revoke_waiter(task: usize, q_id: usize, err: u64) -> bool {
+    if q_id >= MAX_QUEUES {
+        return false;
+    }
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let q = &mut (*QUEUES.get_mut())[q_id];
+        if q.receiver != Some(task) {
+            return false;
+        }
+        q.receiver = None;
+    }
+    sched::set_task_reg(task, 0, err);
+    ipc::wake_partner(task);
+    true
+}
+
+/// Drop a faulted task's pending dequeue. Messages it already queued
+/// stay — they were complete when sent.
+pub fn cleanup_task(task_idx: usize) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        for q in (*QUEUES.get_mut()).iter_mut() {
+            if q.receiver == Some(task_idx) {
+                q.receiver = None;
+            }
+        }
+    }
+}
+
+// ─── Kani formal verification proofs ───────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Proof 1: Queue overflow policy keeps the ring bounded.
+    /// For any configuration, fill level and overflow policy, one enqueue:
+    /// - never exceeds the configured depth
+    /// - DropNewest / FaultSender leave a full queue unchanged
+    /// - DropOldest stores the new message as the newest entry
+    /// - every overflow is counted in `dropped`
+    #[kani::proof]
+    #[kani::unwind(10)]
+    fn queue_overflow_bounded() {
+        let depth: usize = kani::any();
+        kani::assume(depth >= 1 && depth <= QUEUE_MAX_DEPTH);
+        let policy = match kani::any::<u8>() % 3 {
+            0 => OverflowPolicy::DropNewest,
+            1 => OverflowPolicy::DropOldest,
+            _ => OverflowPolicy::FaultSender,
+        };
+        let mut q = MsgQueue {
+            config: Some(QueueConfig { depth, msg_words: MSG_REGS, policy }),
+            ..EMPTY_QUEUE
+        };
+        let count: usize = kani::any();
+        kani::assume(count <= depth);
+        let head: usize = kani::any();
+        kani::assume(head < depth);
+        q.count = count;
+        q.head = head;
+
+        let msg: [u64; MSG_REGS] = kani::any();
+        let before_count = q.count;
+        let before_head = q.head;
+        let result = q.enqueue(msg, 3);
+
+        assert!(q.count <= depth, "fill never exceeds depth");
+        if before_count < depth {
+            assert!(result.is_ok(), "room left → stored");
+            assert!(q.count == before_count + 1);
+            assert!(q.dropped == 0);
+        } else {
+            assert!(result == Err(policy), "overflow reports the policy");
+            assert!(q.dropped == 1, "overflow counted");
+            if policy == OverflowPolicy::DropOldest {
+                assert!(q.count == depth, "still full");
+                let newest = (q.head + q.count - 1) % depth;
+                assert!(q.msgs[newest] == msg, "new message kept");
+            } else {
+                assert!(q.count == before_count && q.head == before_head, "queue untouched");
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+/// AegisOS Sampling Ports — last-value-wins channels (ARINC 653 sampling ports)
+///
+/// A sampling port is a single message slot, configured once at boot with
+/// a message size (1–MSG_REGS words) and a refresh period in ticks. A write
+/// overwrites the slot and timestamps it; a read never consumes it. Neither
+/// side ever blocks. Every read returns the message with its age and a
+/// validity flag: a sample is valid while its age ≤ the refresh period —
+/// the consumer sees at once that its producer has gone quiet.
+///
+/// Read result registers:
+///   x0..x3 = message (x0 = ERR_PORT_EMPTY if never written)
+///   x4     = age in ticks since the last write
+///   x5     = SAMPLE_VALID if age ≤ refresh period, else 0
+///
+/// Ports live in the static SAMPLING_PORTS pool — no heap. Access is
+/// granted per port through capability bits, like IPC endpoints.
+///
+/// Syscalls:
+///   SYS_SAMPLE_WRITE = 20: overwrite port x6 with x0..x3
+///   SYS_SAMPLE_READ  = 21: read port x6 (message, age, validity)
+
+use crate::exception::TrapFrame;
+use crate::ipc::MSG_REGS;
+use crate::kernel::cell::KernelCell;
+use crate::sched;
+use crate::uart_print;
+
+// ─── Constants ─────────────────────────────────────────────────────
+
+#[allow(dead_code)]
+pub const SYS_SAMPLE_WRITE: u64 = 20;
+#[allow(dead_code)]
+pub const SYS_SAMPLE_READ: u64 = 21;
+
+/// Number of sampling ports in the static pool
+pub const MAX_SAMPLING_PORTS: usize = 2;
+
+/// x5 flag on read: sample is within the port's refresh period
+pub const SAMPLE_VALID: u64 = 1 << 0;
+
+/// Port id out of range or port not configured at boot.
+pub const ERR_PORT_INVALID: u64 = 0xFFFF_6001;
+/// Returned in x0 by a read before the first write.
+pub const ERR_PORT_EMPTY: u64 = 0xFFFF_6002;
+
+// ─── Port types ────────────────────────────────────────────────────
+
+/// Static configuration of one sampling port.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SamplingConfig {
+    /// Message size in 64-bit words (1..=MSG_REGS); extra words read as 0
+    pub msg_words: usize,
+    /// Max sample age in ticks that still counts as valid (≥ 1)
+    pub refresh_period: u64,
+}
+
+/// One last-value slot with its write timestamp.
+#[derive(Clone, Copy)]
+pub struct SamplingPort {
+    /// None = not configured (all syscalls fail with ERR_PORT_INVALID)
+    pub config: Option<SamplingConfig>,
+    pub msg: [u64; MSG_REGS],
+    /// Tick of the last write (None = never written)
+    pub written_at: Option<u64>,
+    /// Task that performed the last write
+    pub writer: usize,
+}
+
+pub const EMPTY_PORT: SamplingPort = SamplingPort {
+    config: None,
+    msg: [0; MSG_REGS],
+    written_at: None,
+    writer: 0,
+};
+
+/// Whether `cfg` fits the static pool (usable in const context).
+pub const fn sampling_config_valid(cfg: &SamplingConfig) -> bool {
+    cfg.msg_words >= 1 && cfg.msg_words <= MSG_REGS && cfg.refresh_period >= 1
+}
+
+/// Age in ticks of a sample written at `written_at`, seen at `now`.
+/// Saturates to 0 if `now` is somehow earlier (never underflows).
+pub const fn sample_age(now: u64, written_at: u64) -> u64 {
+    now.saturating_sub(written_at)
+}
+
+/// Validity flags for a sample of age `age` on a port refreshed every
+/// `refresh_period` ticks.
+pub const fn sample_validity(age: u64, refresh_period: u64) -> u64 {
+    if age <= refresh_period { SAMPLE_VALID } else { 0 }
+}
+
+// ─── Static port pool ──────────────────────────────────────────────
+
+pub static SAMPLING_PORTS: KernelCell<[SamplingPort; MAX_SAMPLING_PORTS]> =
+    KernelCell::new([EMPTY_PORT; MAX_SAMPLING_PORTS]);
+
+/// Configure sampling port `port_id` (boot-time). Returns 0, or
+/// ERR_PORT_INVALID if the id is out of range or `cfg` is invalid.
+pub fn sampling_configure(port_id: usize, cfg: SamplingConfig) -> u64 {
+    if port_id >= MAX_SAMPLING_PORTS || !sampling_config_valid(&cfg) {
+        return ERR_PORT_INVALID;
+    }
+    // SAFETY: Single-core kernel, called during boot before interrupts enabled.
+    unsafe {
+        (*SAMPLING_PORTS.get_mut())[port_id] = SamplingPort { config: Some(cfg), ..EMPTY_PORT };
+    }
+    0
+}
+
+// ─── Syscalls ──────────────────────────────────────────────────────
+
+/// Config of port `port_id`, or None (x0 = ERR_PORT_INVALID).
+fn port_config(frame: &mut TrapFrame, port_id: usize) -> Option<SamplingConfig> {
+    let cfg = if port_id < MAX_SAMPLING_PORTS {
+        // SAFETY: Single-core kernel, interrupts masked; read-only access.
+        unsafe { (*SAMPLING_PORTS.get())[port_id].config }
+    } else {
+        None
+    };
+    if cfg.is_none() {
+        uart_print("!!! SAMPLING: invalid port\n");
+        frame.x[0] = ERR_PORT_INVALID;
+    }
+    cfg
+}
+
+/// sys_sample_write(frame, port_id): overwrite the port with x0..x3
+/// (truncated to the configured size) and timestamp it. x0 = 0.
+pub fn sys_sample_write(frame: &mut TrapFrame, port_id: usize) {
+    let Some(cfg) = port_config(frame, port_id) else {
+        return;
+    };
+
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution.
+    // No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let port = &mut (*SAMPLING_PORTS.get_mut())[port_id];
+        for (i, word) in port.msg.iter_mut().enumerate() {
+            *word = if i < cfg.msg_words { frame.x[i] } else { 0 };
+        }
+        port.written_at = Some(crate::timer::tick_count());
+        port.writer = sched::current_task_id() as usize;
+    }
+    frame.x[0] = 0;
+}
+
+/// sys_sample_read(frame, port_id): copy the current sample into x0..x3,
+/// its age into x4 and SAMPLE_VALID (or 0) into x5. The slot is not
+/// consumed. Before the first write: x0 = ERR_PORT_EMPTY, x5 = 0.
+pub fn sys_sample_read(frame: &mut TrapFrame, port_id: usize) {
+    let Some(cfg) = port_config(frame, port_id) else {
+        return;
+    };
+
+    // SAFETY: Single-core kernel, interrupts masked; read-only access.
+    let port = unsafe { (*SAMPLING_PORTS.get())[port_id] };
+    let Some(written_at) = port.written_at else {
+        frame.x[0] = ERR_PORT_EMPTY;
+        frame.x[4] = 0;
+        frame.x[5] = 0;
+        return;
+    };
+    let age = sample_age(crate::timer::tick_count(), written_at);
+    frame.x[..MSG_REGS].copy_from_slice(&port.msg);
+    frame.x[4] = age;
+    frame.x[5] = sample_validity(age, cfg.refresh_period);
+}
+
+// ─── Kani formal verification proofs ───────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Proof 1: Sample age and validity are sound.
+    /// For all write/read ticks and refresh periods:
+    /// - age never underflows and equals the elapsed ticks when now ≥ write
+    /// - a sample is reported valid iff its age ≤ the refresh period
+    /// - validity is monotone: once stale, a later read is never valid
+    #[kani::proof]
+    fn sampling_age_validity_sound() {
+        let written_at: u64 = kani::any();
+        let now: u64 = kani::any();
+        let later: u64 = kani::any();
+        let period: u64 = kani::any();
+        kani::assume(period >= 1);
+        kani::assume(later >= now);
+
+        let age = sample_age(now, written_at);
+        if now >= written_at {
+            assert!(age == now - written_at, "age = elapsed ticks");
+        } else {
+            assert!(age == 0, "clock skew saturates to 0");
+        }
+
+        let valid = sample_validity(age, period) & SAMPLE_VALID != 0;
+        assert!(valid == (age <= period), "valid iff within refresh period");
+
+        let later_valid = sample_validity(sample_age(later, written_at), period) & SAMPLE_VALID != 0;
+        if !valid {
+            assert!(!later_valid, "stale samples never become valid again");
+        }
+    }
+}
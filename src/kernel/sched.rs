@@ -3,14 +3,20 @@
 ///
 /// Tasks run at EL0 (user mode). Each task has:
 ///   - A TrapFrame (saved/restored on context switch)
-///   - Its own 4KB kernel stack (SP_EL1, in .task_stacks section)
-///   - Its own 4KB user stack (SP_EL0, in .user_stacks section)
-///   - A state (Ready, Running, Inactive)
+///   - Its own 4KB user stack (SP_EL0, in .user_stacks section), on an
+///     unmapped guard page, so an overflow faults instead of running into
+///     the neighbouring task's stack
+///   - A reserved 4KB kernel stack (.task_stacks). Exception entry runs on
+///     the shared boot stack (__stack_end, above __stack_guard) instead
+///   - A state (Ready, Running, Blocked, Sleeping, Faulted, Exited, Inactive)
 ///   - A priority (0 = lowest, 7 = highest)
 ///   - A time budget per epoch (0 = unlimited)
 ///   - A watchdog heartbeat interval (0 = disabled)
+///   - A partition (ARINC 653-style time window assignment)
+///   - Optional periodic timing (period, relative deadline, WCET) for EDF
 ///
-/// Context switch: timer IRQ → save frame → pick highest-priority Ready → switch SP_EL1 → load frame → eret to EL0
+/// Context switch: timer IRQ → save frame → pick highest-priority Ready
+/// task in the active partition window → load frame → eret to EL0
 
 use crate::cap::CapBits;
 use crate::exception::TrapFrame;
@@ -27,6 +33,7 @@ pub enum TaskState {
     Blocked  = 3,
     Faulted  = 4,
     Exited   = 5,
+    Sleeping = 6, // waiting in the timer queue (SYS_SLEEP / SYS_SLEEP_UNTIL)
 }
 
 // ─── Task Control Block ────────────────────────────────────────────
@@ -37,14 +44,17 @@ pub struct Tcb {
     pub context: TrapFrame,
     pub state: TaskState,
     pub id: u16,
-    pub stack_top: u64,       // top of this task's kernel stack (SP_EL1)
+    pub stack_top: u64,       // top of this task's reserved kernel stack (unused: see module doc)
     pub entry_point: u64,     // original entry address (for restart)
     pub user_stack_top: u64,  // original SP_EL0 top (for restart)
     pub fault_tick: u64,      // tick when task was marked Faulted
     pub caps: CapBits,        // capability bitmask (survives restart)
+    pub badges: [u64; crate::ipc::MAX_ENDPOINTS], // badge per endpoint send right (survives restart)
     pub ttbr0: u64,           // TTBR0_EL1 value (ASID << 48 | L1 base)
     pub notify_pending: u64,  // bitmask of pending notification bits
     pub notify_waiting: bool, // true if task is blocked in wait_notify
+    pub notify_mask: u64,     // bits that end a notification wait (NOTIFY_ALL for wait_notify)
+    pub wait_any: bool,       // true while blocked in SYS_RECV_ANY (wait set)
     // ─── Phase K fields ────────────────────────────────────────────
     pub priority: u8,            // current effective priority (0=lowest, 7=highest)
     pub base_priority: u8,       // original priority (before inheritance)
@@ -52,12 +62,23 @@ pub struct Tcb {
     pub ticks_used: u64,         // ticks consumed in current epoch
     pub heartbeat_interval: u64, // max ticks between heartbeats (0 = disabled)
     pub last_heartbeat: u64,     // TICK_COUNT at last heartbeat
+    pub partition: u8,           // partition id (runs only inside its windows)
+    pub mem_quota: usize,        // anonymous frames the task may hold (SYS_MAP_ANON)
+    pub elf_slot: Option<usize>, // ELF load slot (None = kernel-linked entry)
+    // ─── Periodic task model (EDF) ─────────────────────────────────
+    pub timing: PeriodicParams,  // period/deadline/WCET (period 0 = aperiodic)
+    pub next_release: u64,       // tick of the next job release
+    pub abs_deadline: u64,       // absolute deadline of the current job
+    pub job_active: bool,        // true between job release and completion
+    // ─── IPC reply object ──────────────────────────────────────────
+    pub reply_to: Option<usize>, // one-shot reply cap: caller awaiting our SYS_REPLY
+    pub awaiting_reply: bool,    // true while blocked in SYS_CALL until answered
 }
 
 // ─── Static task table ─────────────────────────────────────────────
 
-/// Task count: 0 = uart_driver, 1 = client, 2..6 = reserved, 7 = idle
-pub const NUM_TASKS: usize = 8;
+/// Task count (system.toml `num_tasks`); the last slot is always idle
+pub const NUM_TASKS: usize = crate::sysconf::NUM_TASKS;
 
 /// Index of the idle task (always the last task slot).
 pub const IDLE_TASK_ID: usize = NUM_TASKS - 1;
@@ -98,61 +119,698 @@ pub const EMPTY_TCB: Tcb = Tcb {
     user_stack_top: 0,
     fault_tick: 0,
     caps: 0,
+    badges: crate::ipc::NO_BADGES,
     ttbr0: 0,
     notify_pending: 0,
     notify_waiting: false,
+    notify_mask: crate::ipc::NOTIFY_ALL,
+    wait_any: false,
     priority: 0,
     base_priority: 0,
     time_budget: 0,
     ticks_used: 0,
     heartbeat_interval: 0,
     last_heartbeat: 0,
+    partition: 0,
+    mem_quota: 0,
+    elf_slot: None,
+    timing: APERIODIC,
+    next_release: 0,
+    abs_deadline: 0,
+    job_active: false,
+    reply_to: None,
+    awaiting_reply: false,
 };
 
 // ─── Task metadata (Phase N) ───────────────────────────────────────
 
-/// Static per-task boot configuration: capabilities, priority, budget.
+/// Static per-task boot configuration: capabilities, badges, priority, budget.
 /// Defined as a const array in main.rs; applied in kernel_main() loop.
 pub struct TaskMetadata {
     pub caps: CapBits,
+    pub badges: [u64; crate::ipc::MAX_ENDPOINTS],
+    pub cspace: crate::cspace::CSpace,
     pub priority: u8,
     pub time_budget: u64,
     pub heartbeat_interval: u64,
+    pub partition: u8,
+    pub mem_quota: usize,
+    pub timing: PeriodicParams,
 }
 
-// ─── Public API ────────────────────────────────────────────────────
+// ─── Partition schedule (ARINC 653-style) ──────────────────────────
 
-/// Initialize scheduler: set up TCBs for NUM_TASKS tasks.
-/// `entries[i]` = entry point address for task i.
-/// Must be called before enabling timer interrupts.
+/// One window of the major frame: `partition` owns the CPU for ticks
+/// `[offset, offset + duration)` of every major frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PartitionWindow {
+    pub partition: u8,
+    pub offset: u64,
+    pub duration: u64,
+}
+
+/// Static major-frame schedule: ordered, non-overlapping windows that
+/// repeat every `major_frame` ticks. Ticks not covered by any window
+/// belong to no partition — only idle runs there.
+#[derive(Clone, Copy, Debug)]
+pub struct PartitionSchedule {
+    pub major_frame: u64,
+    pub windows: &'static [PartitionWindow],
+}
+
+/// Maximum windows per major frame (bounds the lookup loop).
+pub const MAX_PARTITION_WINDOWS: usize = 8;
+
+/// Default schedule: a single window for partition 0 spanning the whole
+/// frame. All tasks default to partition 0, so this is plain priority
+/// scheduling — identical to the behaviour before partitioning.
+pub const DEFAULT_PARTITION_SCHEDULE: PartitionSchedule = PartitionSchedule {
+    major_frame: EPOCH_LENGTH,
+    windows: &[PartitionWindow { partition: 0, offset: 0, duration: EPOCH_LENGTH }],
+};
+
+/// Active major-frame schedule. Installed once at boot via
+/// `set_partition_schedule()`; read by `schedule()` on every decision.
+pub static PARTITION_SCHEDULE: KernelCell<PartitionSchedule> =
+    KernelCell::new(DEFAULT_PARTITION_SCHEDULE);
+
+/// Check a window table: non-empty, at most MAX_PARTITION_WINDOWS,
+/// every window has a non-zero duration, windows are sorted by offset,
+/// do not overlap, and end within the major frame.
+/// `const fn` so boot tables can be checked with `const _: () = assert!(..)`.
+pub const fn partition_schedule_valid(windows: &[PartitionWindow], major_frame: u64) -> bool {
+    if major_frame == 0 || windows.is_empty() || windows.len() > MAX_PARTITION_WINDOWS {
+        return false;
+    }
+    let mut end: u64 = 0;
+    let mut i: usize = 0;
+    while i < windows.len() {
+        let w = windows[i];
+        if w.duration == 0 || w.offset < end {
+            return false;
+        }
+        end = match w.offset.checked_add(w.duration) {
+            Some(e) => e,
+            None => return false,
+        };
+        if end > major_frame {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Pure window lookup: which partition owns tick `now`?
+/// Returns `None` if `now` falls in a gap between windows (idle time).
+pub fn partition_at(windows: &[PartitionWindow], major_frame: u64, now: u64) -> Option<u8> {
+    if major_frame == 0 {
+        return None;
+    }
+    let offset = now % major_frame;
+    let mut i: usize = 0;
+    while i < windows.len() && i < MAX_PARTITION_WINDOWS {
+        let w = windows[i];
+        if offset >= w.offset && offset - w.offset < w.duration {
+            return Some(w.partition);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Pure: ticks from `now` until the active partition may change — the next
+/// window start or end, or the end of the major frame. `u64::MAX` if the
+/// frame length is 0. Always >= 1 otherwise.
+pub fn window_boundary_delta(windows: &[PartitionWindow], major_frame: u64, now: u64) -> u64 {
+    if major_frame == 0 {
+        return u64::MAX;
+    }
+    let offset = now % major_frame;
+    let mut delta = major_frame - offset;
+    let mut i: usize = 0;
+    while i < windows.len() && i < MAX_PARTITION_WINDOWS {
+        let w = windows[i];
+        if w.offset > offset && w.offset - offset < delta {
+            delta = w.offset - offset;
+        }
+        let end = w.offset.saturating_add(w.duration);
+        if end > offset && end - offset < delta {
+            delta = end - offset;
+        }
+        i += 1;
+    }
+    delta
+}
+
+/// Install the major-frame schedule. Returns false (and keeps the current
+/// schedule) if the table fails `partition_schedule_valid()`.
+pub fn set_partition_schedule(schedule: PartitionSchedule) -> bool {
+    if !partition_schedule_valid(schedule.windows, schedule.major_frame) {
+        return false;
+    }
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe { *PARTITION_SCHEDULE.get_mut() = schedule; }
+    true
+}
+
+/// Partition owning the current tick, or `None` during a gap.
+pub fn active_partition() -> Option<u8> {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    let schedule = unsafe { *PARTITION_SCHEDULE.get() };
+    partition_at(schedule.windows, schedule.major_frame, crate::timer::tick_count())
+}
+
+// ─── Periodic task model + EDF class ───────────────────────────────
+
+/// Periodic timing parameters, all in ticks. `period == 0` marks an
+/// aperiodic task, which ignores the other two fields.
+/// Constrained-deadline model: 0 < wcet <= deadline <= period.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PeriodicParams {
+    pub period: u64,
+    pub deadline: u64,
+    pub wcet: u64,
+}
+
+/// Timing for tasks that are not periodic.
+pub const APERIODIC: PeriodicParams = PeriodicParams { period: 0, deadline: 0, wcet: 0 };
+
+/// Which scheduling class `schedule()` runs.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SchedPolicy {
+    /// Highest priority first (Phase K behaviour). Periodic tasks still
+    /// get job release and deadline monitoring, but compete by priority.
+    FixedPriority,
+    /// Earliest absolute deadline first among periodic tasks with a
+    /// released job; aperiodic tasks run by priority in the slack.
+    Edf,
+}
+
+/// Active scheduling class. Set once at boot, after admission control.
+pub static SCHED_POLICY: KernelCell<SchedPolicy> = KernelCell::new(SchedPolicy::FixedPriority);
+
+/// Longest hyperperiod admission control will analyse (ticks).
+/// Task sets with a larger LCM of periods are rejected.
+pub const EDF_MAX_HYPERPERIOD: u64 = 100_000;
+
+/// Why a periodic task set was rejected at boot.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AdmissionError {
+    /// A periodic task violates 0 < wcet <= deadline <= period, or the
+    /// partition table does not match the task set / is invalid.
+    InvalidParams,
+    /// LCM of periods (or of it and the major frame) exceeds
+    /// EDF_MAX_HYPERPERIOD (or overflows).
+    HyperperiodTooLong,
+    /// A partition's utilisation sum(wcet / period) exceeds its share of
+    /// the major frame.
+    Utilisation,
+    /// Processor demand exceeds the partition's guaranteed supply at some deadline.
+    DemandBound,
+}
+
+const fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// Processor demand bound: total WCET of all jobs that are both released
+/// and due within [0, t] (synchronous release at tick 0).
+pub const fn demand_bound(tasks: &[PeriodicParams], t: u64) -> u64 {
+    partition_demand(tasks, &[], 0, t)
+}
+
+/// demand_bound() restricted to the tasks of `partition` (`partitions[i]`
+/// is task i's partition). An empty `partitions` slice selects every task.
+pub const fn partition_demand(tasks: &[PeriodicParams], partitions: &[u8], partition: u8, t: u64) -> u64 {
+    let mut demand: u64 = 0;
+    let mut i: usize = 0;
+    while i < tasks.len() {
+        let p = tasks[i];
+        let member = partitions.is_empty() || (i < partitions.len() && partitions[i] == partition);
+        if member && p.period > 0 && t >= p.deadline {
+            demand = demand.saturating_add(((t - p.deadline) / p.period + 1).saturating_mul(p.wcet));
+        }
+        i += 1;
+    }
+    demand
+}
+
+/// Ticks `partition`'s windows supply in `[start, start + len)`, for
+/// `start < major_frame` and `len <= major_frame` (wraps once).
+const fn window_supply(windows: &[PartitionWindow], major_frame: u64, partition: u8, start: u64, len: u64) -> u64 {
+    let end = start + len;
+    let mut supply: u64 = 0;
+    let mut i: usize = 0;
+    while i < windows.len() {
+        let w = windows[i];
+        if w.partition == partition {
+            // The window in this frame and in the next one
+            let mut shift: u64 = 0;
+            while shift <= major_frame {
+                let lo = if w.offset + shift > start { w.offset + shift } else { start };
+                let hi = if w.offset + w.duration + shift < end { w.offset + w.duration + shift } else { end };
+                if hi > lo {
+                    supply += hi - lo;
+                }
+                shift += major_frame;
+            }
+        }
+        i += 1;
+    }
+    supply
+}
+
+/// Supply bound: the fewest ticks `partition` is guaranteed in any
+/// interval of length `t`, wherever the interval starts in the major
+/// frame. The worst start is always the end of one of its windows.
+pub const fn supply_bound(windows: &[PartitionWindow], major_frame: u64, partition: u8, t: u64) -> u64 {
+    if major_frame == 0 {
+        return 0;
+    }
+    let per_frame = window_supply(windows, major_frame, partition, 0, major_frame);
+    let mut worst = per_frame;
+    let mut i: usize = 0;
+    while i < windows.len() {
+        let w = windows[i];
+        if w.partition == partition {
+            let start = (w.offset + w.duration) % major_frame;
+            let supply = window_supply(windows, major_frame, partition, start, t % major_frame);
+            if supply < worst {
+                worst = supply;
+            }
+        }
+        i += 1;
+    }
+    (t / major_frame).saturating_mul(per_frame).saturating_add(worst)
+}
+
+/// EDF admission control for constrained-deadline periodic tasks, each
+/// confined to its partition's windows (`partitions[i]` is task i's
+/// partition; `windows` / `major_frame` the partition schedule). Every
+/// partition with periodic tasks is checked on its own:
+///
+/// 1. Parameter sanity: 0 < wcet <= deadline <= period.
+/// 2. Utilisation: sum(wcet_i / period_i) <= S / F, where S is the
+///    partition's window time per major frame F — checked exactly over
+///    the hyperperiod H as sum(wcet_i * H / period_i) * F <= S * H.
+/// 3. Demand bound: dbf(t) <= sbf(t) (supply_bound) at every absolute
+///    deadline t <= L + max(D), where L = lcm(H, F).
+///
+/// A task set that owns the whole CPU passes a single full-frame window.
+pub const fn edf_admission(
+    tasks: &[PeriodicParams],
+    partitions: &[u8],
+    windows: &[PartitionWindow],
+    major_frame: u64,
+) -> Result<(), AdmissionError> {
+    if partitions.len() != tasks.len() || !partition_schedule_valid(windows, major_frame) {
+        return Err(AdmissionError::InvalidParams);
+    }
+
+    // 1. Parameters + hyperperiod
+    let mut hyper: u64 = 1;
+    let mut max_deadline: u64 = 0;
+    let mut any = false;
+    let mut i: usize = 0;
+    while i < tasks.len() {
+        let p = tasks[i];
+        if p.period > 0 {
+            if p.wcet == 0 || p.wcet > p.deadline || p.deadline > p.period {
+                return Err(AdmissionError::InvalidParams);
+            }
+            hyper = match (hyper / gcd(hyper, p.period)).checked_mul(p.period) {
+                Some(h) if h <= EDF_MAX_HYPERPERIOD => h,
+                _ => return Err(AdmissionError::HyperperiodTooLong),
+            };
+            if p.deadline > max_deadline {
+                max_deadline = p.deadline;
+            }
+            any = true;
+        }
+        i += 1;
+    }
+    if !any {
+        return Ok(());
+    }
+    // Demand repeats every H, supply every F: both together every L
+    let horizon = match (hyper / gcd(hyper, major_frame)).checked_mul(major_frame) {
+        Some(l) if l <= EDF_MAX_HYPERPERIOD => l + max_deadline,
+        _ => return Err(AdmissionError::HyperperiodTooLong),
+    };
+
+    let mut first: usize = 0;
+    while first < tasks.len() {
+        let part = partitions[first];
+        // Check each partition once, at its first periodic task
+        let mut seen = tasks[first].period == 0;
+        let mut j: usize = 0;
+        while j < first && !seen {
+            seen = tasks[j].period > 0 && partitions[j] == part;
+            j += 1;
+        }
+        if !seen {
+            // 2. Utilisation (exact, integer-only). Each term <= hyper since wcet <= period.
+            let mut work: u64 = 0;
+            i = 0;
+            while i < tasks.len() {
+                let p = tasks[i];
+                if partitions[i] == part {
+                    if let Some(n) = hyper.checked_div(p.period) {
+                        work += p.wcet * n;
+                    }
+                }
+                i += 1;
+            }
+            let share = window_supply(windows, major_frame, part, 0, major_frame);
+            if work as u128 * major_frame as u128 > share as u128 * hyper as u128 {
+                return Err(AdmissionError::Utilisation);
+            }
+
+            // 3. Demand bound at every absolute deadline in (0, L + max D]
+            i = 0;
+            while i < tasks.len() {
+                let p = tasks[i];
+                if p.period > 0 && partitions[i] == part {
+                    let mut t = p.deadline;
+                    while t <= horizon {
+                        if partition_demand(tasks, partitions, part, t)
+                            > supply_bound(windows, major_frame, part, t)
+                        {
+                            return Err(AdmissionError::DemandBound);
+                        }
+                        t += p.period;
+                    }
+                }
+                i += 1;
+            }
+        }
+        first += 1;
+    }
+    Ok(())
+}
+
+/// Pure deadline check: has an active job reached its absolute deadline?
+/// Mirrors the inner check in deadline_scan().
+pub fn deadline_missed(job_active: bool, abs_deadline: u64, now: u64) -> bool {
+    job_active && now >= abs_deadline
+}
+
+/// Set the scheduling class (boot-time, after `edf_admission()` passes).
+pub fn set_sched_policy(policy: SchedPolicy) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe { *SCHED_POLICY.get_mut() = policy; }
+}
+
+/// Release a new job of a periodic task at tick `release`.
+///
+/// SAFETY: Caller must ensure single-core kernel context with interrupts masked.
+unsafe fn start_job(task_idx: usize, release: u64) {
+    // SAFETY: Single-core kernel, interrupts masked. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let t = (*TCBS.get_mut())[task_idx].timing;
+        (*TCBS.get_mut())[task_idx].job_active = true;
+        (*TCBS.get_mut())[task_idx].abs_deadline = release + t.deadline;
+        (*TCBS.get_mut())[task_idx].next_release = release + t.period;
+    }
+}
+
+/// Release jobs for every periodic task whose next release time has come.
+/// Called from timer tick_handler (after deadline_scan) and once at boot.
+pub fn release_jobs() {
+    let now = crate::timer::tick_count();
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        for i in 0..NUM_TASKS {
+            let state = (*TCBS.get_mut())[i].state;
+            if (*TCBS.get_mut())[i].timing.period == 0
+                || state == TaskState::Inactive
+                || state == TaskState::Faulted
+                || state == TaskState::Exited
+            {
+                continue;
+            }
+            if now >= (*TCBS.get_mut())[i].next_release {
+                let release = (*TCBS.get_mut())[i].next_release;
+                start_job(i, release);
+            }
+        }
+    }
+}
+
+/// Scan periodic tasks for deadline misses. A task whose current job is
+/// still active at its absolute deadline is marked Faulted, exactly like
+/// a watchdog violation (it auto-restarts after RESTART_DELAY_TICKS).
+pub fn deadline_scan() {
+    let now = crate::timer::tick_count();
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        for i in 0..NUM_TASKS {
+            if (*TCBS.get_mut())[i].timing.period == 0 {
+                continue;
+            }
+            let state = (*TCBS.get_mut())[i].state;
+            if state == TaskState::Faulted
+                || state == TaskState::Inactive
+                || state == TaskState::Exited
+            {
+                continue;
+            }
+            if deadline_missed(
+                (*TCBS.get_mut())[i].job_active,
+                (*TCBS.get_mut())[i].abs_deadline,
+                now,
+            ) {
+                #[cfg(target_arch = "aarch64")]
+                {
+                    uart_print("[AegisOS] DEADLINE: task ");
+                    crate::uart_print_hex((*TCBS.get_mut())[i].id as u64);
+                    uart_print(" missed deadline\n");
+                }
+                (*TCBS.get_mut())[i].job_active = false;
+                (*TCBS.get_mut())[i].state = TaskState::Faulted;
+                (*TCBS.get_mut())[i].fault_tick = now;
+                cleanup_task_resources(i);
+            }
+        }
+    }
+}
+
+/// Mark the current job of a periodic task as complete. The task stays
+/// ineligible until its next release. No-op for aperiodic tasks.
+pub fn complete_job(task_idx: usize) {
+    if task_idx < NUM_TASKS {
+        // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+        unsafe {
+            if (*TCBS.get_mut())[task_idx].timing.period > 0 {
+                (*TCBS.get_mut())[task_idx].job_active = false;
+            }
+        }
+    }
+}
+
+/// Handle SYS_YIELD: a periodic task yielding signals the end of its
+/// current job; then reschedule.
+pub fn sys_yield(frame: &mut TrapFrame) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    let current = unsafe { *CURRENT.get() };
+    complete_job(current);
+    schedule(frame);
+}
+
+/// Handle SYS_SLEEP_UNTIL: block the current task until absolute tick
+/// `deadline`. Returns (in x0) the tick count at wake-up. A deadline that
+/// is not in the future returns immediately without blocking — so
+/// `sleep_until(0)` reads the current time and periodic tasks that overrun
+/// a release do not drift. Sleeping completes the current periodic job.
+pub fn sys_sleep_until(frame: &mut TrapFrame, deadline: u64) {
+    let now = crate::timer::tick_count();
+    if deadline <= now {
+        frame.x[0] = now;
+        return;
+    }
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let current = *CURRENT.get();
+        if !(*crate::timer::SLEEP_QUEUE.get_mut()).insert(current, deadline) {
+            frame.x[0] = now;
+            return;
+        }
+        complete_job(current);
+        (*TCBS.get_mut())[current].state = TaskState::Sleeping;
+    }
+    schedule(frame);
+}
+
+/// Handle SYS_SLEEP: block the current task for `ticks` ticks.
+/// `ticks == 0` returns the current tick count immediately.
+pub fn sys_sleep(frame: &mut TrapFrame, ticks: u64) {
+    let deadline = crate::timer::tick_count().saturating_add(ticks);
+    sys_sleep_until(frame, deadline);
+}
+
+// ─── Tickless idle support ─────────────────────────────────────────
+
+/// Ticks from now until the next event that needs the kernel: epoch
+/// boundary, watchdog scan (if any task is monitored), faulted-task
+/// restart, partition window change, periodic job release or deadline,
+/// sleeping-task wake-up, or budget expiry of the current task. Used by the timer to program a
+/// single one-shot interrupt while idle runs.
+/// Result is clamped to [1, MAX_TICKLESS_TICKS].
+pub fn ticks_until_next_event() -> u64 {
+    let now = crate::timer::tick_count();
+    let mut next = crate::timer::MAX_TICKLESS_TICKS;
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        // Epoch boundary (budget replenishment)
+        next = next.min(EPOCH_LENGTH.saturating_sub(*EPOCH_TICKS.get()));
+
+        // Partition window change
+        let schedule = *PARTITION_SCHEDULE.get();
+        next = next.min(window_boundary_delta(schedule.windows, schedule.major_frame, now));
+
+        // Budget expiry of the running task
+        let current = *CURRENT.get();
+        let budget = (*TCBS.get_mut())[current].time_budget;
+        if budget > 0 {
+            next = next.min(budget.saturating_sub((*TCBS.get_mut())[current].ticks_used));
+        }
+
+        // Earliest sleeping task wake-up
+        if let Some(e) = (*crate::timer::SLEEP_QUEUE.get()).peek() {
+            next = next.min(e.deadline.saturating_sub(now));
+        }
+
+        let mut watchdog = false;
+        for i in 0..NUM_TASKS {
+            let t = &(*TCBS.get_mut())[i];
+            match t.state {
+                TaskState::Inactive | TaskState::Exited => continue,
+                TaskState::Faulted => {
+                    next = next.min((t.fault_tick + RESTART_DELAY_TICKS).saturating_sub(now));
+                    continue;
+                }
+                _ => {}
+            }
+            if t.heartbeat_interval > 0 {
+                watchdog = true;
+            }
+            if t.timing.period > 0 {
+                next = next.min(t.next_release.saturating_sub(now));
+                if t.job_active {
+                    next = next.min(t.abs_deadline.saturating_sub(now));
+                }
+            }
+        }
+
+        // Watchdog scan — only needed while some task is monitored
+        if watchdog {
+            next = next.min(WATCHDOG_SCAN_PERIOD - now % WATCHDOG_SCAN_PERIOD);
+        }
+    }
+    next.max(1)
+}
+
+// ─── Stack layout ──────────────────────────────────────────────────
+
+/// Bytes of one task stack (kernel or user)
+pub const STACK_SIZE: u64 = 4096;
+
+/// Bytes per task in .user_stacks: an unmapped guard page, then the
+/// stack growing down towards it (linker.ld)
+pub const STACK_SLOT_SIZE: u64 = 2 * STACK_SIZE;
+
+/// The two per-task stack regions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StackKind {
+    /// .task_stacks — reserved, contiguous (no guard pages)
+    Kernel,
+    /// .user_stacks — SP_EL0
+    User,
+}
+
+/// Top of `task`'s user stack in the region starting at `region_start`.
+pub const fn stack_top(region_start: u64, task: usize) -> u64 {
+    region_start + (task as u64 + 1) * STACK_SLOT_SIZE
+}
+
+/// Task whose guard page holds `addr`, in the stack region starting at
+/// `region_start`. Returns None outside the guard pages.
+pub const fn guard_page_owner(region_start: u64, addr: u64) -> Option<usize> {
+    if addr < region_start {
+        return None;
+    }
+    let offset = addr - region_start;
+    let task = (offset / STACK_SLOT_SIZE) as usize;
+    if task >= NUM_TASKS || offset % STACK_SLOT_SIZE >= STACK_SIZE {
+        return None;
+    }
+    Some(task)
+}
+
+/// Start of a stack region (linker.ld `__task_stacks_start` / `__user_stacks_start`).
 #[cfg(target_arch = "aarch64")]
-pub fn init(entries: &[u64; NUM_TASKS]) {
+pub fn stack_region_base(kind: StackKind) -> u64 {
     extern "C" {
         static __task_stacks_start: u8;  // kernel stacks (SP_EL1)
         static __user_stacks_start: u8;  // user stacks (SP_EL0)
     }
+    // SAFETY: Linker-provided symbols, address taken for stack calculation.
+    unsafe {
+        match kind {
+            StackKind::Kernel => &__task_stacks_start as *const u8 as u64,
+            StackKind::User => &__user_stacks_start as *const u8 as u64,
+        }
+    }
+}
+
+/// Host-test stub: fake, non-overlapping stack regions within the first 2MiB.
+#[cfg(not(target_arch = "aarch64"))]
+pub fn stack_region_base(kind: StackKind) -> u64 {
+    match kind {
+        StackKind::Kernel => 0x400B_0000,
+        StackKind::User => 0x400C_0000,
+    }
+}
 
-    // SAFETY: Linker-provided symbol, address taken for stack calculation.
-    let kstacks_base = unsafe { &__task_stacks_start as *const u8 as u64 };
-    // SAFETY: Linker-provided symbol, address taken for stack calculation.
-    let ustacks_base = unsafe { &__user_stacks_start as *const u8 as u64 };
+/// Which task's user-stack guard page, if any, a faulting address hit.
+/// An EL1 overflow hits the boot stack's __stack_guard instead.
+pub fn stack_guard_hit(addr: u64) -> Option<usize> {
+    guard_page_owner(stack_region_base(StackKind::User), addr)
+}
+
+// ─── Public API ────────────────────────────────────────────────────
+
+/// Initialize scheduler: set up TCBs for NUM_TASKS tasks.
+/// `entries[i]` = entry point address for task i.
+/// Must be called before enabling timer interrupts.
+#[cfg(target_arch = "aarch64")]
+pub fn init(entries: &[u64; NUM_TASKS]) {
+    let kstacks_base = stack_region_base(StackKind::Kernel);
+    let ustacks_base = stack_region_base(StackKind::User);
 
-    // Each stack is 4KB. Stack grows downward, so top = base + (i+1)*4096
+    // Each user stack is 4KB above its guard page. Stack grows downward,
+    // so top = base + (i+1)*STACK_SLOT_SIZE; kernel stacks are contiguous
     // SPSR = 0x000 = EL0t: eret drops to EL0, uses SP_EL0
-    // When exception from EL0 → EL1, CPU automatically uses SP_EL1
+    // When exception from EL0 → EL1, SAVE_CONTEXT_LOWER moves SP to __stack_end
     // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
     unsafe {
         for i in 0..NUM_TASKS {
             (*TCBS.get_mut())[i].id = i as u16;
-            (*TCBS.get_mut())[i].stack_top = kstacks_base + (i as u64 + 1) * 4096;
-            (*TCBS.get_mut())[i].user_stack_top = ustacks_base + (i as u64 + 1) * 4096;
+            (*TCBS.get_mut())[i].stack_top = kstacks_base + (i as u64 + 1) * STACK_SIZE;
+            (*TCBS.get_mut())[i].user_stack_top = stack_top(ustacks_base, i);
             if entries[i] != 0 {
                 // Active task: set entry point and mark Ready
                 (*TCBS.get_mut())[i].state = TaskState::Ready;
                 (*TCBS.get_mut())[i].entry_point = entries[i];
                 (*TCBS.get_mut())[i].context.elr_el1 = entries[i];
                 (*TCBS.get_mut())[i].context.spsr_el1 = 0x000; // EL0t
-                (*TCBS.get_mut())[i].context.sp_el0 = ustacks_base + (i as u64 + 1) * 4096;
+                (*TCBS.get_mut())[i].context.sp_el0 = stack_top(ustacks_base, i);
             }
             // else: stays Inactive (from EMPTY_TCB)
         }
@@ -202,10 +860,18 @@ pub fn schedule(frame: &mut TrapFrame) {
             }
         }
 
+        // Partition window: only tasks of the active partition may run.
+        let active = active_partition();
+
         // Phase K: Priority-based selection with budget check.
         // Scan all tasks, pick the Ready task with highest priority
         // that still has budget remaining. Round-robin tiebreaker.
+        // EDF class: periodic tasks with a released job are picked by
+        // earliest absolute deadline first; priority only fills the slack.
+        let edf = *SCHED_POLICY.get() == SchedPolicy::Edf;
         let mut best_prio: i16 = -1;
+        let mut best_deadline = u64::MAX;
+        let mut found_edf = false;
         let mut next = IDLE_TASK_ID; // default to idle
         let mut found = false;
         for offset in 0..NUM_TASKS {
@@ -214,7 +880,20 @@ pub fn schedule(frame: &mut TrapFrame) {
                 // Check time budget (0 = unlimited)
                 let budget_ok = (*TCBS.get_mut())[idx].time_budget == 0
                     || (*TCBS.get_mut())[idx].ticks_used < (*TCBS.get_mut())[idx].time_budget;
-                if budget_ok && ((*TCBS.get_mut())[idx].priority as i16) > best_prio {
+                let in_window = active == Some((*TCBS.get_mut())[idx].partition);
+                // Periodic tasks wait for their next release between jobs
+                let periodic = (*TCBS.get_mut())[idx].timing.period > 0;
+                if !budget_ok || !in_window || (periodic && !(*TCBS.get_mut())[idx].job_active) {
+                    continue;
+                }
+                if edf && periodic {
+                    if (*TCBS.get_mut())[idx].abs_deadline < best_deadline {
+                        best_deadline = (*TCBS.get_mut())[idx].abs_deadline;
+                        next = idx;
+                        found_edf = true;
+                        found = true;
+                    }
+                } else if !found_edf && ((*TCBS.get_mut())[idx].priority as i16) > best_prio {
                     best_prio = (*TCBS.get_mut())[idx].priority as i16;
                     next = idx;
                     found = true;
@@ -223,7 +902,7 @@ pub fn schedule(frame: &mut TrapFrame) {
         }
 
         if !found {
-            // No ready task with budget — force idle
+            // No ready task with budget in this window — force idle
             next = IDLE_TASK_ID;
             if (*TCBS.get_mut())[IDLE_TASK_ID].state == TaskState::Faulted {
                 restart_task(IDLE_TASK_ID);
@@ -270,6 +949,16 @@ pub fn set_task_state(task_idx: usize, state: TaskState) {
     }
 }
 
+/// Get task state (Inactive for an out-of-range index)
+pub fn get_task_state(task_idx: usize) -> TaskState {
+    if task_idx < NUM_TASKS {
+        // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+        unsafe { (*TCBS.get())[task_idx].state }
+    } else {
+        TaskState::Inactive
+    }
+}
+
 /// Get a register value from a task's saved context
 pub fn get_task_reg(task_idx: usize, reg: usize) -> u64 {
     // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
@@ -307,7 +996,8 @@ pub fn load_frame(task_idx: usize, frame: &mut TrapFrame) {
 }
 
 /// Cleanup all resources held by a task: IPC endpoints, grants, IRQ bindings,
-/// watchdog, and priority inheritance. Shared by fault_current_task() and sys_exit().
+/// watchdog, and priority inheritance. Shared by fault_current_task(),
+/// sys_exit(), deadline_scan() and watchdog_scan().
 ///
 /// SAFETY: Caller must ensure single-core kernel context with interrupts masked.
 pub unsafe fn cleanup_task_resources(task_idx: usize) {
@@ -323,11 +1013,23 @@ pub unsafe fn cleanup_task_resources(task_idx: usize) {
     // Clean up IPC endpoints — unblock any partner waiting for this task
     crate::ipc::cleanup_task(task_idx);
 
+    // Drop any pending queue dequeue
+    crate::queue::cleanup_task(task_idx);
+
     // Clean up shared memory grants — revoke all grants involving this task
     crate::grant::cleanup_task(task_idx);
 
+    // Reclaim anonymous frames — unmap and return them to the pool
+    crate::frame::cleanup_task(task_idx);
+
+    // Make the stack page read-write again (SYS_PAGE_PROTECT)
+    crate::protect::cleanup_task(task_idx);
+
     // Clean up IRQ bindings — unbind all IRQs owned by this task
     crate::irq::irq_cleanup_task(task_idx);
+
+    // Cancel any pending sleep wake-up
+    crate::timer::cancel_sleep(task_idx);
 }
 
 /// Mark the currently running task as Faulted, cleanup IPC, and schedule away.
@@ -394,8 +1096,8 @@ pub fn restart_task(task_idx: usize) {
         #[cfg(target_arch = "aarch64")]
         {
             let ustack_top = (*TCBS.get_mut())[task_idx].user_stack_top;
-            let ustack_base = (ustack_top - 4096) as *mut u8;
-            core::ptr::write_bytes(ustack_base, 0, 4096);
+            let ustack_base = (ustack_top - STACK_SIZE) as *mut u8;
+            core::ptr::write_bytes(ustack_base, 0, STACK_SIZE as usize);
         }
 
         // Zero entire TrapFrame
@@ -413,12 +1115,23 @@ pub fn restart_task(task_idx: usize) {
         (*TCBS.get_mut())[task_idx].state = TaskState::Ready;
         (*TCBS.get_mut())[task_idx].notify_pending = 0;
         (*TCBS.get_mut())[task_idx].notify_waiting = false;
+        (*TCBS.get_mut())[task_idx].notify_mask = crate::ipc::NOTIFY_ALL;
+        (*TCBS.get_mut())[task_idx].wait_any = false;
+        (*TCBS.get_mut())[task_idx].reply_to = None;
+        (*TCBS.get_mut())[task_idx].awaiting_reply = false;
+        crate::ipc::clear_ipc_buffer(task_idx);
 
         // Phase K: Reset scheduling state on restart
         (*TCBS.get_mut())[task_idx].priority = (*TCBS.get_mut())[task_idx].base_priority;
         (*TCBS.get_mut())[task_idx].ticks_used = 0;
         (*TCBS.get_mut())[task_idx].last_heartbeat = crate::timer::tick_count();
 
+        // Periodic tasks restart with a fresh job released now
+        (*TCBS.get_mut())[task_idx].job_active = false;
+        if (*TCBS.get_mut())[task_idx].timing.period > 0 {
+            start_job(task_idx, crate::timer::tick_count());
+        }
+
         uart_print("[AegisOS] TASK ");
         crate::uart_print_hex(id as u64);
         uart_print(" RESTARTED\n");
@@ -471,8 +1184,7 @@ pub fn watchdog_scan() {
                 }
                 (*TCBS.get_mut())[i].state = TaskState::Faulted;
                 (*TCBS.get_mut())[i].fault_tick = now;
-                (*TCBS.get_mut())[i].priority = (*TCBS.get_mut())[i].base_priority;
-                crate::ipc::cleanup_task(i);
+                cleanup_task_resources(i);
             }
         }
     }
@@ -626,6 +1338,41 @@ fn pick_next_task_pure(
     next
 }
 
+/// Pure EDF/priority decision — mirrors the selection loop in schedule()
+/// with the EDF class enabled. `is_eligible` already folds in Ready, budget,
+/// partition window and (for periodic tasks) job release.
+#[cfg(kani)]
+fn pick_next_edf_pure(
+    is_eligible: &[bool; NUM_TASKS],
+    is_periodic: &[bool; NUM_TASKS],
+    deadlines: &[u64; NUM_TASKS],
+    priorities: &[u8; NUM_TASKS],
+    old: usize,
+) -> usize {
+    let mut best_prio: i16 = -1;
+    let mut best_deadline = u64::MAX;
+    let mut found_edf = false;
+    let mut next = IDLE_TASK_ID;
+    let mut offset: usize = 0;
+    while offset < NUM_TASKS {
+        let idx = (old + 1 + offset) % NUM_TASKS;
+        if is_eligible[idx] {
+            if is_periodic[idx] {
+                if deadlines[idx] < best_deadline {
+                    best_deadline = deadlines[idx];
+                    next = idx;
+                    found_edf = true;
+                }
+            } else if !found_edf && (priorities[idx] as i16) > best_prio {
+                best_prio = priorities[idx] as i16;
+                next = idx;
+            }
+        }
+        offset += 1;
+    }
+    next
+}
+
 /// Pure restart logic — mirrors restart_task() but operates on explicit
 /// state fields instead of globals.
 #[cfg(kani)]
@@ -753,6 +1500,245 @@ mod kani_proofs {
         assert_eq!(s6, TaskState::Exited, "state unchanged");
     }
 
+    // ─── Partition proofs ──────────────────────────────────────────
+
+    /// Prove: temporal isolation — the scheduler never picks a task outside
+    /// the active partition window. The only exception is idle, which fills
+    /// gaps and windows with nothing runnable.
+    #[kani::proof]
+    #[kani::unwind(9)] // NUM_TASKS=8, loop needs unwind bound = 9
+    fn partition_schedule_isolation() {
+        let mut is_eligible = [false; NUM_TASKS];
+        let mut priorities = [0u8; NUM_TASKS];
+        let mut partitions = [0u8; NUM_TASKS];
+        let old: usize = kani::any();
+        kani::assume(old < NUM_TASKS);
+        let active: Option<u8> = kani::any();
+
+        let mut i: usize = 0;
+        while i < NUM_TASKS {
+            let ready: bool = kani::any();
+            partitions[i] = kani::any();
+            priorities[i] = kani::any();
+            kani::assume(priorities[i] <= 7);
+            // Mirrors schedule(): Ready + budget_ok + in_window
+            is_eligible[i] = ready && active == Some(partitions[i]);
+            i += 1;
+        }
+
+        let next = pick_next_task_pure(&is_eligible, &priorities, old);
+
+        assert!(next < NUM_TASKS, "scheduler returned invalid index");
+        assert!(
+            next == IDLE_TASK_ID || active == Some(partitions[next]),
+            "picked a task outside the active partition"
+        );
+    }
+
+    /// Prove: for any valid window table, every tick maps to at most one
+    /// window, and `partition_at` returns exactly that window's partition
+    /// (or None when the tick falls in a gap).
+    #[kani::proof]
+    #[kani::unwind(4)] // 3 windows, loop needs unwind bound = 4
+    fn partition_window_lookup() {
+        let mut windows = [PartitionWindow { partition: 0, offset: 0, duration: 0 }; 3];
+        let major_frame: u64 = kani::any();
+        kani::assume(major_frame > 0 && major_frame <= 64);
+        let mut i: usize = 0;
+        while i < 3 {
+            windows[i].partition = kani::any();
+            windows[i].offset = kani::any();
+            windows[i].duration = kani::any();
+            kani::assume(windows[i].offset <= 64 && windows[i].duration <= 64);
+            i += 1;
+        }
+        kani::assume(partition_schedule_valid(&windows, major_frame));
+
+        let now: u64 = kani::any();
+        kani::assume(now < 256);
+        let offset = now % major_frame;
+
+        let mut owners: usize = 0;
+        let mut owner: Option<u8> = None;
+        let mut j: usize = 0;
+        while j < 3 {
+            let w = windows[j];
+            if offset >= w.offset && offset < w.offset + w.duration {
+                owners += 1;
+                owner = Some(w.partition);
+            }
+            j += 1;
+        }
+
+        // PROPERTY 1: valid windows never overlap
+        assert!(owners <= 1, "two windows own the same tick");
+        // PROPERTY 2: lookup agrees with the owning window (None in gaps)
+        assert_eq!(partition_at(&windows, major_frame, now), owner);
+    }
+
+    // ─── EDF proofs ────────────────────────────────────────────────
+
+    /// Prove: with the EDF class active, if any periodic job is eligible the
+    /// scheduler picks an eligible periodic task with the earliest deadline;
+    /// aperiodic tasks only run when no periodic job is eligible.
+    #[kani::proof]
+    #[kani::unwind(9)] // NUM_TASKS=8, loop needs unwind bound = 9
+    fn edf_picks_earliest_deadline() {
+        let mut is_eligible = [false; NUM_TASKS];
+        let mut is_periodic = [false; NUM_TASKS];
+        let mut deadlines = [0u64; NUM_TASKS];
+        let mut priorities = [0u8; NUM_TASKS];
+        let old: usize = kani::any();
+        kani::assume(old < NUM_TASKS);
+
+        let mut i: usize = 0;
+        while i < NUM_TASKS {
+            is_eligible[i] = kani::any();
+            is_periodic[i] = kani::any();
+            deadlines[i] = kani::any();
+            kani::assume(deadlines[i] < u64::MAX);
+            priorities[i] = kani::any();
+            kani::assume(priorities[i] <= 7);
+            i += 1;
+        }
+
+        let next = pick_next_edf_pure(&is_eligible, &is_periodic, &deadlines, &priorities, old);
+        assert!(next < NUM_TASKS, "scheduler returned invalid index");
+
+        let mut any_periodic = false;
+        let mut j: usize = 0;
+        while j < NUM_TASKS {
+            if is_eligible[j] && is_periodic[j] {
+                any_periodic = true;
+            }
+            j += 1;
+        }
+        if any_periodic {
+            assert!(is_eligible[next] && is_periodic[next], "EDF must pick a periodic job");
+            let mut k: usize = 0;
+            while k < NUM_TASKS {
+                if is_eligible[k] && is_periodic[k] {
+                    assert!(deadlines[next] <= deadlines[k], "picked job is not earliest deadline");
+                }
+                k += 1;
+            }
+        }
+    }
+
+    /// Prove: any two-task set accepted by edf_admission() on the whole
+    /// CPU (one full-frame window) has utilisation <= 1, respects the
+    /// constrained-deadline model, and meets the demand bound at every
+    /// tick of the first hyperperiod.
+    #[kani::proof]
+    #[kani::unwind(40)]
+    fn edf_admission_soundness() {
+        let mut tasks = [APERIODIC; 2];
+        let mut i: usize = 0;
+        while i < 2 {
+            tasks[i].period = kani::any();
+            tasks[i].deadline = kani::any();
+            tasks[i].wcet = kani::any();
+            kani::assume(tasks[i].period >= 1 && tasks[i].period <= 4);
+            kani::assume(tasks[i].deadline <= 4 && tasks[i].wcet <= 4);
+            i += 1;
+        }
+
+        let whole_cpu = [PartitionWindow { partition: 0, offset: 0, duration: 1 }];
+        if edf_admission(&tasks, &[0; 2], &whole_cpu, 1).is_ok() {
+            let (a, b) = (tasks[0], tasks[1]);
+            // PROPERTY 1: constrained-deadline model
+            assert!(a.wcet > 0 && a.wcet <= a.deadline && a.deadline <= a.period);
+            assert!(b.wcet > 0 && b.wcet <= b.deadline && b.deadline <= b.period);
+            // PROPERTY 2: U = Ca/Pa + Cb/Pb <= 1
+            assert!(a.wcet * b.period + b.wcet * a.period <= a.period * b.period);
+            // PROPERTY 3: demand never exceeds supply
+            let mut t: u64 = 0;
+            while t <= 16 {
+                assert!(demand_bound(&tasks, t) <= t, "demand exceeds interval");
+                t += 1;
+            }
+        }
+    }
+
+    /// Proof: an active job is reported missed exactly when its absolute
+    /// deadline has been reached; completed jobs are never reported.
+    #[kani::proof]
+    fn deadline_miss_detection() {
+        let job_active: bool = kani::any();
+        let abs_deadline: u64 = kani::any();
+        let now: u64 = kani::any();
+
+        let missed = deadline_missed(job_active, abs_deadline, now);
+
+        if !job_active {
+            assert!(!missed, "completed job must not miss");
+        } else {
+            assert_eq!(missed, now >= abs_deadline);
+        }
+    }
+
+    // ─── Tickless proofs ───────────────────────────────────────────
+
+    /// Prove: sleeping for `window_boundary_delta()` ticks never skips a
+    /// partition switch — every tick in [now, now + delta) maps to the same
+    /// partition (or gap) as `now`, and delta is at least 1.
+    #[kani::proof]
+    #[kani::unwind(17)]
+    fn tickless_window_boundary_exact() {
+        let mut windows = [PartitionWindow { partition: 0, offset: 0, duration: 0 }; 2];
+        let major_frame: u64 = kani::any();
+        kani::assume(major_frame > 0 && major_frame <= 16);
+        let mut i: usize = 0;
+        while i < 2 {
+            windows[i].partition = kani::any();
+            windows[i].offset = kani::any();
+            windows[i].duration = kani::any();
+            kani::assume(windows[i].offset <= 16 && windows[i].duration <= 16);
+            i += 1;
+        }
+        kani::assume(partition_schedule_valid(&windows, major_frame));
+        let now: u64 = kani::any();
+        kani::assume(now < 64);
+
+        let delta = window_boundary_delta(&windows, major_frame, now);
+        assert!(delta >= 1 && delta <= major_frame, "delta out of range");
+
+        let here = partition_at(&windows, major_frame, now);
+        let mut d: u64 = 1;
+        while d < delta {
+            assert_eq!(partition_at(&windows, major_frame, now + d), here, "missed a window switch");
+            d += 1;
+        }
+    }
+
+    // ─── Stack guard pages ─────────────────────────────────────────
+
+    /// Proof: Every task's stack is bounded below by its own guard page.
+    /// For any page-aligned region base, task and address:
+    /// - no byte of a stack [top - STACK_SIZE, top) is a guard byte
+    /// - the page just below the stack is that task's guard
+    /// - an address reported as a guard lies in its owner's slot
+    #[kani::proof]
+    fn stack_guard_below_every_stack() {
+        let pfn: u32 = kani::any();
+        let base = (pfn as u64) * STACK_SIZE;
+        let task: usize = kani::any();
+        kani::assume(task < NUM_TASKS);
+        let offset: u64 = kani::any();
+        kani::assume(offset < STACK_SIZE);
+
+        let top = stack_top(base, task);
+        assert!(guard_page_owner(base, top - STACK_SIZE + offset).is_none(), "stack byte is not a guard");
+        assert_eq!(guard_page_owner(base, top - STACK_SLOT_SIZE + offset), Some(task), "guard below the stack");
+
+        let addr: u64 = kani::any();
+        if let Some(owner) = guard_page_owner(base, addr) {
+            assert!(owner < NUM_TASKS);
+            assert!(addr >= base + owner as u64 * STACK_SLOT_SIZE);
+            assert!(addr < stack_top(base, owner) - STACK_SIZE, "guard hit never inside a stack");
+        }
+    }
+
     // ─── Phase P proofs: watchdog + budget ─────────────────────────
 
     /// Proof: If a task doesn't heartbeat within its interval, watchdog detects it.
@@ -788,14 +1774,15 @@ mod kani_proofs {
         let mut i: usize = 0;
         while i < NUM_TASKS {
             let s: u8 = kani::any();
-            kani::assume(s <= 5); // TaskState variants 0..=5
+            kani::assume(s <= 6); // TaskState variants 0..=6
             states[i] = match s {
                 0 => TaskState::Inactive,
                 1 => TaskState::Ready,
                 2 => TaskState::Running,
                 3 => TaskState::Blocked,
                 4 => TaskState::Faulted,
-                _ => TaskState::Exited,
+                5 => TaskState::Exited,
+                _ => TaskState::Sleeping,
             };
             ticks_used[i] = kani::any();
             i += 1;
@@ -3,6 +3,13 @@
 ///
 /// Uses the EL1 Physical Timer (CNTP) with PPI INTID 30.
 /// QEMU virt timer frequency: 62,500,000 Hz (62.5 MHz).
+///
+/// Two modes:
+///   - Periodic (default): CNTP re-armed every tick, TICK_COUNT += 1.
+///   - Tickless idle: while a real task runs, ticks stay periodic but are
+///     aligned to CNTPCT. When only idle is runnable, CNTP is programmed
+///     once for the next kernel event and TICK_COUNT is caught up from
+///     CNTPCT on wake (timer or device IRQ).
 
 use crate::kernel::cell::KernelCell;
 #[cfg(target_arch = "aarch64")]
@@ -20,6 +27,22 @@ static TICK_INTERVAL: KernelCell<u64> = KernelCell::new(0);
 /// Encapsulated in KernelCell (Phase M1) — access via unsafe get()/get_mut().
 pub static TICK_COUNT: KernelCell<u64> = KernelCell::new(0);
 
+/// Tickless idle mode enabled (set once at boot via `set_tickless()`).
+pub static TICKLESS: KernelCell<bool> = KernelCell::new(false);
+
+/// Longest one-shot sleep in ticks (10 s at 10 ms/tick). Keeps the
+/// CNTP_TVAL countdown well inside its signed 32-bit range.
+pub const MAX_TICKLESS_TICKS: u64 = 1000;
+
+/// Pending sleep wake-ups (SYS_SLEEP / SYS_SLEEP_UNTIL) and IPC timeout
+/// deadlines, earliest first. A task waits on at most one of them.
+/// Encapsulated in KernelCell (Phase M1) — access via unsafe get()/get_mut().
+pub static SLEEP_QUEUE: KernelCell<TimerQueue> = KernelCell::new(TimerQueue::new());
+
+/// CNTPCT value at the most recently accounted tick boundary (tickless mode).
+#[cfg(target_arch = "aarch64")]
+static LAST_TICK_CNT: KernelCell<u64> = KernelCell::new(0);
+
 /// Initialize timer for periodic ticks
 /// `tick_ms` = interval in milliseconds (e.g., 10 for 10ms)
 #[cfg(target_arch = "aarch64")]
@@ -32,7 +55,10 @@ pub fn init(tick_ms: u32) {
 
     let ticks = freq * (tick_ms as u64) / 1000;
     // SAFETY: Called once during boot, before interrupts are enabled. No concurrent access.
-    unsafe { *TICK_INTERVAL.get_mut() = ticks; }
+    unsafe {
+        *TICK_INTERVAL.get_mut() = ticks;
+        *LAST_TICK_CNT.get_mut() = read_cntpct();
+    }
 
     // Set countdown value
     // SAFETY: Writing CNTP_TVAL_EL0 to arm the EL1 physical timer. Called at EL1 during boot.
@@ -82,31 +108,273 @@ pub fn rearm() {
 #[cfg(target_arch = "aarch64")]
 pub fn tick_handler(frame: &mut crate::exception::TrapFrame) {
     // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
-    unsafe { *TICK_COUNT.get_mut() += 1; }
+    let tickless = unsafe { *TICKLESS.get() };
 
-    // Re-arm for next tick
-    rearm();
+    let elapsed = if tickless {
+        catch_up()
+    } else {
+        // Re-arm for next tick
+        rearm();
+        1
+    };
 
-    // Phase K: Track budget for current running task
+    let before = tick_count();
+    advance_ticks(elapsed);
+
+    // Page-table self-audit once per crossed PT_CHECK_PERIOD boundary
+    if before / crate::ptcheck::PT_CHECK_PERIOD != tick_count() / crate::ptcheck::PT_CHECK_PERIOD {
+        crate::ptcheck::self_audit();
+    }
+
+    // Context switch via scheduler
+    crate::sched::schedule(frame);
+
+    if tickless {
+        program_next_event();
+    }
+}
+
+/// Advance kernel time by `elapsed` ticks and run the per-tick bookkeeping:
+/// budget accounting for the current task, epoch reset, watchdog scan,
+/// deadline checks and job releases. `elapsed` is 1 in periodic mode and
+/// may be larger after a tickless sleep.
+pub fn advance_ticks(elapsed: u64) {
+    if elapsed == 0 {
+        return;
+    }
     // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
     unsafe {
+        let old = *TICK_COUNT.get();
+        let now = old + elapsed;
+        *TICK_COUNT.get_mut() = now;
+
+        // Phase K: Track budget for current running task
         let current = *crate::sched::CURRENT.get();
-        (*crate::sched::TCBS.get_mut())[current].ticks_used += 1;
+        (*crate::sched::TCBS.get_mut())[current].ticks_used += elapsed;
 
         // Phase K: Epoch management — reset budgets every EPOCH_LENGTH ticks
-        *crate::sched::EPOCH_TICKS.get_mut() += 1;
+        *crate::sched::EPOCH_TICKS.get_mut() += elapsed;
         if *crate::sched::EPOCH_TICKS.get() >= crate::sched::EPOCH_LENGTH {
+            let carry = *crate::sched::EPOCH_TICKS.get() % crate::sched::EPOCH_LENGTH;
             crate::sched::epoch_reset();
+            *crate::sched::EPOCH_TICKS.get_mut() = carry;
         }
 
-        // Phase K: Watchdog scan at regular intervals
-        if *TICK_COUNT.get() % crate::sched::WATCHDOG_SCAN_PERIOD == 0 {
+        // Phase K: Watchdog scan at regular intervals (once per crossed boundary)
+        if now / crate::sched::WATCHDOG_SCAN_PERIOD != old / crate::sched::WATCHDOG_SCAN_PERIOD {
             crate::sched::watchdog_scan();
         }
     }
 
-    // Context switch via scheduler
+    // Periodic tasks: check deadlines of current jobs, then release new ones
+    crate::sched::deadline_scan();
+    crate::sched::release_jobs();
+
+    // Wake sleeping tasks whose deadline has passed
+    wake_sleepers();
+}
+
+// ─── Sleep timer queue ─────────────────────────────────────────────
+
+/// One pending wake-up: `task` becomes Ready at tick `deadline`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimerEntry {
+    pub deadline: u64,
+    pub task: usize,
+}
+
+const EMPTY_TIMER_ENTRY: TimerEntry = TimerEntry { deadline: 0, task: 0 };
+
+/// Kernel timer queue: wake-ups sorted by deadline (earliest first, FIFO
+/// among equal deadlines), at most one entry per task. Fixed array, no heap.
+pub struct TimerQueue {
+    entries: [TimerEntry; crate::sched::NUM_TASKS],
+    len: usize,
+}
+
+impl Default for TimerQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimerQueue {
+    pub const fn new() -> Self {
+        Self { entries: [EMPTY_TIMER_ENTRY; crate::sched::NUM_TASKS], len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Pending entries in wake-up order.
+    pub fn entries(&self) -> &[TimerEntry] {
+        &self.entries[..self.len]
+    }
+
+    /// Queue (or re-queue) `task` to wake at `deadline`. Any previous
+    /// entry for the task is replaced. Returns false if `task` is invalid.
+    pub fn insert(&mut self, task: usize, deadline: u64) -> bool {
+        if task >= crate::sched::NUM_TASKS {
+            return false;
+        }
+        self.remove(task);
+        // Find insertion point: after all entries with deadline <= new one
+        let mut pos = self.len;
+        while pos > 0 && self.entries[pos - 1].deadline > deadline {
+            self.entries[pos] = self.entries[pos - 1];
+            pos -= 1;
+        }
+        self.entries[pos] = TimerEntry { deadline, task };
+        self.len += 1;
+        true
+    }
+
+    /// Remove `task`'s entry, if any. Returns true if one was removed.
+    pub fn remove(&mut self, task: usize) -> bool {
+        let mut i = 0;
+        while i < self.len {
+            if self.entries[i].task == task {
+                while i + 1 < self.len {
+                    self.entries[i] = self.entries[i + 1];
+                    i += 1;
+                }
+                self.len -= 1;
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// Earliest pending entry.
+    pub fn peek(&self) -> Option<TimerEntry> {
+        if self.len == 0 { None } else { Some(self.entries[0]) }
+    }
+
+    /// Remove and return the earliest entry if its deadline is <= `now`.
+    pub fn pop_expired(&mut self, now: u64) -> Option<TimerEntry> {
+        match self.peek() {
+            Some(e) if e.deadline <= now => {
+                self.remove(e.task);
+                Some(e)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Wake every sleeping task whose deadline has passed: mark it Ready and
+/// return the current tick in its x0. A task blocked in IPC is handed to
+/// `queue::expire_timeout` / `ipc::expire_timeout`. Stale entries (task faulted or restarted while
+/// waiting) are dropped.
+pub fn wake_sleepers() {
+    let now = tick_count();
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        while let Some(e) = (*SLEEP_QUEUE.get_mut()).pop_expired(now) {
+            let tcb = &mut (*crate::sched::TCBS.get_mut())[e.task];
+            match tcb.state {
+                crate::sched::TaskState::Sleeping => {
+                    tcb.state = crate::sched::TaskState::Ready;
+                    tcb.context.x[0] = now;
+                }
+                // Blocked on a queue dequeue, else on an endpoint / reply
+                crate::sched::TaskState::Blocked if crate::queue::expire_timeout(e.task) => {}
+                crate::sched::TaskState::Blocked => crate::ipc::expire_timeout(e.task),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Cancel a task's pending wake-up or IPC timeout (fault / exit cleanup).
+pub fn cancel_sleep(task_idx: usize) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe { (*SLEEP_QUEUE.get_mut()).remove(task_idx); }
+}
+
+/// Enable or disable tickless idle (boot-time).
+pub fn set_tickless(enabled: bool) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe { *TICKLESS.get_mut() = enabled; }
+}
+
+/// Pure catch-up: whole ticks between counter values `last_cnt` and
+/// `now_cnt` for a tick of `interval` counts (0 if interval is 0).
+pub fn ticks_elapsed(last_cnt: u64, now_cnt: u64, interval: u64) -> u64 {
+    if interval == 0 {
+        return 0;
+    }
+    now_cnt.wrapping_sub(last_cnt) / interval
+}
+
+/// Read the physical counter (CNTPCT_EL0).
+#[cfg(target_arch = "aarch64")]
+fn read_cntpct() -> u64 {
+    let cnt: u64;
+    // SAFETY: Reading CNTPCT_EL0 (physical counter). Read-only; isb orders it after prior instructions. Called at EL1.
+    unsafe {
+        core::arch::asm!("isb", "mrs {}, CNTPCT_EL0", out(reg) cnt, options(nomem, nostack));
+    }
+    cnt
+}
+
+/// Tickless mode: account whole ticks elapsed since LAST_TICK_CNT.
+/// Returns the number of ticks to pass to `advance_ticks()`.
+#[cfg(target_arch = "aarch64")]
+fn catch_up() -> u64 {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    unsafe {
+        let interval = *TICK_INTERVAL.get();
+        let elapsed = ticks_elapsed(*LAST_TICK_CNT.get(), read_cntpct(), interval);
+        *LAST_TICK_CNT.get_mut() += elapsed * interval;
+        elapsed
+    }
+}
+
+/// Tickless mode: program CNTP for the next tick boundary if a real task
+/// runs, or once for the next kernel event if only idle is runnable.
+#[cfg(target_arch = "aarch64")]
+fn program_next_event() {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    let (interval, last, current) = unsafe {
+        (*TICK_INTERVAL.get(), *LAST_TICK_CNT.get(), *crate::sched::CURRENT.get())
+    };
+    let ticks = if current == crate::sched::IDLE_TASK_ID {
+        crate::sched::ticks_until_next_event()
+    } else {
+        1
+    };
+    let target = last + ticks * interval;
+    let tval = target.saturating_sub(read_cntpct()).max(1);
+    // SAFETY: Writing CNTP_TVAL_EL0 to arm the one-shot countdown. Called at EL1.
+    unsafe {
+        core::arch::asm!(
+            "msr CNTP_TVAL_EL0, {t}",
+            t = in(reg) tval,
+            options(nomem, nostack)
+        );
+    }
+}
+
+/// Tickless mode: a device IRQ arrived while the CPU slept in idle.
+/// Catch up kernel time, let the scheduler switch to any task the IRQ
+/// woke, and re-program the timer for the new situation.
+#[cfg(target_arch = "aarch64")]
+pub fn wake_from_idle(frame: &mut crate::exception::TrapFrame) {
+    // SAFETY: Single-core kernel, interrupts masked during kernel execution. No concurrent access on uniprocessor QEMU virt.
+    let (tickless, current) = unsafe { (*TICKLESS.get(), *crate::sched::CURRENT.get()) };
+    if !tickless || current != crate::sched::IDLE_TASK_ID {
+        return;
+    }
+    advance_ticks(catch_up());
     crate::sched::schedule(frame);
+    program_next_event();
 }
 
 /// Get current tick count
@@ -135,3 +403,70 @@ fn print_decimal(mut val: u32) {
         crate::uart_write(buf[i]);
     }
 }
+
+// ─── Kani formal verification proofs ───────────────────────────────
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    /// Prove: tickless catch-up is exact — after accounting `n` ticks the
+    /// new tick boundary is not in the future and less than one interval
+    /// behind the counter, so no tick is lost or counted twice.
+    #[kani::proof]
+    fn tickless_catch_up_exact() {
+        let last: u64 = kani::any();
+        let now: u64 = kani::any();
+        let interval: u64 = kani::any();
+        kani::assume(interval > 0 && interval <= 1 << 20);
+        kani::assume(now >= last && now - last <= 1 << 40);
+
+        let n = ticks_elapsed(last, now, interval);
+        let boundary = last + n * interval;
+        assert!(boundary <= now, "catch-up overshoots the counter");
+        assert!(now - boundary < interval, "catch-up lost a whole tick");
+    }
+
+    /// Prove: the sleep queue stays sorted by deadline with at most one
+    /// entry per task across inserts (including re-inserts) and removals,
+    /// and pop_expired only ever returns the earliest, already-due entry.
+    #[kani::proof]
+    #[kani::unwind(10)]
+    fn timer_queue_sorted_unique() {
+        let mut q = TimerQueue::new();
+        let mut step: usize = 0;
+        while step < 4 {
+            let task: usize = kani::any();
+            kani::assume(task < 4);
+            let deadline: u64 = kani::any();
+            if kani::any() {
+                assert!(q.insert(task, deadline));
+            } else {
+                q.remove(task);
+            }
+            step += 1;
+        }
+
+        let e = q.entries();
+        let mut i: usize = 0;
+        while i < e.len() {
+            let mut j = i + 1;
+            while j < e.len() {
+                assert!(e[i].deadline <= e[j].deadline, "queue not sorted");
+                assert!(e[i].task != e[j].task, "duplicate task in queue");
+                j += 1;
+            }
+            i += 1;
+        }
+
+        let now: u64 = kani::any();
+        let head = q.peek();
+        match q.pop_expired(now) {
+            Some(p) => {
+                assert_eq!(Some(p), head);
+                assert!(p.deadline <= now);
+            }
+            None => assert!(head.is_none() || head.unwrap().deadline > now),
+        }
+    }
+}
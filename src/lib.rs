@@ -20,6 +20,9 @@ pub mod kernel;
 /// Platform constants (MMIO addresses, memory map)
 pub mod platform;
 
+/// Build-time system configuration (generated from system.toml)
+pub mod sysconf;
+
 // ─── UART: stays at root (tiny, dual-cfg) ──────────────────────────
 
 pub mod uart;
@@ -45,10 +48,19 @@ pub mod exception; // reads src/exception.rs (host-only stub)
 // `crate::timer`, `crate::grant`, `crate::irq` paths keep working.
 
 pub use kernel::ipc;
+pub use kernel::queue;
+pub use kernel::sampling;
 pub use kernel::cap;
+pub use kernel::cspace;
+pub use kernel::policy;
+pub use kernel::audit;
 pub use kernel::sched;
 pub use kernel::timer;
 pub use kernel::grant;
+pub use kernel::frame;
+pub use kernel::protect;
+pub use kernel::ptcheck;
+pub use kernel::memmap;
 pub use kernel::irq;
 pub use kernel::elf;
 pub use kernel::log;
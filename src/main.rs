@@ -46,22 +46,28 @@ pub fn syscall_yield() {
 }
 
 /// SYS_SEND (syscall #1): send message on endpoint.
+/// Returns x0 — ERR_CAP_DENIED or an ERR_IPC_* code if the send failed,
+/// otherwise `m0` unchanged.
 #[cfg(target_arch = "aarch64")]
 #[inline(always)]
-pub fn syscall_send(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) {
+pub fn syscall_send(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
+    let result: u64;
     // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") m0,
+            inlateout("x0") m0 => result,
             in("x1") m1,
             in("x2") m2,
             in("x3") m3,
+            in("x4") u64::MAX, // TIMEOUT_INFINITE
+            in("x5") 0u64, // no IPC-buffer payload
             in("x6") ep_id,
             in("x7") 1u64, // SYS_SEND
             options(nomem, nostack)
         );
     }
+    result
 }
 
 /// SYS_RECV (syscall #2): receive message from endpoint.
@@ -73,33 +79,39 @@ pub fn syscall_recv(ep_id: u64) -> u64 {
     unsafe {
         core::arch::asm!(
             "svc #0",
+            inlateout("x4") u64::MAX => _, // TIMEOUT_INFINITE; sender id on return
             in("x6") ep_id,
             in("x7") 2u64, // SYS_RECV
             lateout("x0") msg0,
+            lateout("x5") _, // sender badge
             options(nomem, nostack)
         );
     }
     msg0
 }
 
-/// SYS_RECV variant returning first two message registers (x0, x1).
+/// SYS_RECV variant returning the first two message registers (x0, x1)
+/// and the kernel-stamped sender badge (x5).
 #[cfg(target_arch = "aarch64")]
 #[inline(always)]
-pub fn syscall_recv2(ep_id: u64) -> (u64, u64) {
+pub fn syscall_recv2(ep_id: u64) -> (u64, u64, u64) {
     let msg0: u64;
     let msg1: u64;
+    let badge: u64;
     // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
     unsafe {
         core::arch::asm!(
             "svc #0",
+            inlateout("x4") u64::MAX => _, // TIMEOUT_INFINITE; sender id on return
             in("x6") ep_id,
             in("x7") 2u64, // SYS_RECV
             lateout("x0") msg0,
             lateout("x1") msg1,
+            lateout("x5") badge,
             options(nomem, nostack)
         );
     }
-    (msg0, msg1)
+    (msg0, msg1, badge)
 }
 
 /// SYS_CALL (syscall #3): send message then wait for reply.
@@ -115,6 +127,8 @@ pub fn syscall_call(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
             in("x1") m1,
             in("x2") m2,
             in("x3") m3,
+            inlateout("x4") u64::MAX => _, // TIMEOUT_INFINITE; replier id on return
+            inlateout("x5") 0u64 => _, // no IPC-buffer payload; badge on return
             in("x6") ep_id,
             in("x7") 3u64, // SYS_CALL
             lateout("x0") reply0,
@@ -124,6 +138,80 @@ pub fn syscall_call(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
     reply0
 }
 
+/// SYS_REPLY_RECV (syscall #17): reply to the pending caller with `r0`,
+/// then receive the next request on `ep_id`. Returns (x0, x1, badge).
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub fn syscall_reply_recv2(ep_id: u64, r0: u64) -> (u64, u64, u64) {
+    let msg0: u64;
+    let msg1: u64;
+    let badge: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x4") u64::MAX => _, // TIMEOUT_INFINITE; sender id on return
+            inlateout("x5") 0u64 => badge, // no IPC-buffer payload; badge on return
+            in("x6") ep_id,
+            in("x7") 17u64, // SYS_REPLY_RECV
+            inlateout("x0") r0 => msg0,
+            lateout("x1") msg1,
+            lateout("x2") _,
+            lateout("x3") _,
+            options(nomem, nostack)
+        );
+    }
+    (msg0, msg1, badge)
+}
+
+/// SYS_REPLY (syscall #16): answer the pending caller with `r0`.
+/// Returns 0, or ERR_IPC_NO_REPLY if no call is pending.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub fn syscall_reply(r0: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x5") 0u64, // no IPC-buffer payload
+            in("x7") 16u64, // SYS_REPLY
+            inlateout("x0") r0 => result,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_RECV_ANY (syscall #22): wait for a message on any endpoint in
+/// `ep_set` (bit i = EP i) or any bit of `notify_mask`. Returns
+/// (source, x0, x1, badge): source is the endpoint id, or
+/// WAIT_SRC_NOTIFY with the fired notification bits in x0.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub fn syscall_recv_any2(ep_set: u64, notify_mask: u64) -> (u64, u64, u64, u64) {
+    let source: u64;
+    let msg0: u64;
+    let msg1: u64;
+    let badge: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") notify_mask => msg0,
+            lateout("x1") msg1,
+            lateout("x2") _,
+            lateout("x3") _,
+            inlateout("x4") u64::MAX => _, // TIMEOUT_INFINITE; sender id on return
+            lateout("x5") badge,
+            inlateout("x6") ep_set => source,
+            in("x7") 22u64, // SYS_RECV_ANY
+            options(nomem, nostack)
+        );
+    }
+    (source, msg0, msg1, badge)
+}
+
 /// SYS_WRITE (syscall #4): write string to UART via kernel.
 #[cfg(target_arch = "aarch64")]
 #[inline(always)]
@@ -132,7 +220,7 @@ pub fn syscall_write(buf: *const u8, len: usize) {
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") buf as u64,
+            inlateout("x0") buf as u64 => _, // ERR_CAP_DENIED if refused
             in("x1") len as u64,
             in("x7") 4u64, // SYS_WRITE
             options(nomem, nostack)
@@ -148,19 +236,23 @@ pub fn user_print(s: &str) {
 }
 
 /// SYS_NOTIFY (syscall #5): send notification bitmask to target task.
+/// Returns x0 — ERR_CAP_DENIED, or 0xFFFF_DEAD for an invalid target,
+/// otherwise `bits` unchanged.
 #[cfg(target_arch = "aarch64")]
 #[inline(always)]
-pub fn syscall_notify(target_id: u64, bits: u64) {
+pub fn syscall_notify(target_id: u64, bits: u64) -> u64 {
+    let result: u64;
     // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") bits,
+            inlateout("x0") bits => result,
             in("x6") target_id,
             in("x7") 5u64, // SYS_NOTIFY
             options(nomem, nostack)
         );
     }
+    result
 }
 
 /// SYS_WAIT_NOTIFY (syscall #6): block until notification arrives.
@@ -181,24 +273,27 @@ pub fn syscall_wait_notify() -> u64 {
     bits
 }
 /// SYS_GRANT_CREATE (syscall #7): create shared memory grant.
-/// x0 = grant_id, x6 = peer_task_id.
-/// Returns result in x0 (0 = success).
+/// x0 = grant_id, x6 = peer_task_id, x1 = pages, x2 = alignment (pages),
+/// x3 = GRANT_PEER_RO / GRANT_OWNER_RO flags.
+/// Returns (result, region base): x0 = 0 on success, x1 = base address.
 #[cfg(target_arch = "aarch64")]
 #[inline(always)]
-pub fn syscall_grant_create(grant_id: u64, peer_task_id: u64) -> u64 {
-    let result: u64;
+pub fn syscall_grant_create(grant_id: u64, peer_task_id: u64, pages: u64, align: u64, flags: u64) -> (u64, u64) {
+    let (result, base): (u64, u64);
     // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") grant_id,
+            inlateout("x0") grant_id => result,
+            inlateout("x1") pages => base,
+            in("x2") align,
+            in("x3") flags,
             in("x6") peer_task_id,
             in("x7") 7u64, // SYS_GRANT_CREATE
-            lateout("x0") result,
             options(nomem, nostack)
         );
     }
-    result
+    (result, base)
 }
 
 /// SYS_GRANT_REVOKE (syscall #8): revoke shared memory grant.
@@ -281,6 +376,28 @@ pub fn syscall_device_map(device_id: u64) -> u64 {
     result
 }
 
+/// SYS_CAP_MINT (syscall #23): copy own capability slot into another task.
+/// x0 = own slot, x1 = target task, x2 = target slot, x3 = rights mask.
+/// Returns result in x0 (0 = success).
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub fn syscall_cap_mint(slot: u64, target_task: u64, target_slot: u64, rights: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel vector table. Register ABI is documented in syscall convention.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inout("x0") slot => result,
+            in("x1") target_task,
+            in("x2") target_slot,
+            in("x3") rights,
+            in("x7") 23u64, // SYS_CAP_MINT
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
 /// SYS_HEARTBEAT (syscall #12): register/refresh watchdog heartbeat.
 /// x0 = heartbeat interval in ticks (0 = disable watchdog).
 /// Returns result in x0 (0 = success).
@@ -307,11 +424,52 @@ pub fn syscall_heartbeat(interval: u64) -> u64 {
 #[cfg(target_arch = "aarch64")]
 const UART0_DR: *mut u8 = 0x0900_0000 as *mut u8;
 
+/// UART0 PL011 Flag Register (bit 4 RXFE = receive FIFO empty)
+#[cfg(target_arch = "aarch64")]
+const UART0_FR: *const u32 = 0x0900_0018 as *const u32;
+
+/// UART0 PL011 Interrupt Mask Set/Clear Register (bit 4 RXIM, bit 6 RTIM)
+#[cfg(target_arch = "aarch64")]
+const UART0_IMSC: *mut u32 = 0x0900_0038 as *mut u32;
+
+/// GIC INTID of UART0 on QEMU virt (SPI 1)
+#[cfg(target_arch = "aarch64")]
+const UART0_INTID: u64 = 33;
+
+/// Notification bit the UART0 receive IRQ raises for the driver
+#[cfg(target_arch = "aarch64")]
+const UART_RX_BIT: u64 = 1 << 0;
+
+/// Driver's CSpace slot holding EP 0 with the right to delegate it
+/// (system.toml `cspace`)
+#[cfg(target_arch = "aarch64")]
+const DRIVER_UART_SLOT: u64 = 0;
+
+/// Client's CSpace slot holding its EP 0 send + receive capability
+/// (minted by the driver at startup)
+#[cfg(target_arch = "aarch64")]
+const CLIENT_UART_SLOT: u64 = 0;
+
+/// Client task id
+#[cfg(target_arch = "aarch64")]
+const CLIENT_TASK: u64 = TASK_CLIENT as u64;
+
+/// Badge on the client's EP 0 send right — the only caller the UART
+/// driver serves ("CL", system.toml `badges`).
+#[cfg(target_arch = "aarch64")]
+const UART_CLIENT_BADGE: u64 = CLIENT_BADGES[0];
+
+// ─── Task table (generated by build.rs from system.toml) ───────────
+// TASK_<NAME> ids, <NAME>_BADGES, task_entries(), TASK_META, ELF_IMAGES
+
+include!(concat!(env!("OUT_DIR"), "/task_table.rs"));
+
 /// Task 0 — UART User-Mode Driver
 ///
 /// Requests UART MMIO access from kernel, then loops serving IPC requests
 /// from client tasks. Reads data from shared grant page and writes each
-/// byte directly to UART DR — a genuine EL0 device driver.
+/// byte directly to UART DR — a genuine EL0 device driver. One wait set
+/// covers both client requests on EP 0 and the UART receive IRQ.
 #[cfg(target_arch = "aarch64")]
 #[no_mangle]
 pub extern "C" fn uart_driver_entry() -> ! {
@@ -321,15 +479,46 @@ pub extern "C" fn uart_driver_entry() -> ! {
     // 2. Register watchdog heartbeat (50 ticks = 500ms interval)
     syscall_heartbeat(50);
 
-    // 3. Announce we're ready (still using SYS_WRITE for initial status)
+    // 3. Hand the client its call capability on EP 0 — send + receive,
+    //    without the right to pass it on — then announce we're ready
+    let rights = (aegis_os::cspace::RIGHT_SEND | aegis_os::cspace::RIGHT_RECV) as u64;
+    if syscall_cap_mint(DRIVER_UART_SLOT, CLIENT_TASK, CLIENT_UART_SLOT, rights) == 0 {
+        user_print("DRV:mint ");
+    }
     user_print("DRV:ready ");
 
-    // 4. Serve client requests forever
+    // 4. Route UART receive interrupts to us as a notification
+    syscall_irq_bind(UART0_INTID, UART_RX_BIT);
+    // SAFETY: UART0 IMSC is in the device page mapped for this task above.
+    unsafe { core::ptr::write_volatile(UART0_IMSC, (1 << 4) | (1 << 6)) };
+
+    // 5. Serve client requests and echo received input forever
     loop {
         // Refresh heartbeat each iteration
         syscall_heartbeat(50);
-        // Block waiting for an IPC request on EP 0
-        let (buf_addr_raw, len_raw) = syscall_recv2(0);
+
+        // Block until a client request on EP 0 or a UART receive IRQ
+        let (source, buf_addr_raw, len_raw, badge) = syscall_recv_any2(1 << 0, UART_RX_BIT);
+
+        if source == aegis_os::ipc::WAIT_SRC_NOTIFY {
+            // Drain the receive FIFO, echoing each byte, then re-arm the IRQ
+            // SAFETY: UART0 FR/DR are in the device page mapped for this task.
+            unsafe {
+                while core::ptr::read_volatile(UART0_FR) & (1 << 4) == 0 {
+                    let byte = core::ptr::read_volatile(UART0_DR);
+                    core::ptr::write_volatile(UART0_DR, byte);
+                }
+            }
+            syscall_irq_ack(UART0_INTID);
+            continue;
+        }
+
+        // Authorise by the kernel-stamped badge, not by anything the
+        // caller wrote into its message registers
+        if badge != UART_CLIENT_BADGE {
+            syscall_reply(0x4E4F); // "NO"
+            continue;
+        }
 
         // msg x0 = buffer address in grant page
         // msg x1 = byte count to write
@@ -345,8 +534,8 @@ pub extern "C" fn uart_driver_entry() -> ! {
             }
         }
 
-        // Reply "OK" to unblock the client
-        syscall_send(0, 0x4F4B, 0, 0, 0); // "OK"
+        // Reply "OK" to exactly this caller
+        syscall_reply(0x4F4B); // "OK"
     }
 }
 
@@ -359,14 +548,14 @@ pub extern "C" fn uart_driver_entry() -> ! {
 #[no_mangle]
 pub extern "C" fn client_entry() -> ! {
     // 1. Create a shared memory grant: grant 0, owner=us(task 1), peer=driver(task 0)
-    syscall_grant_create(0, 0); // grant_id=0, peer_task_id=0
+    //    One page from the grant pool; the kernel returns its (identity-mapped) base.
+    //    The driver only reads the message, so its side is read-only.
+    let (_, grant_base) = syscall_grant_create(0, 0, 1, 1, aegis_os::grant::GRANT_PEER_RO); // grant_id=0, peer_task_id=0
+    let grant_addr = grant_base as *mut u8;
 
     // 2. Register watchdog heartbeat (50 ticks = 500ms interval)
     syscall_heartbeat(50);
 
-    // 3. Get the grant page address (identity-mapped, known at compile time)
-    let grant_addr = aegis_os::grant::grant_page_addr(0).unwrap_or(0) as *mut u8;
-
     loop {
         // Refresh heartbeat each iteration
         syscall_heartbeat(50);
@@ -380,7 +569,7 @@ pub extern "C" fn client_entry() -> ! {
         }
 
         // 4. Call the UART driver: send buffer address + length via IPC
-        syscall_call(0, grant_addr as u64, msg.len() as u64, 0, 0);
+        syscall_call(aegis_os::cspace::CPTR_FLAG | CLIENT_UART_SLOT, grant_addr as u64, msg.len() as u64, 0, 0);
     }
 }
 
@@ -410,99 +599,100 @@ pub extern "C" fn kernel_main() -> ! {
     gic::set_priority(timer::TIMER_INTID, 0);
     gic::enable_intid(timer::TIMER_INTID);
 
-    sched::init(&[
-        uart_driver_entry as *const () as u64,  // task 0: UART driver
-        client_entry as *const () as u64,        // task 1: client
-        idle_entry as *const () as u64,           // task 2: placeholder (ELF overrides)
-        idle_entry as *const () as u64,           // task 3: placeholder (ELF overrides)
-        idle_entry as *const () as u64,           // task 4: placeholder (ELF overrides)
-        0,                                        // task 5: reserved (Inactive)
-        0,                                        // task 6: reserved (Inactive)
-        idle_entry as *const () as u64,           // task 7: idle (IDLE_TASK_ID)
-    ]);
-
-    // ─── Phase N: Apply per-task metadata from const table ─────────
+    // Entry points from system.toml; ELF tasks start at idle_entry until loaded
+    sched::init(&task_entries());
+
+    // ─── Phase N: Apply per-task metadata from the generated table ──
     {
-        use aegis_os::cap::*;
-        use aegis_os::sched::TaskMetadata;
+        use aegis_os::queue;
+        use aegis_os::sampling;
+        use aegis_os::sched::{PartitionSchedule, APERIODIC};
         use aegis_os::mmu;
 
-        // Metadata for inactive tasks (zero caps, lowest priority)
-        const INACTIVE: TaskMetadata = TaskMetadata {
-            caps: 0, priority: 0, time_budget: 0, heartbeat_interval: 0,
-        };
-
-        const TASK_META: [TaskMetadata; sched::NUM_TASKS] = [
-            // Task 0 (UART driver): high priority, unlimited budget, full driver caps
-            TaskMetadata {
-                caps: CAP_IPC_SEND_EP0 | CAP_IPC_RECV_EP0 | CAP_WRITE | CAP_YIELD
-                    | CAP_NOTIFY | CAP_WAIT_NOTIFY | CAP_GRANT_CREATE | CAP_GRANT_REVOKE
-                    | CAP_IRQ_BIND | CAP_IRQ_ACK | CAP_DEVICE_MAP | CAP_HEARTBEAT,
-                priority: 6,
-                time_budget: 0,
-                heartbeat_interval: 0,
-            },
-            // Task 1 (client): medium priority, 50 ticks budget
-            TaskMetadata {
-                caps: CAP_IPC_SEND_EP0 | CAP_IPC_RECV_EP0 | CAP_WRITE | CAP_YIELD
-                    | CAP_NOTIFY | CAP_WAIT_NOTIFY | CAP_GRANT_CREATE | CAP_GRANT_REVOKE
-                    | CAP_HEARTBEAT,
-                priority: 4,
-                time_budget: 50,
-                heartbeat_interval: 0,
-            },
-            // Task 2 (hello): ELF-loaded, medium-high priority, basic caps
-            TaskMetadata {
-                caps: CAP_WRITE | CAP_YIELD | CAP_EXIT,
-                priority: 5,
-                time_budget: 2,
-                heartbeat_interval: 0,
-            },
-            // Task 3 (sensor): ELF-loaded, IPC sender + heartbeat
-            TaskMetadata {
-                caps: CAP_IPC_SEND_EP1 | CAP_WRITE | CAP_YIELD | CAP_HEARTBEAT | CAP_EXIT,
-                priority: 4,
-                time_budget: 10,
-                heartbeat_interval: 0,
-            },
-            // Task 4 (logger): ELF-loaded, IPC receiver + writer
-            TaskMetadata {
-                caps: CAP_IPC_RECV_EP1 | CAP_WRITE | CAP_YIELD | CAP_EXIT,
-                priority: 3,
-                time_budget: 10,
-                heartbeat_interval: 0,
-            },
-            INACTIVE, // task 5: reserved
-            INACTIVE, // task 6: reserved
-            // Task 7 (idle): pure wfi loop, minimal caps, lowest priority
-            TaskMetadata {
-                caps: CAP_YIELD,
-                priority: 0,
-                time_budget: 0,
-                heartbeat_interval: 0,
-            },
-        ];
-
         // SAFETY: Single-core kernel, called during boot before interrupts enabled.
         unsafe {
             for i in 0..sched::NUM_TASKS {
                 (*sched::TCBS.get_mut())[i].caps = TASK_META[i].caps;
+                (*sched::TCBS.get_mut())[i].badges = TASK_META[i].badges;
+                aegis_os::cspace::install(i, TASK_META[i].cspace);
                 (*sched::TCBS.get_mut())[i].priority = TASK_META[i].priority;
                 (*sched::TCBS.get_mut())[i].base_priority = TASK_META[i].priority;
                 (*sched::TCBS.get_mut())[i].time_budget = TASK_META[i].time_budget;
                 (*sched::TCBS.get_mut())[i].heartbeat_interval = TASK_META[i].heartbeat_interval;
+                (*sched::TCBS.get_mut())[i].partition = TASK_META[i].partition;
+                (*sched::TCBS.get_mut())[i].mem_quota = TASK_META[i].mem_quota;
+                (*sched::TCBS.get_mut())[i].timing = TASK_META[i].timing;
                 // ASID = task_id + 1 (ASID 0 is reserved for kernel boot)
                 // All tasks get page tables (even inactive — no harm, enables future activation)
                 (*sched::TCBS.get_mut())[i].ttbr0 = mmu::ttbr0_for_task(i, (i + 1) as u16);
             }
         }
+
+        // Partition windows and major frame come from system.toml [schedule]
+        sched::set_partition_schedule(PartitionSchedule {
+            major_frame: MAJOR_FRAME,
+            windows: &PARTITION_WINDOWS,
+        });
+
+        // EDF admission control: refuse to boot a task set that cannot
+        // meet its deadlines inside its partition's windows
+        let mut timing = [APERIODIC; sched::NUM_TASKS];
+        let mut partitions = [0u8; sched::NUM_TASKS];
+        for (i, meta) in TASK_META.iter().enumerate() {
+            timing[i] = meta.timing;
+            partitions[i] = meta.partition;
+        }
+        if let Err(e) = sched::edf_admission(&timing, &partitions, &PARTITION_WINDOWS, MAJOR_FRAME) {
+            uart_print("!!! EDF admission failed: ");
+            uart_print(match e {
+                sched::AdmissionError::InvalidParams => "invalid periodic parameters",
+                sched::AdmissionError::HyperperiodTooLong => "hyperperiod too long",
+                sched::AdmissionError::Utilisation => "utilisation > partition share",
+                sched::AdmissionError::DemandBound => "demand exceeds partition supply",
+            });
+            uart_print(" — halting\n");
+            loop {
+                // SAFETY: wfe is a hint instruction, safe at EL1
+                unsafe { core::arch::asm!("wfe", options(nomem, nostack)) };
+            }
+        }
+        sched::set_sched_policy(sched::SchedPolicy::Edf);
+        sched::release_jobs();
+
+        // Queue and sampling-port configs from system.toml
+        for &(id, cfg) in QUEUE_CONFIGS.iter() {
+            queue::queue_configure(id, cfg);
+        }
+        for &(id, cfg) in SAMPLING_CONFIGS.iter() {
+            sampling::sampling_configure(id, cfg);
+        }
     }
     uart_print("[AegisOS] capabilities assigned\n");
+    uart_print("[AegisOS] capability spaces installed (8 slots/task)\n");
     uart_print("[AegisOS] priority scheduler configured\n");
+    uart_print("[AegisOS] partition schedule installed (");
+    aegis_os::uart_print_dec(PARTITION_WINDOWS.len() as u64);
+    uart_print(" windows, major frame ");
+    aegis_os::uart_print_dec(MAJOR_FRAME);
+    uart_print(" ticks)\n");
+    uart_print("[AegisOS] EDF admission passed, EDF scheduling enabled\n");
     uart_print("[AegisOS] time budget enforcement enabled\n");
     uart_print("[AegisOS] watchdog heartbeat enabled\n");
     uart_print("[AegisOS] notification system ready\n");
-    uart_print("[AegisOS] grant system ready\n");
+    uart_print("[AegisOS] message queues ready (");
+    aegis_os::uart_print_dec(QUEUE_CONFIGS.len() as u64);
+    uart_print(" of ");
+    aegis_os::uart_print_dec(aegis_os::queue::MAX_QUEUES as u64);
+    uart_print(" configured)\n");
+    uart_print("[AegisOS] sampling ports ready (");
+    aegis_os::uart_print_dec(SAMPLING_CONFIGS.len() as u64);
+    uart_print(" of ");
+    aegis_os::uart_print_dec(aegis_os::sampling::MAX_SAMPLING_PORTS as u64);
+    uart_print(" configured)\n");
+    aegis_os::grant::init();
+    uart_print("[AegisOS] grant system ready (");
+    aegis_os::uart_print_dec(aegis_os::grant::GRANT_POOL_PAGES as u64);
+    uart_print("-page pool, scrubbed)\n");
     uart_print("[AegisOS] IRQ routing ready\n");
     uart_print("[AegisOS] device MMIO mapping ready\n");
     uart_print("[AegisOS] per-task address spaces assigned\n");
@@ -516,24 +706,8 @@ pub extern "C" fn kernel_main() -> ! {
     // ─── Phase O: Multi-ELF Loader ─────────────────────────────────────
     uart_print("[AegisOS] ELF loader ready\n");
     {
-        // Embed user-space ELF binaries (built separately via user/ workspace)
-        static HELLO_ELF: &[u8] = include_bytes!("../user/target/aarch64-user/release/hello");
-        static SENSOR_ELF: &[u8] = include_bytes!("../user/target/aarch64-user/release/sensor");
-        static LOGGER_ELF: &[u8] = include_bytes!("../user/target/aarch64-user/release/logger");
-
-        // Compile-time size check: each binary must fit in 16 KiB slot
-        const _: () = assert!(HELLO_ELF.len() <= 16 * 1024, "hello ELF > 16 KiB");
-        const _: () = assert!(SENSOR_ELF.len() <= 16 * 1024, "sensor ELF > 16 KiB");
-        const _: () = assert!(LOGGER_ELF.len() <= 16 * 1024, "logger ELF > 16 KiB");
-
-        // (task_id, slot, elf_data, name)
-        let tasks: [(usize, usize, &[u8], &str); 3] = [
-            (2, 0, HELLO_ELF,  "hello"),
-            (3, 1, SENSOR_ELF, "sensor"),
-            (4, 2, LOGGER_ELF, "logger"),
-        ];
-
-        for &(task_id, slot, elf_data, name) in &tasks {
+        // ELF images and their load slots come from system.toml (ELF_IMAGES)
+        for &(task_id, slot, elf_data, name) in &ELF_IMAGES {
             // SAFETY: boot-time, single-core, .elf_load region is writable.
             match unsafe { aegis_os::elf::load_elf_to_task(task_id, slot, elf_data) } {
                 Ok(entry) => {
@@ -557,10 +731,17 @@ pub extern "C" fn kernel_main() -> ! {
     }
     uart_print("[AegisOS] multi-ELF loading complete\n");
     timer::init(10);
+    timer::set_tickless(true);
+    uart_print("[AegisOS] tickless idle enabled\n");
 
     uart_print("[AegisOS] enhanced panic handler ready\n");
     uart_print("[AegisOS] klog ready\n");
     uart_print("[AegisOS] safety audit complete\n");
+    // Every task's tables against the mapping policy — halts on a violation
+    aegis_os::ptcheck::self_audit();
+    uart_print("[AegisOS] page tables verified (");
+    aegis_os::uart_print_dec(sched::NUM_TASKS as u64);
+    uart_print(" address spaces)\n");
     uart_print("[AegisOS] bootstrapping into uart_driver (EL0)...\n");
     sched::bootstrap();
 }
@@ -603,6 +784,9 @@ fn panic(info: &PanicInfo) -> ! {
     aegis_os::uart_print_hex(far);
     uart_print("\n");
 
+    // Queue fill levels (overflow / stuck consumer diagnosis)
+    aegis_os::queue::print_queue_levels();
+
     loop {
         // SAFETY: wfe is a hint instruction, safe at EL1
         unsafe { core::arch::asm!("wfe", options(nomem, nostack)) };
@@ -61,8 +61,8 @@ pub const DEVICE_BLOCK: u64 = BLOCK | ATTR_DEVICE | AP_RW_EL1 | AF | XN;
 /// Device MMIO for EL0: Device-nGnRnE, RW for EL0+EL1, non-executable, AF=1
 pub const DEVICE_BLOCK_EL0: u64 = BLOCK | ATTR_DEVICE | AP_RW_EL0 | AF | XN;
 
-/// Normal RAM: Write-Back, RW, Inner Shareable, AF=1
-pub const RAM_BLOCK: u64 = BLOCK | ATTR_NORMAL_WB | AP_RW_EL1 | SH_INNER | AF;
+/// Normal RAM: Write-Back, RW, Inner Shareable, non-executable, AF=1
+pub const RAM_BLOCK: u64 = BLOCK | ATTR_NORMAL_WB | AP_RW_EL1 | SH_INNER | AF | XN;
 
 /// Kernel code page: Normal WB, RO, executable, Inner Shareable, AF=1
 #[allow(dead_code)]
@@ -77,6 +77,9 @@ pub const KERNEL_DATA_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RW_EL1 | SH_INNER |
 /// User data/stack page: Normal WB, RW (EL0+EL1), non-executable
 pub const USER_DATA_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RW_EL0 | SH_INNER | AF | XN;
 
+/// User read-only data page: Normal WB, RO (EL0+EL1), non-executable
+pub const USER_RO_DATA_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RO_EL0 | SH_INNER | AF | XN;
+
 /// User code page: Normal WB, RO (EL0+EL1), EL0-executable (UXN=0), PXN=1
 #[allow(dead_code)]
 pub const USER_CODE_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RO_EL0 | SH_INNER | AF | PXN;
@@ -88,6 +91,7 @@ pub const SHARED_CODE_PAGE: u64 = PAGE | ATTR_NORMAL_WB | AP_RO_EL0 | SH_INNER |
 
 /// Number of page table pages: 4 per task + 4 kernel.
 pub const NUM_PAGE_TABLE_PAGES: usize = 4 * NUM_TASKS + 4;
+const _: () = assert!(NUM_PAGE_TABLE_PAGES == crate::sysconf::PAGE_TABLE_PAGES);
 
 // ─── Page table type and computed indexing (Phase N) ───────────────
 
@@ -133,13 +137,11 @@ pub struct DeviceInfo {
     pub name: &'static str,
 }
 
-/// Device table — device_id indexes into this array.
-pub const DEVICES: &[DeviceInfo] = &[
-    DeviceInfo { l2_index: 72, intid: 33, name: "UART0" }, // device_id=0
-];
+/// Device table — device_id indexes into this array (system.toml [[device]]).
+pub const DEVICES: &[DeviceInfo] = crate::sysconf::DEVICES;
 
 /// Maximum device_id (for host tests)
-pub const MAX_DEVICE_ID: usize = 0;
+pub const MAX_DEVICE_ID: usize = DEVICES.len() - 1;
 
 // Error codes for map_device_for_task
 pub const DEVICE_MAP_ERR_INVALID_ID: u64 = 0xFFFF_2001;
@@ -170,6 +172,18 @@ pub fn map_device_for_task(device_id: u64, task_id: usize) -> u64 {
     0 // success
 }
 
+/// Host-test stub for unmap_device_for_task
+pub fn unmap_device_for_task(device_id: u64, task_id: usize) -> u64 {
+    let did = device_id as usize;
+    if did >= DEVICES.len() {
+        return DEVICE_MAP_ERR_INVALID_ID;
+    }
+    if task_id >= NUM_TASKS {
+        return DEVICE_MAP_ERR_INVALID_TASK;
+    }
+    0 // success
+}
+
 // ─── Phase L4: Page attribute manipulation ─────────────────────────
 
 /// Error: invalid task_id for set_page_attr
@@ -38,14 +38,14 @@ pub const TIMER_FREQ_HZ: u64 = 62_500_000;
 
 // ─── ELF Load Region (Phase O) ────────────────────────────────────
 
-/// Base address for ELF load region (matches linker.ld .elf_load)
-pub const ELF_LOAD_BASE: u64 = 0x4010_0000;
+/// Base address for ELF load region (system.toml; linker.ld .elf_load)
+pub const ELF_LOAD_BASE: u64 = crate::sysconf::ELF_LOAD_BASE;
 
 /// Per-task ELF slot size (16 KiB)
-pub const ELF_LOAD_SIZE_PER_TASK: usize = 16 * 1024;
+pub const ELF_LOAD_SIZE_PER_TASK: usize = crate::sysconf::ELF_SLOT_SIZE;
 
-/// Number of ELF task slots (tasks 2–7, slot 0..5)
-pub const MAX_ELF_TASKS: usize = 6;
+/// Number of ELF load slots
+pub const MAX_ELF_TASKS: usize = crate::sysconf::ELF_SLOTS;
 
 /// First task ID that can hold an ELF binary (tasks 0–1 are kernel tasks)
 pub const ELF_FIRST_TASK_ID: usize = 2;
@@ -0,0 +1,8 @@
+//! System configuration — generated by build.rs from `system.toml`.
+//!
+//! Kernel constants that size static tables (task count, ELF slots, grant
+//! pages, device whitelist) are taken from here, so the kernel, linker.ld
+//! and the task table in main.rs are always built from the same file.
+//! Edit `system.toml`, never the generated source.
+
+include!(concat!(env!("OUT_DIR"), "/sysconf.rs"));
@@ -19,8 +19,17 @@ use aegis_os::exception::{TrapFrame, TRAPFRAME_SIZE, validate_write_args};
 use aegis_os::mmu;
 use aegis_os::sched::{
     self, TaskState, Tcb, EMPTY_TCB, NUM_TASKS, IDLE_TASK_ID, RESTART_DELAY_TICKS,
+    PartitionSchedule, PartitionWindow,
+    PeriodicParams, SchedPolicy, AdmissionError, APERIODIC,
 };
 use aegis_os::ipc::{self, EMPTY_EP, MAX_ENDPOINTS, MSG_REGS};
+use aegis_os::queue::{self, OverflowPolicy, QueueConfig};
+use aegis_os::sampling::{self, SamplingConfig};
+use aegis_os::audit::{self, AuditRecord, DenyPolicy, DenyReason, AUDIT_CAPACITY};
+use aegis_os::cspace::{
+    self, Authority, CapSlot, ObjKind, SlotError, SlotRef, CPTR_FLAG, EMPTY_CSPACE, RIGHT_GRANT,
+    RIGHT_READ, RIGHT_RECV, RIGHT_SEND, RIGHT_WRITE,
+};
 use aegis_os::cap::{
     self, CAP_IPC_SEND_EP0, CAP_IPC_RECV_EP0,
     CAP_IPC_SEND_EP1, CAP_IPC_RECV_EP1, CAP_WRITE, CAP_YIELD,
@@ -32,9 +41,23 @@ use aegis_os::cap::{
     CAP_DEVICE_MAP,
     CAP_HEARTBEAT,
     CAP_EXIT,
+    CAP_SLEEP,
+    CAP_REPLY,
+    CAP_QUEUE_SEND_Q0, CAP_QUEUE_RECV_Q0, CAP_QUEUE_SEND_Q1, CAP_QUEUE_RECV_Q1,
+    CAP_SAMPLE_WRITE_P0, CAP_SAMPLE_READ_P0, CAP_SAMPLE_WRITE_P1, CAP_SAMPLE_READ_P1,
+    CAP_DELEGATE,
+    CAP_AUDIT,
+    CAP_MAP_ANON,
+    CAP_PAGE_PROTECT,
+    CAP_MAP_DUMP,
+    CAP_IPC, CAP_QUEUE, CAP_SAMPLE,
     CAP_ALL, CAP_NONE,
 };
 use aegis_os::grant::{self, EMPTY_GRANT, MAX_GRANTS};
+use aegis_os::frame;
+use aegis_os::protect;
+use aegis_os::ptcheck::{self, MemLayout, TaskMemory, TaskTables, ViolationKind};
+use aegis_os::memmap::{Access, MapRange, MapWalker, MemAttr, Owner};
 use aegis_os::irq::{self, EMPTY_BINDING, MAX_IRQ_BINDINGS};
 use aegis_os::elf::{self, ElfError, ElfLoadError, ElfSegment, ElfInfo, MAX_SEGMENTS, PF_R, PF_W, PF_X};
 use aegis_os::cell::KernelCell;
@@ -80,10 +103,49 @@ unsafe fn reset_test_state() {
         (*grant::GRANTS.get_mut())[i] = EMPTY_GRANT;
     }
 
+    // Reset frame pool ownership
+    *frame::FRAME_OWNER.get_mut() = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+
+    // Reset ELF loader page templates
+    *elf::ELF_TEMPLATES.get_mut() = [[0; elf::ELF_SLOT_PAGES]; aegis_os::platform::qemu_virt::MAX_ELF_TASKS];
+
     // Reset IRQ bindings
     for i in 0..MAX_IRQ_BINDINGS {
         (*irq::IRQ_BINDINGS.get_mut())[i] = EMPTY_BINDING;
     }
+
+    // Reset partition schedule (single window, partition 0)
+    *sched::PARTITION_SCHEDULE.get_mut() = sched::DEFAULT_PARTITION_SCHEDULE;
+
+    // Reset scheduling class (fixed priority)
+    *sched::SCHED_POLICY.get_mut() = sched::SchedPolicy::FixedPriority;
+
+    // Reset epoch counter and tickless mode
+    *sched::EPOCH_TICKS.get_mut() = 0;
+    *aegis_os::timer::TICKLESS.get_mut() = false;
+
+    // Reset sleep timer queue
+    *aegis_os::timer::SLEEP_QUEUE.get_mut() = aegis_os::timer::TimerQueue::new();
+
+    // Reset message queues (unconfigured)
+    for i in 0..queue::MAX_QUEUES {
+        (*queue::QUEUES.get_mut())[i] = queue::EMPTY_QUEUE;
+    }
+
+    // Reset sampling ports (unconfigured)
+    for i in 0..sampling::MAX_SAMPLING_PORTS {
+        (*sampling::SAMPLING_PORTS.get_mut())[i] = sampling::EMPTY_PORT;
+    }
+
+    // Reset capability spaces (no slots)
+    for i in 0..NUM_TASKS {
+        (*cspace::CSPACES.get_mut())[i] = EMPTY_CSPACE;
+        (*cspace::DERIVATION.get_mut())[i] = [None; cspace::CSPACE_SLOTS];
+    }
+
+    // Reset audit log and deny policy
+    *audit::AUDIT.get_mut() = audit::EMPTY_AUDIT_LOG;
+    *audit::DENY_POLICY.get_mut() = DenyPolicy::Fault;
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -393,6 +455,8 @@ fn sched_task_state_values() {
     assert_eq!(TaskState::Running as u8, 2);
     assert_eq!(TaskState::Blocked as u8, 3);
     assert_eq!(TaskState::Faulted as u8, 4);
+    assert_eq!(TaskState::Exited as u8, 5);
+    assert_eq!(TaskState::Sleeping as u8, 6);
 }
 
 #[test]
@@ -744,6 +808,18 @@ fn cap_bits_are_distinct_powers_of_two() {
         CAP_IRQ_BIND, CAP_IRQ_ACK,
         CAP_DEVICE_MAP,
         CAP_HEARTBEAT,
+        CAP_SLEEP,
+        CAP_REPLY,
+        CAP_QUEUE_SEND_Q0, CAP_QUEUE_RECV_Q0,
+        CAP_QUEUE_SEND_Q1, CAP_QUEUE_RECV_Q1,
+        CAP_SAMPLE_WRITE_P0, CAP_SAMPLE_READ_P0,
+        CAP_SAMPLE_WRITE_P1, CAP_SAMPLE_READ_P1,
+        CAP_DELEGATE,
+        CAP_AUDIT,
+        CAP_MAP_ANON,
+        CAP_PAGE_PROTECT,
+        CAP_MAP_DUMP,
+        CAP_IPC, CAP_QUEUE, CAP_SAMPLE,
     ];
     // Each must be a single bit (power of 2)
     for &c in &all {
@@ -1275,7 +1351,7 @@ fn grant_create_success() {
         assert_eq!(result, 0, "grant_create should return 0 on success");
         assert!((*grant::GRANTS.get_mut())[0].active, "grant 0 should be active");
         assert_eq!((*grant::GRANTS.get_mut())[0].owner, Some(0));
-        assert_eq!((*grant::GRANTS.get_mut())[0].peer, Some(1));
+        assert_eq!((*grant::GRANTS.get_mut())[0].peers, 1 << 1);
         assert_ne!((*grant::GRANTS.get_mut())[0].phys_addr, 0, "phys_addr should be non-zero");
     }
 }
@@ -1326,7 +1402,7 @@ fn grant_revoke_by_owner() {
         let r = grant::grant_revoke(0, 0);
         assert_eq!(r, 0, "owner should be able to revoke");
         assert!(!(*grant::GRANTS.get_mut())[0].active, "grant should be inactive after revoke");
-        assert_eq!((*grant::GRANTS.get_mut())[0].peer, None, "peer should be None after revoke");
+        assert_eq!((*grant::GRANTS.get_mut())[0].peers, 0, "no peers after revoke");
         // Owner is still recorded so it can re-create later
     }
 }
@@ -1370,10 +1446,10 @@ fn grant_cleanup_owner_faulted() {
         grant::cleanup_task(0);
         assert!(!(*grant::GRANTS.get_mut())[0].active);
         assert_eq!((*grant::GRANTS.get_mut())[0].owner, None);
-        assert_eq!((*grant::GRANTS.get_mut())[0].peer, None);
+        assert_eq!((*grant::GRANTS.get_mut())[0].peers, 0);
         assert!(!(*grant::GRANTS.get_mut())[1].active);
         assert_eq!((*grant::GRANTS.get_mut())[1].owner, None);
-        assert_eq!((*grant::GRANTS.get_mut())[1].peer, None);
+        assert_eq!((*grant::GRANTS.get_mut())[1].peers, 0);
     }
 }
 
@@ -1382,36 +1458,735 @@ fn grant_cleanup_peer_faulted() {
     unsafe {
         reset_test_state();
         grant::grant_create(0, 0, 1);
-        // Task 1 (peer) faults — peer access removed, grant deactivated
+        // Task 1 (peer) faults — peer access removed, grant kept for the owner
         grant::cleanup_task(1);
-        assert!(!(*grant::GRANTS.get_mut())[0].active, "grant should be inactive after peer fault");
-        assert_eq!((*grant::GRANTS.get_mut())[0].peer, None, "peer should be None");
+        assert!((*grant::GRANTS.get_mut())[0].active, "grant survives a peer fault");
+        assert_eq!((*grant::GRANTS.get_mut())[0].peers, 0, "faulted peer dropped");
         // Owner field preserved (EMPTY_GRANT clears it only when owner faults)
         assert_eq!((*grant::GRANTS.get_mut())[0].owner, Some(0), "owner should be preserved");
     }
 }
 
 #[test]
-fn grant_page_addr_valid() {
-    // On host, returns fake addresses in 0x4010_0000 range
-    assert!(grant::grant_page_addr(0).is_some());
-    assert!(grant::grant_page_addr(1).is_some());
-    let a0 = grant::grant_page_addr(0).unwrap();
-    let a1 = grant::grant_page_addr(1).unwrap();
-    assert_ne!(a0, a1, "each grant page should have a distinct address");
-    assert_eq!(a1 - a0, 4096, "grant pages should be 4KB apart");
+fn grant_base_follows_active_grant() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(grant::grant_base(0), None, "inactive grant has no region");
+        grant::grant_create(0, 0, 1);
+        grant::grant_create(1, 0, 2);
+        let a0 = grant::grant_base(0).unwrap();
+        let a1 = grant::grant_base(1).unwrap();
+        assert_eq!(a0, grant::grant_pool_base(), "first fit starts at the pool base");
+        assert_eq!(a1 - a0, 4096, "next page of the pool");
+        assert_eq!(grant::grant_base(MAX_GRANTS), None);
+    }
+}
+
+#[test]
+fn grant_region_multi_page_aligned() {
+    unsafe {
+        reset_test_state();
+        let base = grant::grant_pool_base();
+        assert_eq!(grant::grant_create(0, 0, 1), 0);
+        // 2 pages aligned to 2 pages skip the hole at page 1
+        assert_eq!(grant::grant_create_region(1, 2, 3, 2, 2, 0), 0);
+        assert_eq!(grant::grant_base(1), Some(base + 2 * 4096));
+        assert_eq!((*grant::GRANTS.get()).get(1).map(|g| g.pages), Some(2));
+        // Unaligned request fills the hole at page 1
+        assert_eq!(grant::grant_create_region(2, 2, 3, 1, 1, 0), 0);
+        assert_eq!(grant::grant_base(2), Some(base + 4096));
+    }
+}
+
+#[test]
+fn grant_region_errors() {
+    unsafe {
+        reset_test_state();
+        let pool = grant::GRANT_POOL_PAGES;
+        assert_eq!(grant::grant_create_region(0, 0, 1, 0, 1, 0), grant::ERR_GRANT_SIZE);
+        assert_eq!(grant::grant_create_region(0, 0, 1, pool + 1, 1, 0), grant::ERR_GRANT_SIZE);
+        assert_eq!(grant::grant_create_region(0, 0, 1, 1, 3, 0), grant::ERR_GRANT_SIZE, "alignment must be a power of two");
+        assert!(!(*grant::GRANTS.get())[0].active, "failed create leaves the slot free");
+
+        // Pages 0-2 taken, then page 1 freed
+        assert_eq!(grant::grant_create(0, 0, 1), 0);
+        assert_eq!(grant::grant_create(1, 0, 1), 0);
+        assert_eq!(grant::grant_create(2, 0, 1), 0);
+        grant::grant_revoke(1, 0);
+        assert_eq!(grant::grant_create_region(1, 0, 1, pool - 3, 1, 0), 0, "the run from page 3 fits");
+        // One page free (page 1), two requested
+        assert_eq!(grant::grant_create_region(3, 0, 1, 2, 1, 0), grant::ERR_GRANT_EXHAUSTED);
+        grant::grant_revoke(0, 0);
+        // Pages 0 and 1 free now — contiguous
+        assert_eq!(grant::grant_create_region(3, 0, 1, 2, 1, 0), 0);
+        grant::grant_revoke(3, 0);
+        grant::grant_revoke(1, 0);
+        // Pages 0,1 and 3.. free but page 2 held: the whole pool cannot fit
+        assert_eq!(grant::grant_create_region(3, 0, 1, pool - 1, 1, 0), grant::ERR_GRANT_FRAGMENTED);
+    }
+}
+
+#[test]
+fn grant_peer_fault_keeps_region_for_owner() {
+    unsafe {
+        reset_test_state();
+        let pool = grant::GRANT_POOL_PAGES;
+        assert_eq!(grant::grant_create_region(0, 0, 1, pool, 1, 0), 0);
+        // Peer fault: the owner keeps the region, and may share it again
+        grant::cleanup_task(1);
+        assert_eq!((*grant::GRANTS.get())[0].pages, pool);
+        assert_eq!(grant::grant_create(1, 0, 2), grant::ERR_GRANT_EXHAUSTED, "region still held");
+        assert_eq!(grant::grant_revoke(0, 2), grant::ERR_GRANT_NOT_OWNER);
+        assert_eq!(grant::grant_add_peer(0, 0, 1, 0), 0, "restarted peer re-added");
+        assert_eq!(grant::grant_revoke(0, 0), 0);
+        assert_eq!((*grant::GRANTS.get())[0].pages, 0);
+        assert_eq!(grant::grant_create_region(1, 0, 2, pool, 1, 0), 0);
+    }
+}
+
+#[test]
+fn grant_broadcast_add_remove_peers() {
+    use grant::GrantPerm::{ReadOnly, ReadWrite};
+    unsafe {
+        reset_test_state();
+        // One navigation buffer, three consumers: two read-only, one read-write
+        assert_eq!(grant::grant_create_region(0, 0, 1, 2, 1, grant::GRANT_PEER_RO), 0);
+        assert_eq!(grant::grant_add_peer(0, 0, 2, grant::GRANT_PEER_RO), 0);
+        assert_eq!(grant::grant_add_peer(0, 0, 3, 0), 0);
+        let g = (*grant::GRANTS.get())[0];
+        assert_eq!(g.peers, 0b1110);
+        assert_eq!((g.peer_perm(1), g.peer_perm(2), g.peer_perm(3)), (Some(ReadOnly), Some(ReadOnly), Some(ReadWrite)));
+        assert_eq!(g.peer_perm(0), None, "the owner is not a peer");
+        assert_eq!(grant::grant_base(0), Some(grant::grant_pool_base()), "one region for all");
+
+        // Re-adding a peer changes its access only
+        assert_eq!(grant::grant_add_peer(0, 0, 3, grant::GRANT_PEER_RO), 0);
+        assert_eq!((*grant::GRANTS.get())[0].peers_ro, 0b1110);
+
+        // Removing one peer leaves the others mapped and the grant active
+        assert_eq!(grant::grant_remove_peer(0, 0, 2), 0);
+        let g = (*grant::GRANTS.get())[0];
+        assert!(g.active);
+        assert_eq!((g.peers, g.peers_ro), (0b1010, 0b1010));
+        assert_eq!(grant::grant_remove_peer(0, 0, 2), grant::ERR_GRANT_NOT_PEER);
+    }
+}
+
+#[test]
+fn grant_peer_update_errors() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(grant::grant_add_peer(MAX_GRANTS, 0, 1, 0), grant::ERR_GRANT_INVALID_ID);
+        assert_eq!(grant::grant_add_peer(0, 0, 1, 0), grant::ERR_GRANT_INACTIVE);
+        assert_eq!(grant::grant_remove_peer(0, 0, 1), grant::ERR_GRANT_INACTIVE);
+        assert_eq!(grant::grant_create(0, 0, 1), 0);
+        assert_eq!(grant::grant_add_peer(0, 1, 2, 0), grant::ERR_GRANT_NOT_OWNER, "a peer cannot share on");
+        assert_eq!(grant::grant_remove_peer(0, 1, 1), grant::ERR_GRANT_NOT_OWNER);
+        assert_eq!(grant::grant_add_peer(0, 0, 0, 0), grant::ERR_GRANT_SELF);
+        assert_eq!(grant::grant_add_peer(0, 0, sched::NUM_TASKS, 0), grant::ERR_GRANT_INVALID_PEER);
+        assert_eq!(grant::grant_add_peer(0, 0, 2, grant::GRANT_OWNER_RO), grant::ERR_GRANT_FLAGS,
+            "the owner's access is fixed at create");
+        assert_eq!((*grant::GRANTS.get())[0].peers, 1 << 1, "failed updates change nothing");
+    }
+}
+
+#[test]
+fn grant_cleanup_drops_only_faulting_peer() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(grant::grant_create(0, 0, 1), 0);
+        assert_eq!(grant::grant_add_peer(0, 0, 2, grant::GRANT_PEER_RO), 0);
+        assert_eq!(grant::grant_add_peer(0, 0, 3, 0), 0);
+        grant::cleanup_task(2);
+        let g = (*grant::GRANTS.get())[0];
+        assert!(g.active, "grant alive for the other peers");
+        assert_eq!((g.peers, g.peers_ro), (0b1010, 0));
+        // The owner faulting still tears the whole grant down
+        grant::cleanup_task(0);
+        assert!(!(*grant::GRANTS.get())[0].active);
+        assert_eq!((*grant::GRANTS.get())[0].peers, 0);
+    }
+}
+
+/// Fill `len` bytes of the grant pool at `addr` with `byte`.
+unsafe fn fill_pool(addr: u64, len: usize, byte: u8) {
+    let p = grant::pool_ptr(addr).expect("address in the grant pool");
+    for i in 0..len {
+        *p.add(i) = byte;
+    }
+}
+
+/// Whether all `len` bytes of the grant pool at `addr` equal `byte`.
+unsafe fn pool_is(addr: u64, len: usize, byte: u8) -> bool {
+    let p = grant::pool_ptr(addr).expect("address in the grant pool");
+    (0..len).all(|i| *p.add(i) == byte)
+}
+
+#[test]
+fn grant_revoke_scrubs_region() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(grant::grant_create_region(0, 0, 1, 2, 1, 0), 0);
+        assert_eq!(grant::grant_create(1, 2, 3), 0);
+        let (base0, base1) = (grant::grant_base(0).unwrap(), grant::grant_base(1).unwrap());
+        fill_pool(base0, 2 * grant::GRANT_PAGE_SIZE, 0x5A);
+        fill_pool(base1, grant::GRANT_PAGE_SIZE, 0x5A);
+        assert_eq!(grant::grant_revoke(0, 0), 0);
+        assert!(pool_is(base0, 2 * grant::GRANT_PAGE_SIZE, grant::GRANT_SCRUB), "both pages scrubbed");
+        assert!(pool_is(base1, grant::GRANT_PAGE_SIZE, 0x5A), "other regions untouched");
+        // The next grant over the same pages starts clean
+        assert_eq!(grant::grant_create(0, 4, 5), 0);
+        assert_eq!(grant::grant_base(0), Some(base0));
+        assert!(pool_is(base0, grant::GRANT_PAGE_SIZE, grant::GRANT_SCRUB));
+        // A refused revoke scrubs nothing
+        fill_pool(base1, grant::GRANT_PAGE_SIZE, 0x5A);
+        assert_eq!(grant::grant_revoke(1, 3), grant::ERR_GRANT_NOT_OWNER);
+        assert!(pool_is(base1, grant::GRANT_PAGE_SIZE, 0x5A));
+    }
+}
+
+#[test]
+fn grant_cleanup_scrubs_only_on_owner_fault() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(grant::grant_create(0, 0, 1), 0);
+        let base = grant::grant_base(0).unwrap();
+        fill_pool(base, grant::GRANT_PAGE_SIZE, 0x5A);
+        grant::cleanup_task(1);
+        assert!(pool_is(base, grant::GRANT_PAGE_SIZE, 0x5A), "owner keeps its data when a peer faults");
+        grant::cleanup_task(0);
+        assert!(pool_is(base, grant::GRANT_PAGE_SIZE, grant::GRANT_SCRUB), "owner fault scrubs the region");
+    }
+}
+
+#[test]
+fn grant_scrub_pattern_and_bounds() {
+    unsafe {
+        let pool = grant::grant_pool_base();
+        let size = grant::GRANT_POOL_PAGES * grant::GRANT_PAGE_SIZE;
+        assert!(grant::pool_ptr(pool - 1).is_none());
+        assert!(grant::pool_ptr(pool + size as u64).is_none());
+        assert!(grant::pool_ptr(pool + size as u64 - 1).is_some());
+        // A pattern fill makes use-after-revoke reads recognisable
+        grant::scrub_region(pool, 1, 0xA5);
+        assert!(pool_is(pool, grant::GRANT_PAGE_SIZE, 0xA5));
+        // Ranges not wholly inside the pool are ignored
+        fill_pool(pool, size, 0x5A);
+        grant::scrub_region(pool + grant::GRANT_PAGE_SIZE as u64, grant::GRANT_POOL_PAGES, 0);
+        grant::scrub_region(pool, 0, 0);
+        assert!(pool_is(pool, size, 0x5A));
+        // Boot scrubs the whole pool
+        grant::init();
+        assert!(pool_is(pool, size, grant::GRANT_SCRUB));
+    }
+}
+
+// ─── Anonymous memory (frame pool) ─────────────────────────────────
+
+/// Whether all bytes of `pages` frames at `addr` are zero.
+unsafe fn frames_zeroed(addr: u64, pages: usize) -> bool {
+    let p = frame::frame_ptr(addr).expect("address in the frame pool");
+    (0..pages * frame::FRAME_SIZE).all(|i| *p.add(i) == 0)
+}
+
+#[test]
+fn frame_map_anon_zeroes_and_records_owner() {
+    unsafe {
+        reset_test_state();
+        let pool = frame::frame_pool_base();
+        // Leave stale data behind, as a previous owner would
+        core::ptr::write_bytes(frame::frame_ptr(pool).unwrap(), 0x5A, frame::FRAME_POOL_PAGES * frame::FRAME_SIZE);
+        (*sched::TCBS.get_mut())[2].mem_quota = 3;
+        (*sched::TCBS.get_mut())[4].mem_quota = 2;
+        assert_eq!(frame::map_anon(2, 2), Ok(pool));
+        assert_eq!(frame::map_anon(4, 2), Ok(pool + 2 * frame::FRAME_SIZE as u64), "first fit after task 2");
+        assert!(frames_zeroed(pool, 4));
+        assert_eq!(*frame::frame_ptr(pool + 4 * frame::FRAME_SIZE as u64).unwrap(), 0x5A, "unmapped frames untouched");
+        let owners = *frame::FRAME_OWNER.get();
+        assert_eq!(&owners[..5], &[2, 2, 4, 4, frame::FRAME_FREE]);
+        assert_eq!(frame::frames_held(&owners, 2), 2);
+        assert_eq!(frame::map_anon(2, 1), Ok(pool + 4 * frame::FRAME_SIZE as u64), "quota allows one more");
+    }
+}
+
+#[test]
+fn frame_map_anon_errors() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[1].mem_quota = frame::FRAME_POOL_PAGES;
+        assert_eq!(frame::map_anon(1, 0), Err(frame::ERR_FRAME_SIZE));
+        assert_eq!(frame::map_anon(1, frame::FRAME_POOL_PAGES + 1), Err(frame::ERR_FRAME_SIZE));
+        assert_eq!(frame::map_anon(2, 1), Err(frame::ERR_FRAME_QUOTA), "no quota, no frames");
+        // Fragment the pool: every other frame held
+        let pool = frame::frame_pool_base();
+        for i in 0..frame::FRAME_POOL_PAGES {
+            assert_eq!(frame::map_anon(1, 1), Ok(pool + (i * frame::FRAME_SIZE) as u64));
+        }
+        for i in (0..frame::FRAME_POOL_PAGES).step_by(2) {
+            assert_eq!(frame::unmap(1, pool + (i * frame::FRAME_SIZE) as u64, 1), 0);
+        }
+        assert_eq!(frame::map_anon(1, 2), Err(frame::ERR_FRAME_NO_SPACE));
+        assert_eq!(frame::map_anon(1, 1), Ok(pool));
+        assert_eq!(frame::map_anon(1, frame::FRAME_POOL_PAGES / 2), Err(frame::ERR_FRAME_QUOTA));
+    }
+}
+
+#[test]
+fn frame_unmap_only_own_whole_frames() {
+    unsafe {
+        reset_test_state();
+        let pool = frame::frame_pool_base();
+        let page = frame::FRAME_SIZE as u64;
+        (*sched::TCBS.get_mut())[1].mem_quota = 2;
+        (*sched::TCBS.get_mut())[2].mem_quota = 1;
+        assert_eq!(frame::map_anon(1, 2), Ok(pool));
+        assert_eq!(frame::map_anon(2, 1), Ok(pool + 2 * page));
+        assert_eq!(frame::unmap(2, pool, 1), frame::ERR_FRAME_NOT_OWNER, "another task's frame");
+        assert_eq!(frame::unmap(1, pool + page, 2), frame::ERR_FRAME_NOT_OWNER, "runs into task 2's frame");
+        assert_eq!(frame::unmap(1, pool + 8, 1), frame::ERR_FRAME_NOT_OWNER, "not page aligned");
+        assert_eq!(frame::unmap(1, pool - page, 1), frame::ERR_FRAME_NOT_OWNER, "below the pool");
+        assert_eq!(frame::unmap(1, pool, 0), frame::ERR_FRAME_SIZE);
+        assert_eq!(frame::frames_held(frame::FRAME_OWNER.get(), 1), 2, "failed unmaps change nothing");
+        assert_eq!(frame::unmap(1, pool + page, 1), 0);
+        assert_eq!(frame::frames_held(frame::FRAME_OWNER.get(), 1), 1);
+        assert_eq!(frame::map_anon(1, 1), Ok(pool + page), "freed frame and quota reusable");
+    }
+}
+
+#[test]
+fn frame_cleanup_reclaims_task_frames() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[1].mem_quota = 2;
+        (*sched::TCBS.get_mut())[2].mem_quota = 2;
+        assert!(frame::map_anon(1, 2).is_ok());
+        assert!(frame::map_anon(2, 1).is_ok());
+        sched::cleanup_task_resources(1);
+        let owners = *frame::FRAME_OWNER.get();
+        assert_eq!(frame::frames_held(&owners, 1), 0);
+        assert_eq!(frame::frames_held(&owners, 2), 1, "other tasks keep their frames");
+        // The restarted task gets its whole quota back
+        assert!(frame::map_anon(1, 2).is_ok());
+        assert_eq!(cap::cap_for_syscall(29, 0), CAP_MAP_ANON);
+        assert_eq!(cap::cap_for_syscall(30, 0), CAP_MAP_ANON);
+        assert_eq!(cap::cap_name(CAP_MAP_ANON), "MAP_ANON");
+    }
+}
+
+// ─── Page-table checker ────────────────────────────────────────────
+
+/// Fake kernel layout for checker tests (no region overlaps an ELF slot).
+const PT_LAYOUT: MemLayout = MemLayout {
+    text: (0x4008_0000, 0x4009_0000),
+    rodata: (0x4009_0000, 0x400A_0000),
+    user_stacks: 0x400C_0000,
+    ipc_buffers: 0x400E_0000,
+    grant_pool: 0x400F_0000,
+    frame_pool: 0x4014_0000,
+};
+
+/// Tables as build_l3 / build_l2_ram / build_l2_device lay them out for
+/// `task`, plus its IPC alias, one frame-pool page and the UART for EL0.
+fn pt_clean_tables(task: usize) -> ([u64; 512], [u64; 512], [u64; 512]) {
+    let mut l2_device = [0u64; 512];
+    for i in 64..=72 {
+        l2_device[i] = (i as u64 * 0x20_0000) | mmu::DEVICE_BLOCK;
+    }
+    l2_device[72] = 0x0900_0000 | mmu::DEVICE_BLOCK_EL0;
+    let mut l2_ram = [0u64; 512];
+    l2_ram[0] = 0x8000_3000 | mmu::TABLE;
+    for i in 1..64 {
+        l2_ram[i] = (0x4000_0000 + i as u64 * 0x20_0000) | mmu::RAM_BLOCK;
+    }
+    let mut l3 = [0u64; 512];
+    let stack_top = sched::stack_top(PT_LAYOUT.user_stacks, task);
+    for (i, e) in l3.iter_mut().enumerate() {
+        let pa = 0x4000_0000 + i as u64 * 4096;
+        *e = if pa >= PT_LAYOUT.text.0 && pa < PT_LAYOUT.text.1 {
+            pa | mmu::SHARED_CODE_PAGE
+        } else if pa >= PT_LAYOUT.rodata.0 && pa < PT_LAYOUT.rodata.1 {
+            pa | mmu::KERNEL_RODATA_PAGE
+        } else if pa == stack_top - 4096 {
+            pa | mmu::USER_DATA_PAGE
+        } else if sched::guard_page_owner(PT_LAYOUT.user_stacks, pa).is_some() {
+            0
+        } else {
+            pa | mmu::KERNEL_DATA_PAGE
+        };
+    }
+    l3[511] = (PT_LAYOUT.ipc_buffers + task as u64 * 4096) | mmu::USER_DATA_PAGE;
+    (l2_device, l2_ram, l3)
+}
+
+/// L3 index of physical page `pa`.
+fn l3_index(pa: u64) -> usize {
+    ((pa - 0x4000_0000) / 4096) as usize
+}
+
+/// Run the checker on `task`'s tables with the given grants and frame owners.
+fn pt_check(
+    task: usize,
+    t: &([u64; 512], [u64; 512], [u64; 512]),
+    grants: &[grant::Grant; MAX_GRANTS],
+    owners: &[u8; frame::FRAME_POOL_PAGES],
+) -> Result<(), ptcheck::Violation> {
+    let tables = TaskTables { l2_device: &t.0, l2_ram: &t.1, l3: &t.2 };
+    let mem = TaskMemory { task, elf_slot: None, grants, frame_owners: owners };
+    ptcheck::check_task_tables(&tables, &mem, &PT_LAYOUT)
+}
+
+#[test]
+fn ptcheck_clean_tables_pass() {
+    let grants = [EMPTY_GRANT; MAX_GRANTS];
+    let mut owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+    for task in 0..NUM_TASKS {
+        assert_eq!(pt_check(task, &pt_clean_tables(task), &grants, &owners), Ok(()), "task {task}");
+    }
+    // An anonymous frame the task holds, mapped read-only (sealed)
+    owners[1] = 2;
+    let mut t = pt_clean_tables(2);
+    t.2[l3_index(PT_LAYOUT.frame_pool + 4096)] = (PT_LAYOUT.frame_pool + 4096) | mmu::USER_RO_DATA_PAGE;
+    assert_eq!(pt_check(2, &t, &grants, &owners), Ok(()));
 }
 
 #[test]
-fn grant_page_addr_invalid() {
-    assert!(grant::grant_page_addr(MAX_GRANTS).is_none());
-    assert!(grant::grant_page_addr(MAX_GRANTS + 1).is_none());
+fn ptcheck_detects_each_rule() {
+    use ptcheck::Violation;
+    let grants = [EMPTY_GRANT; MAX_GRANTS];
+    let owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+    let broken = |task: usize, edit: &dyn Fn(&mut ([u64; 512], [u64; 512], [u64; 512]))| {
+        let mut t = pt_clean_tables(task);
+        edit(&mut t);
+        pt_check(task, &t, &grants, &owners)
+    };
+
+    // W^X: a data page left executable
+    let data = 0x400A_0000;
+    let r = broken(1, &|t| t.2[l3_index(data)] = data | (mmu::KERNEL_DATA_PAGE & !mmu::PXN));
+    assert_eq!(r, Err(Violation { task: 1, kind: ViolationKind::WriteExec, va: data }));
+    // Kernel data and rodata reachable or writable from EL0
+    let r = broken(1, &|t| t.2[l3_index(data)] = data | mmu::USER_DATA_PAGE);
+    assert_eq!(r.unwrap_err().kind, ViolationKind::KernelData);
+    let ro = PT_LAYOUT.rodata.0;
+    let r = broken(1, &|t| t.2[l3_index(ro)] = ro | mmu::USER_DATA_PAGE);
+    assert_eq!(r.unwrap_err().kind, ViolationKind::KernelData);
+    // Someone else's IPC buffer behind the alias
+    let r = broken(1, &|t| t.2[511] = PT_LAYOUT.ipc_buffers | mmu::USER_DATA_PAGE);
+    assert_eq!(r, Err(Violation { task: 1, kind: ViolationKind::KernelData, va: 0x401F_F000 }));
+    // GIC for EL0
+    let r = broken(0, &|t| t.0[64] = 0x0800_0000 | mmu::DEVICE_BLOCK_EL0);
+    assert_eq!(r, Err(Violation { task: 0, kind: ViolationKind::Gic, va: 0x0800_0000 }));
+    // RAM above the L3 range mapped for EL0
+    let r = broken(0, &|t| t.1[5] = (0x4000_0000 + 5 * 0x20_0000) | mmu::DEVICE_BLOCK_EL0);
+    assert_eq!(r.unwrap_err().kind, ViolationKind::KernelData);
+    // Task 2's stack in task 1's table
+    let other = sched::stack_top(PT_LAYOUT.user_stacks, 2) - 4096;
+    let r = broken(1, &|t| t.2[l3_index(other)] = other | mmu::USER_RO_DATA_PAGE);
+    assert_eq!(r, Err(Violation { task: 1, kind: ViolationKind::ForeignStack, va: other }));
+    // Frame held by nobody still mapped for EL0
+    let f = PT_LAYOUT.frame_pool;
+    let r = broken(1, &|t| t.2[l3_index(f)] = f | mmu::USER_DATA_PAGE);
+    assert_eq!(r.unwrap_err().kind, ViolationKind::KernelData);
+}
+
+#[test]
+fn ptcheck_grant_pages_follow_membership() {
+    let owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+    let pool = PT_LAYOUT.grant_pool;
+    let mut grants = [EMPTY_GRANT; MAX_GRANTS];
+    grants[0] = grant::Grant {
+        owner: Some(1),
+        phys_addr: pool,
+        pages: 2,
+        active: true,
+        ..EMPTY_GRANT
+    }
+    .with_peer(2, grant::GrantPerm::ReadOnly)
+    .with_peer(3, grant::GrantPerm::ReadWrite);
+    let mapped = |task: usize, attrs: u64| {
+        let mut t = pt_clean_tables(task);
+        t.2[l3_index(pool)] = pool | attrs;
+        t.2[l3_index(pool + 4096)] = (pool + 4096) | attrs;
+        t
+    };
+
+    assert_eq!(pt_check(1, &mapped(1, mmu::USER_DATA_PAGE), &grants, &owners), Ok(()));
+    assert_eq!(pt_check(2, &mapped(2, mmu::USER_RO_DATA_PAGE), &grants, &owners), Ok(()));
+    assert_eq!(pt_check(3, &mapped(3, mmu::USER_DATA_PAGE), &grants, &owners), Ok(()));
+    let r = pt_check(2, &mapped(2, mmu::USER_DATA_PAGE), &grants, &owners);
+    assert_eq!(r.unwrap_err().kind, ViolationKind::Grant, "read-only side mapped writable");
+    let r = pt_check(4, &mapped(4, mmu::USER_RO_DATA_PAGE), &grants, &owners);
+    assert_eq!(r.unwrap_err().kind, ViolationKind::Grant, "not a member");
+    // After revoke the region must be gone from every table
+    grants[0].active = false;
+    let r = pt_check(1, &mapped(1, mmu::USER_DATA_PAGE), &grants, &owners);
+    assert_eq!(r.unwrap_err().kind, ViolationKind::Grant);
+    assert_eq!(pt_check(1, &mapped(1, mmu::KERNEL_DATA_PAGE), &grants, &owners), Ok(()));
+}
+
+// ─── Address-space dump (memmap) ───────────────────────────────────
+
+/// Decode `task`'s tables into ranges with the given grants and frame owners.
+fn decode_map(
+    task: usize,
+    t: &([u64; 512], [u64; 512], [u64; 512]),
+    grants: &[grant::Grant; MAX_GRANTS],
+    owners: &[u8; frame::FRAME_POOL_PAGES],
+) -> Vec<MapRange> {
+    let tables = TaskTables { l2_device: &t.0, l2_ram: &t.1, l3: &t.2 };
+    let mem = TaskMemory { task, elf_slot: None, grants, frame_owners: owners };
+    MapWalker::new(&tables, &mem, &PT_LAYOUT).collect()
+}
+
+#[test]
+fn memmap_decodes_clean_tables() {
+    let grants = [EMPTY_GRANT; MAX_GRANTS];
+    let owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+    let map = decode_map(1, &pt_clean_tables(1), &grants, &owners);
+
+    // Ranges come out in table order and never overlap within a table
+    for w in map.windows(2) {
+        assert!(w[0].va + w[0].size <= w[1].va || w[1].va < w[0].va, "{:?} / {:?}", w[0], w[1]);
+    }
+    // Only the UART, shared text and rodata, own stack and own IPC buffer reach EL0
+    let user: Vec<_> = map.iter().filter(|r| r.ap.el0()).collect();
+    assert_eq!(user.len(), 5, "{user:?}");
+    assert_eq!(
+        *user[0],
+        MapRange {
+            va: 0x0900_0000,
+            size: 0x20_0000,
+            pa: 0x0900_0000,
+            ap: Access::El0ReadWrite,
+            uxn: true,
+            pxn: true,
+            attr: MemAttr::Device,
+            owner: Owner::Device("UART0"),
+        }
+    );
+    assert_eq!((user[1].va, user[1].size, user[1].owner), (0x4008_0000, 0x1_0000, Owner::Text));
+    assert_eq!((user[1].ap, user[1].uxn, user[1].pxn), (Access::El0ReadOnly, false, false));
+    assert_eq!((user[2].va, user[2].size, user[2].owner), (PT_LAYOUT.rodata.0, 0x1_0000, Owner::Rodata));
+    assert_eq!((user[2].ap, user[2].uxn), (Access::El0ReadOnly, true));
+    let stack = sched::stack_top(PT_LAYOUT.user_stacks, 1) - 4096;
+    assert_eq!((user[3].va, user[3].size, user[3].owner), (stack, 4096, Owner::Stack(1)));
+    assert_eq!((user[4].va, user[4].pa, user[4].owner), (0x401F_F000, PT_LAYOUT.ipc_buffers + 4096, Owner::IpcBuffer(1)));
+
+    // Kernel-only ranges keep their owner
+    let find = |va: u64| map.iter().find(|r| r.va == va).copied().unwrap();
+    assert_eq!(find(0x0800_0000).owner, Owner::Device("GIC"));
+    assert_eq!(find(0x0820_0000).owner, Owner::Device("MMIO"));
+    let data = find(PT_LAYOUT.rodata.1);
+    assert_eq!((data.ap, data.owner), (Access::El1ReadWrite, Owner::Kernel));
+    // RAM blocks above the L3 range merge into one range
+    let ram = find(0x4020_0000);
+    assert_eq!((ram.size, ram.owner, ram.attr), (63 * 0x20_0000, Owner::Ram, MemAttr::NormalWriteBack));
+    assert!(ram.uxn && ram.pxn);
+}
+
+#[test]
+fn memmap_labels_grants_and_frames() {
+    let pool = PT_LAYOUT.grant_pool;
+    let mut grants = [EMPTY_GRANT; MAX_GRANTS];
+    grants[1] = grant::Grant { owner: Some(2), phys_addr: pool, pages: 2, active: true, ..EMPTY_GRANT };
+    let mut owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+    owners[0] = 2;
+    let f = PT_LAYOUT.frame_pool;
+    let mut t = pt_clean_tables(2);
+    t.2[l3_index(pool)] = pool | mmu::USER_DATA_PAGE;
+    t.2[l3_index(pool + 4096)] = (pool + 4096) | mmu::USER_DATA_PAGE;
+    t.2[l3_index(f)] = f | mmu::USER_RO_DATA_PAGE;
+
+    let map = decode_map(2, &t, &grants, &owners);
+    let g = map.iter().find(|r| r.va == pool).unwrap();
+    assert_eq!((g.size, g.ap, g.owner), (2 * 4096, Access::El0ReadWrite, Owner::Grant(1)));
+    let a = map.iter().find(|r| r.va == f).unwrap();
+    assert_eq!((a.size, a.ap, a.owner), (4096, Access::El0ReadOnly, Owner::Frame(2)));
+
+    // Once revoked the pages are plain kernel memory again
+    grants[1].active = false;
+    let map = decode_map(2, &t, &grants, &owners);
+    assert_eq!(map.iter().find(|r| r.va == pool).unwrap().owner, Owner::Kernel);
+}
+
+#[test]
+fn memmap_splits_on_attribute_and_address_changes() {
+    let grants = [EMPTY_GRANT; MAX_GRANTS];
+    let owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+    let text = PT_LAYOUT.text.0;
+    let mut t = pt_clean_tables(0);
+    // One text page sealed differently splits the text run in three
+    t.2[l3_index(text) + 1] = (text + 4096) | mmu::USER_CODE_PAGE;
+    // A hole in the rodata run splits it in two
+    t.2[l3_index(PT_LAYOUT.rodata.0) + 2] = 0;
+    let map = decode_map(0, &t, &grants, &owners);
+
+    let texts: Vec<_> = map.iter().filter(|r| r.owner == Owner::Text).map(|r| (r.va, r.size, r.pxn)).collect();
+    assert_eq!(texts, [(text, 4096, false), (text + 4096, 4096, true), (text + 8192, 0x1_0000 - 8192, false)]);
+    let rodata: Vec<_> = map.iter().filter(|r| r.owner == Owner::Rodata).map(|r| (r.va, r.size)).collect();
+    assert_eq!(rodata, [(PT_LAYOUT.rodata.0, 8192), (PT_LAYOUT.rodata.0 + 3 * 4096, 0x1_0000 - 3 * 4096)]);
+}
+
+// ─── Page protection (SYS_PAGE_PROTECT) ────────────────────────────
+
+/// User stack page of `task` (host stub stack region).
+fn user_stack_page(task: usize) -> u64 {
+    sched::stack_top(sched::stack_region_base(sched::StackKind::User), task) - sched::STACK_SIZE
+}
+
+#[test]
+fn page_protect_templates_are_w_xor_x() {
+    use protect::{el0_executable, el0_writable, protect_template, OwnRegion, PageProt};
+    let rw = protect_template(Some(OwnRegion::Anon), PageProt::ReadWrite).unwrap();
+    let ro = protect_template(Some(OwnRegion::Stack), PageProt::ReadOnly).unwrap();
+    let rx = protect_template(Some(OwnRegion::Elf { text: true }), PageProt::ReadExec).unwrap();
+    assert!(el0_writable(rw) && !el0_executable(rw));
+    assert!(!el0_writable(ro) && !el0_executable(ro));
+    assert!(!el0_writable(rx) && el0_executable(rx));
+    assert_eq!(rx & aegis_os::mmu::PXN, aegis_os::mmu::PXN, "kernel never runs user code");
+    for region in [OwnRegion::Elf { text: false }, OwnRegion::Stack, OwnRegion::Anon] {
+        assert_eq!(protect_template(Some(region), PageProt::ReadExec), Err(protect::ERR_PROT_NO_EXEC));
+    }
+    assert_eq!(protect_template(None, PageProt::ReadOnly), Err(protect::ERR_PROT_NOT_OWNER));
+    assert_eq!(PageProt::from_u64(protect::PROT_RX), Some(PageProt::ReadExec));
+    assert_eq!(PageProt::from_u64(3), None);
+    assert!(!el0_writable(aegis_os::mmu::KERNEL_DATA_PAGE), "EL1-only RW is not EL0-writable");
+}
+
+#[test]
+fn page_protect_text_never_writable() {
+    use aegis_os::platform::qemu_virt::elf_load_addr;
+    use protect::{el0_writable, protect_template, OwnRegion, PageProt};
+    let text = Some(OwnRegion::Elf { text: true });
+    assert_eq!(protect_template(text, PageProt::ReadWrite), Err(protect::ERR_PROT_TEXT_WRITE));
+    assert!(!el0_writable(protect_template(text, PageProt::ReadOnly).unwrap()));
+    assert!(!el0_writable(protect_template(text, PageProt::ReadExec).unwrap()));
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[4].elf_slot = Some(2);
+        (*elf::ELF_TEMPLATES.get_mut())[2] = [mmu::USER_CODE_PAGE, mmu::USER_DATA_PAGE, 0, 0];
+        let slot = elf_load_addr(2);
+        // RX → RO → RW → RX would be a JIT: the RW step is refused
+        assert_eq!(protect::page_protect(4, slot, 1, protect::PROT_RO), 0);
+        assert_eq!(protect::page_protect(4, slot, 1, protect::PROT_RW), protect::ERR_PROT_TEXT_WRITE);
+        assert_eq!(protect::page_protect(4, slot, 2, protect::PROT_RW), protect::ERR_PROT_TEXT_WRITE, "range starting on text");
+        assert_eq!(protect::page_protect(4, slot, 1, protect::PROT_RX), 0);
+        // Data next to it stays RW-able
+        assert_eq!(protect::page_protect(4, slot + 4096, 1, protect::PROT_RW), 0);
+    }
+}
+
+#[test]
+fn page_protect_own_region_bounds() {
+    use protect::{own_region, OwnRegion};
+    use aegis_os::platform::qemu_virt::{elf_load_addr, ELF_LOAD_SIZE_PER_TASK};
+    let mut owners = [frame::FRAME_FREE; frame::FRAME_POOL_PAGES];
+    owners[1] = 4;
+    owners[2] = 3;
+    let pool = frame::frame_pool_base();
+    let page = frame::FRAME_SIZE as u64;
+    let slot2 = elf_load_addr(2);
+    let mut templates = [[0; elf::ELF_SLOT_PAGES]; aegis_os::platform::qemu_virt::MAX_ELF_TASKS];
+    templates[2] = [mmu::USER_CODE_PAGE, mmu::KERNEL_RODATA_PAGE, 0, mmu::USER_DATA_PAGE];
+    let top = sched::stack_top(sched::stack_region_base(sched::StackKind::User), 4);
+    let region = |addr| own_region(4, addr, Some(2), &templates, top, &owners, pool);
+
+    assert_eq!(region(slot2), Some(OwnRegion::Elf { text: true }));
+    assert_eq!(region(slot2 + page), Some(OwnRegion::Elf { text: false }));
+    assert_eq!(region(slot2 + 2 * page), None, "not mapped by the loader");
+    assert_eq!(region(slot2 + ELF_LOAD_SIZE_PER_TASK as u64 - page), Some(OwnRegion::Elf { text: false }));
+    assert_eq!(region(slot2 + ELF_LOAD_SIZE_PER_TASK as u64), None, "next task's slot");
+    assert_eq!(region(elf_load_addr(1)), None);
+    assert_eq!(region(top - page), Some(OwnRegion::Stack));
+    assert_eq!(region(top - 2 * page), None, "guard page");
+    assert_eq!(region(user_stack_page(3)), None, "another task's stack");
+    assert_eq!(region(pool + page), Some(OwnRegion::Anon));
+    assert_eq!(region(pool + 2 * page), None, "another task's frame");
+    assert_eq!(region(pool), None, "free frame");
+    assert_eq!(own_region(4, slot2, None, &templates, top, &owners, pool), None, "kernel-linked task has no ELF slot");
+}
+
+#[test]
+fn page_protect_checks_whole_range() {
+    use aegis_os::platform::qemu_virt::elf_load_addr;
+    unsafe {
+        reset_test_state();
+        let page = frame::FRAME_SIZE as u64;
+        (*sched::TCBS.get_mut())[4].elf_slot = Some(2);
+        (*sched::TCBS.get_mut())[4].mem_quota = 2;
+        let anon = frame::map_anon(4, 2).unwrap();
+        let slot = elf_load_addr(2);
+        (*elf::ELF_TEMPLATES.get_mut())[2] = [mmu::USER_CODE_PAGE, mmu::USER_CODE_PAGE, mmu::USER_DATA_PAGE, 0];
+
+        assert_eq!(protect::page_protect(4, anon, 2, protect::PROT_RO), 0);
+        assert_eq!(protect::page_protect(4, anon, 2, protect::PROT_RW), 0);
+        assert_eq!(protect::page_protect(4, anon, 3, protect::PROT_RO), protect::ERR_PROT_NOT_OWNER, "runs past own frames");
+        assert_eq!(protect::page_protect(4, anon, 1, protect::PROT_RX), protect::ERR_PROT_NO_EXEC);
+        assert_eq!(protect::page_protect(4, user_stack_page(4), 1, protect::PROT_RO), 0);
+        assert_eq!(protect::page_protect(4, user_stack_page(4), 1, protect::PROT_RX), protect::ERR_PROT_NO_EXEC);
+        assert_eq!(protect::page_protect(4, slot, 2, protect::PROT_RO), 0, "drop execute on text");
+        assert_eq!(protect::page_protect(4, slot, 2, protect::PROT_RX), 0);
+        assert_eq!(protect::page_protect(4, slot, 3, protect::PROT_RX), protect::ERR_PROT_NO_EXEC, "runs into data");
+        assert_eq!(protect::page_protect(4, slot + 2 * page, 1, protect::PROT_RO), 0);
+        assert_eq!(protect::page_protect(4, slot + 2 * page, 2, protect::PROT_RO), protect::ERR_PROT_NOT_OWNER, "unmapped slot page");
+        assert_eq!(protect::page_protect(4, slot + 8, 1, protect::PROT_RO), protect::ERR_PROT_NOT_OWNER, "misaligned");
+        assert_eq!(protect::page_protect(4, slot, 0, protect::PROT_RO), protect::ERR_PROT_INVALID);
+        assert_eq!(protect::page_protect(4, slot, 1, 7), protect::ERR_PROT_INVALID);
+        assert_eq!(protect::page_protect(4, u64::MAX - page + 1, 2, protect::PROT_RO), protect::ERR_PROT_NOT_OWNER);
+        assert_eq!(protect::page_protect(3, anon, 1, protect::PROT_RO), protect::ERR_PROT_NOT_OWNER, "not task 3's frame");
+        assert_eq!(protect::page_protect(3, slot, 1, protect::PROT_RO), protect::ERR_PROT_NOT_OWNER, "not task 3's slot");
+        assert_eq!(cap::cap_for_syscall(31, 0), CAP_PAGE_PROTECT);
+        assert_eq!(cap::cap_name(CAP_PAGE_PROTECT), "PAGE_PROTECT");
+    }
+}
+
+#[test]
+fn grant_perm_flags_select_page_attrs() {
+    use grant::GrantPerm::{ReadOnly, ReadWrite};
+    assert_eq!(grant::grant_perms(0), Ok((ReadWrite, ReadWrite)));
+    assert_eq!(grant::grant_perms(grant::GRANT_PEER_RO), Ok((ReadWrite, ReadOnly)));
+    assert_eq!(grant::grant_perms(grant::GRANT_OWNER_RO), Ok((ReadOnly, ReadWrite)));
+    assert_eq!(grant::grant_perms(1 << 2), Err(grant::ERR_GRANT_FLAGS));
+
+    // AP[7:6]: a read-only side is AP_RO_EL0 — an EL0 store faults
+    let ap = |attrs: u64| attrs & (0b11 << 6);
+    assert_eq!(ap(ReadOnly.page_attrs()), mmu::AP_RO_EL0);
+    assert_eq!(ap(ReadWrite.page_attrs()), mmu::AP_RW_EL0);
+    assert_eq!(ReadOnly.page_attrs() & !(0b11 << 6), mmu::USER_DATA_PAGE & !(0b11 << 6),
+        "only the access permission differs");
+}
+
+#[test]
+fn grant_create_records_side_permissions() {
+    use grant::GrantPerm::{ReadOnly, ReadWrite};
+    unsafe {
+        reset_test_state();
+        assert_eq!(grant::grant_create_region(0, 0, 1, 1, 1, 1 << 5), grant::ERR_GRANT_FLAGS);
+        assert!(!(*grant::GRANTS.get())[0].active, "bad flags leave the slot free");
+
+        assert_eq!(grant::grant_create_region(0, 0, 1, 1, 1, grant::GRANT_PEER_RO), 0);
+        let g = (*grant::GRANTS.get())[0];
+        assert_eq!((g.owner_perm, g.peer_perm(1)), (ReadWrite, Some(ReadOnly)));
+        assert_eq!(grant::grant_create(1, 0, 2), 0);
+        let g = (*grant::GRANTS.get())[1];
+        assert_eq!((g.owner_perm, g.peer_perm(2)), (ReadWrite, Some(ReadWrite)), "default is read-write");
+
+        // Revoke forgets the permissions along with the region
+        assert_eq!(grant::grant_revoke(0, 0), 0);
+        assert_eq!((*grant::GRANTS.get())[0].peers_ro, 0);
+        assert_eq!(grant::grant_create_region(0, 0, 1, 1, 1, grant::GRANT_OWNER_RO | grant::GRANT_PEER_RO), 0);
+        let g = (*grant::GRANTS.get())[0];
+        assert_eq!((g.owner_perm, g.peer_perm(1)), (ReadOnly, Some(ReadOnly)));
+    }
 }
 
 #[test]
 fn cap_for_syscall_grant() {
     assert_eq!(cap::cap_for_syscall(7, 0), CAP_GRANT_CREATE);
     assert_eq!(cap::cap_for_syscall(8, 0), CAP_GRANT_REVOKE);
+    // Adding a peer shares like a create, removing one takes back like a revoke
+    assert_eq!(cap::cap_for_syscall(27, 0), CAP_GRANT_CREATE);
+    assert_eq!(cap::cap_for_syscall(28, 0), CAP_GRANT_REVOKE);
 }
 
 #[test]
@@ -1449,7 +2224,7 @@ fn grant_re_create_after_revoke() {
         let r = grant::grant_create(0, 1, 2);
         assert_eq!(r, 0, "re-create after revoke should succeed");
         assert_eq!((*grant::GRANTS.get_mut())[0].owner, Some(1));
-        assert_eq!((*grant::GRANTS.get_mut())[0].peer, Some(2));
+        assert_eq!((*grant::GRANTS.get_mut())[0].peers, 1 << 2);
     }
 }
 
@@ -2466,6 +3241,21 @@ fn elf_load_segments_rejects_invalid() {
     }
 }
 
+#[test]
+fn elf_page_templates_follow_segments() {
+    // Text over pages 0-1, data on page 2, page 3 untouched
+    let base = 0x4010_0000;
+    let seg = |vaddr, memsz, flags| Some(ElfSegment { vaddr, offset: 0, filesz: 0, memsz, flags });
+    let info = ElfInfo {
+        entry: base,
+        segments: [seg(base, 0x1800, PF_R | PF_X), seg(base + 0x2000, 0x100, PF_R | PF_W), None, None],
+        num_segments: 2,
+    };
+    let pages = elf::page_templates(&info, base);
+    assert_eq!(pages, [mmu::USER_CODE_PAGE, mmu::USER_CODE_PAGE, mmu::USER_DATA_PAGE, 0]);
+    assert_eq!(elf::segment_template(PF_R), mmu::KERNEL_RODATA_PAGE);
+}
+
 #[test]
 fn mmu_set_page_attr_host_stub() {
     // Valid task and address
@@ -2826,7 +3616,7 @@ fn ipc_sys_recv_invalid_endpoint() {
 
 #[test]
 fn ipc_sys_call_with_receiver_waiting() {
-    // Receiver waiting → deliver message, caller blocks as new receiver
+    // Receiver waiting → deliver message, caller blocks on the reply object
     unsafe {
         reset_test_state();
         // Task 1 is waiting to receive on EP0
@@ -2847,9 +3637,11 @@ fn ipc_sys_call_with_receiver_waiting() {
         assert!((*sched::TCBS.get_mut())[1].state == TaskState::Ready
             || (*sched::TCBS.get_mut())[1].state == TaskState::Running);
 
-        // Caller (task 0) should now be receiver, blocked
+        // Caller (task 0) blocked awaiting reply; server holds the reply object
         assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked);
-        assert_eq!((*ipc::ENDPOINTS.get_mut())[0].receiver, Some(0));
+        assert!((*sched::TCBS.get_mut())[0].awaiting_reply);
+        assert_eq!((*sched::TCBS.get_mut())[1].reply_to, Some(0));
+        assert!((*ipc::ENDPOINTS.get_mut())[0].receiver.is_none());
     }
 }
 
@@ -2936,13 +3728,15 @@ fn ipc_sys_call_priority_boost() {
 }
 
 #[test]
-fn ipc_send_restores_receiver_priority() {
-    // After send delivers message, receiver's base priority is restored
+fn ipc_send_keeps_reply_holder_priority() {
+    // Priority inheritance follows the reply object: a plain send to a
+    // boosted server that still owes a reply does not undo the boost
     unsafe {
         reset_test_state();
-        // Receiver was boosted from previous call
+        // Receiver was boosted by a call it has not answered yet
         (*sched::TCBS.get_mut())[1].priority = 7;
         (*sched::TCBS.get_mut())[1].base_priority = 2;
+        (*sched::TCBS.get_mut())[1].reply_to = Some(3);
         (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(1);
         (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
 
@@ -2953,8 +3747,8 @@ fn ipc_send_restores_receiver_priority() {
         let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
         ipc::sys_send(&mut frame, 0);
 
-        // sys_send calls restore_base_priority on receiver
-        assert_eq!((*sched::TCBS.get_mut())[1].priority, 2);
+        assert_eq!((*sched::TCBS.get_mut())[1].priority, 7);
+        assert_eq!((*sched::TCBS.get_mut())[1].reply_to, Some(3));
     }
 }
 
@@ -3028,6 +3822,42 @@ fn sched_restart_task_non_faulted_noop() {
     }
 }
 
+#[test]
+fn stack_guard_pages_interleave_stacks() {
+    use sched::{guard_page_owner, stack_top, STACK_SIZE, STACK_SLOT_SIZE};
+    let base = 0x400C_0000;
+    for task in 0..NUM_TASKS {
+        let top = stack_top(base, task);
+        let guard = top - STACK_SLOT_SIZE;
+        assert_eq!(guard, base + task as u64 * STACK_SLOT_SIZE);
+        // Guard page: first and last byte belong to the task below whose stack it sits
+        assert_eq!(guard_page_owner(base, guard), Some(task));
+        assert_eq!(guard_page_owner(base, guard + STACK_SIZE - 1), Some(task));
+        // Stack page: never a guard, from its lowest byte to just below the top
+        assert_eq!(guard_page_owner(base, top - STACK_SIZE), None);
+        assert_eq!(guard_page_owner(base, top - 8), None);
+    }
+    // Nothing outside the region is a guard
+    assert_eq!(guard_page_owner(base, base - 1), None);
+    assert_eq!(guard_page_owner(base, stack_top(base, NUM_TASKS - 1)), None);
+}
+
+#[test]
+fn stack_guard_hit_names_user_stack_task() {
+    use sched::{stack_guard_hit, stack_region_base, StackKind, STACK_SIZE, STACK_SLOT_SIZE};
+    let kernel = stack_region_base(StackKind::Kernel);
+    let user = stack_region_base(StackKind::User);
+    assert_eq!(stack_guard_hit(user + 3 * STACK_SLOT_SIZE + 0x10), Some(3));
+    // The reserved kernel stacks have no guard pages
+    assert_eq!(stack_guard_hit(kernel), None);
+    // An overflow by one word off the bottom of task 2's user stack
+    assert_eq!(stack_guard_hit(sched::stack_top(user, 2) - STACK_SIZE - 8), Some(2));
+    // In-stack and unrelated addresses are not stack overflows
+    assert_eq!(stack_guard_hit(user + STACK_SIZE), None);
+    assert_eq!(stack_guard_hit(kernel + STACK_SIZE + 0x800), None);
+    assert_eq!(stack_guard_hit(grant::grant_pool_base()), None);
+}
+
 #[test]
 fn sched_schedule_no_ready_task_forces_idle() {
     // All tasks blocked/faulted → scheduler forces idle (IDLE_TASK_ID) Ready
@@ -3179,8 +4009,9 @@ fn grant_cleanup_clears_both_roles() {
 
         // Grant 0 should be deactivated (task 0 was owner)
         assert!(!(*grant::GRANTS.get_mut())[0].active);
-        // Grant 1 should be deactivated (task 0 was peer)
-        assert!(!(*grant::GRANTS.get_mut())[1].active);
+        // Grant 1 loses task 0 as a peer (task 1 still owns it)
+        assert!(!(*grant::GRANTS.get_mut())[1].is_peer(0));
+        assert_eq!((*grant::GRANTS.get_mut())[1].owner, Some(1));
     }
 }
 
@@ -3489,7 +4320,7 @@ fn test_grant_create_validates_correctly() {
         let g = &(*grant::GRANTS.get_mut())[0];
         assert!(g.active);
         assert_eq!(g.owner, Some(0));
-        assert_eq!(g.peer, Some(1));
+        assert!(g.is_peer(1));
     }
 
     // Creating on active slot should fail
@@ -3511,10 +4342,10 @@ fn test_grant_cleanup_completeness_logic() {
 
         // Task 2 is owner of grant 0, peer of grant 1
         (*grant::GRANTS.get_mut())[0] = grant::Grant {
-            owner: Some(2), peer: Some(3), phys_addr: 0x5000, active: true,
+            owner: Some(2), peers: 1 << 3, phys_addr: 0x5000, pages: 1, active: true, ..EMPTY_GRANT
         };
         (*grant::GRANTS.get_mut())[1] = grant::Grant {
-            owner: Some(4), peer: Some(2), phys_addr: 0x6000, active: true,
+            owner: Some(4), peers: 1 << 2 | 1 << 5, phys_addr: 0x6000, pages: 1, active: true, ..EMPTY_GRANT
         };
 
         grant::cleanup_task(2);
@@ -3524,7 +4355,7 @@ fn test_grant_cleanup_completeness_logic() {
             let g = &(*grant::GRANTS.get_mut())[i];
             if g.active {
                 assert!(g.owner != Some(2), "cleanup must remove task from owner");
-                assert!(g.peer != Some(2), "cleanup must remove task from peer");
+                assert!(!g.is_peer(2), "cleanup must remove task from peers");
             }
         }
 
@@ -3532,9 +4363,9 @@ fn test_grant_cleanup_completeness_logic() {
         assert!(!(*grant::GRANTS.get_mut())[0].active);
         assert_eq!((*grant::GRANTS.get_mut())[0].owner, None);
 
-        // Grant 1: task was peer → active=false, peer=None
-        assert!(!(*grant::GRANTS.get_mut())[1].active);
-        assert_eq!((*grant::GRANTS.get_mut())[1].peer, None);
+        // Grant 1: task was a peer → dropped; grant stays active for task 5
+        assert!((*grant::GRANTS.get_mut())[1].active);
+        assert_eq!((*grant::GRANTS.get_mut())[1].peers, 1 << 5);
     }
 }
 
@@ -3548,7 +4379,7 @@ fn test_grant_slot_exhaustion_safe_logic() {
         // Fill all slots
         for i in 0..MAX_GRANTS {
             (*grant::GRANTS.get_mut())[i] = grant::Grant {
-                owner: Some(i), peer: Some(i + 1), phys_addr: 0x5000 + (i as u64 * 0x1000), active: true,
+                owner: Some(i), peers: 1 << (i + 1), phys_addr: 0x5000 + (i as u64 * 0x1000), pages: 1, active: true, ..EMPTY_GRANT
             };
         }
 
@@ -3718,3 +4549,2452 @@ fn test_budget_epoch_reset_fairness_logic() {
         assert_eq!((*sched::TCBS.get_mut())[5].ticks_used, 55, "Exited task preserved");
     }
 }
+
+// ═══════════════════════════════════════════════════════════════════
+// Partition Scheduler Tests (ARINC 653-style major frame)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Two windows with a gap: P0 [0,4), P1 [4,8), idle [8,10).
+static TEST_WINDOWS: [PartitionWindow; 2] = [
+    PartitionWindow { partition: 0, offset: 0, duration: 4 },
+    PartitionWindow { partition: 1, offset: 4, duration: 4 },
+];
+
+const TEST_SCHEDULE: PartitionSchedule = PartitionSchedule {
+    major_frame: 10,
+    windows: &TEST_WINDOWS,
+};
+
+#[test]
+fn partition_schedule_valid_accepts_default_and_test_tables() {
+    let d = sched::DEFAULT_PARTITION_SCHEDULE;
+    assert!(sched::partition_schedule_valid(d.windows, d.major_frame));
+    assert!(sched::partition_schedule_valid(&TEST_WINDOWS, 10));
+}
+
+#[test]
+fn partition_schedule_valid_rejects_bad_tables() {
+    let w = |partition, offset, duration| PartitionWindow { partition, offset, duration };
+    // Empty table / zero-length frame
+    assert!(!sched::partition_schedule_valid(&[], 10));
+    assert!(!sched::partition_schedule_valid(&[w(0, 0, 5)], 0));
+    // Zero-duration window
+    assert!(!sched::partition_schedule_valid(&[w(0, 0, 0)], 10));
+    // Overlap and out-of-order windows
+    assert!(!sched::partition_schedule_valid(&[w(0, 0, 5), w(1, 4, 2)], 10));
+    assert!(!sched::partition_schedule_valid(&[w(0, 5, 2), w(1, 0, 2)], 10));
+    // Window runs past the major frame / offset overflow
+    assert!(!sched::partition_schedule_valid(&[w(0, 8, 3)], 10));
+    assert!(!sched::partition_schedule_valid(&[w(0, u64::MAX, 2)], u64::MAX));
+    // Too many windows
+    let many = [w(0, 0, 1); sched::MAX_PARTITION_WINDOWS + 1];
+    assert!(!sched::partition_schedule_valid(&many, 100));
+}
+
+#[test]
+fn partition_at_maps_ticks_to_windows() {
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 0), Some(0));
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 3), Some(0));
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 4), Some(1));
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 7), Some(1));
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 8), None, "gap");
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 9), None, "gap");
+    // Frame repeats
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 14), Some(1));
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 10, 20), Some(0));
+    assert_eq!(sched::partition_at(&TEST_WINDOWS, 0, 5), None, "zero frame");
+}
+
+#[test]
+fn partition_set_schedule_rejects_invalid() {
+    unsafe {
+        reset_test_state();
+        static BAD: [PartitionWindow; 1] = [PartitionWindow { partition: 1, offset: 0, duration: 20 }];
+        assert!(!sched::set_partition_schedule(PartitionSchedule { major_frame: 10, windows: &BAD }));
+        // Default schedule still in place
+        assert_eq!((*sched::PARTITION_SCHEDULE.get()).major_frame, sched::EPOCH_LENGTH);
+        assert!(sched::set_partition_schedule(TEST_SCHEDULE));
+        assert_eq!((*sched::PARTITION_SCHEDULE.get()).major_frame, 10);
+    }
+}
+
+#[test]
+fn partition_schedule_only_runs_active_partition() {
+    unsafe {
+        reset_test_state();
+        assert!(sched::set_partition_schedule(TEST_SCHEDULE));
+
+        // Task 1 in P0 (low prio), task 2 in P1 (high prio)
+        for i in 0..NUM_TASKS {
+            (*sched::TCBS.get_mut())[i].state = TaskState::Inactive;
+        }
+        (*sched::TCBS.get_mut())[IDLE_TASK_ID].state = TaskState::Ready;
+        (*sched::TCBS.get_mut())[1].state = TaskState::Ready;
+        (*sched::TCBS.get_mut())[1].priority = 1;
+        (*sched::TCBS.get_mut())[1].partition = 0;
+        (*sched::TCBS.get_mut())[2].state = TaskState::Ready;
+        (*sched::TCBS.get_mut())[2].priority = 6;
+        (*sched::TCBS.get_mut())[2].partition = 1;
+
+        let mut frame = TrapFrame {
+            x: [0; 31], sp_el0: 0, elr_el1: 0, spsr_el1: 0, _pad: [0; 2],
+        };
+
+        // Tick 1: P0 window — higher-priority P1 task must not run
+        *aegis_os::timer::TICK_COUNT.get_mut() = 1;
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), 1);
+
+        // Tick 5: P1 window — task 1 is preempted by task 2
+        *aegis_os::timer::TICK_COUNT.get_mut() = 5;
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), 2);
+        assert_eq!((*sched::TCBS.get_mut())[1].state, TaskState::Ready);
+
+        // Tick 8: gap — only idle runs
+        *aegis_os::timer::TICK_COUNT.get_mut() = 8;
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), IDLE_TASK_ID);
+
+        // Tick 10: next major frame starts with P0 again
+        *aegis_os::timer::TICK_COUNT.get_mut() = 10;
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), 1);
+    }
+}
+
+#[test]
+fn partition_window_with_nothing_runnable_falls_back_to_idle() {
+    unsafe {
+        reset_test_state();
+        assert!(sched::set_partition_schedule(TEST_SCHEDULE));
+
+        // Every task (except idle) lives in P1, P1's task is Blocked
+        for i in 0..NUM_TASKS {
+            (*sched::TCBS.get_mut())[i].partition = 1;
+            (*sched::TCBS.get_mut())[i].priority = 3;
+        }
+        (*sched::TCBS.get_mut())[IDLE_TASK_ID].partition = 0;
+        (*sched::TCBS.get_mut())[IDLE_TASK_ID].priority = 0;
+
+        let mut frame = TrapFrame {
+            x: [0; 31], sp_el0: 0, elr_el1: 0, spsr_el1: 0, _pad: [0; 2],
+        };
+        // Tick 2: P0 window, no P0 task other than idle
+        *aegis_os::timer::TICK_COUNT.get_mut() = 2;
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), IDLE_TASK_ID);
+    }
+}
+
+#[test]
+fn partition_default_field_is_zero() {
+    assert_eq!(EMPTY_TCB.partition, 0);
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// EDF Scheduling Class Tests (periodic tasks + admission control)
+// ═══════════════════════════════════════════════════════════════════
+
+const fn periodic(period: u64, deadline: u64, wcet: u64) -> PeriodicParams {
+    PeriodicParams { period, deadline, wcet }
+}
+
+/// EDF admission with every task in partition 0 owning the whole CPU.
+fn edf_whole_cpu(set: &[PeriodicParams]) -> Result<(), AdmissionError> {
+    let windows = [PartitionWindow { partition: 0, offset: 0, duration: 1 }];
+    sched::edf_admission(set, &vec![0; set.len()], &windows, 1)
+}
+
+/// Boot schedule shape: partition 0 for 60 ticks, partition 1 for 40.
+const EDF_WINDOWS: [PartitionWindow; 2] = [
+    PartitionWindow { partition: 0, offset: 0, duration: 60 },
+    PartitionWindow { partition: 1, offset: 60, duration: 40 },
+];
+
+#[test]
+fn edf_admission_accepts_feasible_sets() {
+    // No periodic tasks at all
+    assert_eq!(edf_whole_cpu(&[APERIODIC; NUM_TASKS]), Ok(()));
+    // U = 1/4 + 2/5 + 3/10 = 0.95, implicit deadlines
+    let set = [periodic(4, 4, 1), periodic(5, 5, 2), periodic(10, 10, 3), APERIODIC];
+    assert_eq!(edf_whole_cpu(&set), Ok(()));
+    // U exactly 1 is still schedulable under EDF
+    assert_eq!(edf_whole_cpu(&[periodic(2, 2, 1), periodic(4, 4, 2)]), Ok(()));
+}
+
+#[test]
+fn edf_admission_rejects_invalid_params() {
+    assert_eq!(edf_whole_cpu(&[periodic(10, 10, 0)]), Err(AdmissionError::InvalidParams));
+    assert_eq!(edf_whole_cpu(&[periodic(10, 4, 5)]), Err(AdmissionError::InvalidParams));
+    assert_eq!(edf_whole_cpu(&[periodic(10, 12, 5)]), Err(AdmissionError::InvalidParams));
+}
+
+#[test]
+fn edf_admission_rejects_overload() {
+    // U = 1/2 + 2/3 > 1
+    assert_eq!(
+        edf_whole_cpu(&[periodic(2, 2, 1), periodic(3, 3, 2)]),
+        Err(AdmissionError::Utilisation)
+    );
+}
+
+#[test]
+fn edf_admission_rejects_demand_bound_violation() {
+    // U = 2/10 + 2/10 = 0.4, but both jobs need 4 ticks before tick 3
+    assert_eq!(
+        edf_whole_cpu(&[periodic(10, 3, 2), periodic(10, 3, 2)]),
+        Err(AdmissionError::DemandBound)
+    );
+    assert_eq!(sched::demand_bound(&[periodic(10, 3, 2), periodic(10, 3, 2)], 3), 4);
+}
+
+#[test]
+fn edf_admission_rejects_long_hyperperiod() {
+    // Co-prime periods: LCM far beyond EDF_MAX_HYPERPERIOD
+    let set = [periodic(997, 997, 1), periodic(991, 991, 1), periodic(983, 983, 1)];
+    assert_eq!(edf_whole_cpu(&set), Err(AdmissionError::HyperperiodTooLong));
+}
+
+#[test]
+fn edf_admission_limits_tasks_to_their_partition_windows() {
+    // U = 0.5 fits the CPU, but partition 1 only gets 40 of every 100 ticks
+    let set = [APERIODIC, periodic(100, 100, 50)];
+    assert_eq!(edf_whole_cpu(&set), Ok(()));
+    assert_eq!(sched::edf_admission(&set, &[0, 1], &EDF_WINDOWS, 100), Err(AdmissionError::Utilisation));
+    assert_eq!(sched::edf_admission(&set, &[0, 0], &EDF_WINDOWS, 100), Ok(()));
+    // Within the share, but a short deadline can fall in the 60-tick blackout
+    assert_eq!(sched::edf_admission(&[periodic(100, 50, 10)], &[1], &EDF_WINDOWS, 100), Err(AdmissionError::DemandBound));
+    assert_eq!(sched::edf_admission(&[periodic(100, 100, 10)], &[1], &EDF_WINDOWS, 100), Ok(()));
+    // Each partition is checked against its own supply
+    let both = [periodic(100, 100, 60), periodic(100, 100, 40)];
+    assert_eq!(sched::edf_admission(&both, &[0, 1], &EDF_WINDOWS, 100), Ok(()));
+    assert_eq!(sched::edf_admission(&both, &[1, 0], &EDF_WINDOWS, 100), Err(AdmissionError::Utilisation));
+    // A partition without windows gets nothing; mismatched tables are invalid
+    assert_eq!(sched::edf_admission(&[periodic(100, 100, 1)], &[2], &EDF_WINDOWS, 100), Err(AdmissionError::Utilisation));
+    assert_eq!(sched::edf_admission(&set, &[0], &EDF_WINDOWS, 100), Err(AdmissionError::InvalidParams));
+}
+
+#[test]
+fn edf_supply_bound_takes_the_worst_start() {
+    // Partition 1 (ticks 60..100): an interval starting at tick 0 sees nothing for 60 ticks
+    assert_eq!(sched::supply_bound(&EDF_WINDOWS, 100, 1, 60), 0);
+    assert_eq!(sched::supply_bound(&EDF_WINDOWS, 100, 1, 70), 10);
+    assert_eq!(sched::supply_bound(&EDF_WINDOWS, 100, 1, 100), 40);
+    assert_eq!(sched::supply_bound(&EDF_WINDOWS, 100, 1, 250), 80);
+    // Partition 0 (ticks 0..60): worst start is tick 60
+    assert_eq!(sched::supply_bound(&EDF_WINDOWS, 100, 0, 40), 0);
+    assert_eq!(sched::supply_bound(&EDF_WINDOWS, 100, 0, 100), 60);
+    // Whole CPU: supply equals the interval length
+    let whole = [PartitionWindow { partition: 0, offset: 0, duration: 1 }];
+    assert_eq!(sched::supply_bound(&whole, 1, 0, 37), 37);
+}
+
+#[test]
+fn edf_release_jobs_and_deadline_miss_faults_task() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[1].timing = periodic(10, 5, 2);
+
+        sched::release_jobs();
+        assert!((*sched::TCBS.get_mut())[1].job_active);
+        assert_eq!((*sched::TCBS.get_mut())[1].abs_deadline, 5);
+        assert_eq!((*sched::TCBS.get_mut())[1].next_release, 10);
+
+        // Tick 4: before the deadline — nothing happens
+        *aegis_os::timer::TICK_COUNT.get_mut() = 4;
+        sched::deadline_scan();
+        assert_eq!((*sched::TCBS.get_mut())[1].state, TaskState::Ready);
+
+        // Tick 5: job still active at its deadline → Faulted
+        *aegis_os::timer::TICK_COUNT.get_mut() = 5;
+        sched::deadline_scan();
+        assert_eq!((*sched::TCBS.get_mut())[1].state, TaskState::Faulted);
+        assert_eq!((*sched::TCBS.get_mut())[1].fault_tick, 5);
+        assert!(!(*sched::TCBS.get_mut())[1].job_active);
+    }
+}
+
+#[test]
+fn edf_deadline_miss_releases_queue_receiver() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        (*sched::TCBS.get_mut())[4].timing = periodic(10, 5, 2);
+        sched::release_jobs();
+
+        // Task 4 blocks on an empty queue and misses its deadline there
+        queue_recv_as(4, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!((*queue::QUEUES.get())[0].receiver, Some(4));
+        *aegis_os::timer::TICK_COUNT.get_mut() = 5;
+        sched::deadline_scan();
+        assert_eq!((*sched::TCBS.get_mut())[4].state, TaskState::Faulted);
+        assert!((*queue::QUEUES.get())[0].receiver.is_none());
+
+        // A later send is queued instead of reviving the faulted task
+        assert_eq!(queue_send_as(3, 0, 0x42), 0);
+        assert_eq!((*sched::TCBS.get_mut())[4].state, TaskState::Faulted);
+        assert_eq!(queue::queue_stats(0).unwrap().fill, 1);
+    }
+}
+
+#[test]
+fn edf_completed_job_waits_for_next_release() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].timing = periodic(10, 10, 2);
+        (*sched::TCBS.get_mut())[0].priority = 7;
+        sched::release_jobs();
+
+        // Task 0 yields → job complete, it must not be picked again
+        let mut frame = TrapFrame {
+            x: [0; 31], sp_el0: 0, elr_el1: 0, spsr_el1: 0, _pad: [0; 2],
+        };
+        sched::sys_yield(&mut frame);
+        assert!(!(*sched::TCBS.get_mut())[0].job_active);
+        assert_ne!(read_current(), 0);
+
+        // No deadline miss for a completed job
+        *aegis_os::timer::TICK_COUNT.get_mut() = 10;
+        sched::deadline_scan();
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+
+        // Tick 10: next job released, task 0 (priority 7) wins again
+        sched::release_jobs();
+        assert!((*sched::TCBS.get_mut())[0].job_active);
+        assert_eq!((*sched::TCBS.get_mut())[0].abs_deadline, 20);
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), 0);
+    }
+}
+
+#[test]
+fn edf_picks_earliest_deadline_over_priority() {
+    unsafe {
+        reset_test_state();
+        sched::set_sched_policy(SchedPolicy::Edf);
+
+        // Task 1: periodic, deadline 8, low priority
+        // Task 2: periodic, deadline 4, lowest priority
+        // Task 3: aperiodic, high priority
+        (*sched::TCBS.get_mut())[1].timing = periodic(8, 8, 1);
+        (*sched::TCBS.get_mut())[1].priority = 1;
+        (*sched::TCBS.get_mut())[2].timing = periodic(4, 4, 1);
+        (*sched::TCBS.get_mut())[2].priority = 0;
+        (*sched::TCBS.get_mut())[3].priority = 7;
+        sched::release_jobs();
+
+        let mut frame = TrapFrame {
+            x: [0; 31], sp_el0: 0, elr_el1: 0, spsr_el1: 0, _pad: [0; 2],
+        };
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), 2, "earliest deadline first");
+
+        sched::sys_yield(&mut frame);
+        assert_eq!(read_current(), 1, "next earliest deadline");
+
+        // All periodic jobs done → aperiodic tasks run by priority
+        sched::sys_yield(&mut frame);
+        assert_eq!(read_current(), 3);
+    }
+}
+
+#[test]
+fn edf_fixed_priority_policy_ignores_deadlines() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[1].timing = periodic(4, 4, 1);
+        (*sched::TCBS.get_mut())[1].priority = 1;
+        (*sched::TCBS.get_mut())[2].priority = 5;
+        sched::release_jobs();
+
+        let mut frame = TrapFrame {
+            x: [0; 31], sp_el0: 0, elr_el1: 0, spsr_el1: 0, _pad: [0; 2],
+        };
+        sched::schedule(&mut frame);
+        assert_eq!(read_current(), 2);
+    }
+}
+
+#[test]
+fn edf_restart_releases_fresh_job() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[1].timing = periodic(10, 6, 2);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Faulted;
+        *aegis_os::timer::TICK_COUNT.get_mut() = 200;
+
+        sched::restart_task(1);
+        assert_eq!((*sched::TCBS.get_mut())[1].state, TaskState::Ready);
+        assert!((*sched::TCBS.get_mut())[1].job_active);
+        assert_eq!((*sched::TCBS.get_mut())[1].abs_deadline, 206);
+        assert_eq!((*sched::TCBS.get_mut())[1].next_release, 210);
+    }
+}
+
+#[test]
+fn edf_empty_tcb_is_aperiodic() {
+    assert_eq!(EMPTY_TCB.timing, APERIODIC);
+    assert!(!EMPTY_TCB.job_active);
+    assert!(!sched::deadline_missed(false, 0, 100));
+    assert!(sched::deadline_missed(true, 100, 100));
+    assert!(!sched::deadline_missed(true, 100, 99));
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Tickless Idle Tests (one-shot timer, next-event computation)
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn tickless_ticks_elapsed_catch_up() {
+    use aegis_os::timer::ticks_elapsed;
+    assert_eq!(ticks_elapsed(1000, 1000, 625_000), 0);
+    assert_eq!(ticks_elapsed(1000, 1000 + 624_999, 625_000), 0);
+    assert_eq!(ticks_elapsed(1000, 1000 + 625_000, 625_000), 1);
+    assert_eq!(ticks_elapsed(0, 625_000 * 37 + 5, 625_000), 37);
+    assert_eq!(ticks_elapsed(0, 12345, 0), 0, "zero interval");
+}
+
+#[test]
+fn tickless_advance_ticks_single_matches_periodic_tick() {
+    unsafe {
+        reset_test_state();
+        *sched::EPOCH_TICKS.get_mut() = 98;
+        aegis_os::timer::advance_ticks(1);
+        assert_eq!(aegis_os::timer::tick_count(), 1);
+        assert_eq!((*sched::TCBS.get_mut())[0].ticks_used, 1, "current task charged");
+        assert_eq!(*sched::EPOCH_TICKS.get(), 99);
+
+        aegis_os::timer::advance_ticks(1);
+        assert_eq!(*sched::EPOCH_TICKS.get(), 0, "epoch boundary resets budgets");
+        assert_eq!((*sched::TCBS.get_mut())[0].ticks_used, 0);
+
+        // Zero elapsed ticks is a no-op
+        aegis_os::timer::advance_ticks(0);
+        assert_eq!(aegis_os::timer::tick_count(), 2);
+    }
+}
+
+#[test]
+fn tickless_advance_ticks_catch_up_runs_bookkeeping() {
+    unsafe {
+        reset_test_state();
+        // Task 1 monitored by watchdog, last heartbeat at 0, interval 5
+        (*sched::TCBS.get_mut())[1].heartbeat_interval = 5;
+        *sched::EPOCH_TICKS.get_mut() = 90;
+
+        // Sleep 25 ticks in one go: crosses epoch + several watchdog scans
+        aegis_os::timer::advance_ticks(25);
+        assert_eq!(aegis_os::timer::tick_count(), 25);
+        assert_eq!(*sched::EPOCH_TICKS.get(), 15, "epoch carry preserved");
+        assert_eq!((*sched::TCBS.get_mut())[1].state, TaskState::Faulted, "watchdog ran");
+    }
+}
+
+#[test]
+fn tickless_window_boundary_delta() {
+    // TEST_WINDOWS: P0 [0,4), P1 [4,8), gap [8,10)
+    assert_eq!(sched::window_boundary_delta(&TEST_WINDOWS, 10, 0), 4);
+    assert_eq!(sched::window_boundary_delta(&TEST_WINDOWS, 10, 3), 1);
+    assert_eq!(sched::window_boundary_delta(&TEST_WINDOWS, 10, 5), 3);
+    assert_eq!(sched::window_boundary_delta(&TEST_WINDOWS, 10, 8), 2, "gap ends at frame end");
+    assert_eq!(sched::window_boundary_delta(&TEST_WINDOWS, 10, 19), 1);
+    assert_eq!(sched::window_boundary_delta(&TEST_WINDOWS, 0, 5), u64::MAX);
+}
+
+#[test]
+fn tickless_next_event_epoch_only() {
+    unsafe {
+        reset_test_state();
+        // Default schedule: window == epoch, nothing else pending
+        *aegis_os::timer::TICK_COUNT.get_mut() = 30;
+        *sched::EPOCH_TICKS.get_mut() = 30;
+        *sched::CURRENT.get_mut() = IDLE_TASK_ID;
+        assert_eq!(sched::ticks_until_next_event(), 70);
+    }
+}
+
+#[test]
+fn tickless_next_event_considers_watchdog_restart_and_jobs() {
+    unsafe {
+        reset_test_state();
+        *sched::CURRENT.get_mut() = IDLE_TASK_ID;
+        *aegis_os::timer::TICK_COUNT.get_mut() = 3;
+        *sched::EPOCH_TICKS.get_mut() = 3;
+
+        // Watchdog monitoring → next scan at tick 10
+        (*sched::TCBS.get_mut())[1].heartbeat_interval = 50;
+        assert_eq!(sched::ticks_until_next_event(), 7);
+
+        // Periodic job due at tick 8
+        (*sched::TCBS.get_mut())[2].timing = PeriodicParams { period: 20, deadline: 8, wcet: 1 };
+        (*sched::TCBS.get_mut())[2].job_active = true;
+        (*sched::TCBS.get_mut())[2].abs_deadline = 8;
+        (*sched::TCBS.get_mut())[2].next_release = 20;
+        assert_eq!(sched::ticks_until_next_event(), 5);
+
+        // Faulted at tick 50 → restart due at tick 150 (5 ticks away)
+        (*sched::TCBS.get_mut())[2].timing = APERIODIC;
+        (*sched::TCBS.get_mut())[1].heartbeat_interval = 0;
+        (*sched::TCBS.get_mut())[3].state = TaskState::Faulted;
+        (*sched::TCBS.get_mut())[3].fault_tick = 50;
+        *aegis_os::timer::TICK_COUNT.get_mut() = 45 + RESTART_DELAY_TICKS;
+        *sched::EPOCH_TICKS.get_mut() = 0;
+        assert_eq!(sched::ticks_until_next_event(), 5);
+    }
+}
+
+#[test]
+fn tickless_next_event_clamped() {
+    unsafe {
+        reset_test_state();
+        *sched::CURRENT.get_mut() = IDLE_TASK_ID;
+        // Job deadline already passed → still at least 1 tick
+        (*sched::TCBS.get_mut())[2].timing = PeriodicParams { period: 20, deadline: 8, wcet: 1 };
+        (*sched::TCBS.get_mut())[2].job_active = true;
+        (*sched::TCBS.get_mut())[2].abs_deadline = 0;
+        (*sched::TCBS.get_mut())[2].next_release = 20;
+        assert_eq!(sched::ticks_until_next_event(), 1);
+
+        // Huge single window, nothing pending → capped at MAX_TICKLESS_TICKS
+        static LONG: [PartitionWindow; 1] = [PartitionWindow { partition: 0, offset: 0, duration: 1_000_000 }];
+        (*sched::TCBS.get_mut())[2].timing = APERIODIC;
+        *sched::PARTITION_SCHEDULE.get_mut() = PartitionSchedule { major_frame: 1_000_000, windows: &LONG };
+        *sched::EPOCH_TICKS.get_mut() = 0;
+        let n = sched::ticks_until_next_event();
+        assert!(n <= aegis_os::timer::MAX_TICKLESS_TICKS);
+        assert_eq!(n, sched::EPOCH_LENGTH.min(aegis_os::timer::MAX_TICKLESS_TICKS));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Sleep Tests (SYS_SLEEP / SYS_SLEEP_UNTIL + kernel timer queue)
+// ═══════════════════════════════════════════════════════════════════
+
+use aegis_os::timer::{TimerQueue, TimerEntry};
+
+#[test]
+fn timer_queue_sorted_fifo_and_unique() {
+    let mut q = TimerQueue::new();
+    assert!(q.is_empty());
+    assert!(q.insert(1, 50));
+    assert!(q.insert(2, 20));
+    assert!(q.insert(3, 50)); // equal deadline → after task 1
+    assert!(q.insert(4, 10));
+    let order: Vec<usize> = q.entries().iter().map(|e| e.task).collect();
+    assert_eq!(order, vec![4, 2, 1, 3]);
+
+    // Re-insert replaces the old entry
+    assert!(q.insert(4, 60));
+    assert_eq!(q.len(), 4);
+    let order: Vec<usize> = q.entries().iter().map(|e| e.task).collect();
+    assert_eq!(order, vec![2, 1, 3, 4]);
+
+    // Invalid task rejected
+    assert!(!q.insert(NUM_TASKS, 5));
+}
+
+#[test]
+fn timer_queue_pop_expired_and_remove() {
+    let mut q = TimerQueue::new();
+    q.insert(1, 30);
+    q.insert(2, 10);
+    assert_eq!(q.pop_expired(9), None);
+    assert_eq!(q.pop_expired(10), Some(TimerEntry { deadline: 10, task: 2 }));
+    assert_eq!(q.pop_expired(29), None);
+    assert!(q.remove(1));
+    assert!(!q.remove(1));
+    assert_eq!(q.peek(), None);
+}
+
+#[test]
+fn sleep_blocks_and_wakes_at_deadline() {
+    unsafe {
+        reset_test_state();
+        *aegis_os::timer::TICK_COUNT.get_mut() = 100;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+
+        sched::sys_sleep(&mut frame, 5);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Sleeping);
+        assert_ne!(read_current(), 0);
+        assert_eq!((*aegis_os::timer::SLEEP_QUEUE.get()).peek(),
+            Some(TimerEntry { deadline: 105, task: 0 }));
+
+        // Sleeping tasks are never scheduled
+        sched::schedule(&mut frame);
+        assert_ne!(read_current(), 0);
+
+        aegis_os::timer::advance_ticks(4);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Sleeping);
+        aegis_os::timer::advance_ticks(1);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], 105, "wake-up tick in x0");
+        assert!((*aegis_os::timer::SLEEP_QUEUE.get()).is_empty());
+    }
+}
+
+#[test]
+fn sleep_until_past_deadline_returns_immediately() {
+    unsafe {
+        reset_test_state();
+        *aegis_os::timer::TICK_COUNT.get_mut() = 42;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+
+        sched::sys_sleep_until(&mut frame, 40);
+        assert_eq!(frame.x[0], 42);
+        assert_eq!(read_current(), 0, "no context switch");
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Running);
+
+        // sleep(0) reads the clock
+        sched::sys_sleep(&mut frame, 0);
+        assert_eq!(frame.x[0], 42);
+        assert!((*aegis_os::timer::SLEEP_QUEUE.get()).is_empty());
+    }
+}
+
+#[test]
+fn sleep_until_completes_periodic_job() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].timing = PeriodicParams { period: 10, deadline: 10, wcet: 2 };
+        sched::release_jobs();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+
+        sched::sys_sleep_until(&mut frame, 10);
+        assert!(!(*sched::TCBS.get_mut())[0].job_active);
+
+        // No deadline miss at tick 10, new job released and task woken
+        aegis_os::timer::advance_ticks(10);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert!((*sched::TCBS.get_mut())[0].job_active);
+    }
+}
+
+#[test]
+fn sleep_cancelled_on_fault_cleanup() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[2].state = TaskState::Sleeping;
+        (*aegis_os::timer::SLEEP_QUEUE.get_mut()).insert(2, 50);
+
+        sched::cleanup_task_resources(2);
+        assert!((*aegis_os::timer::SLEEP_QUEUE.get()).is_empty());
+    }
+}
+
+#[test]
+fn sleep_stale_entry_does_not_wake_faulted_task() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[2].state = TaskState::Faulted;
+        (*aegis_os::timer::SLEEP_QUEUE.get_mut()).insert(2, 1);
+        aegis_os::timer::advance_ticks(1);
+        assert_eq!((*sched::TCBS.get_mut())[2].state, TaskState::Faulted);
+        assert!((*aegis_os::timer::SLEEP_QUEUE.get()).is_empty());
+    }
+}
+
+#[test]
+fn sleep_next_event_includes_wakeup() {
+    unsafe {
+        reset_test_state();
+        *sched::CURRENT.get_mut() = IDLE_TASK_ID;
+        (*sched::TCBS.get_mut())[2].state = TaskState::Sleeping;
+        (*aegis_os::timer::SLEEP_QUEUE.get_mut()).insert(2, 17);
+        assert_eq!(sched::ticks_until_next_event(), 17);
+    }
+}
+
+#[test]
+fn cap_sleep_bit_and_syscall_mapping() {
+    assert_eq!(CAP_SLEEP, 1 << 19);
+    assert!(cap::cap_check(CAP_ALL, CAP_SLEEP));
+    assert_eq!(cap::cap_for_syscall(14, 0), CAP_SLEEP);
+    assert_eq!(cap::cap_for_syscall(15, 3), CAP_SLEEP);
+    assert_eq!(cap::cap_for_syscall(33, 0), 0);
+    assert_eq!(cap::cap_name(CAP_SLEEP), "SLEEP");
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// IPC timeouts — SYS_SEND / SYS_RECV / SYS_CALL with x4 timeout
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn ipc_timeout_send_poll_without_receiver() {
+    unsafe {
+        reset_test_state();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_send_timeout(&mut frame, 0, ipc::TIMEOUT_POLL);
+
+        assert_eq!(frame.x[0], ipc::ERR_IPC_TIMEOUT);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Running);
+        assert!(!(*ipc::ENDPOINTS.get_mut())[0].sender_queue.contains(0));
+    }
+}
+
+#[test]
+fn ipc_timeout_recv_poll_without_sender() {
+    unsafe {
+        reset_test_state();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_recv_timeout(&mut frame, 0, ipc::TIMEOUT_POLL);
+
+        assert_eq!(frame.x[0], ipc::ERR_IPC_TIMEOUT);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Running);
+        assert!((*ipc::ENDPOINTS.get_mut())[0].receiver.is_none());
+    }
+}
+
+#[test]
+fn ipc_timeout_recv_poll_with_sender_delivers() {
+    unsafe {
+        reset_test_state();
+        (*ipc::ENDPOINTS.get_mut())[0].sender_queue.push(1);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
+        (*sched::TCBS.get_mut())[1].context.x[0] = 0x5EED;
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_recv_timeout(&mut frame, 0, ipc::TIMEOUT_POLL);
+
+        assert_eq!(frame.x[0], 0x5EED);
+        assert_eq!((*sched::TCBS.get_mut())[1].state, TaskState::Ready);
+    }
+}
+
+#[test]
+fn ipc_timeout_recv_expires() {
+    unsafe {
+        reset_test_state();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_recv_timeout(&mut frame, 0, 5);
+
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked);
+        assert_eq!((*aegis_os::timer::SLEEP_QUEUE.get()).peek().unwrap().deadline, 5);
+
+        aegis_os::timer::advance_ticks(4);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked);
+
+        aegis_os::timer::advance_ticks(1);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], ipc::ERR_IPC_TIMEOUT);
+        assert!((*ipc::ENDPOINTS.get_mut())[0].receiver.is_none());
+    }
+}
+
+#[test]
+fn ipc_timeout_send_expires_and_dequeues() {
+    unsafe {
+        reset_test_state();
+        (*ipc::ENDPOINTS.get_mut())[0].sender_queue.push(1);
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_send_timeout(&mut frame, 0, 3);
+        assert!((*ipc::ENDPOINTS.get_mut())[0].sender_queue.contains(0));
+
+        aegis_os::timer::advance_ticks(3);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], ipc::ERR_IPC_TIMEOUT);
+        // Only the timed-out sender left the queue
+        let q = &(*ipc::ENDPOINTS.get_mut())[0].sender_queue;
+        assert!(!q.contains(0));
+        assert!(q.contains(1));
+    }
+}
+
+#[test]
+fn ipc_timeout_disarmed_on_delivery() {
+    unsafe {
+        reset_test_state();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_recv_timeout(&mut frame, 0, 10);
+        assert_eq!((*aegis_os::timer::SLEEP_QUEUE.get()).len(), 1);
+
+        // Task 1 sends before the deadline
+        *sched::CURRENT.get_mut() = 1;
+        (*sched::TCBS.get_mut())[1].state = TaskState::Running;
+        (*sched::TCBS.get_mut())[1].context.x[0] = 0xAB;
+        let mut f1 = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_send(&mut f1, 0);
+
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert!((*aegis_os::timer::SLEEP_QUEUE.get()).is_empty());
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], 0xAB);
+    }
+}
+
+#[test]
+fn ipc_timeout_infinite_arms_nothing() {
+    unsafe {
+        reset_test_state();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_recv_timeout(&mut frame, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked);
+        assert!((*aegis_os::timer::SLEEP_QUEUE.get()).is_empty());
+    }
+}
+
+#[test]
+fn ipc_timeout_call_reply_wait_expires() {
+    unsafe {
+        reset_test_state();
+        // Server (task 1) waiting on EP0
+        (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(1);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_call_timeout(&mut frame, 0, 2);
+        assert_ne!((*sched::TCBS.get_mut())[1].state, TaskState::Blocked);
+        assert_eq!((*sched::TCBS.get_mut())[1].reply_to, Some(0));
+
+        // Server never replies — its reply object is revoked
+        aegis_os::timer::advance_ticks(2);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], ipc::ERR_IPC_TIMEOUT);
+        assert!((*sched::TCBS.get_mut())[1].reply_to.is_none());
+        assert!(!(*sched::TCBS.get_mut())[0].awaiting_reply);
+    }
+}
+
+#[test]
+fn ipc_timeout_ignores_task_not_on_endpoint() {
+    unsafe {
+        reset_test_state();
+        // Task 2 blocked in WAIT_NOTIFY with a stale timer entry
+        (*sched::TCBS.get_mut())[2].state = TaskState::Blocked;
+        (*sched::TCBS.get_mut())[2].context.x[0] = 0x77;
+        (*aegis_os::timer::SLEEP_QUEUE.get_mut()).insert(2, 1);
+
+        aegis_os::timer::advance_ticks(1);
+        assert_eq!((*sched::TCBS.get_mut())[2].state, TaskState::Blocked);
+        assert_eq!((*sched::TCBS.get_mut())[2].context.x[0], 0x77);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Reply objects — SYS_REPLY / SYS_REPLY_RECV
+// ═══════════════════════════════════════════════════════════════════
+
+/// Task `caller` calls on EP0 while `server` waits in recv; leaves
+/// `server` as the current task holding the reply object.
+unsafe fn setup_pending_call(caller: usize, server: usize) {
+    (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(server);
+    (*sched::TCBS.get_mut())[server].state = TaskState::Blocked;
+    *sched::CURRENT.get_mut() = caller;
+    (*sched::TCBS.get_mut())[caller].state = TaskState::Running;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[caller].context);
+    ipc::sys_call(&mut frame, 0);
+
+    *sched::CURRENT.get_mut() = server;
+    (*sched::TCBS.get_mut())[server].state = TaskState::Running;
+}
+
+#[test]
+fn reply_answers_exactly_the_caller() {
+    unsafe {
+        reset_test_state();
+        setup_pending_call(0, 1);
+        setup_pending_call(2, 3);
+
+        // Server 1 answers its own caller only
+        *sched::CURRENT.get_mut() = 1;
+        (*sched::TCBS.get_mut())[1].context.x[0] = 0x4F4B;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_reply(&mut frame);
+
+        assert_eq!(frame.x[0], 0);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], 0x4F4B);
+        assert!(!(*sched::TCBS.get_mut())[0].awaiting_reply);
+        assert!((*sched::TCBS.get_mut())[1].reply_to.is_none());
+        // The other client is untouched
+        assert_eq!((*sched::TCBS.get_mut())[2].state, TaskState::Blocked);
+        assert_eq!((*sched::TCBS.get_mut())[3].reply_to, Some(2));
+    }
+}
+
+#[test]
+fn reply_without_pending_call_fails() {
+    unsafe {
+        reset_test_state();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_reply(&mut frame);
+        assert_eq!(frame.x[0], ipc::ERR_IPC_NO_REPLY);
+    }
+}
+
+#[test]
+fn reply_is_one_shot() {
+    unsafe {
+        reset_test_state();
+        setup_pending_call(0, 1);
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_reply(&mut frame);
+        assert_eq!(frame.x[0], 0);
+        ipc::sys_reply(&mut frame);
+        assert_eq!(frame.x[0], ipc::ERR_IPC_NO_REPLY);
+    }
+}
+
+#[test]
+fn reply_queued_call_stays_blocked_after_recv() {
+    unsafe {
+        reset_test_state();
+        // Caller 0 (prio 6) queues a call — no server waiting yet
+        (*sched::TCBS.get_mut())[0].priority = 6;
+        (*sched::TCBS.get_mut())[0].base_priority = 6;
+        (*sched::TCBS.get_mut())[0].context.x[0] = 0xCA11;
+        let mut f0 = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_call(&mut f0, 0);
+        assert!((*ipc::ENDPOINTS.get_mut())[0].sender_queue.contains(0));
+
+        // Server 1 (prio 2) receives the request
+        (*sched::TCBS.get_mut())[1].priority = 2;
+        (*sched::TCBS.get_mut())[1].base_priority = 2;
+        *sched::CURRENT.get_mut() = 1;
+        (*sched::TCBS.get_mut())[1].state = TaskState::Running;
+        let mut f1 = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_recv(&mut f1, 0);
+
+        assert_eq!(f1.x[0], 0xCA11);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked);
+        assert_eq!((*sched::TCBS.get_mut())[1].reply_to, Some(0));
+        assert_eq!((*sched::TCBS.get_mut())[1].priority, 6, "server inherits caller priority");
+    }
+}
+
+#[test]
+fn reply_restores_server_priority() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].priority = 7;
+        (*sched::TCBS.get_mut())[0].base_priority = 7;
+        (*sched::TCBS.get_mut())[1].priority = 2;
+        (*sched::TCBS.get_mut())[1].base_priority = 2;
+        setup_pending_call(0, 1);
+        assert_eq!((*sched::TCBS.get_mut())[1].priority, 7);
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_reply(&mut frame);
+        assert_eq!((*sched::TCBS.get_mut())[1].priority, 2);
+    }
+}
+
+#[test]
+fn reply_recv_answers_then_takes_next_request() {
+    unsafe {
+        reset_test_state();
+        setup_pending_call(0, 1);
+
+        // Another client (task 2) has queued a call meanwhile
+        (*ipc::ENDPOINTS.get_mut())[0].sender_queue.push(2);
+        (*sched::TCBS.get_mut())[2].state = TaskState::Blocked;
+        (*sched::TCBS.get_mut())[2].awaiting_reply = true;
+        (*sched::TCBS.get_mut())[2].context.x[0] = 0x2222;
+
+        (*sched::TCBS.get_mut())[1].context.x[0] = 0x4F4B;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_reply_recv(&mut frame, 0, ipc::TIMEOUT_INFINITE);
+
+        // First caller answered, second request received in the same trap
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], 0x4F4B);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!(frame.x[0], 0x2222);
+        assert_eq!((*sched::TCBS.get_mut())[1].reply_to, Some(2));
+    }
+}
+
+#[test]
+fn reply_recv_without_pending_reply_just_receives() {
+    unsafe {
+        reset_test_state();
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_reply_recv(&mut frame, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked);
+        assert_eq!((*ipc::ENDPOINTS.get_mut())[0].receiver, Some(0));
+    }
+}
+
+#[test]
+fn reply_lost_when_server_faults() {
+    unsafe {
+        reset_test_state();
+        setup_pending_call(0, 1);
+        ipc::cleanup_task(1);
+
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], ipc::ERR_IPC_REPLY_LOST);
+        assert!((*sched::TCBS.get_mut())[1].reply_to.is_none());
+    }
+}
+
+#[test]
+fn reply_revoked_when_caller_faults() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].priority = 7;
+        (*sched::TCBS.get_mut())[0].base_priority = 7;
+        (*sched::TCBS.get_mut())[1].priority = 2;
+        (*sched::TCBS.get_mut())[1].base_priority = 2;
+        setup_pending_call(0, 1);
+
+        ipc::cleanup_task(0);
+        assert!((*sched::TCBS.get_mut())[1].reply_to.is_none());
+        assert_eq!((*sched::TCBS.get_mut())[1].priority, 2);
+        assert!(!(*sched::TCBS.get_mut())[0].awaiting_reply);
+    }
+}
+
+#[test]
+fn reply_overwritten_by_next_call_is_dropped() {
+    unsafe {
+        reset_test_state();
+        setup_pending_call(0, 1);
+        // Server receives a second call before answering the first
+        setup_pending_call(2, 1);
+
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], ipc::ERR_IPC_REPLY_LOST);
+        assert_eq!((*sched::TCBS.get_mut())[1].reply_to, Some(2));
+    }
+}
+
+#[test]
+fn cap_reply_bit_and_syscall_mapping() {
+    assert_eq!(CAP_REPLY, 1 << 20);
+    assert!(cap::cap_check(CAP_ALL, CAP_REPLY));
+    assert_eq!(cap::cap_for_syscall(16, 0), CAP_REPLY);
+    assert_eq!(cap::cap_for_syscall(17, 1), CAP_REPLY | CAP_IPC_RECV_EP1);
+    assert_eq!(cap::cap_for_syscall(17, 9), 0);
+    assert_eq!(cap::cap_name(CAP_REPLY), "REPLY");
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// IPC buffer page — long messages (MSG_FLAG_BYTES)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Stage a length-prefixed payload in `task`'s IPC buffer.
+unsafe fn stage_bytes(task: usize, data: &[u8]) {
+    let buf = ipc::ipc_buffer_addr(task).unwrap() as *mut u8;
+    core::ptr::write_unaligned(buf as *mut u64, data.len() as u64);
+    core::ptr::copy_nonoverlapping(data.as_ptr(), buf.add(ipc::IPC_BUF_HEADER), data.len());
+}
+
+/// Read the length-prefixed payload from `task`'s IPC buffer.
+unsafe fn buffer_bytes(task: usize) -> Vec<u8> {
+    let buf = ipc::ipc_buffer_addr(task).unwrap() as *const u8;
+    let len = core::ptr::read_unaligned(buf as *const u64) as usize;
+    core::slice::from_raw_parts(buf.add(ipc::IPC_BUF_HEADER), len).to_vec()
+}
+
+#[test]
+fn ipc_buffer_addr_per_task() {
+    let a0 = ipc::ipc_buffer_addr(0).unwrap();
+    let a1 = ipc::ipc_buffer_addr(1).unwrap();
+    assert_eq!(a1 - a0, ipc::IPC_BUF_SIZE as u64);
+    assert!(ipc::ipc_buffer_addr(NUM_TASKS).is_none());
+    assert_eq!(ipc::IPC_BUFFER_VA & 0xFFF, 0, "alias must be page-aligned");
+}
+
+#[test]
+fn ipc_buffer_copy_len_bounds() {
+    assert_eq!(ipc::buffer_copy_len(0, 100), 0);
+    assert_eq!(ipc::buffer_copy_len(ipc::MSG_FLAG_BYTES, 100), 100);
+    assert_eq!(ipc::buffer_copy_len(ipc::MSG_FLAG_BYTES, u64::MAX), ipc::IPC_BUF_MAX_PAYLOAD);
+    assert!(ipc::IPC_BUF_HEADER + ipc::IPC_BUF_MAX_PAYLOAD <= ipc::IPC_BUF_SIZE);
+}
+
+#[test]
+fn ipc_buffer_send_delivers_payload() {
+    unsafe {
+        reset_test_state();
+        (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(1);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
+
+        let frame_bytes = [0xA5u8; 300];
+        stage_bytes(0, &frame_bytes);
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        frame.x[5] = ipc::MSG_FLAG_BYTES;
+        ipc::sys_send(&mut frame, 0);
+
+        assert_eq!(buffer_bytes(1), frame_bytes.to_vec());
+    }
+}
+
+#[test]
+fn ipc_buffer_queued_sender_payload_delivered_on_recv() {
+    unsafe {
+        reset_test_state();
+        stage_bytes(0, b"telemetry-frame");
+        let mut f0 = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        f0.x[5] = ipc::MSG_FLAG_BYTES;
+        ipc::sys_send(&mut f0, 0);
+        assert!((*ipc::ENDPOINTS.get_mut())[0].sender_queue.contains(0));
+
+        *sched::CURRENT.get_mut() = 1;
+        (*sched::TCBS.get_mut())[1].state = TaskState::Running;
+        let mut f1 = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_recv(&mut f1, 0);
+
+        assert_eq!(buffer_bytes(1), b"telemetry-frame".to_vec());
+    }
+}
+
+#[test]
+fn ipc_buffer_register_only_message_clears_stale_payload() {
+    unsafe {
+        reset_test_state();
+        stage_bytes(1, b"stale");
+        stage_bytes(0, b"not attached");
+        (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(1);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_send(&mut frame, 0); // x5 = 0 → registers only
+
+        assert!(buffer_bytes(1).is_empty());
+    }
+}
+
+#[test]
+fn ipc_buffer_oversized_payload_rejected() {
+    unsafe {
+        reset_test_state();
+        (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(1);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
+        let buf = ipc::ipc_buffer_addr(0).unwrap();
+        core::ptr::write_unaligned(buf as *mut u64, ipc::IPC_BUF_MAX_PAYLOAD as u64 + 1);
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        frame.x[5] = ipc::MSG_FLAG_BYTES;
+        ipc::sys_send(&mut frame, 0);
+
+        assert_eq!(frame.x[0], ipc::ERR_IPC_MSG_TOO_LONG);
+        // Nothing delivered; receiver still waiting
+        assert_eq!((*ipc::ENDPOINTS.get_mut())[0].receiver, Some(1));
+        assert_eq!((*sched::TCBS.get_mut())[1].state, TaskState::Blocked);
+    }
+}
+
+#[test]
+fn ipc_buffer_reply_carries_payload() {
+    unsafe {
+        reset_test_state();
+        setup_pending_call(0, 1);
+        stage_bytes(1, b"config-blob");
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        frame.x[5] = ipc::MSG_FLAG_BYTES;
+        ipc::sys_reply(&mut frame);
+
+        assert_eq!(frame.x[0], 0);
+        assert_eq!(buffer_bytes(0), b"config-blob".to_vec());
+    }
+}
+
+#[test]
+fn ipc_buffer_cleared_on_restart() {
+    unsafe {
+        reset_test_state();
+        stage_bytes(2, b"secret");
+        (*sched::TCBS.get_mut())[2].state = TaskState::Faulted;
+        sched::restart_task(2);
+        assert!(buffer_bytes(2).is_empty());
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Sender badges — kernel-stamped identity on receive (x4/x5)
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn badge_direct_send_stamps_sender_and_badge() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].badges[0] = 0x434C;
+        (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(1);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        frame.x[0] = 42;
+        ipc::sys_send(&mut frame, 0);
+
+        let rx = &(*sched::TCBS.get_mut())[1].context;
+        assert_eq!(rx.x[0], 42);
+        assert_eq!(rx.x[ipc::MSG_REG_SENDER], 0);
+        assert_eq!(rx.x[ipc::MSG_REG_BADGE], 0x434C);
+    }
+}
+
+#[test]
+fn badge_cannot_be_forged_through_registers() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[3].badges[1] = 0x534E; // genuine sensor
+        *sched::CURRENT.get_mut() = 2; // unbadged impostor
+        (*sched::TCBS.get_mut())[2].state = TaskState::Running;
+        (*ipc::ENDPOINTS.get_mut())[1].receiver = Some(4);
+        (*sched::TCBS.get_mut())[4].state = TaskState::Blocked;
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[2].context);
+        frame.x[1] = 0x534E; // self-written tag, ignored
+        frame.x[4] = 3;      // claims to be the sensor — but x4 is its timeout
+        frame.x[5] = 0x534E << 1; // no MSG_FLAG_BYTES; badge bits ignored
+        ipc::sys_send(&mut frame, 1);
+
+        let rx = &(*sched::TCBS.get_mut())[4].context;
+        assert_eq!(rx.x[ipc::MSG_REG_SENDER], 2);
+        assert_eq!(rx.x[ipc::MSG_REG_BADGE], ipc::NO_BADGE);
+    }
+}
+
+#[test]
+fn badge_queued_sender_visible_in_receiver_frame() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].badges[0] = 7;
+        let mut f0 = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_send(&mut f0, 0);
+
+        *sched::CURRENT.get_mut() = 1;
+        (*sched::TCBS.get_mut())[1].state = TaskState::Running;
+        let mut f1 = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        f1.x[4] = ipc::TIMEOUT_INFINITE;
+        ipc::sys_recv(&mut f1, 0);
+
+        assert_eq!(f1.x[ipc::MSG_REG_SENDER], 0);
+        assert_eq!(f1.x[ipc::MSG_REG_BADGE], 7);
+    }
+}
+
+#[test]
+fn badge_is_per_endpoint() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].badges = [0, 0xB1, 0, 0];
+        (*ipc::ENDPOINTS.get_mut())[0].receiver = Some(1);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Blocked;
+
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[0].context);
+        ipc::sys_send(&mut frame, 0);
+
+        // The EP1 badge is not presented on EP0
+        assert_eq!((*sched::TCBS.get_mut())[1].context.x[ipc::MSG_REG_BADGE], ipc::NO_BADGE);
+        assert_eq!(ipc::sender_badge(0, 1), 0xB1);
+    }
+}
+
+#[test]
+fn badge_call_and_reply_identity() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].badges[0] = 0x434C;
+        setup_pending_call(0, 1);
+
+        // Server sees the caller's id and badge
+        let srv = &(*sched::TCBS.get_mut())[1].context;
+        assert_eq!(srv.x[ipc::MSG_REG_SENDER], 0);
+        assert_eq!(srv.x[ipc::MSG_REG_BADGE], 0x434C);
+
+        // Caller sees the replier's id; replies carry no badge
+        (*sched::TCBS.get_mut())[1].badges[0] = 0x99;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[1].context);
+        ipc::sys_reply(&mut frame);
+        let caller = &(*sched::TCBS.get_mut())[0].context;
+        assert_eq!(caller.x[ipc::MSG_REG_SENDER], 1);
+        assert_eq!(caller.x[ipc::MSG_REG_BADGE], ipc::NO_BADGE);
+    }
+}
+
+#[test]
+fn badge_lookup_out_of_range() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].badges = [5; MAX_ENDPOINTS];
+    }
+    assert_eq!(ipc::sender_badge(0, MAX_ENDPOINTS), ipc::NO_BADGE);
+    assert_eq!(ipc::sender_badge(NUM_TASKS, 0), ipc::NO_BADGE);
+}
+
+#[test]
+fn badge_survives_restart() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[2].badges[1] = 0x534E;
+        (*sched::TCBS.get_mut())[2].state = TaskState::Faulted;
+        sched::restart_task(2);
+        assert_eq!(ipc::sender_badge(2, 1), 0x534E);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Message queues — buffered asynchronous IPC (queuing ports)
+// ═══════════════════════════════════════════════════════════════════
+
+const TEST_QUEUE: QueueConfig = QueueConfig { depth: 3, msg_words: MSG_REGS, policy: OverflowPolicy::DropNewest };
+
+/// Enqueue `m0` on queue `q` as task `task` (made current). Returns x0.
+unsafe fn queue_send_as(task: usize, q: usize, m0: u64) -> u64 {
+    *sched::CURRENT.get_mut() = task;
+    (*sched::TCBS.get_mut())[task].state = TaskState::Running;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[task].context);
+    frame.x[0] = m0;
+    queue::sys_queue_send(&mut frame, q);
+    frame.x[0]
+}
+
+/// Dequeue from queue `q` as task `task` (made current). Returns the frame.
+unsafe fn queue_recv_as(task: usize, q: usize, timeout: u64) -> TrapFrame {
+    *sched::CURRENT.get_mut() = task;
+    (*sched::TCBS.get_mut())[task].state = TaskState::Running;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[task].context);
+    queue::sys_queue_recv(&mut frame, q, timeout);
+    frame
+}
+
+#[test]
+fn queue_config_validation() {
+    assert!(queue::queue_config_valid(&TEST_QUEUE));
+    assert!(!queue::queue_config_valid(&QueueConfig { depth: 0, ..TEST_QUEUE }));
+    assert!(!queue::queue_config_valid(&QueueConfig { depth: queue::QUEUE_MAX_DEPTH + 1, ..TEST_QUEUE }));
+    assert!(!queue::queue_config_valid(&QueueConfig { msg_words: 0, ..TEST_QUEUE }));
+    assert!(!queue::queue_config_valid(&QueueConfig { msg_words: MSG_REGS + 1, ..TEST_QUEUE }));
+    unsafe { reset_test_state(); }
+    assert_eq!(queue::queue_configure(queue::MAX_QUEUES, TEST_QUEUE), queue::ERR_QUEUE_INVALID);
+    assert_eq!(queue::queue_configure(0, TEST_QUEUE), 0);
+}
+
+#[test]
+fn queue_unconfigured_rejected() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(queue_send_as(3, 0, 1), queue::ERR_QUEUE_INVALID);
+        assert_eq!(queue_recv_as(4, 1, ipc::TIMEOUT_INFINITE).x[0], queue::ERR_QUEUE_INVALID);
+        assert_eq!(queue_send_as(3, 7, 1), queue::ERR_QUEUE_INVALID);
+    }
+}
+
+#[test]
+fn queue_send_never_blocks_and_fifo_order() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        for v in 10..13 {
+            assert_eq!(queue_send_as(3, 0, v), 0);
+            assert_eq!((*sched::TCBS.get_mut())[3].state, TaskState::Running);
+        }
+        for v in 10..13 {
+            let f = queue_recv_as(4, 0, ipc::TIMEOUT_POLL);
+            assert_eq!(f.x[0], v);
+            assert_eq!(f.x[ipc::MSG_REG_SENDER], 3);
+            assert_eq!(f.x[ipc::MSG_REG_BADGE], ipc::NO_BADGE);
+        }
+    }
+}
+
+#[test]
+fn queue_msg_words_truncates_payload() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, QueueConfig { msg_words: 1, ..TEST_QUEUE });
+        *sched::CURRENT.get_mut() = 3;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[3].context);
+        frame.x[0] = 1;
+        frame.x[1] = 2;
+        frame.x[3] = 4;
+        queue::sys_queue_send(&mut frame, 0);
+        let f = queue_recv_as(4, 0, ipc::TIMEOUT_POLL);
+        assert_eq!(&f.x[..MSG_REGS], &[1, 0, 0, 0]);
+    }
+}
+
+#[test]
+fn queue_drop_newest_rejects_and_counts() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        for v in 0..3 {
+            queue_send_as(3, 0, v);
+        }
+        assert_eq!(queue_send_as(3, 0, 99), queue::ERR_QUEUE_FULL);
+        let stats = queue::queue_stats(0).unwrap();
+        assert_eq!((stats.fill, stats.dropped), (3, 1));
+        assert_eq!(queue_recv_as(4, 0, ipc::TIMEOUT_POLL).x[0], 0, "oldest kept");
+    }
+}
+
+#[test]
+fn queue_drop_oldest_keeps_newest() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, QueueConfig { policy: OverflowPolicy::DropOldest, ..TEST_QUEUE });
+        for v in 0..4 {
+            assert_eq!(queue_send_as(3, 0, v), 0);
+        }
+        let got: Vec<u64> = (0..3).map(|_| queue_recv_as(4, 0, ipc::TIMEOUT_POLL).x[0]).collect();
+        assert_eq!(got, vec![1, 2, 3]);
+        assert_eq!(queue::queue_stats(0).unwrap().dropped, 1);
+    }
+}
+
+#[test]
+fn queue_fault_sender_on_overflow() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, QueueConfig { policy: OverflowPolicy::FaultSender, ..TEST_QUEUE });
+        for v in 0..3 {
+            queue_send_as(3, 0, v);
+        }
+        queue_send_as(3, 0, 3);
+        assert_eq!((*sched::TCBS.get_mut())[3].state, TaskState::Faulted);
+        assert_eq!(queue::queue_stats(0).unwrap().fill, 3, "queued data survives");
+    }
+}
+
+#[test]
+fn queue_blocked_receiver_gets_direct_delivery() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        queue_recv_as(4, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!((*sched::TCBS.get_mut())[4].state, TaskState::Blocked);
+        assert_eq!((*queue::QUEUES.get())[0].receiver, Some(4));
+
+        assert_eq!(queue_send_as(3, 0, 0x42), 0);
+        let rx = &(*sched::TCBS.get_mut())[4];
+        assert_eq!(rx.state, TaskState::Ready);
+        assert_eq!(rx.context.x[0], 0x42);
+        assert_eq!(rx.context.x[ipc::MSG_REG_SENDER], 3);
+        assert_eq!(queue::queue_stats(0).unwrap().fill, 0, "bypassed the ring");
+    }
+}
+
+#[test]
+fn queue_send_skips_receiver_no_longer_blocked() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        queue_recv_as(4, 0, ipc::TIMEOUT_INFINITE);
+        // Faulted behind the queue's back (no cleanup ran)
+        (*sched::TCBS.get_mut())[4].state = TaskState::Faulted;
+
+        assert_eq!(queue_send_as(3, 0, 0x42), 0);
+        assert_eq!((*sched::TCBS.get_mut())[4].state, TaskState::Faulted);
+        assert!((*queue::QUEUES.get())[0].receiver.is_none());
+        assert_eq!(queue::queue_stats(0).unwrap().fill, 1, "queued, not handed over");
+        assert_eq!(queue_recv_as(2, 0, ipc::TIMEOUT_POLL).x[0], 0x42);
+    }
+}
+
+#[test]
+fn queue_poll_empty_and_busy_receiver() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        assert_eq!(queue_recv_as(4, 0, ipc::TIMEOUT_POLL).x[0], ipc::ERR_IPC_TIMEOUT);
+        queue_recv_as(4, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!(queue_recv_as(2, 0, ipc::TIMEOUT_INFINITE).x[0], queue::ERR_QUEUE_BUSY);
+    }
+}
+
+#[test]
+fn queue_timed_recv_expires() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        queue_recv_as(4, 0, 5);
+        aegis_os::timer::advance_ticks(5);
+        assert_eq!((*sched::TCBS.get_mut())[4].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[4].context.x[0], ipc::ERR_IPC_TIMEOUT);
+        assert!((*queue::QUEUES.get())[0].receiver.is_none());
+    }
+}
+
+#[test]
+fn queue_stats_and_fault_cleanup() {
+    unsafe {
+        reset_test_state();
+        queue::queue_configure(0, TEST_QUEUE);
+        queue::queue_configure(1, TEST_QUEUE);
+        queue_send_as(3, 0, 1);
+        queue_send_as(3, 0, 2);
+        queue_recv_as(4, 0, ipc::TIMEOUT_POLL);
+        let s = queue::queue_stats(0).unwrap();
+        assert_eq!((s.fill, s.depth, s.high_water, s.dropped), (1, 3, 2, 0));
+
+        queue_recv_as(4, 1, ipc::TIMEOUT_INFINITE);
+        sched::cleanup_task_resources(4);
+        assert!((*queue::QUEUES.get())[1].receiver.is_none());
+    }
+}
+
+#[test]
+fn cap_queue_bits_and_syscall_mapping() {
+    assert_eq!(CAP_QUEUE_SEND_Q0, 1 << 21);
+    assert!(cap::cap_check(CAP_ALL, CAP_QUEUE_RECV_Q1));
+    assert_eq!(cap::cap_for_syscall(18, 0), CAP_QUEUE_SEND_Q0);
+    assert_eq!(cap::cap_for_syscall(18, 1), CAP_QUEUE_SEND_Q1);
+    assert_eq!(cap::cap_for_syscall(19, 0), CAP_QUEUE_RECV_Q0);
+    assert_eq!(cap::cap_for_syscall(19, 2), 0);
+    assert_eq!(cap::cap_name(CAP_QUEUE_RECV_Q0), "QUEUE_RECV_Q0");
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Sampling ports — last-value channels with freshness metadata
+// ═══════════════════════════════════════════════════════════════════
+
+const TEST_PORT: SamplingConfig = SamplingConfig { msg_words: MSG_REGS, refresh_period: 10 };
+
+/// Write `m0` to sampling port `port` as task `task` (made current). Returns x0.
+unsafe fn sample_write_as(task: usize, port: usize, m0: u64) -> u64 {
+    *sched::CURRENT.get_mut() = task;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[task].context);
+    frame.x[0] = m0;
+    sampling::sys_sample_write(&mut frame, port);
+    frame.x[0]
+}
+
+/// Read sampling port `port` as task `task`; returns the result frame.
+unsafe fn sample_read_as(task: usize, port: usize) -> TrapFrame {
+    *sched::CURRENT.get_mut() = task;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[task].context);
+    sampling::sys_sample_read(&mut frame, port);
+    frame
+}
+
+#[test]
+fn sampling_config_validation() {
+    assert!(sampling::sampling_config_valid(&TEST_PORT));
+    assert!(!sampling::sampling_config_valid(&SamplingConfig { msg_words: 0, ..TEST_PORT }));
+    assert!(!sampling::sampling_config_valid(&SamplingConfig { msg_words: MSG_REGS + 1, ..TEST_PORT }));
+    assert!(!sampling::sampling_config_valid(&SamplingConfig { refresh_period: 0, ..TEST_PORT }));
+    unsafe { reset_test_state(); }
+    assert_eq!(sampling::sampling_configure(sampling::MAX_SAMPLING_PORTS, TEST_PORT), sampling::ERR_PORT_INVALID);
+    assert_eq!(sampling::sampling_configure(0, TEST_PORT), 0);
+}
+
+#[test]
+fn sampling_unconfigured_rejected() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(sample_write_as(3, 0, 1), sampling::ERR_PORT_INVALID);
+        assert_eq!(sample_read_as(4, 0).x[0], sampling::ERR_PORT_INVALID);
+        assert_eq!(sample_write_as(3, 9, 1), sampling::ERR_PORT_INVALID);
+    }
+}
+
+#[test]
+fn sampling_read_before_first_write_is_empty() {
+    unsafe {
+        reset_test_state();
+        sampling::sampling_configure(0, TEST_PORT);
+        let f = sample_read_as(4, 0);
+        assert_eq!(f.x[0], sampling::ERR_PORT_EMPTY);
+        assert_eq!(f.x[5] & sampling::SAMPLE_VALID, 0);
+    }
+}
+
+#[test]
+fn sampling_read_reports_age_and_validity() {
+    unsafe {
+        reset_test_state();
+        sampling::sampling_configure(0, TEST_PORT);
+        aegis_os::timer::advance_ticks(5);
+        assert_eq!(sample_write_as(3, 0, 0x77), 0);
+
+        let f = sample_read_as(4, 0);
+        assert_eq!((f.x[0], f.x[4], f.x[5]), (0x77, 0, sampling::SAMPLE_VALID));
+
+        aegis_os::timer::advance_ticks(10);
+        let f = sample_read_as(4, 0);
+        assert_eq!((f.x[4], f.x[5]), (10, sampling::SAMPLE_VALID), "age == period still valid");
+
+        aegis_os::timer::advance_ticks(1);
+        let f = sample_read_as(4, 0);
+        assert_eq!((f.x[0], f.x[4], f.x[5]), (0x77, 11, 0), "stale sample still returned");
+    }
+}
+
+#[test]
+fn sampling_last_value_wins_and_refreshes() {
+    unsafe {
+        reset_test_state();
+        sampling::sampling_configure(0, TEST_PORT);
+        sample_write_as(3, 0, 1);
+        aegis_os::timer::advance_ticks(20);
+        sample_write_as(3, 0, 2);
+        let f = sample_read_as(4, 0);
+        assert_eq!((f.x[0], f.x[4], f.x[5]), (2, 0, sampling::SAMPLE_VALID));
+        assert_eq!((*sampling::SAMPLING_PORTS.get())[0].writer, 3);
+    }
+}
+
+#[test]
+fn sampling_read_does_not_consume() {
+    unsafe {
+        reset_test_state();
+        sampling::sampling_configure(1, TEST_PORT);
+        sample_write_as(3, 1, 0xAB);
+        assert_eq!(sample_read_as(4, 1).x[0], 0xAB);
+        assert_eq!(sample_read_as(2, 1).x[0], 0xAB, "second reader sees same sample");
+        assert_eq!(sample_read_as(4, 0).x[0], sampling::ERR_PORT_INVALID, "ports are independent");
+    }
+}
+
+#[test]
+fn sampling_msg_words_truncates_payload() {
+    unsafe {
+        reset_test_state();
+        sampling::sampling_configure(0, SamplingConfig { msg_words: 2, ..TEST_PORT });
+        *sched::CURRENT.get_mut() = 3;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[3].context);
+        frame.x[..MSG_REGS].copy_from_slice(&[1, 2, 3, 4]);
+        sampling::sys_sample_write(&mut frame, 0);
+        assert_eq!(&sample_read_as(4, 0).x[..MSG_REGS], &[1, 2, 0, 0]);
+    }
+}
+
+#[test]
+fn cap_sample_bits_and_syscall_mapping() {
+    assert_eq!(CAP_SAMPLE_WRITE_P0, 1 << 25);
+    assert!(cap::cap_check(CAP_ALL, CAP_SAMPLE_READ_P1));
+    assert_eq!(cap::cap_for_syscall(20, 0), CAP_SAMPLE_WRITE_P0);
+    assert_eq!(cap::cap_for_syscall(20, 1), CAP_SAMPLE_WRITE_P1);
+    assert_eq!(cap::cap_for_syscall(21, 0), CAP_SAMPLE_READ_P0);
+    assert_eq!(cap::cap_for_syscall(21, 2), 0);
+    assert_eq!(cap::cap_name(CAP_SAMPLE_READ_P0), "SAMPLE_READ_P0");
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Wait sets — SYS_RECV_ANY over endpoints + notification bits
+// ═══════════════════════════════════════════════════════════════════
+
+/// SYS_RECV_ANY as task `task` (made current); returns the result frame.
+unsafe fn recv_any_as(task: usize, ep_set: u64, mask: u64, timeout: u64) -> TrapFrame {
+    *sched::CURRENT.get_mut() = task;
+    (*sched::TCBS.get_mut())[task].state = TaskState::Running;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[task].context);
+    ipc::sys_recv_any(&mut frame, ep_set, mask, timeout);
+    frame
+}
+
+/// SYS_SEND of `m0` on `ep` as task `task` (made current).
+unsafe fn send_as(task: usize, ep: usize, m0: u64) {
+    *sched::CURRENT.get_mut() = task;
+    (*sched::TCBS.get_mut())[task].state = TaskState::Running;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[task].context);
+    frame.x[0] = m0;
+    frame.x[5] = 0;
+    ipc::sys_send(&mut frame, ep);
+}
+
+#[test]
+fn wait_any_source_priority() {
+    assert_eq!(ipc::wait_any_source(0b0110, 0b0110, 0), Some(1), "lowest ready endpoint");
+    assert_eq!(ipc::wait_any_source(0b0100, 0b0110, 0), Some(2), "only endpoints in the set");
+    assert_eq!(ipc::wait_any_source(0b0110, 0b0110, 1), Some(ipc::WAIT_SRC_NOTIFY), "notifications first");
+    assert_eq!(ipc::wait_any_source(0b0001, 0b0110, 0), None);
+}
+
+#[test]
+fn recv_any_pending_notification_returns_at_once() {
+    unsafe {
+        reset_test_state();
+        (*sched::TCBS.get_mut())[0].notify_pending = 0b101;
+        let f = recv_any_as(0, 0b1, 0b100, ipc::TIMEOUT_INFINITE);
+        assert_eq!((f.x[0], f.x[6]), (0b100, ipc::WAIT_SRC_NOTIFY));
+        assert_eq!((*sched::TCBS.get_mut())[0].notify_pending, 0b001, "unmasked bits stay pending");
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Running);
+    }
+}
+
+#[test]
+fn recv_any_takes_queued_sender_on_lowest_endpoint() {
+    unsafe {
+        reset_test_state();
+        send_as(2, 2, 0x22);
+        send_as(3, 1, 0x11);
+        let f = recv_any_as(0, 0b0110, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!((f.x[0], f.x[ipc::MSG_REG_SENDER], f.x[6]), (0x11, 3, 1));
+        assert_eq!((*sched::TCBS.get_mut())[3].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get_mut())[2].state, TaskState::Blocked, "EP 2 sender still queued");
+    }
+}
+
+#[test]
+fn recv_any_message_ends_whole_wait() {
+    unsafe {
+        reset_test_state();
+        recv_any_as(0, 0b0101, 0b1, ipc::TIMEOUT_INFINITE);
+        let eps = &*ipc::ENDPOINTS.get();
+        assert_eq!((eps[0].receiver, eps[2].receiver), (Some(0), Some(0)));
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked);
+
+        send_as(1, 2, 0x42);
+        let t0 = &(*sched::TCBS.get_mut())[0];
+        assert_eq!(t0.state, TaskState::Ready);
+        assert_eq!((t0.context.x[0], t0.context.x[6]), (0x42, 2));
+        assert!(!t0.wait_any && !t0.notify_waiting);
+        assert!((*ipc::ENDPOINTS.get())[0].receiver.is_none(), "withdrawn from EP 0");
+
+        // A late notification no longer wakes or clobbers it
+        ipc::notify(0, 0b1);
+        assert_eq!((*sched::TCBS.get_mut())[0].context.x[0], 0x42);
+    }
+}
+
+#[test]
+fn recv_any_notification_ends_whole_wait() {
+    unsafe {
+        reset_test_state();
+        recv_any_as(0, 0b0001, 0b1, ipc::TIMEOUT_INFINITE);
+        ipc::notify(0, 0b10);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Blocked, "bit not in mask");
+
+        ipc::notify(0, 0b1);
+        let t0 = &(*sched::TCBS.get_mut())[0];
+        assert_eq!(t0.state, TaskState::Ready);
+        assert_eq!((t0.context.x[0], t0.context.x[6]), (0b1, ipc::WAIT_SRC_NOTIFY));
+        assert_eq!(t0.notify_pending, 0b10);
+        assert_eq!(t0.notify_mask, ipc::NOTIFY_ALL);
+        assert!((*ipc::ENDPOINTS.get())[0].receiver.is_none());
+    }
+}
+
+#[test]
+fn recv_any_woken_by_bound_irq() {
+    unsafe {
+        reset_test_state();
+        assert_eq!(irq::irq_bind(33, 0, 1 << 3), 0);
+        recv_any_as(0, 0b0001, 1 << 3, ipc::TIMEOUT_INFINITE);
+        irq::irq_route_test(33, 0);
+        let t0 = &(*sched::TCBS.get_mut())[0];
+        assert_eq!(t0.state, TaskState::Ready);
+        assert_eq!((t0.context.x[0], t0.context.x[6]), (1 << 3, ipc::WAIT_SRC_NOTIFY));
+    }
+}
+
+#[test]
+fn recv_any_timeout_withdraws_from_all_sources() {
+    unsafe {
+        reset_test_state();
+        recv_any_as(0, 0b0011, 0b1, 5);
+        aegis_os::timer::advance_ticks(5);
+        let t0 = &(*sched::TCBS.get_mut())[0];
+        assert_eq!(t0.state, TaskState::Ready);
+        assert_eq!((t0.context.x[0], t0.context.x[6]), (ipc::ERR_IPC_TIMEOUT, ipc::WAIT_SRC_NONE));
+        assert!(!t0.notify_waiting);
+        let eps = &*ipc::ENDPOINTS.get();
+        assert!(eps[0].receiver.is_none() && eps[1].receiver.is_none());
+    }
+}
+
+#[test]
+fn recv_any_poll_busy_and_invalid_set() {
+    unsafe {
+        reset_test_state();
+        let f = recv_any_as(0, 0b0001, 0b1, ipc::TIMEOUT_POLL);
+        assert_eq!((f.x[0], f.x[6]), (ipc::ERR_IPC_TIMEOUT, ipc::WAIT_SRC_NONE));
+
+        (*ipc::ENDPOINTS.get_mut())[1].receiver = Some(4);
+        assert_eq!(recv_any_as(0, 0b0011, 0, ipc::TIMEOUT_INFINITE).x[0], ipc::ERR_IPC_BUSY);
+
+        recv_any_as(0, 0b0001 | 1 << MAX_ENDPOINTS, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!((*sched::TCBS.get_mut())[0].state, TaskState::Running, "rejected, not blocked");
+        assert!((*ipc::ENDPOINTS.get())[0].receiver.is_none());
+    }
+}
+
+#[test]
+fn recv_does_not_steal_wait_set_registration() {
+    unsafe {
+        reset_test_state();
+        recv_any_as(0, 0b0011, 0, ipc::TIMEOUT_INFINITE);
+
+        // A plain SYS_RECV on endpoint 1 is refused, not registered
+        *sched::CURRENT.get_mut() = 4;
+        (*sched::TCBS.get_mut())[4].state = TaskState::Running;
+        let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[4].context);
+        ipc::sys_recv_timeout(&mut frame, 1, 5);
+        assert_eq!(frame.x[0], ipc::ERR_IPC_BUSY);
+        assert_eq!((*sched::TCBS.get_mut())[4].state, TaskState::Running, "rejected, not blocked");
+        assert_eq!((*ipc::ENDPOINTS.get())[1].receiver, Some(0));
+
+        // Endpoint 1 still wakes the wait-set task
+        send_as(2, 1, 0x77);
+        let t0 = &(*sched::TCBS.get_mut())[0];
+        assert_eq!(t0.state, TaskState::Ready);
+        assert_eq!((t0.context.x[0], t0.context.x[6]), (0x77, 1));
+    }
+}
+
+#[test]
+fn cap_recv_any_needs_every_recv_right() {
+    assert_eq!(cap::cap_for_syscall(22, 0), CAP_WAIT_NOTIFY);
+    assert_eq!(
+        cap::cap_for_syscall(22, 0b0101),
+        CAP_WAIT_NOTIFY | CAP_IPC_RECV_EP0 | CAP_IPC_RECV_EP2
+    );
+    assert!(!cap::cap_check(CAP_WAIT_NOTIFY | CAP_IPC_RECV_EP0, cap::cap_for_syscall(22, 0b0011)));
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// CSpace-lite — typed capability slots
+// ═══════════════════════════════════════════════════════════════════
+
+/// Frame issuing syscall `nr` with `x6` and `x0` set.
+fn slot_frame(nr: u64, x6: u64, x0: u64) -> TrapFrame {
+    let mut frame: TrapFrame = unsafe { core::mem::zeroed() };
+    frame.x[7] = nr;
+    frame.x[6] = x6;
+    frame.x[0] = x0;
+    frame
+}
+
+#[test]
+fn cspace_check_slot_kind_and_rights() {
+    let ep_send = cspace::slot(ObjKind::Endpoint, 2, RIGHT_SEND);
+    assert_eq!(cspace::check_slot(&ep_send, ObjKind::Endpoint, RIGHT_SEND), Ok(2));
+    assert_eq!(cspace::check_slot(&ep_send, ObjKind::Endpoint, RIGHT_SEND | RIGHT_RECV), Err(SlotError::NoRights));
+    assert_eq!(cspace::check_slot(&ep_send, ObjKind::Queue, RIGHT_SEND), Err(SlotError::WrongKind));
+    assert_eq!(cspace::check_slot(&cspace::EMPTY_SLOT, ObjKind::Endpoint, 0), Err(SlotError::Empty));
+}
+
+#[test]
+fn cspace_object_ref_mapping() {
+    assert_eq!(cspace::object_ref(1), Some((6, ObjKind::Endpoint, RIGHT_SEND)));
+    assert_eq!(cspace::object_ref(3), Some((6, ObjKind::Endpoint, RIGHT_SEND | RIGHT_RECV)));
+    assert_eq!(cspace::object_ref(5), Some((6, ObjKind::Notification, RIGHT_SEND)));
+    assert_eq!(cspace::object_ref(7), Some((0, ObjKind::Grant, RIGHT_READ)));
+    assert_eq!(cspace::object_ref(27), Some((0, ObjKind::Grant, RIGHT_READ)));
+    assert_eq!(cspace::object_ref(11), Some((0, ObjKind::Device, RIGHT_READ | RIGHT_WRITE)));
+    assert_eq!(cspace::object_ref(21), Some((6, ObjKind::SamplingPort, RIGHT_RECV)));
+    for nr in [0, 4, 12, 13, 16, 22] {
+        assert_eq!(cspace::object_ref(nr), None, "syscall {} names no object", nr);
+    }
+    assert_eq!(cspace::coarse_caps(17), CAP_REPLY | CAP_IPC);
+    assert_eq!(cspace::coarse_caps(1), CAP_IPC);
+}
+
+#[test]
+fn cspace_slot_path_keeps_class_bit() {
+    let class = [
+        (1, CAP_IPC), (2, CAP_IPC), (3, CAP_IPC), (5, CAP_NOTIFY),
+        (7, CAP_GRANT_CREATE), (27, CAP_GRANT_CREATE), (8, CAP_GRANT_REVOKE), (28, CAP_GRANT_REVOKE),
+        (9, CAP_IRQ_BIND), (10, CAP_IRQ_ACK), (11, CAP_DEVICE_MAP),
+        (18, CAP_QUEUE), (19, CAP_QUEUE), (20, CAP_SAMPLE), (21, CAP_SAMPLE),
+    ];
+    for (nr, bit) in class {
+        assert_eq!(cspace::coarse_caps(nr), bit, "syscall {}", nr);
+        assert!(!cap::cap_check(CAP_ALL & !bit, cspace::coarse_caps(nr)), "dropping the class bit blocks syscall {}", nr);
+    }
+    // Class bits name no object: they never satisfy the raw-id path
+    for nr in 0..=32 {
+        for id in 0..4 {
+            assert_eq!(cap::cap_for_syscall(nr, id) & (CAP_IPC | CAP_QUEUE | CAP_SAMPLE), 0);
+        }
+    }
+    assert_eq!(cap::cap_name(CAP_QUEUE), "QUEUE");
+}
+
+#[test]
+fn cspace_raw_id_uses_bitmask() {
+    unsafe { reset_test_state(); }
+    assert_eq!(cspace::resolve(1, 1, &slot_frame(1, 0, 0)), Authority::Bitmask);
+    assert_eq!(cspace::resolve(1, 4, &slot_frame(4, CPTR_FLAG, 0)), Authority::Bitmask, "SYS_WRITE has no object");
+}
+
+#[test]
+fn cspace_slot_resolves_object_id() {
+    unsafe { reset_test_state(); }
+    let mut cs = EMPTY_CSPACE;
+    cs[3] = cspace::slot(ObjKind::Endpoint, 2, RIGHT_SEND | RIGHT_RECV);
+    cs[4] = cspace::slot(ObjKind::Device, 0, RIGHT_READ | RIGHT_WRITE);
+    cspace::install(1, cs);
+    assert_eq!(cspace::resolve(1, 3, &slot_frame(3, CPTR_FLAG | 3, 0)), Authority::Slot { reg: 6, id: 2, rights: RIGHT_SEND | RIGHT_RECV });
+    assert_eq!(cspace::resolve(1, 11, &slot_frame(11, 0, CPTR_FLAG | 4)), Authority::Slot { reg: 0, id: 0, rights: RIGHT_READ | RIGHT_WRITE });
+}
+
+#[test]
+fn cspace_slot_denials() {
+    unsafe { reset_test_state(); }
+    let mut cs = EMPTY_CSPACE;
+    cs[0] = cspace::slot(ObjKind::Endpoint, 0, RIGHT_SEND);
+    cspace::install(1, cs);
+    let deny = |nr, x6| cspace::resolve(1, nr, &slot_frame(nr, x6, 0));
+    assert_eq!(deny(3, CPTR_FLAG), Authority::Denied(SlotError::NoRights), "send-only slot cannot call");
+    assert_eq!(deny(18, CPTR_FLAG), Authority::Denied(SlotError::WrongKind));
+    assert_eq!(deny(1, CPTR_FLAG | 1), Authority::Denied(SlotError::Empty));
+    assert_eq!(deny(1, CPTR_FLAG | cspace::CSPACE_SLOTS as u64), Authority::Denied(SlotError::BadIndex));
+    assert_eq!(deny(1, CPTR_FLAG), Authority::Slot { reg: 6, id: 0, rights: RIGHT_SEND });
+}
+
+#[test]
+fn cspace_read_only_grant_slot_maps_read_only() {
+    use grant::GrantPerm::{ReadOnly, ReadWrite};
+    unsafe {
+        reset_test_state();
+        let mut cs = EMPTY_CSPACE;
+        cs[0] = cspace::slot(ObjKind::Grant, 0, RIGHT_READ);
+        cs[1] = cspace::slot(ObjKind::Grant, 1, RIGHT_READ | RIGHT_WRITE);
+        cspace::install(0, cs);
+        let Authority::Slot { id, rights, .. } = cspace::resolve(0, 7, &slot_frame(7, 0, CPTR_FLAG)) else {
+            panic!("READ is enough to name the grant");
+        };
+        assert_eq!(rights, RIGHT_READ);
+
+        // Asking for read-write through a READ-only slot maps both sides RO
+        let flags = cspace::grant_flags(7, rights, 0);
+        assert_eq!(grant::grant_create_region(id as usize, 0, 1, 1, 1, flags), 0);
+        let g = (*grant::GRANTS.get())[0];
+        assert_eq!((g.owner_perm, g.peer_perm(1)), (ReadOnly, Some(ReadOnly)));
+        assert_eq!(grant::grant_add_peer(0, 0, 2, cspace::grant_flags(27, rights, 0)), 0);
+        assert_eq!((*grant::GRANTS.get())[0].peer_perm(2), Some(ReadOnly));
+        assert_eq!(ReadOnly.page_attrs() & (0b11 << 6), mmu::AP_RO_EL0, "no EL0 store");
+
+        // RIGHT_WRITE leaves the caller's flags alone
+        let rw = RIGHT_READ | RIGHT_WRITE;
+        assert_eq!(cspace::grant_flags(7, rw, 0), 0);
+        assert_eq!(cspace::grant_flags(27, rw, grant::GRANT_PEER_RO), grant::GRANT_PEER_RO);
+        assert_eq!(grant::grant_create_region(1, 0, 1, 1, 1, cspace::grant_flags(7, rw, 0)), 0);
+        assert_eq!((*grant::GRANTS.get())[1].owner_perm, ReadWrite);
+        assert_eq!(cspace::grant_flags(8, RIGHT_READ, 0), 0, "revoke maps nothing");
+    }
+}
+
+#[test]
+fn cspace_tables_are_per_task() {
+    unsafe { reset_test_state(); }
+    let mut cs = EMPTY_CSPACE;
+    cs[0] = cspace::slot(ObjKind::Queue, 1, RIGHT_RECV);
+    cspace::install(4, cs);
+    assert_eq!(cspace::resolve(4, 19, &slot_frame(19, CPTR_FLAG, 0)), Authority::Slot { reg: 6, id: 1, rights: RIGHT_RECV });
+    assert_eq!(cspace::resolve(3, 19, &slot_frame(19, CPTR_FLAG, 0)), Authority::Denied(SlotError::Empty));
+    assert_eq!(cspace::get_slot(NUM_TASKS, 0), None);
+    assert_eq!(cspace::get_slot(4, cspace::CSPACE_SLOTS), None);
+}
+
+#[test]
+fn cspace_survives_restart() {
+    unsafe {
+        reset_test_state();
+        let slot: CapSlot = cspace::slot(ObjKind::Notification, 0, RIGHT_SEND);
+        let mut cs = EMPTY_CSPACE;
+        cs[2] = slot;
+        cspace::install(1, cs);
+        (*sched::TCBS.get_mut())[1].state = TaskState::Faulted;
+        sched::restart_task(1);
+        assert_eq!(cspace::get_slot(1, 2), Some(slot));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Capability delegation — mint / revoke
+// ═══════════════════════════════════════════════════════════════════
+
+/// Task 0 holds EP 1 with every right (incl. grant) in slot 0.
+unsafe fn setup_delegator() {
+    reset_test_state();
+    let mut cs = EMPTY_CSPACE;
+    cs[0] = cspace::slot(ObjKind::Endpoint, 1, cspace::RIGHTS_ALL);
+    cspace::install(0, cs);
+}
+
+/// SYS_RECV on `ep` as task `task` (made current); blocks if no sender.
+unsafe fn recv_as(task: usize, ep: usize) {
+    *sched::CURRENT.get_mut() = task;
+    (*sched::TCBS.get_mut())[task].state = TaskState::Running;
+    let mut frame = core::ptr::read(&(*sched::TCBS.get_mut())[task].context);
+    ipc::sys_recv(&mut frame, ep);
+}
+
+#[test]
+fn cspace_mint_attenuates_and_records_parent() {
+    unsafe {
+        setup_delegator();
+        assert_eq!(cspace::mint(0, 0, 2, 3, RIGHT_SEND), 0);
+        let copy = cspace::get_slot(2, 3).unwrap();
+        assert_eq!(copy, cspace::slot(ObjKind::Endpoint, 1, RIGHT_SEND), "only the masked rights");
+        assert_eq!(cspace::parent_of(2, 3), Some(SlotRef { task: 0, index: 0 }));
+        assert_eq!(cspace::parent_of(0, 0), None, "installed slots are roots");
+        assert_eq!(cspace::resolve(2, 1, &slot_frame(1, CPTR_FLAG | 3, 0)), Authority::Slot { reg: 6, id: 1, rights: RIGHT_SEND });
+        assert_eq!(cspace::resolve(2, 2, &slot_frame(2, CPTR_FLAG | 3, 0)), Authority::Denied(SlotError::NoRights));
+    }
+}
+
+#[test]
+fn cspace_mint_requires_grant_right() {
+    unsafe {
+        setup_delegator();
+        assert_eq!(cspace::mint(0, 0, 2, 0, RIGHT_SEND), 0);
+        assert_eq!(cspace::mint(2, 0, 3, 0, RIGHT_SEND), cspace::ERR_SLOT_NO_GRANT, "copy without grant cannot re-mint");
+        assert_eq!(cspace::mint(0, 0, 2, 1, RIGHT_SEND | RIGHT_GRANT), 0);
+        assert_eq!(cspace::mint(2, 1, 3, 0, cspace::RIGHTS_ALL), 0);
+        assert_eq!(cspace::get_slot(3, 0).unwrap().rights, RIGHT_SEND | RIGHT_GRANT, "never more than the source");
+    }
+}
+
+#[test]
+fn cspace_mint_rejects_bad_slots() {
+    unsafe {
+        setup_delegator();
+        assert_eq!(cspace::mint(0, 1, 2, 0, RIGHT_SEND), cspace::ERR_SLOT_INVALID, "empty source");
+        assert_eq!(cspace::mint(0, cspace::CSPACE_SLOTS, 2, 0, RIGHT_SEND), cspace::ERR_SLOT_INVALID);
+        assert_eq!(cspace::mint(0, 0, NUM_TASKS, 0, RIGHT_SEND), cspace::ERR_SLOT_INVALID);
+        assert_eq!(cspace::mint(0, 0, 2, cspace::CSPACE_SLOTS, RIGHT_SEND), cspace::ERR_SLOT_INVALID);
+        assert_eq!(cspace::mint(0, 0, 0, 0, RIGHT_SEND), cspace::ERR_SLOT_OCCUPIED);
+        assert_eq!(cspace::get_slot(0, 0).unwrap().rights, cspace::RIGHTS_ALL, "source untouched");
+    }
+}
+
+#[test]
+fn cspace_revoke_deletes_derived_subtree() {
+    unsafe {
+        setup_delegator();
+        let mut other = EMPTY_CSPACE;
+        other[0] = cspace::slot(ObjKind::Endpoint, 1, cspace::RIGHTS_ALL);
+        cspace::install(4, other);
+        // 0:0 → 2:0 → 3:0, and an unrelated copy 4:0 → 2:1
+        assert_eq!(cspace::mint(0, 0, 2, 0, cspace::RIGHTS_ALL), 0);
+        assert_eq!(cspace::mint(2, 0, 3, 0, RIGHT_SEND), 0);
+        assert_eq!(cspace::mint(4, 0, 2, 1, RIGHT_SEND), 0);
+
+        assert_eq!(cspace::revoke(0, 0), 2);
+        assert!(cspace::get_slot(0, 0).unwrap().kind == ObjKind::Endpoint, "revoked slot itself kept");
+        assert_eq!(cspace::get_slot(2, 0), Some(cspace::EMPTY_SLOT));
+        assert_eq!(cspace::get_slot(3, 0), Some(cspace::EMPTY_SLOT), "grandchild deleted");
+        assert_eq!(cspace::parent_of(3, 0), None);
+        assert_eq!(cspace::get_slot(2, 1).unwrap().id, 1, "copy from another root survives");
+        assert_eq!(cspace::revoke(0, 0), 0, "nothing left to revoke");
+        assert_eq!(cspace::revoke(0, 1), cspace::ERR_SLOT_INVALID);
+    }
+}
+
+#[test]
+fn cspace_revoke_unblocks_endpoint_waiters() {
+    unsafe {
+        setup_delegator();
+        assert_eq!(cspace::mint(0, 0, 2, 0, RIGHT_RECV), 0);
+        assert_eq!(cspace::mint(0, 0, 3, 0, RIGHT_SEND), 0);
+        recv_as(2, 1);
+        assert_eq!((*ipc::ENDPOINTS.get())[1].receiver, Some(2));
+        // a sender queued on another endpoint through a minted slot
+        let mut cs = EMPTY_CSPACE;
+        cs[0] = cspace::slot(ObjKind::Endpoint, 2, RIGHT_SEND | RIGHT_GRANT);
+        cspace::install(5, cs);
+        assert_eq!(cspace::mint(5, 0, 4, 0, RIGHT_SEND), 0);
+        send_as(4, 2, 0xAB);
+        assert!((*ipc::ENDPOINTS.get())[2].sender_queue.contains(4));
+
+        assert_eq!(cspace::revoke(0, 0), 2);
+        assert_eq!((*ipc::ENDPOINTS.get())[1].receiver, None);
+        assert_eq!((*sched::TCBS.get())[2].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get())[2].context.x[0], cspace::ERR_CAP_REVOKED);
+
+        assert_eq!(cspace::revoke(5, 0), 1);
+        assert!(!(*ipc::ENDPOINTS.get())[2].sender_queue.contains(4));
+        assert_eq!((*sched::TCBS.get())[4].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get())[4].context.x[0], cspace::ERR_CAP_REVOKED);
+    }
+}
+
+#[test]
+fn cspace_revoke_ends_wait_set_and_queue_wait() {
+    unsafe {
+        setup_delegator();
+        queue::queue_configure(0, TEST_QUEUE);
+        let mut cs = EMPTY_CSPACE;
+        cs[0] = cspace::slot(ObjKind::Queue, 0, RIGHT_RECV | RIGHT_GRANT);
+        cspace::install(5, cs);
+        assert_eq!(cspace::mint(0, 0, 2, 0, RIGHT_RECV), 0);
+        assert_eq!(cspace::mint(5, 0, 3, 0, RIGHT_RECV), 0);
+
+        recv_any_as(2, 0b0011, 0, ipc::TIMEOUT_INFINITE);
+        queue_recv_as(3, 0, ipc::TIMEOUT_INFINITE);
+        assert_eq!((*sched::TCBS.get())[2].state, TaskState::Blocked);
+        assert_eq!((*sched::TCBS.get())[3].state, TaskState::Blocked);
+
+        cspace::revoke(0, 0);
+        let tcb = &(*sched::TCBS.get())[2];
+        assert_eq!(tcb.state, TaskState::Ready);
+        assert!(!tcb.wait_any);
+        assert_eq!(tcb.context.x[6], ipc::WAIT_SRC_NONE);
+        assert_eq!((*ipc::ENDPOINTS.get())[0].receiver, None, "whole wait set withdrawn");
+
+        cspace::revoke(5, 0);
+        assert_eq!((*queue::QUEUES.get())[0].receiver, None);
+        assert_eq!((*sched::TCBS.get())[3].state, TaskState::Ready);
+        assert_eq!((*sched::TCBS.get())[3].context.x[0], cspace::ERR_CAP_REVOKED);
+    }
+}
+
+#[test]
+fn cspace_revoke_unbinds_irq_unless_still_held() {
+    unsafe {
+        reset_test_state();
+        let mut cs = EMPTY_CSPACE;
+        cs[0] = cspace::slot(ObjKind::Irq, 40, RIGHT_RECV | RIGHT_GRANT);
+        cs[1] = cspace::slot(ObjKind::Irq, 41, RIGHT_RECV | RIGHT_GRANT);
+        cspace::install(0, cs);
+        assert_eq!(cspace::mint(0, 0, 2, 0, RIGHT_RECV), 0);
+        assert_eq!(cspace::mint(0, 1, 2, 1, RIGHT_RECV), 0);
+        assert_eq!(cspace::mint(0, 1, 2, 2, RIGHT_RECV), 0);
+        assert_eq!(irq::irq_bind(40, 2, 1), 0);
+        assert_eq!(irq::irq_bind(41, 2, 2), 0);
+
+        let bound = |intid: u32| (*irq::IRQ_BINDINGS.get()).iter().any(|b| b.active && b.intid == intid);
+        cspace::revoke(0, 0);
+        assert!(!bound(40), "revoked IRQ unbound");
+
+        // two copies of INTID 41: the binding goes only with the last one
+        assert_eq!(cspace::revoke(0, 1), 2);
+        assert!(!bound(41));
+
+        assert_eq!(cspace::mint(0, 1, 2, 1, RIGHT_RECV), 0);
+        (*cspace::CSPACES.get_mut())[2][5] = cspace::slot(ObjKind::Irq, 41, RIGHT_RECV);
+        assert_eq!(irq::irq_bind(41, 2, 2), 0);
+        cspace::revoke(0, 1);
+        assert!(bound(41), "still named by an independent slot");
+    }
+}
+
+#[test]
+fn cspace_revoke_grant_slot_revokes_its_grant() {
+    unsafe {
+        reset_test_state();
+        let mut cs = EMPTY_CSPACE;
+        cs[0] = cspace::slot(ObjKind::Grant, 0, cspace::RIGHTS_ALL);
+        cs[1] = cspace::slot(ObjKind::Grant, 1, cspace::RIGHTS_ALL);
+        cspace::install(0, cs);
+        assert_eq!(cspace::mint(0, 0, 2, 0, RIGHT_READ | RIGHT_WRITE), 0);
+        assert_eq!(cspace::mint(0, 1, 2, 1, RIGHT_READ | RIGHT_WRITE), 0);
+        // Task 2 creates grant 0 through its slot; task 3 owns grant 1
+        assert_eq!(grant::grant_create(0, 2, 1), 0);
+        assert_eq!(grant::grant_create(1, 3, 1), 0);
+
+        assert_eq!(cspace::revoke(0, 0), 1);
+        let g = (*grant::GRANTS.get())[0];
+        assert!(!g.active && !g.is_peer(1), "region unmapped for owner and peer");
+        assert_eq!(grant::grant_base(0), None);
+
+        cspace::revoke(0, 1);
+        assert!((*grant::GRANTS.get())[1].active, "not created by the slot holder");
+    }
+}
+
+#[test]
+fn cspace_mint_revoke_syscalls() {
+    unsafe {
+        setup_delegator();
+        *sched::CURRENT.get_mut() = 0;
+        let mut frame = slot_frame(23, 0, CPTR_FLAG);
+        frame.x[1] = 1;
+        frame.x[2] = 4;
+        frame.x[3] = (RIGHT_SEND | RIGHT_RECV) as u64;
+        cspace::sys_cap_mint(&mut frame);
+        assert_eq!(frame.x[0], 0);
+        assert_eq!(cspace::get_slot(1, 4), Some(cspace::slot(ObjKind::Endpoint, 1, RIGHT_SEND | RIGHT_RECV)));
+
+        frame.x[0] = 0;
+        frame.x[1] = NUM_TASKS as u64;
+        cspace::sys_cap_mint(&mut frame);
+        assert_eq!(frame.x[0], cspace::ERR_SLOT_INVALID);
+
+        let mut frame = slot_frame(24, 0, 0);
+        cspace::sys_cap_revoke(&mut frame);
+        assert_eq!(frame.x[0], 1);
+        assert_eq!(cspace::get_slot(1, 4), Some(cspace::EMPTY_SLOT));
+
+        assert_eq!(cap::cap_for_syscall(23, 0), CAP_DELEGATE);
+        assert_eq!(cap::cap_for_syscall(24, 0), CAP_DELEGATE);
+        assert_eq!(CAP_DELEGATE, 1 << 29);
+        assert_eq!(cap::cap_name(CAP_DELEGATE), "DELEGATE");
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Audit Log (kernel/audit.rs)
+// ═══════════════════════════════════════════════════════════════════
+
+fn denial(task: usize, syscall: u64) -> AuditRecord {
+    AuditRecord { tick: 0, task, syscall, required: CAP_WRITE, held: CAP_YIELD, reason: DenyReason::Caps }
+}
+
+#[test]
+fn audit_ring_fifo_and_overflow() {
+    let mut log = audit::EMPTY_AUDIT_LOG;
+    assert_eq!(audit::audit_pop(&mut log), None);
+    for i in 0..AUDIT_CAPACITY as u64 + 3 {
+        audit::audit_push(&mut log, denial(1, i));
+    }
+    assert_eq!(log.len, AUDIT_CAPACITY);
+    assert_eq!(log.lost, 3, "three oldest records overwritten");
+    assert_eq!(log.denials[1], AUDIT_CAPACITY as u64 + 3, "counter includes lost records");
+    for i in 3..AUDIT_CAPACITY as u64 + 3 {
+        assert_eq!(audit::audit_pop(&mut log).map(|r| r.syscall), Some(i));
+    }
+    assert_eq!(audit::audit_pop(&mut log), None);
+}
+
+#[test]
+fn audit_record_denial_counts_per_task() {
+    unsafe {
+        reset_test_state();
+        *aegis_os::timer::TICK_COUNT.get_mut() = 42;
+        let reason = DenyReason::Slot(SlotError::NoRights);
+        assert_eq!(audit::record_denial(2, 7, CAP_GRANT_CREATE, CAP_YIELD, reason), DenyPolicy::Fault);
+        audit::set_deny_policy(DenyPolicy::LogAndDeny);
+        assert_eq!(audit::record_denial(2, 4, CAP_WRITE, 0, DenyReason::Caps), DenyPolicy::LogAndDeny);
+        audit::record_denial(5, 4, CAP_WRITE, 0, DenyReason::Caps);
+        assert_eq!(audit::denial_count(2), 2);
+        assert_eq!(audit::denial_count(5), 1);
+        assert_eq!(audit::denial_count(0), 0);
+        assert_eq!(audit::denial_count(NUM_TASKS), 0);
+
+        let rec = audit::audit_pop(&mut *audit::AUDIT.get_mut()).unwrap();
+        assert_eq!(rec, AuditRecord { tick: 42, task: 2, syscall: 7, required: CAP_GRANT_CREATE, held: CAP_YIELD, reason });
+        assert_eq!(reason.code(), 1 + SlotError::NoRights as u64);
+        assert_eq!(DenyReason::Caps.code(), 0);
+
+        // Counters outlive the task's restart
+        sched::restart_task(2);
+        assert_eq!(audit::denial_count(2), 2);
+    }
+}
+
+#[test]
+fn audit_drain_and_stats_syscalls() {
+    unsafe {
+        reset_test_state();
+        *aegis_os::timer::TICK_COUNT.get_mut() = 9;
+        audit::record_denial(3, 11, CAP_DEVICE_MAP, CAP_WRITE, DenyReason::Caps);
+
+        let mut frame = slot_frame(25, 0, 0);
+        audit::sys_audit_drain(&mut frame);
+        assert_eq!(frame.x[..6], [9, 3, 11, CAP_DEVICE_MAP, CAP_WRITE, 0]);
+        audit::sys_audit_drain(&mut frame);
+        assert_eq!(frame.x[0], audit::ERR_AUDIT_EMPTY);
+
+        let mut frame = slot_frame(26, 3, 0);
+        audit::sys_audit_stats(&mut frame, 3);
+        assert_eq!((frame.x[0], frame.x[1]), (1, 0), "drained records stay counted");
+        audit::sys_audit_stats(&mut frame, NUM_TASKS);
+        assert_eq!(frame.x[0], audit::ERR_AUDIT_TASK);
+
+        assert_eq!(cap::cap_for_syscall(25, 0), CAP_AUDIT);
+        assert_eq!(cap::cap_for_syscall(26, 3), CAP_AUDIT);
+        assert_eq!(CAP_AUDIT, 1 << 30);
+        assert_eq!(cap::cap_name(CAP_AUDIT), "AUDIT");
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// System configuration (build.rs ← system.toml)
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn sysconf_sizes_kernel_tables() {
+    use aegis_os::platform::qemu_virt as platform;
+    use aegis_os::sysconf;
+    assert_eq!(NUM_TASKS, sysconf::NUM_TASKS);
+    assert_eq!(mmu::NUM_PAGE_TABLE_PAGES, sysconf::PAGE_TABLE_PAGES);
+    assert_eq!(MAX_GRANTS, sysconf::MAX_GRANTS);
+    assert_eq!(grant::GRANT_POOL_PAGES, sysconf::GRANT_PAGES);
+    assert_eq!(platform::ELF_LOAD_BASE, sysconf::ELF_LOAD_BASE);
+    assert_eq!(platform::ELF_LOAD_SIZE_PER_TASK, sysconf::ELF_SLOT_SIZE);
+    assert_eq!(platform::MAX_ELF_TASKS, sysconf::ELF_SLOTS);
+    // ELF region stays below the per-task IPC buffer alias page
+    let elf_end = platform::elf_load_addr(sysconf::ELF_SLOTS);
+    assert!(elf_end <= ipc::IPC_BUFFER_VA);
+}
+
+#[test]
+fn sysconf_devices_are_el0_safe() {
+    assert_eq!(mmu::DEVICES.len(), mmu::MAX_DEVICE_ID + 1);
+    for (i, d) in mmu::DEVICES.iter().enumerate() {
+        assert!(!(64..=66).contains(&d.l2_index), "{} maps GIC MMIO", d.name);
+        assert!(d.intid >= 32, "{} INTID is not an SPI", d.name);
+        for other in &mmu::DEVICES[i + 1..] {
+            assert_ne!(d.l2_index, other.l2_index);
+            assert_ne!(d.intid, other.intid);
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Flow Policy Analyzer (kernel/policy.rs)
+// ═══════════════════════════════════════════════════════════════════
+
+use aegis_os::policy::{self, FlowGraph, GrantDecl, MintDecl, NodeSet, Policy, SystemModel, MAX_FLOW_NODES};
+use aegis_os::sched::TaskMetadata;
+
+/// Task metadata holding only `caps` (and an optional CSpace).
+const fn flow_task(caps: u64, cspace: cspace::CSpace) -> TaskMetadata {
+    TaskMetadata {
+        caps, badges: ipc::NO_BADGES, cspace, priority: 0, time_budget: 0,
+        heartbeat_interval: 0, partition: 0, mem_quota: 0, timing: APERIODIC,
+    }
+}
+
+fn flow_reach(model: &SystemModel) -> [NodeSet; MAX_FLOW_NODES] {
+    policy::reachability(&policy::flow_graph(model))
+}
+
+fn edge(g: &FlowGraph, a: usize, b: usize) -> bool {
+    g.edges[a] & (1 << b) != 0
+}
+
+#[test]
+fn policy_channel_directions() {
+    let tasks = [
+        flow_task(CAP_IPC_SEND_EP1 | CAP_QUEUE_SEND_Q1 | CAP_SAMPLE_WRITE_P1, EMPTY_CSPACE),
+        flow_task(CAP_IPC_RECV_EP1, EMPTY_CSPACE),
+        flow_task(CAP_QUEUE_RECV_Q1, EMPTY_CSPACE),
+        flow_task(CAP_SAMPLE_READ_P1, EMPTY_CSPACE),
+        flow_task(CAP_IPC_RECV_EP2 | CAP_QUEUE_RECV_Q0 | CAP_SAMPLE_READ_P0, EMPTY_CSPACE),
+    ];
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &[] });
+    assert_eq!(g.nodes, 5);
+    assert!(edge(&g, 0, 1) && edge(&g, 1, 0), "endpoint: outcome flows back to the sender");
+    assert!(edge(&g, 0, 2) && edge(&g, 2, 0), "queue: fullness flows back to the sender");
+    assert!(edge(&g, 0, 3) && !edge(&g, 3, 0), "sampling port is one-way");
+    assert_eq!(g.edges[4], 0, "receive rights without a sender carry nothing");
+}
+
+#[test]
+fn policy_notify_needs_a_waiter() {
+    let tasks = [
+        flow_task(CAP_NOTIFY, EMPTY_CSPACE),
+        flow_task(CAP_WAIT_NOTIFY, EMPTY_CSPACE),
+        flow_task(CAP_YIELD, EMPTY_CSPACE),
+    ];
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &[] });
+    assert!(edge(&g, 0, 1));
+    assert!(!edge(&g, 0, 2), "target cannot observe notifications");
+    assert!(!edge(&g, 1, 0));
+
+    // A notification slot still needs CAP_NOTIFY
+    let mut cs = EMPTY_CSPACE;
+    cs[0] = cspace::slot(ObjKind::Notification, 2, RIGHT_SEND);
+    let tasks = [
+        flow_task(0, cs),
+        flow_task(CAP_WAIT_NOTIFY, EMPTY_CSPACE),
+        flow_task(CAP_WAIT_NOTIFY, EMPTY_CSPACE),
+    ];
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &[] });
+    assert_eq!(g.edges[0], 0);
+}
+
+#[test]
+fn policy_slots_and_declared_mints() {
+    let mut cs = EMPTY_CSPACE;
+    cs[0] = cspace::slot(ObjKind::Endpoint, 3, RIGHT_SEND | RIGHT_RECV | RIGHT_GRANT);
+    let tasks = [
+        flow_task(CAP_DELEGATE | CAP_IPC, cs),
+        flow_task(CAP_IPC, EMPTY_CSPACE),
+        flow_task(CAP_IPC, EMPTY_CSPACE),
+    ];
+    let mints = [MintDecl { from: 0, slot: 0, to: 1, rights: RIGHT_SEND }];
+    let model = SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &mints };
+    assert!(policy::may(&model, 1, 1, 3), "minted send right authorises SYS_SEND");
+    assert!(!policy::may(&model, 1, 2, 3), "attenuated: no receive");
+    assert!(!policy::may(&model, 2, 1, 3), "not a mint target");
+    let g = policy::flow_graph(&model);
+    assert!(edge(&g, 1, 0) && !edge(&g, 2, 0));
+
+    // Same delegator, no declared mint: the slot may go anywhere
+    let open = SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &[] };
+    assert!(policy::may(&open, 2, 1, 3));
+    // Without CAP_IPC the minted slot authorises nothing
+    let no_class = [flow_task(CAP_DELEGATE, cs), flow_task(0, EMPTY_CSPACE)];
+    assert!(!policy::may(&SystemModel { tasks: &no_class, devices: &[], grants: &[], mints: &mints }, 1, 1, 3));
+    // Without CAP_DELEGATE the slot cannot leave its holder
+    let held = [flow_task(CAP_IPC, cs), flow_task(CAP_IPC, EMPTY_CSPACE)];
+    assert!(!policy::may(&SystemModel { tasks: &held, devices: &[], grants: &[], mints: &[] }, 1, 1, 3));
+}
+
+#[test]
+fn policy_audit_reader_observes_every_task() {
+    let tasks = [
+        flow_task(CAP_AUDIT, EMPTY_CSPACE),
+        flow_task(0, EMPTY_CSPACE),
+        flow_task(CAP_WRITE, EMPTY_CSPACE),
+    ];
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &[] });
+    assert!(edge(&g, 1, 0) && edge(&g, 2, 0), "any task can be denied, and is logged");
+    assert!(!edge(&g, 0, 1) && !edge(&g, 1, 2));
+}
+
+#[test]
+fn policy_grants_follow_declarations() {
+    let tasks = [
+        flow_task(CAP_GRANT_CREATE, EMPTY_CSPACE),
+        flow_task(0, EMPTY_CSPACE),
+        flow_task(0, EMPTY_CSPACE),
+    ];
+    let grants = [GrantDecl { id: 0, owner: 0, peer: 1 }];
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &grants, mints: &[] });
+    assert!(edge(&g, 0, 1) && edge(&g, 1, 0), "grant page is shared both ways");
+    assert!(!edge(&g, 0, 2));
+
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &[] });
+    assert!(edge(&g, 0, 2) && edge(&g, 2, 0), "undeclared creator may share with anyone");
+
+    // One grant declared for two peers: the peers share the region too
+    let broadcast = [GrantDecl { id: 0, owner: 0, peer: 1 }, GrantDecl { id: 0, owner: 0, peer: 2 }];
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &broadcast, mints: &[] });
+    assert!(edge(&g, 0, 2) && edge(&g, 1, 2) && edge(&g, 2, 1), "peers of one grant reach each other");
+
+    // A declaration without creation authority adds nothing
+    let tasks = [flow_task(0, EMPTY_CSPACE), flow_task(0, EMPTY_CSPACE)];
+    let g = policy::flow_graph(&SystemModel { tasks: &tasks, devices: &[], grants: &grants, mints: &[] });
+    assert_eq!(g.edges[0] | g.edges[1], 0);
+}
+
+#[test]
+fn policy_device_nodes() {
+    let tasks = [
+        flow_task(CAP_DEVICE_MAP, EMPTY_CSPACE),
+        flow_task(CAP_WRITE, EMPTY_CSPACE),
+        flow_task(CAP_IRQ_BIND, EMPTY_CSPACE),
+    ];
+    let model = SystemModel { tasks: &tasks, devices: mmu::DEVICES, grants: &[], mints: &[] };
+    let g = policy::flow_graph(&model);
+    let uart = tasks.len(); // device 0 = UART0
+    assert_eq!(g.nodes, tasks.len() + mmu::DEVICES.len());
+    assert!(edge(&g, 0, uart) && edge(&g, uart, 0), "MMIO mapping is read + write");
+    assert!(edge(&g, 1, uart) && !edge(&g, uart, 1), "SYS_WRITE only drives the console");
+    assert!(edge(&g, uart, 2), "IRQ delivery reaches the binder");
+
+    // console writer → UART → driver, transitively
+    let reach = flow_reach(&model);
+    assert!(policy::may_influence(&reach, 1, 0));
+    assert!(!policy::may_influence(&reach, 0, 1));
+}
+
+#[test]
+fn policy_violation_reports_pair() {
+    // 0 → 1 (sampling) → 2 (sampling P0 after P1)
+    let tasks = [
+        flow_task(CAP_SAMPLE_WRITE_P1, EMPTY_CSPACE),
+        flow_task(CAP_SAMPLE_READ_P1 | CAP_SAMPLE_WRITE_P0, EMPTY_CSPACE),
+        flow_task(CAP_SAMPLE_READ_P0, EMPTY_CSPACE),
+    ];
+    let reach = flow_reach(&SystemModel { tasks: &tasks, devices: &[], grants: &[], mints: &[] });
+    let forward = Policy { from: 1 << 0, to: 1 << 2 };
+    let backward = Policy { from: 1 << 2, to: (1 << 0) | (1 << 1) };
+    assert_eq!(policy::violation(&reach, &forward), Some((0, 2)), "indirect flow is a violation");
+    assert!(!policy::holds(&reach, &forward));
+    assert!(policy::holds(&reach, &backward), "sampling chain never flows back");
+}
+
+#[test]
+fn policy_evaluates_at_compile_time() {
+    const TASKS: [TaskMetadata; 2] = [
+        flow_task(CAP_IPC_SEND_EP0, EMPTY_CSPACE),
+        flow_task(CAP_IPC_RECV_EP0, EMPTY_CSPACE),
+    ];
+    const REACH: [NodeSet; MAX_FLOW_NODES] = policy::reachability(&policy::flow_graph(&SystemModel {
+        tasks: &TASKS, devices: &[], grants: &[], mints: &[],
+    }));
+    const _: () = assert!(policy::may_influence(&REACH, 0, 1));
+    assert!(policy::may_influence(&REACH, 1, 0));
+    assert_eq!(cap::cap_for_syscall(22, 0b11), CAP_WAIT_NOTIFY | CAP_IPC_RECV_EP0 | CAP_IPC_RECV_EP1);
+}
@@ -116,12 +116,26 @@ Check-Output "Timer started"          "[AegisOS] timer started"
 Check-Output "Enhanced panic handler" "[AegisOS] enhanced panic handler ready"
 Check-Output "klog ready"            "[AegisOS] klog ready"
 Check-Output "Safety audit complete" "[AegisOS] safety audit complete"
+Check-Output "Page tables verified"  "[AegisOS] page tables verified"
 Check-Output "Bootstrap into EL0"     "[AegisOS] bootstrapping into uart_driver"
 Check-Output "UART Driver ready"      "DRV:ready"
 Check-Output "L5 ELF task output"     "L5:ELF"
 Check-Output "Task 2 exited"          "[AegisOS] task 2 exited (code=0)"
 Check-Output "Sensor initialized"     "SENSOR:init"
 Check-Output "Client uses driver"     "J4:UserDrv"
+Check-Output "Partition schedule"     "[AegisOS] partition schedule installed"
+Check-Output "EDF admission"          "[AegisOS] EDF admission passed"
+Check-Output "Tickless idle"          "[AegisOS] tickless idle enabled"
+Check-Output "Message queues"         "[AegisOS] message queues ready"
+Check-Output "Sampling ports"         "[AegisOS] sampling ports ready"
+Check-Output "CSpace slots"           "[AegisOS] capability spaces installed"
+Check-Output "Capability delegated"   "DRV:mint"
+Check-Output "Read-only grant read"   "LOG:ro"
+Check-Output "Read-only grant fault"  "(Permission fault L"
+Check-Output "Revoked grant scrubbed" "SENSOR:scrub"
+Check-Output "Anonymous memory"       "LOG:anon"
+Check-Output "Config page sealed"     "LOG:seal"
+Check-Output "Fault address-space dump" "(EL0-visible):"
 
 # ─── Summary ───────────────────────────────────────────────────────
 Write-Host ""
@@ -97,12 +97,26 @@ check "Timer started"               "[AegisOS] timer started"
 check "Enhanced panic handler"      "[AegisOS] enhanced panic handler ready"
 check "klog ready"                   "[AegisOS] klog ready"
 check "Safety audit complete"        "[AegisOS] safety audit complete"
+check "Page tables verified"         "[AegisOS] page tables verified"
 check "Bootstrap into EL0"          "[AegisOS] bootstrapping into uart_driver"
 check "UART driver ready"           "DRV:ready"
 check "L5 ELF task output"          "L5:ELF"
 check "Task 2 exited"               "[AegisOS] task 2 exited (code=0)"
 check "Sensor initialized"          "SENSOR:init"
 check "Client uses driver"          "J4:UserDrv"
+check "Partition schedule"          "[AegisOS] partition schedule installed"
+check "EDF admission"               "[AegisOS] EDF admission passed"
+check "Tickless idle"               "[AegisOS] tickless idle enabled"
+check "Message queues"              "[AegisOS] message queues ready"
+check "Sampling ports"              "[AegisOS] sampling ports ready"
+check "CSpace slots"                "[AegisOS] capability spaces installed"
+check "Capability delegated"        "DRV:mint"
+check "Read-only grant readable"    "LOG:ro"
+check "Read-only grant store faults" "(Permission fault L"
+check "Revoked grant scrubbed"      "SENSOR:scrub"
+check "Anonymous memory mapped"     "LOG:anon"
+check "Config page sealed"          "LOG:seal"
+check "Fault address-space dump"    "(EL0-visible):"
 
 # ─── Summary ───────────────────────────────────────────────────────
 echo ""
@@ -3,7 +3,11 @@
 //! Single source of truth for all syscall wrappers + constants.
 //! Every user binary depends on this crate instead of duplicating asm.
 //!
-//! Syscall ABI: x7 = syscall number, x6 = endpoint ID, x0–x3 = payload.
+//! Syscall ABI: x7 = syscall number, x6 = endpoint ID, x0–x3 = payload,
+//! x4 = IPC timeout in ticks (SYS_SEND / SYS_RECV / SYS_CALL),
+//! x5 = message flags (MSG_FLAG_BYTES attaches the IPC buffer payload).
+//! On every IPC receive the kernel returns the sender's task id in x4
+//! and its capability badge in x5 — identity the sender cannot forge.
 
 #![no_std]
 #![deny(unsafe_op_in_unsafe_fn)]
@@ -24,6 +28,191 @@ pub const SYS_IRQ_ACK: u64 = 10;
 pub const SYS_DEVICE_MAP: u64 = 11;
 pub const SYS_HEARTBEAT: u64 = 12;
 pub const SYS_EXIT: u64 = 13;
+pub const SYS_SLEEP: u64 = 14;
+pub const SYS_SLEEP_UNTIL: u64 = 15;
+pub const SYS_REPLY: u64 = 16;
+pub const SYS_REPLY_RECV: u64 = 17;
+pub const SYS_QUEUE_SEND: u64 = 18;
+pub const SYS_QUEUE_RECV: u64 = 19;
+pub const SYS_SAMPLE_WRITE: u64 = 20;
+pub const SYS_SAMPLE_READ: u64 = 21;
+pub const SYS_RECV_ANY: u64 = 22;
+pub const SYS_CAP_MINT: u64 = 23;
+pub const SYS_CAP_REVOKE: u64 = 24;
+pub const SYS_AUDIT_DRAIN: u64 = 25;
+pub const SYS_AUDIT_STATS: u64 = 26;
+pub const SYS_GRANT_ADD_PEER: u64 = 27;
+pub const SYS_GRANT_REMOVE_PEER: u64 = 28;
+pub const SYS_MAP_ANON: u64 = 29;
+pub const SYS_UNMAP: u64 = 30;
+pub const SYS_PAGE_PROTECT: u64 = 31;
+pub const SYS_MAP_DUMP: u64 = 32;
+
+// ─── IPC Timeouts ──────────────────────────────────────────────────
+
+/// x4 timeout: block until a partner arrives.
+pub const TIMEOUT_INFINITE: u64 = u64::MAX;
+/// x4 timeout: non-blocking poll.
+pub const TIMEOUT_POLL: u64 = 0;
+/// Returned in x0 when an IPC operation times out (or a poll finds no partner).
+pub const ERR_IPC_TIMEOUT: u64 = 0xFFFF_4001;
+/// Returned by SYS_REPLY when no call is pending.
+pub const ERR_IPC_NO_REPLY: u64 = 0xFFFF_4002;
+/// Returned to a caller whose server faulted or dropped the reply.
+pub const ERR_IPC_REPLY_LOST: u64 = 0xFFFF_4003;
+/// Returned when an IPC buffer payload exceeds IPC_BUF_MAX_PAYLOAD.
+pub const ERR_IPC_MSG_TOO_LONG: u64 = 0xFFFF_4004;
+/// Returned by SYS_RECV_ANY when another task already waits on one of its endpoints.
+pub const ERR_IPC_BUSY: u64 = 0xFFFF_4005;
+
+// ─── Wait Sets ─────────────────────────────────────────────────────
+
+/// Source after SYS_RECV_ANY: notification bits fired (bits in regs[0]).
+pub const WAIT_SRC_NOTIFY: u64 = 0xFF;
+/// Source after SYS_RECV_ANY timed out (regs[0] = ERR_IPC_TIMEOUT).
+pub const WAIT_SRC_NONE: u64 = u64::MAX;
+
+// ─── IPC Buffer Page ───────────────────────────────────────────────
+
+/// Address of this task's own IPC buffer page (same VA in every task).
+pub const IPC_BUFFER_VA: u64 = 0x401F_F000;
+/// Length prefix (u64 byte count) at the start of the IPC buffer.
+pub const IPC_BUF_HEADER: usize = 8;
+/// Largest payload one message can carry through the IPC buffer.
+pub const IPC_BUF_MAX_PAYLOAD: usize = 512;
+/// x5 flag: attach the payload staged in the IPC buffer to this message.
+pub const MSG_FLAG_BYTES: u64 = 1 << 0;
+
+// ─── Message Queues ────────────────────────────────────────────────
+
+/// Queue id out of range or not configured.
+pub const ERR_QUEUE_INVALID: u64 = 0xFFFF_5001;
+/// Queue full (drop-newest policy) — the message was discarded.
+pub const ERR_QUEUE_FULL: u64 = 0xFFFF_5002;
+/// Another task is already waiting on this queue.
+pub const ERR_QUEUE_BUSY: u64 = 0xFFFF_5003;
+
+// ─── Sampling Ports ────────────────────────────────────────────────
+
+/// Validity flag on a sample read: age ≤ the port's refresh period.
+pub const SAMPLE_VALID: u64 = 1 << 0;
+/// Port id out of range or not configured.
+pub const ERR_PORT_INVALID: u64 = 0xFFFF_6001;
+/// Returned in x0 when reading a port that was never written.
+pub const ERR_PORT_EMPTY: u64 = 0xFFFF_6002;
+
+// ─── Shared Memory Grants ──────────────────────────────────────────
+
+/// Grant id out of range.
+pub const ERR_GRANT_INVALID_ID: u64 = 0xFFFF_0001;
+/// Grant id already active.
+pub const ERR_GRANT_ACTIVE: u64 = 0xFFFF_0002;
+/// Peer task id out of range.
+pub const ERR_GRANT_INVALID_PEER: u64 = 0xFFFF_0003;
+/// Owner and peer are the same task.
+pub const ERR_GRANT_SELF: u64 = 0xFFFF_0004;
+/// Caller does not own the grant.
+pub const ERR_GRANT_NOT_OWNER: u64 = 0xFFFF_0005;
+/// Size larger than the grant pool, or alignment not a power of two.
+pub const ERR_GRANT_SIZE: u64 = 0xFFFF_0006;
+/// Fewer free grant pool pages than requested.
+pub const ERR_GRANT_EXHAUSTED: u64 = 0xFFFF_0007;
+/// Enough free pages, but no contiguous run at the requested alignment.
+pub const ERR_GRANT_FRAGMENTED: u64 = 0xFFFF_0008;
+/// Unknown bits in the SYS_GRANT_CREATE / SYS_GRANT_ADD_PEER flags.
+pub const ERR_GRANT_FLAGS: u64 = 0xFFFF_0009;
+/// Peer update on a grant that is not active.
+pub const ERR_GRANT_INACTIVE: u64 = 0xFFFF_000A;
+/// Removed task is not a peer of the grant.
+pub const ERR_GRANT_NOT_PEER: u64 = 0xFFFF_000B;
+/// Grant flag: the peer maps the region read-only (a store faults).
+pub const GRANT_PEER_RO: u64 = 1 << 0;
+/// Grant flag: the owner maps the region read-only.
+pub const GRANT_OWNER_RO: u64 = 1 << 1;
+
+// ─── Anonymous Memory ──────────────────────────────────────────────
+
+/// Page size of SYS_MAP_ANON mappings.
+pub const PAGE_SIZE: usize = 4096;
+/// Page count is 0 or larger than the frame pool.
+pub const ERR_FRAME_SIZE: u64 = 0xFFFF_9001;
+/// Mapping would take the task past its mem_quota.
+pub const ERR_FRAME_QUOTA: u64 = 0xFFFF_9002;
+/// No contiguous run of free frames that long.
+pub const ERR_FRAME_NO_SPACE: u64 = 0xFFFF_9003;
+/// Unmapped range is not entirely this task's frames.
+pub const ERR_FRAME_NOT_OWNER: u64 = 0xFFFF_9004;
+
+// ─── Page Protection ───────────────────────────────────────────────
+
+/// SYS_PAGE_PROTECT access: read-write, never executable.
+pub const PROT_RW: u64 = 0;
+/// SYS_PAGE_PROTECT access: read-only, never executable.
+pub const PROT_RO: u64 = 1;
+/// SYS_PAGE_PROTECT access: read-only and executable (ELF text pages only).
+pub const PROT_RX: u64 = 2;
+/// Unknown access value, or a page count of 0.
+pub const ERR_PROT_INVALID: u64 = 0xFFFF_3003;
+/// A page is misaligned or not this task's mapped ELF slot, stack or anonymous memory.
+pub const ERR_PROT_NOT_OWNER: u64 = 0xFFFF_3004;
+/// Execute asked for outside the text pages of this task's ELF slot.
+pub const ERR_PROT_NO_EXEC: u64 = 0xFFFF_3005;
+/// Write asked for on a text page of this task's ELF slot.
+pub const ERR_PROT_TEXT_WRITE: u64 = 0xFFFF_3006;
+
+// ─── Address-Space Dump ────────────────────────────────────────────
+
+/// SYS_MAP_DUMP filter: every mapped range.
+pub const MAP_DUMP_ALL: u64 = 0;
+/// SYS_MAP_DUMP filter: only ranges EL0 can reach.
+pub const MAP_DUMP_USER: u64 = 1;
+/// SYS_MAP_DUMP: no such task.
+pub const ERR_MAP_DUMP_INVALID_TASK: u64 = 0xFFFF_3001;
+
+// ─── Capability Slots ──────────────────────────────────────────────
+
+/// Set in an object register (x6, or x0 for grants/IRQs/devices):
+/// the value is a slot in this task's capability table, not a raw id.
+pub const CPTR_FLAG: u64 = 1 << 63;
+/// Slot right: send / write / signal.
+pub const RIGHT_SEND: u8 = 1 << 0;
+/// Slot right: receive / read a port / take an IRQ.
+pub const RIGHT_RECV: u8 = 1 << 1;
+/// Slot right: map a grant or device readable.
+pub const RIGHT_READ: u8 = 1 << 2;
+/// Slot right: map a grant or device writable.
+pub const RIGHT_WRITE: u8 = 1 << 3;
+/// Slot right: mint copies of the slot into other tasks.
+pub const RIGHT_GRANT: u8 = 1 << 4;
+/// Source slot or target out of range, or source slot empty.
+pub const ERR_SLOT_INVALID: u64 = 0xFFFF_7001;
+/// Source slot lacks RIGHT_GRANT.
+pub const ERR_SLOT_NO_GRANT: u64 = 0xFFFF_7002;
+/// Target slot already holds a capability.
+pub const ERR_SLOT_OCCUPIED: u64 = 0xFFFF_7003;
+/// A blocked wait was cut because its capability was revoked.
+pub const ERR_CAP_REVOKED: u64 = 0xFFFF_7004;
+
+/// Object reference naming capability slot `slot`.
+pub const fn cptr(slot: u64) -> u64 {
+    CPTR_FLAG | slot
+}
+
+// ─── Audit Log ─────────────────────────────────────────────────────
+
+/// Returned by a refused syscall when the kernel runs `deny_policy = "log"`.
+pub const ERR_CAP_DENIED: u64 = 0xFFFF_8001;
+/// Returned by SYS_AUDIT_DRAIN when no denial is logged.
+pub const ERR_AUDIT_EMPTY: u64 = 0xFFFF_8002;
+/// Returned by SYS_AUDIT_STATS for a task id out of range.
+pub const ERR_AUDIT_TASK: u64 = 0xFFFF_8003;
+/// Audit reason: the task's CapBits lacked the required bits.
+pub const DENY_REASON_CAPS: u64 = 0;
+
+// ─── Sender Identity ───────────────────────────────────────────────
+
+/// Badge of an unbadged send right (and of every reply).
+pub const NO_BADGE: u64 = 0;
 
 // ─── Syscall Wrappers ──────────────────────────────────────────────
 
@@ -42,25 +231,32 @@ pub fn syscall_yield() {
 }
 
 /// SYS_SEND (syscall #1): send message on endpoint.
+/// Returns x0 — ERR_CAP_DENIED or an ERR_IPC_* code if the send failed,
+/// otherwise `m0` unchanged.
 #[inline(always)]
-pub fn syscall_send(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) {
+pub fn syscall_send(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
+    let result: u64;
     // SAFETY: SVC triggers synchronous exception handled by kernel.
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") m0,
+            inlateout("x0") m0 => result,
             in("x1") m1,
             in("x2") m2,
             in("x3") m3,
+            in("x4") TIMEOUT_INFINITE,
+            in("x5") 0u64, // no IPC-buffer payload
             in("x6") ep_id,
             in("x7") SYS_SEND,
             options(nomem, nostack)
         );
     }
+    result
 }
 
 /// SYS_RECV (syscall #2): receive message from endpoint.
-/// Returns first message register (x0).
+/// Returns first message register (x0), or ERR_IPC_BUSY if another task
+/// already waits on the endpoint.
 #[inline(always)]
 pub fn syscall_recv(ep_id: u64) -> u64 {
     let msg0: u64;
@@ -68,9 +264,11 @@ pub fn syscall_recv(ep_id: u64) -> u64 {
     unsafe {
         core::arch::asm!(
             "svc #0",
+            inlateout("x4") TIMEOUT_INFINITE => _, // sender id on return
             in("x6") ep_id,
             in("x7") SYS_RECV,
             lateout("x0") msg0,
+            lateout("x5") _, // sender badge
             options(nomem, nostack)
         );
     }
@@ -86,10 +284,12 @@ pub fn syscall_recv2(ep_id: u64) -> (u64, u64) {
     unsafe {
         core::arch::asm!(
             "svc #0",
+            inlateout("x4") TIMEOUT_INFINITE => _, // sender id on return
             in("x6") ep_id,
             in("x7") SYS_RECV,
             lateout("x0") msg0,
             lateout("x1") msg1,
+            lateout("x5") _, // sender badge
             options(nomem, nostack)
         );
     }
@@ -108,6 +308,8 @@ pub fn syscall_call(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
             in("x1") m1,
             in("x2") m2,
             in("x3") m3,
+            inlateout("x4") TIMEOUT_INFINITE => _, // replier id on return
+            inlateout("x5") 0u64 => _, // no IPC-buffer payload
             in("x6") ep_id,
             in("x7") SYS_CALL,
             lateout("x0") reply0,
@@ -117,6 +319,332 @@ pub fn syscall_call(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
     reply0
 }
 
+/// SYS_SEND with timeout: give up after `timeout` ticks (x4).
+/// Returns x0 — ERR_IPC_TIMEOUT if no receiver took the message in time.
+#[inline(always)]
+pub fn syscall_send_timeout(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64, timeout: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") m0 => result,
+            in("x1") m1,
+            in("x2") m2,
+            in("x3") m3,
+            in("x4") timeout,
+            in("x5") 0u64, // no IPC-buffer payload
+            in("x6") ep_id,
+            in("x7") SYS_SEND,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_RECV with timeout: give up after `timeout` ticks (x4).
+/// Returns first message register (x0), ERR_IPC_TIMEOUT or ERR_IPC_BUSY.
+#[inline(always)]
+pub fn syscall_recv_timeout(ep_id: u64, timeout: u64) -> u64 {
+    let msg0: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x4") timeout => _, // sender id on return
+            in("x6") ep_id,
+            in("x7") SYS_RECV,
+            lateout("x0") msg0,
+            lateout("x5") _, // sender badge
+            options(nomem, nostack)
+        );
+    }
+    msg0
+}
+
+/// SYS_CALL with timeout: one deadline covers the send and the reply wait.
+/// Returns first reply register (x0), or ERR_IPC_TIMEOUT.
+#[inline(always)]
+pub fn syscall_call_timeout(ep_id: u64, m0: u64, m1: u64, m2: u64, m3: u64, timeout: u64) -> u64 {
+    let reply0: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x0") m0,
+            in("x1") m1,
+            in("x2") m2,
+            in("x3") m3,
+            inlateout("x4") timeout => _, // replier id on return
+            inlateout("x5") 0u64 => _, // no IPC-buffer payload
+            in("x6") ep_id,
+            in("x7") SYS_CALL,
+            lateout("x0") reply0,
+            options(nomem, nostack)
+        );
+    }
+    reply0
+}
+
+/// A received message together with its kernel-stamped sender identity.
+#[derive(Clone, Copy)]
+pub struct Message {
+    /// Message registers x0–x3 (x0 = ERR_IPC_TIMEOUT on timeout)
+    pub regs: [u64; 4],
+    /// Task id of the sender (x4); meaningless after a timeout
+    pub sender: u64,
+    /// Badge of the sender's send capability (x5); NO_BADGE if unbadged
+    pub badge: u64,
+}
+
+/// SYS_RECV returning all message registers plus the sender's task id
+/// and badge, waiting at most `timeout` ticks. Servers authorise on
+/// `badge`, never on a tag the client wrote into its payload.
+#[inline(always)]
+pub fn syscall_recv_from(ep_id: u64, timeout: u64) -> Message {
+    let (m0, m1, m2, m3, sender, badge): (u64, u64, u64, u64, u64, u64);
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x4") timeout => sender,
+            in("x6") ep_id,
+            in("x7") SYS_RECV,
+            lateout("x0") m0,
+            lateout("x1") m1,
+            lateout("x2") m2,
+            lateout("x3") m3,
+            lateout("x5") badge,
+            options(nomem, nostack)
+        );
+    }
+    Message { regs: [m0, m1, m2, m3], sender, badge }
+}
+
+/// SYS_RECV_ANY (syscall #22): wait at most `timeout` ticks for a message
+/// on any endpoint in `ep_set` (bit i = endpoint i) or for any bit of
+/// `notify_mask`. Returns (source, message): source is the endpoint id
+/// the message came from, WAIT_SRC_NOTIFY (fired bits in regs[0]) or
+/// WAIT_SRC_NONE on timeout.
+#[inline(always)]
+pub fn syscall_recv_any(ep_set: u64, notify_mask: u64, timeout: u64) -> (u64, Message) {
+    let (m0, m1, m2, m3, sender, badge, source): (u64, u64, u64, u64, u64, u64, u64);
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") notify_mask => m0,
+            lateout("x1") m1,
+            lateout("x2") m2,
+            lateout("x3") m3,
+            inlateout("x4") timeout => sender,
+            lateout("x5") badge,
+            inlateout("x6") ep_set => source,
+            in("x7") SYS_RECV_ANY,
+            options(nomem, nostack)
+        );
+    }
+    (source, Message { regs: [m0, m1, m2, m3], sender, badge })
+}
+
+/// SYS_QUEUE_SEND (syscall #18): enqueue a message on queue `q_id`.
+/// Never blocks. Returns 0, ERR_QUEUE_FULL or ERR_QUEUE_INVALID.
+#[inline(always)]
+pub fn syscall_queue_send(q_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") m0 => result,
+            in("x1") m1,
+            in("x2") m2,
+            in("x3") m3,
+            in("x6") q_id,
+            in("x7") SYS_QUEUE_SEND,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_QUEUE_RECV (syscall #19): dequeue the oldest message from queue
+/// `q_id`, waiting at most `timeout` ticks. `regs[0]` is ERR_IPC_TIMEOUT
+/// on timeout; `sender` is the kernel-stamped producer task id.
+#[inline(always)]
+pub fn syscall_queue_recv(q_id: u64, timeout: u64) -> Message {
+    let (m0, m1, m2, m3, sender, badge): (u64, u64, u64, u64, u64, u64);
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x4") timeout => sender,
+            in("x6") q_id,
+            in("x7") SYS_QUEUE_RECV,
+            lateout("x0") m0,
+            lateout("x1") m1,
+            lateout("x2") m2,
+            lateout("x3") m3,
+            lateout("x5") badge,
+            options(nomem, nostack)
+        );
+    }
+    Message { regs: [m0, m1, m2, m3], sender, badge }
+}
+
+/// A sampling-port read: last value, its age and validity.
+#[derive(Clone, Copy)]
+pub struct Sample {
+    /// Message words x0–x3 (x0 = ERR_PORT_EMPTY if never written)
+    pub regs: [u64; 4],
+    /// Ticks since the last write (x4)
+    pub age: u64,
+    /// True while age ≤ the port's refresh period (x5 & SAMPLE_VALID)
+    pub valid: bool,
+}
+
+/// SYS_SAMPLE_WRITE (syscall #20): overwrite sampling port `port_id`.
+/// Never blocks. Returns 0 or ERR_PORT_INVALID.
+#[inline(always)]
+pub fn syscall_sample_write(port_id: u64, m0: u64, m1: u64, m2: u64, m3: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") m0 => result,
+            in("x1") m1,
+            in("x2") m2,
+            in("x3") m3,
+            in("x6") port_id,
+            in("x7") SYS_SAMPLE_WRITE,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_SAMPLE_READ (syscall #21): read the latest sample from `port_id`
+/// without consuming it. Never blocks.
+#[inline(always)]
+pub fn syscall_sample_read(port_id: u64) -> Sample {
+    let (m0, m1, m2, m3, age, flags): (u64, u64, u64, u64, u64, u64);
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x6") port_id,
+            in("x7") SYS_SAMPLE_READ,
+            lateout("x0") m0,
+            lateout("x1") m1,
+            lateout("x2") m2,
+            lateout("x3") m3,
+            lateout("x4") age,
+            lateout("x5") flags,
+            options(nomem, nostack)
+        );
+    }
+    Sample { regs: [m0, m1, m2, m3], age, valid: flags & SAMPLE_VALID != 0 }
+}
+
+/// SYS_CAP_MINT (syscall #23): copy own capability `slot` into
+/// `target_slot` of `target_task`, keeping only the rights in `rights`.
+/// Needs RIGHT_GRANT on the source. Returns 0 or ERR_SLOT_*.
+#[inline(always)]
+pub fn syscall_cap_mint(slot: u64, target_task: u64, target_slot: u64, rights: u8) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inout("x0") slot => result,
+            in("x1") target_task,
+            in("x2") target_slot,
+            in("x3") rights as u64,
+            in("x7") SYS_CAP_MINT,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_CAP_REVOKE (syscall #24): delete every copy derived from own
+/// capability `slot`, in every task. Returns the number deleted or
+/// ERR_SLOT_INVALID.
+#[inline(always)]
+pub fn syscall_cap_revoke(slot: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inout("x0") slot => result,
+            in("x7") SYS_CAP_REVOKE,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// One refused syscall from the kernel audit log.
+#[derive(Clone, Copy)]
+pub struct AuditRecord {
+    /// Kernel tick of the denial (x0; ERR_AUDIT_EMPTY if the log was empty)
+    pub tick: u64,
+    /// Task whose syscall was refused (x1)
+    pub task: u64,
+    /// Refused syscall number (x2)
+    pub syscall: u64,
+    /// CapBits the syscall required (x3)
+    pub required: u64,
+    /// CapBits the task held (x4)
+    pub held: u64,
+    /// DENY_REASON_CAPS, or 1 + slot error for a refused slot (x5)
+    pub reason: u64,
+}
+
+/// SYS_AUDIT_DRAIN (syscall #25): pop the oldest denial record.
+/// Needs CAP_AUDIT. `tick` is ERR_AUDIT_EMPTY when nothing is logged.
+#[inline(always)]
+pub fn syscall_audit_drain() -> AuditRecord {
+    let (tick, task, syscall, required, held, reason): (u64, u64, u64, u64, u64, u64);
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x7") SYS_AUDIT_DRAIN,
+            lateout("x0") tick,
+            lateout("x1") task,
+            lateout("x2") syscall,
+            lateout("x3") required,
+            lateout("x4") held,
+            lateout("x5") reason,
+            options(nomem, nostack)
+        );
+    }
+    AuditRecord { tick, task, syscall, required, held, reason }
+}
+
+/// SYS_AUDIT_STATS (syscall #26): denials counted against `task` since
+/// boot, and records the log has overwritten. Needs CAP_AUDIT.
+/// Returns (denials or ERR_AUDIT_TASK, lost).
+#[inline(always)]
+pub fn syscall_audit_stats(task: u64) -> (u64, u64) {
+    let (denials, lost): (u64, u64);
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x6") task,
+            in("x7") SYS_AUDIT_STATS,
+            lateout("x0") denials,
+            lateout("x1") lost,
+            options(nomem, nostack)
+        );
+    }
+    (denials, lost)
+}
+
 /// SYS_WRITE (syscall #4): write string to UART via kernel.
 #[inline(always)]
 pub fn syscall_write(buf: *const u8, len: usize) {
@@ -124,7 +652,7 @@ pub fn syscall_write(buf: *const u8, len: usize) {
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") buf as u64,
+            inlateout("x0") buf as u64 => _, // ERR_CAP_DENIED if refused
             in("x1") len as u64,
             in("x7") SYS_WRITE,
             options(nomem, nostack)
@@ -139,18 +667,22 @@ pub fn print(s: &str) {
 }
 
 /// SYS_NOTIFY (syscall #5): send notification bitmask to target task.
+/// Returns x0 — ERR_CAP_DENIED, or 0xFFFF_DEAD for an invalid target,
+/// otherwise `bits` unchanged.
 #[inline(always)]
-pub fn syscall_notify(target_id: u64, bits: u64) {
+pub fn syscall_notify(target_id: u64, bits: u64) -> u64 {
+    let result: u64;
     // SAFETY: SVC triggers synchronous exception handled by kernel.
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") bits,
+            inlateout("x0") bits => result,
             in("x6") target_id,
             in("x7") SYS_NOTIFY,
             options(nomem, nostack)
         );
     }
+    result
 }
 
 /// SYS_WAIT_NOTIFY (syscall #6): block until notification arrives.
@@ -170,23 +702,27 @@ pub fn syscall_wait_notify() -> u64 {
     bits
 }
 
-/// SYS_GRANT_CREATE (syscall #7): create shared memory grant.
-/// x0 = grant_id, x6 = peer_task_id. Returns result in x0.
+/// SYS_GRANT_CREATE (syscall #7): create shared memory grant of `pages`
+/// pool pages, base aligned to `align` pages (0 = 1 for both).
+/// `flags`: GRANT_PEER_RO / GRANT_OWNER_RO (0 = read-write for both).
+/// x0 = grant_id, x6 = peer_task_id. Returns (x0 result, x1 region base).
 #[inline(always)]
-pub fn syscall_grant_create(grant_id: u64, peer_task_id: u64) -> u64 {
-    let result: u64;
+pub fn syscall_grant_create(grant_id: u64, peer_task_id: u64, pages: u64, align: u64, flags: u64) -> (u64, u64) {
+    let (result, base): (u64, u64);
     // SAFETY: SVC triggers synchronous exception handled by kernel.
     unsafe {
         core::arch::asm!(
             "svc #0",
-            in("x0") grant_id,
+            inlateout("x0") grant_id => result,
+            inlateout("x1") pages => base,
+            in("x2") align,
+            in("x3") flags,
             in("x6") peer_task_id,
             in("x7") SYS_GRANT_CREATE,
-            lateout("x0") result,
             options(nomem, nostack)
         );
     }
-    result
+    (result, base)
 }
 
 /// SYS_GRANT_REVOKE (syscall #8): revoke shared memory grant.
@@ -207,6 +743,122 @@ pub fn syscall_grant_revoke(grant_id: u64) -> u64 {
     result
 }
 
+/// SYS_GRANT_ADD_PEER (syscall #27): share grant `grant_id` with one more
+/// task. `flags`: GRANT_PEER_RO or 0; re-adding a peer changes its access.
+/// x0 = grant_id, x6 = peer_task_id. Returns result in x0.
+#[inline(always)]
+pub fn syscall_grant_add_peer(grant_id: u64, peer_task_id: u64, flags: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") grant_id => result,
+            in("x3") flags,
+            in("x6") peer_task_id,
+            in("x7") SYS_GRANT_ADD_PEER,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_GRANT_REMOVE_PEER (syscall #28): stop sharing grant `grant_id`
+/// with one task; the owner and other peers keep their mapping.
+/// x0 = grant_id, x6 = peer_task_id. Returns result in x0.
+#[inline(always)]
+pub fn syscall_grant_remove_peer(grant_id: u64, peer_task_id: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") grant_id => result,
+            in("x6") peer_task_id,
+            in("x7") SYS_GRANT_REMOVE_PEER,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_MAP_ANON (syscall #29): map `pages` zeroed pages, read-write and
+/// never executable, charged against this task's mem_quota.
+/// x0 = pages. Returns (x0 result, x1 base address).
+#[inline(always)]
+pub fn syscall_map_anon(pages: u64) -> (u64, u64) {
+    let (result, base): (u64, u64);
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") pages => result,
+            lateout("x1") base,
+            in("x7") SYS_MAP_ANON,
+            options(nomem, nostack)
+        );
+    }
+    (result, base)
+}
+
+/// SYS_UNMAP (syscall #30): return `pages` pages at `base`, mapped by
+/// SYS_MAP_ANON, to the kernel. x0 = base, x1 = pages. Returns result in x0.
+#[inline(always)]
+pub fn syscall_unmap(base: u64, pages: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") base => result,
+            in("x1") pages,
+            in("x7") SYS_UNMAP,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_PAGE_PROTECT (syscall #31): set `pages` of this task's own pages
+/// at `base` to `prot` (PROT_RW / PROT_RO / PROT_RX) — e.g. seal a
+/// config table read-only once parsed. x0 = base, x1 = pages, x2 = prot.
+/// Returns result in x0.
+#[inline(always)]
+pub fn syscall_page_protect(base: u64, pages: u64, prot: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") base => result,
+            in("x1") pages,
+            in("x2") prot,
+            in("x7") SYS_PAGE_PROTECT,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_MAP_DUMP (syscall #32): print task `task`'s decoded address space
+/// on the kernel console. x6 = task, x0 = MAP_DUMP_ALL / MAP_DUMP_USER.
+/// Returns the number of ranges printed in x0.
+#[inline(always)]
+pub fn syscall_map_dump(task: u64, filter: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") filter => result,
+            in("x6") task,
+            in("x7") SYS_MAP_DUMP,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
 /// SYS_IRQ_BIND (syscall #9): bind an IRQ INTID to a notification bit.
 /// x0 = intid (≥32, SPIs only), x1 = notify_bit. Returns result in x0.
 #[inline(always)]
@@ -295,3 +947,146 @@ pub fn syscall_exit(code: u64) -> ! {
         );
     }
 }
+
+/// SYS_SLEEP (syscall #14): sleep for `ticks` timer ticks.
+/// x0 = ticks. Returns the tick count at wake-up in x0.
+/// `syscall_sleep(0)` returns the current tick without blocking.
+#[inline(always)]
+pub fn syscall_sleep(ticks: u64) -> u64 {
+    let now: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x7") SYS_SLEEP,
+            inlateout("x0") ticks => now,
+            options(nomem, nostack)
+        );
+    }
+    now
+}
+
+/// SYS_SLEEP_UNTIL (syscall #15): sleep until absolute tick `deadline`.
+/// x0 = deadline. Returns the tick count at wake-up in x0. A deadline
+/// that has already passed returns immediately (no drift accumulation).
+#[inline(always)]
+pub fn syscall_sleep_until(deadline: u64) -> u64 {
+    let now: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x7") SYS_SLEEP_UNTIL,
+            inlateout("x0") deadline => now,
+            options(nomem, nostack)
+        );
+    }
+    now
+}
+
+/// SYS_REPLY (syscall #16): answer the pending caller via the reply object.
+/// x0–x3 = reply payload. Returns 0, or ERR_IPC_NO_REPLY if no call is pending.
+#[inline(always)]
+pub fn syscall_reply(r0: u64, r1: u64, r2: u64, r3: u64) -> u64 {
+    let result: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") r0 => result,
+            in("x1") r1,
+            in("x2") r2,
+            in("x3") r3,
+            in("x5") 0u64, // no IPC-buffer payload
+            in("x7") SYS_REPLY,
+            options(nomem, nostack)
+        );
+    }
+    result
+}
+
+/// SYS_REPLY_RECV (syscall #17): reply to the pending caller (if any), then
+/// block for the next request on `ep_id`. Returns first message register (x0).
+#[inline(always)]
+pub fn syscall_reply_recv(ep_id: u64, r0: u64, r1: u64, r2: u64, r3: u64) -> u64 {
+    let msg0: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") r0 => msg0,
+            inlateout("x1") r1 => _,
+            inlateout("x2") r2 => _,
+            inlateout("x3") r3 => _,
+            inlateout("x4") TIMEOUT_INFINITE => _, // sender id on return
+            inlateout("x5") 0u64 => _, // no IPC-buffer payload; badge on return
+            in("x6") ep_id,
+            in("x7") SYS_REPLY_RECV,
+            options(nomem, nostack)
+        );
+    }
+    msg0
+}
+
+/// Send `data` on `ep_id` through the IPC buffer page (SYS_SEND with
+/// MSG_FLAG_BYTES). x0 also carries the payload length.
+/// Returns Err(ERR_IPC_MSG_TOO_LONG) if `data` exceeds IPC_BUF_MAX_PAYLOAD.
+pub fn send_bytes(ep_id: u64, data: &[u8]) -> Result<(), u64> {
+    if data.len() > IPC_BUF_MAX_PAYLOAD {
+        return Err(ERR_IPC_MSG_TOO_LONG);
+    }
+    let result: u64;
+    // SAFETY: IPC_BUFFER_VA is this task's own RW page, mapped by the kernel;
+    // header + payload ≤ 8 + 512 bytes stays inside it. No `nomem`: the
+    // kernel reads the staged payload during the SVC.
+    unsafe {
+        let buf = IPC_BUFFER_VA as *mut u8;
+        core::ptr::write_volatile(buf as *mut u64, data.len() as u64);
+        core::ptr::copy_nonoverlapping(data.as_ptr(), buf.add(IPC_BUF_HEADER), data.len());
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x0") data.len() as u64 => result,
+            in("x4") TIMEOUT_INFINITE,
+            in("x5") MSG_FLAG_BYTES,
+            in("x6") ep_id,
+            in("x7") SYS_SEND,
+            options(nostack)
+        );
+    }
+    if result == ERR_IPC_MSG_TOO_LONG { Err(result) } else { Ok(()) }
+}
+
+/// Receive a message on `ep_id` and copy its IPC-buffer payload into
+/// `out` (truncated to `out.len()`). Waits at most `timeout` ticks.
+/// Returns the number of bytes copied, or Err(ERR_IPC_TIMEOUT).
+pub fn recv_bytes(ep_id: u64, out: &mut [u8], timeout: u64) -> Result<usize, u64> {
+    let msg0: u64;
+    // SAFETY: SVC triggers synchronous exception handled by kernel. No
+    // `nomem`: the kernel writes the payload into our IPC buffer page.
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            inlateout("x4") timeout => _, // sender id on return
+            in("x6") ep_id,
+            in("x7") SYS_RECV,
+            lateout("x0") msg0,
+            lateout("x1") _,
+            lateout("x2") _,
+            lateout("x3") _,
+            lateout("x5") _, // sender badge
+            options(nostack)
+        );
+    }
+    if msg0 == ERR_IPC_TIMEOUT {
+        return Err(msg0);
+    }
+    // SAFETY: IPC_BUFFER_VA is this task's own page; the kernel caps the
+    // length prefix at IPC_BUF_MAX_PAYLOAD.
+    unsafe {
+        let buf = IPC_BUFFER_VA as *const u8;
+        let len = core::ptr::read_volatile(buf as *const u64) as usize;
+        let n = len.min(IPC_BUF_MAX_PAYLOAD).min(out.len());
+        core::ptr::copy_nonoverlapping(buf.add(IPC_BUF_HEADER), out.as_mut_ptr(), n);
+        Ok(n)
+    }
+}
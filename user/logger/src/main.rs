@@ -1,38 +1,124 @@
 // AegisOS User Task — "logger" (Phase O)
 //
-// Receives IPC data from sensor task on endpoint 1, writes to UART.
+// Drains sensor readings from message queue 0, writes to UART.
+// Readings are attributed by the kernel-stamped sender id.
+// The sensor's shared page is read-only here: one store probe faults.
+// Reading history lives in anonymous pages sized at start-up.
+// Its config table is sealed read-only once filled in.
 // Demonstrates multi-ELF loading + cross-task IPC between user binaries.
 
 #![no_std]
 #![no_main]
 
 use core::panic::PanicInfo;
-use libsyscall::{print, syscall_recv, syscall_yield};
+use libsyscall::{
+    print, syscall_map_anon, syscall_page_protect, syscall_queue_recv, syscall_sample_read, syscall_yield,
+    ERR_IPC_TIMEOUT, ERR_PROT_NO_EXEC, PAGE_SIZE, PROT_RO, PROT_RX,
+};
+
+/// Give up on the sensor after three of its 100-tick periods.
+const SENSOR_TIMEOUT: u64 = 300;
+
+/// Task id of the sensor (must match the task table in src/main.rs).
+const SENSOR_TASK: u64 = 3;
+
+/// Pages of reading history (within the logger's mem_quota in system.toml).
+const HISTORY_PAGES: u64 = 2;
+
+/// Config table: digits printed per reading.
+const CONFIG_DIGITS: u64 = 1;
 
 // ─── Entry point ───────────────────────────────────────────────────
 
-/// Logger task entry — receives sensor readings via IPC and logs to UART.
+/// Logger task entry — dequeues sensor readings and logs to UART.
 #[no_mangle]
 #[link_section = ".text._start"]
 pub extern "C" fn _start() -> ! {
     print("LOGGER:init ");
 
+    // Reading history: fresh zeroed pages, handed back by the kernel
+    // whenever we fault and restart
+    let (result, base) = syscall_map_anon(HISTORY_PAGES);
+    let history = if result == 0 { base as *mut u64 } else { core::ptr::null_mut() };
+    let slots = HISTORY_PAGES as usize * PAGE_SIZE / 8;
+    if !history.is_null() {
+        // SAFETY: [base, base + HISTORY_PAGES pages) is mapped read-write for us.
+        let zeroed = (0..slots).all(|i| unsafe { core::ptr::read_volatile(history.add(i)) } == 0);
+        if zeroed {
+            print("LOG:anon ");
+        }
+    }
+    let config = seal_config();
+    let mut logged: usize = 0;
+
     loop {
-        // Block waiting for IPC message on endpoint 1
-        let reading = syscall_recv(1);
+        // Block waiting for the next queued reading (bounded wait)
+        let msg = syscall_queue_recv(0, SENSOR_TIMEOUT);
+        let reading = msg.regs[0];
+        if reading == ERR_IPC_TIMEOUT {
+            // Queue silent — the sampling port tells whether the sensor
+            // is still publishing (idle) or has gone quiet (stale)
+            let last = syscall_sample_read(0);
+            print(if last.valid { "LOG:idle " } else { "LOG:stale " });
+            continue;
+        }
+        if msg.sender != SENSOR_TASK {
+            // Attribute by kernel-stamped sender id: drop anything not from the sensor
+            print("LOG:reject ");
+            continue;
+        }
+
+        if msg.regs[1] != 0 {
+            // First reading: x1 is the sensor's shared page (grant 1)
+            let shared = msg.regs[1] as *mut u64;
+            // SAFETY: grant 1 is mapped read-only for us; loads are allowed.
+            if unsafe { core::ptr::read_volatile(shared) } == reading {
+                print("LOG:ro ");
+            }
+            // The sensor granted read access only: this store is a
+            // permission fault, and the kernel restarts us.
+            // SAFETY: deliberate fault — the page is mapped, AP_RO_EL0.
+            unsafe { core::ptr::write_volatile(shared, 0) };
+        }
+
+        if !history.is_null() {
+            // SAFETY: logged % slots stays inside the mapped history pages.
+            unsafe { core::ptr::write_volatile(history.add(logged % slots), reading) };
+            logged = logged.wrapping_add(1);
+        }
 
         // Log the received reading
         print("LOG:");
-        // Simple hex digit output for the low nibble
-        let digit = (reading & 0xF) as u8;
-        let ch = if digit < 10 { b'0' + digit } else { b'a' + digit - 10 };
-        libsyscall::syscall_write(&ch as *const u8, 1);
+        // Simple hex digit output, low nibbles first (config: how many)
+        for n in 0..config {
+            let digit = ((reading >> (4 * n)) & 0xF) as u8;
+            let ch = if digit < 10 { b'0' + digit } else { b'a' + digit - 10 };
+            libsyscall::syscall_write(&ch as *const u8, 1);
+        }
         print(" ");
 
         syscall_yield();
     }
 }
 
+/// Fill in the config table on its own anonymous page, then seal it:
+/// read-only from here on, and the kernel refuses to make it executable.
+/// Returns the digits-per-reading setting.
+fn seal_config() -> u64 {
+    let (result, base) = syscall_map_anon(1);
+    if result != 0 {
+        return CONFIG_DIGITS;
+    }
+    let table = base as *mut u64;
+    // SAFETY: the page at base is freshly mapped read-write for us.
+    unsafe { core::ptr::write_volatile(table, CONFIG_DIGITS) };
+    if syscall_page_protect(base, 1, PROT_RO) == 0 && syscall_page_protect(base, 1, PROT_RX) == ERR_PROT_NO_EXEC {
+        print("LOG:seal ");
+    }
+    // SAFETY: still mapped, read-only — loads are allowed.
+    unsafe { core::ptr::read_volatile(table) }
+}
+
 #[panic_handler]
 fn panic(_: &PanicInfo) -> ! {
     loop {}
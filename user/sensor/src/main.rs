@@ -1,30 +1,79 @@
 // AegisOS User Task — "sensor" (Phase O)
 //
-// Simulated sensor: sends data via IPC to logger task on endpoint 1.
-// Demonstrates multi-ELF loading + cross-task IPC between user binaries.
+// Simulated sensor: posts readings to message queue 0 for the logger.
+// The queue decouples the two — the sensor never blocks on the logger.
+// Sampling port 0 always holds the latest reading with its freshness.
+// Grant 1 shares the latest reading with the logger, read-only on its side.
+// At start-up the sensor checks that revoking the grant scrubs the page.
 
 #![no_std]
 #![no_main]
 
 use core::panic::PanicInfo;
-use libsyscall::{print, syscall_send, syscall_yield};
+use libsyscall::{
+    print, syscall_grant_create, syscall_grant_revoke, syscall_queue_send, syscall_sample_write,
+    syscall_sleep, syscall_sleep_until, GRANT_PEER_RO,
+};
+
+/// Sampling period in ticks (100 × 10ms = 1 reading per second).
+const PERIOD: u64 = 100;
+
+/// Grant shared with the logger (system.toml grant 1).
+const SHARED_GRANT: u64 = 1;
+
+/// Task id of the logger (must match the task table in src/main.rs).
+const LOGGER_TASK: u64 = 4;
+
+/// Written before a revoke; must not survive into the re-created grant.
+const SCRUB_CANARY: u64 = 0x5EC2_E7DA_7A5E_C2E7;
 
 // ─── Entry point ───────────────────────────────────────────────────
 
-/// Sensor task entry — sends simulated sensor readings via IPC.
+/// Sensor task entry — enqueues one simulated reading per PERIOD,
+/// pacing itself with SYS_SLEEP_UNTIL.
 #[no_mangle]
 #[link_section = ".text._start"]
 pub extern "C" fn _start() -> ! {
     print("SENSOR:init ");
 
+    // One page the logger may read but not write
+    let (mut result, mut base) = syscall_grant_create(SHARED_GRANT, LOGGER_TASK, 1, 1, GRANT_PEER_RO);
+    if result == 0 {
+        // Revoke scrubs the page: the re-created grant must not show the
+        // canary to anyone who maps it next
+        // SAFETY: grant 1 is mapped read-write for us, its owner.
+        unsafe { core::ptr::write_volatile(base as *mut u64, SCRUB_CANARY) };
+        syscall_grant_revoke(SHARED_GRANT);
+        (result, base) = syscall_grant_create(SHARED_GRANT, LOGGER_TASK, 1, 1, GRANT_PEER_RO);
+        // SAFETY: as above, for the re-created grant.
+        if result == 0 && unsafe { core::ptr::read_volatile(base as *const u64) } != SCRUB_CANARY {
+            print("SENSOR:scrub ");
+        }
+    }
+    let shared = if result == 0 { base as *mut u64 } else { core::ptr::null_mut() };
+    // The first reading carries the page's address
+    let mut announce = shared as u64;
+
     let mut counter: u64 = 0;
+    // Absolute release times: no drift, whatever each iteration costs
+    let mut next_release = syscall_sleep(0);
     loop {
-        // Send sensor reading on endpoint 1: x0=counter, x1=0xCAFE (tag)
-        syscall_send(1, counter, 0xCAFE, 0, 0);
+        if !shared.is_null() {
+            // SAFETY: grant 1 is mapped read-write for us, its owner.
+            unsafe { core::ptr::write_volatile(shared, counter) };
+        }
+        // Post reading to queue 0: x0=counter, x1=shared page (first
+        // reading only). No self-written tag — the kernel stamps our task
+        // id on every queued message.
+        syscall_queue_send(0, counter, announce, 0, 0);
+        announce = 0;
+        // Publish the same reading as the current value on sampling port 0
+        syscall_sample_write(0, counter, 0, 0, 0);
         print("S ");
 
         counter = counter.wrapping_add(1);
-        syscall_yield();
+        next_release += PERIOD;
+        syscall_sleep_until(next_release);
     }
 }
 